@@ -0,0 +1,144 @@
+use std::f64::consts::TAU;
+
+use nalgebra::Complex;
+use ndarray::{s, Array1, ArrayBase, Data, Ix1};
+
+use crate::fft::{InverseFourierTransform, RealFourierTransform};
+
+// M. Guizar-Sicairos, S. T. Thurman and J. R. Fienup, "Efficient subpixel image registration
+// algorithms," Optics Letters, vol. 33, no. 2, pp. 156-158, 2008.
+
+// Lag of the signal `y` behind the signal `x`, in samples, within `max_lag` samples either way:
+// the argument of the maximum of their (demeaned, normalized) cross-correlation, computed by FFT,
+// the shorter signal being zero-padded
+// The correlation is then upsampled by `upsample` within a sample of its integer peak, as by
+// zero-padding its spectrum but evaluating only that region, for a lag to `1 / upsample` samples
+//
+// Returns the lag, positive when `y` is a delayed copy of `x`, and the correlation at it, 1 for
+// exact (and 0 for constant) copies
+// Panics if `upsample` is zero
+pub fn estimate_lag<S, T>(
+    x: &ArrayBase<S, Ix1>,
+    y: &ArrayBase<T, Ix1>,
+    max_lag: usize,
+    upsample: usize,
+) -> (f32, f32)
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+{
+    assert!(upsample > 0, "Upsampling factor must be positive !");
+    let n = (x.len() + y.len()).max(2).next_power_of_two();
+    let demeaned = |signal: Array1<f32>| {
+        let mean = signal.mean().unwrap_or(0.0);
+        let mut padded = Array1::zeros(n);
+        padded
+            .slice_mut(s![..signal.len()])
+            .assign(&(signal - mean));
+        padded
+    };
+    let (x, y) = (demeaned(x.to_owned()), demeaned(y.to_owned()));
+    let norm = (x.dot(&x) as f64 * y.dot(&y) as f64).sqrt();
+    if norm == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    // c[k] = sum_j y[j] x[j - k], peaking at the delay of `y`
+    let spectrum =
+        (&y.rfft() * &x.rfft().mapv(|z| z.conj())).mapv(|z| Complex::new(z.re as f64, z.im as f64));
+    let correlation = spectrum.ifft();
+    let max_lag = max_lag.min(n / 2 - 1) as isize;
+    let coarse = (-max_lag..=max_lag)
+        .max_by(|&a, &b| {
+            let value = |k: isize| correlation[k.rem_euclid(n as isize) as usize].re;
+            value(a).total_cmp(&value(b))
+        })
+        .unwrap();
+
+    // Band-limited interpolation of the correlation at fractional lags around the integer peak
+    let at = |lag: f64| {
+        spectrum
+            .iter()
+            .enumerate()
+            .map(|(k, z)| {
+                if 2 * k == n {
+                    z.re * (TAU * lag / 2.0).cos()
+                } else {
+                    let frequency = if 2 * k < n {
+                        k as f64
+                    } else {
+                        k as f64 - n as f64
+                    };
+                    (z * Complex::from_polar(1.0, TAU * frequency * lag / n as f64)).re
+                }
+            })
+            .sum::<f64>()
+            / n as f64
+    };
+    let (lag, peak) = (-(upsample as isize)..=upsample as isize)
+        .map(|j| coarse as f64 + j as f64 / upsample as f64)
+        .filter(|lag| lag.abs() <= max_lag as f64)
+        .map(|lag| (lag, at(lag)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+
+    (lag as f32, (peak / norm) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    // Sum of 40 sinusoids of random frequencies up to 0.4 cycles per sample and random phases,
+    // evaluated `delay` samples late, plus uniform noise of the amplitude `noise`
+    fn delayed(n: usize, delay: f64, noise: f32, seed: u64) -> Array1<f32> {
+        let mut generator = Lcg::new(1);
+        let components = (0..40)
+            .map(|_| {
+                let frequency = 0.4 * generator.next_f32() as f64;
+                (frequency, TAU * generator.next_f32() as f64)
+            })
+            .collect::<Vec<_>>();
+        let mut generator = Lcg::new(seed);
+
+        Array1::from_shape_fn(n, |t| {
+            let t = t as f64 - delay;
+            let signal = components
+                .iter()
+                .map(|(frequency, phase)| (TAU * frequency * t + phase).sin())
+                .sum::<f64>();
+            signal as f32 + noise * (generator.next_f32() - 0.5)
+        })
+    }
+
+    #[test]
+    fn fractional_delay_between_noisy_copies_is_recovered() {
+        let x = delayed(1000, 0.0, 1.0, 2);
+        for (delay, length) in [(3.37, 1000), (3.37, 800), (-3.37, 1000)] {
+            let y = delayed(length, delay, 1.0, 3);
+            let (lag, peak) = estimate_lag(&x, &y, 20, 100);
+
+            assert!((lag as f64 - delay).abs() < 0.05, "{delay} {length} {lag}");
+            assert!(peak > 0.9 && peak <= 1.0, "{peak}");
+        }
+    }
+
+    #[test]
+    fn exact_copies_correlate_fully() {
+        let x = delayed(500, 0.0, 0.0, 0);
+        let (lag, peak) = estimate_lag(&x, &delayed(500, 5.0, 0.0, 0), 10, 10);
+        assert!(
+            (lag - 5.0).abs() < 1e-3 && (peak - 1.0).abs() < 0.02,
+            "{lag} {peak}"
+        );
+
+        // Lags beyond `max_lag` are not searched
+        let (lag, _) = estimate_lag(&x, &delayed(500, 5.0, 0.0, 0), 3, 10);
+        assert!(lag.abs() <= 3.0);
+        assert_eq!(
+            estimate_lag(&x, &Array1::from_elem(500, 2.0), 10, 10),
+            (0.0, 0.0)
+        );
+    }
+}
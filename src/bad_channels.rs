@@ -0,0 +1,287 @@
+use ndarray::{Array1, ArrayBase, Axis, Data, DataMut, Ix2};
+
+// Variance, relative to the median variance of all channels, under which a channel is flat
+const FLAT_VARIANCE: f32 = 1e-10;
+
+// Indices of the channels of the `data` (channels x samples) deemed bad, in increasing order, for
+// being either:
+// - flat
+// - outliers in log-variance, with a robust z-score (from the median and the median absolute
+//   deviation) beyond `threshold`, 5 being customary
+// - outliers in their correlation with their `neighbors` nearest active channels, with a robust
+//   z-score of their median absolute correlation below `-threshold`
+// The `positions` (channels x 3) are Cartesian, as given by `Header::channel_positions`, where
+// channels of unknown (NaN) positions are never used as neighbors, nor tested for their correlation
+pub fn detect_bad_channels<S, T>(
+    data: &ArrayBase<S, Ix2>,
+    positions: &ArrayBase<T, Ix2>,
+    neighbors: usize,
+    threshold: f32,
+) -> Vec<usize>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f64>,
+{
+    let variances = data.var_axis(Axis(1), 0.0);
+    let median_variance = median(variances.to_vec());
+    let (flat, active): (Vec<usize>, Vec<usize>) =
+        (0..data.nrows()).partition(|&i| variances[i] <= FLAT_VARIANCE * median_variance);
+
+    let mut bad = flat;
+    if active.len() > 2 {
+        let log_variances = active
+            .iter()
+            .map(|&i| variances[i].ln())
+            .collect::<Vec<f32>>();
+        let variance_z = robust_z_scores(&log_variances);
+        bad.extend(
+            active
+                .iter()
+                .zip(&variance_z)
+                .filter(|&(_, z)| z.abs() > threshold)
+                .map(|(&channel, _)| channel),
+        );
+
+        // Correlation between every pair of active channels, from their standardized samples
+        let active_data = data.select(Axis(0), &active);
+        let mean = active_data.mean_axis(Axis(1)).unwrap().insert_axis(Axis(1));
+        let std = active_data.std_axis(Axis(1), 0.0).insert_axis(Axis(1));
+        let standardized = (&active_data - &mean) / &std;
+        let correlations = standardized.dot(&standardized.t()) / data.ncols() as f32;
+
+        let located = (0..active.len())
+            .filter(|&i| known(positions, active[i]))
+            .collect::<Vec<usize>>();
+        let median_correlations = located
+            .iter()
+            .map(|&i| {
+                let candidates = located.iter().copied().filter(|&j| j != i);
+                let nearest = nearest(positions, &active, active[i], candidates, neighbors);
+                median(
+                    nearest
+                        .iter()
+                        .map(|&(j, _)| correlations[[i, j]].abs())
+                        .collect(),
+                )
+            })
+            .collect::<Vec<f32>>();
+        let correlation_z = robust_z_scores(&median_correlations);
+        bad.extend(
+            located
+                .iter()
+                .zip(&correlation_z)
+                .filter(|&(_, &z)| z < -threshold)
+                .map(|(&i, _)| active[i]),
+        );
+    }
+    bad.sort_unstable();
+    bad.dedup();
+
+    bad
+}
+
+// Replace each of the `bad` channels of the `data` (channels x samples) with the inverse-distance
+// weighted average of its `neighbors` nearest good channels
+// The `positions` (channels x 3) are Cartesian, as given by `Header::channel_positions`, where
+// channels of unknown (NaN) positions are never used as neighbors, and are left untouched if bad
+pub fn interpolate_channels<S, T>(
+    data: &mut ArrayBase<S, Ix2>,
+    bad: &[usize],
+    positions: &ArrayBase<T, Ix2>,
+    neighbors: usize,
+) where
+    S: DataMut<Elem = f32>,
+    T: Data<Elem = f64>,
+{
+    let channels = (0..data.nrows()).collect::<Vec<usize>>();
+    let good = (0..data.nrows())
+        .filter(|&i| !bad.contains(&i) && known(positions, i))
+        .collect::<Vec<usize>>();
+
+    for &channel in bad.iter().filter(|&&i| known(positions, i)) {
+        let distances = nearest(
+            positions,
+            &channels,
+            channel,
+            good.iter().copied(),
+            neighbors,
+        );
+        if distances.is_empty() {
+            continue;
+        }
+
+        // A good channel at the very same position is simply copied
+        let weights = match distances.iter().find(|(_, distance)| *distance == 0.0) {
+            Some(&(i, _)) => vec![(i, 1.0)],
+            None => distances.iter().map(|&(i, d)| (i, 1.0 / d)).collect(),
+        };
+        let total = weights.iter().map(|(_, w)| w).sum::<f64>();
+        let mut interpolated = Array1::<f32>::zeros(data.ncols());
+        for (i, weight) in weights {
+            interpolated.scaled_add((weight / total) as f32, &data.row(i));
+        }
+        data.row_mut(channel).assign(&interpolated);
+    }
+}
+
+fn known<T>(positions: &ArrayBase<T, Ix2>, channel: usize) -> bool
+where
+    T: Data<Elem = f64>,
+{
+    positions.row(channel).iter().all(|p| p.is_finite())
+}
+
+// The `k` nearest of the `candidates` to the `channel`, along with their distance to it, in
+// increasing distance, the candidates being indices into the `channels` whose `positions` are known
+fn nearest<T>(
+    positions: &ArrayBase<T, Ix2>,
+    channels: &[usize],
+    channel: usize,
+    candidates: impl Iterator<Item = usize>,
+    k: usize,
+) -> Vec<(usize, f64)>
+where
+    T: Data<Elem = f64>,
+{
+    let mut distances = candidates
+        .map(|i| {
+            let distance = (&positions.row(channels[i]) - &positions.row(channel))
+                .mapv(|d| d * d)
+                .sum()
+                .sqrt();
+            (i, distance)
+        })
+        .collect::<Vec<(usize, f64)>>();
+    distances.sort_by(|a, b| a.1.total_cmp(&b.1));
+    distances.truncate(k);
+
+    distances
+}
+
+fn median(mut values: Vec<f32>) -> f32 {
+    if values.is_empty() {
+        return f32::NAN;
+    }
+    values.sort_by(f32::total_cmp);
+
+    let mid = values.len() / 2;
+    match values.len() % 2 {
+        0 => (values[mid - 1] + values[mid]) / 2.0,
+        _ => values[mid],
+    }
+}
+
+// Robust z-scores, from the median and the median absolute deviation, scaled to be consistent with
+// the standard deviation of normally distributed values
+// Values are all scored 0 when more than half of them are equal
+fn robust_z_scores(values: &[f32]) -> Vec<f32> {
+    let center = median(values.to_vec());
+    let mad = 1.4826 * median(values.iter().map(|v| (v - center).abs()).collect());
+
+    values
+        .iter()
+        .map(|v| if mad > 0.0 { (v - center) / mad } else { 0.0 })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use ndarray::Array2;
+
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    // Channels on a 4 x 4 grid of unit spacing
+    fn positions() -> Array2<f64> {
+        Array2::from_shape_fn((16, 3), |(i, axis)| match axis {
+            0 => (i % 4) as f64,
+            1 => (i / 4) as f64,
+            _ => 0.0,
+        })
+    }
+
+    // Three oscillating sources under distinct corners of the grid, each seen by the channels with
+    // a gain decaying with their distance to it, plus a little independent noise
+    fn correlated() -> Array2<f32> {
+        let sources = [(0.0, 0.0, 5.0), (3.0, 0.0, 9.0), (1.5, 3.0, 13.0)];
+        let mut generator = Lcg::new(7);
+        Array2::from_shape_fn((16, 2000), |(i, t)| {
+            let (x, y) = ((i % 4) as f32, (i / 4) as f32);
+            let time = t as f32 / 500.0;
+            let signal = sources
+                .iter()
+                .map(|&(sx, sy, frequency)| {
+                    let distance2 = (x - sx).powi(2) + (y - sy).powi(2);
+                    (-distance2 / 4.5).exp() * (TAU * frequency * time).sin()
+                })
+                .sum::<f32>();
+            signal + 0.05 * (generator.next_f32() - 0.5)
+        })
+    }
+
+    fn correlation(a: &[f32], b: &[f32]) -> f32 {
+        let (ma, mb) = (
+            a.iter().sum::<f32>() / a.len() as f32,
+            b.iter().sum::<f32>() / b.len() as f32,
+        );
+        let cov = a
+            .iter()
+            .zip(b)
+            .map(|(x, y)| (x - ma) * (y - mb))
+            .sum::<f32>();
+        let va = a.iter().map(|x| (x - ma).powi(2)).sum::<f32>();
+        let vb = b.iter().map(|y| (y - mb).powi(2)).sum::<f32>();
+        cov / (va * vb).sqrt()
+    }
+
+    #[test]
+    fn clean_data_has_no_bad_channel() {
+        assert!(detect_bad_channels(&correlated(), &positions(), 4, 5.0).is_empty());
+    }
+
+    #[test]
+    fn zeroed_channel_is_detected_and_reconstructed() {
+        let original = correlated();
+        let mut data = original.clone();
+        data.row_mut(5).fill(0.0);
+
+        let bad = detect_bad_channels(&data, &positions(), 4, 5.0);
+        assert_eq!(bad, vec![5]);
+
+        interpolate_channels(&mut data, &bad, &positions(), 4);
+        let r = correlation(&data.row(5).to_vec(), &original.row(5).to_vec());
+        assert!(r > 0.8, "{r}");
+        for i in (0..16).filter(|&i| i != 5) {
+            assert_eq!(data.row(i), original.row(i));
+        }
+    }
+
+    #[test]
+    fn uncorrelated_channel_is_detected() {
+        let mut data = correlated();
+        let std = data.row(10).std(0.0);
+        let mut generator = Lcg::new(42);
+        data.row_mut(10)
+            .mapv_inplace(|_| std * 12f32.sqrt() * (generator.next_f32() - 0.5));
+
+        assert_eq!(detect_bad_channels(&data, &positions(), 4, 5.0), vec![10]);
+    }
+
+    #[test]
+    fn channel_of_unknown_position_is_not_tested_for_correlation() {
+        let mut data = correlated();
+        let std = data.row(10).std(0.0);
+        let mut generator = Lcg::new(42);
+        data.row_mut(10)
+            .mapv_inplace(|_| std * 12f32.sqrt() * (generator.next_f32() - 0.5));
+        let mut positions = positions();
+        positions.row_mut(10).fill(f64::NAN);
+
+        assert!(detect_bad_channels(&data, &positions, 4, 5.0).is_empty());
+        // Though still detected when flat
+        data.row_mut(10).fill(0.0);
+        assert_eq!(detect_bad_channels(&data, &positions, 4, 5.0), vec![10]);
+    }
+}
@@ -0,0 +1,176 @@
+use ndarray::{Array1, Array2, ArrayBase, Data, Ix1};
+
+use crate::{fft::welch, read::raw::Raw, source::SignalSource};
+
+// Band of frequencies, in Hz, the power of relative band powers is a fraction of
+pub const TOTAL_BAND: (f32, f32) = (1.0, 45.0);
+// Duration, in seconds, of the Welch segments of the PSD
+const SEGMENT_DURATION: f32 = 2.0;
+
+// Power of a band of frequencies, either as is or relative to the whole EEG band
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BandPowerMode {
+    // In units^2
+    #[default]
+    Absolute,
+    // As a fraction of the power within `TOTAL_BAND`, in [0, 1]
+    Relative,
+}
+
+// Power of each channel in each band, along with the channel names of the recording
+#[derive(Clone, Debug)]
+pub struct BandPowerTopography {
+    pub channel_names: Vec<String>,
+    // Bands (low, high) of frequencies, in Hz
+    pub bands: Vec<(f32, f32)>,
+    // Power (channels x bands)
+    pub power: Array2<f32>,
+}
+
+impl BandPowerTopography {
+    // Power of each channel of the `raw` recording in each of the `bands`, as by
+    // `band_power_topography`
+    pub fn from_raw(raw: &Raw, bands: &[(f32, f32)], mode: BandPowerMode) -> Self {
+        BandPowerTopography {
+            channel_names: raw.channel_names().into_iter().map(Into::into).collect(),
+            bands: bands.to_vec(),
+            power: band_power_topography(&raw.data(), raw.sfreq() as f32, bands, mode),
+        }
+    }
+}
+
+// Power of the `psd` of the `freqs` in Hz within the `band` (low, high) in Hz, both included,
+// integrated by the trapezoidal rule over the frequencies within it
+pub fn band_power<S, T>(
+    freqs: &ArrayBase<S, Ix1>,
+    psd: &ArrayBase<T, Ix1>,
+    (low, high): (f32, f32),
+) -> f32
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+{
+    let within = freqs
+        .iter()
+        .zip(psd)
+        .filter(|&(&f, _)| f >= low && f <= high)
+        .map(|(&f, &p)| (f, p))
+        .collect::<Vec<(f32, f32)>>();
+
+    within
+        .windows(2)
+        .map(|pair| 0.5 * (pair[0].1 + pair[1].1) * (pair[1].0 - pair[0].0))
+        .sum()
+}
+
+// Power of each channel of the `data` (channels x samples), sampled at `fs` Hz, in each of the
+// `bands` (low, high) in Hz, from its Welch PSD over segments of 2 seconds
+// The `data` is any `SignalSource`, e.g. an array or a `ScaledView` of Int16 data, converted a
+// channel at a time
+// Channels are independent of each other: a channel holding NaN values (e.g. marked as flat) has
+// NaN powers, as does a constant one in relative powers, leaving the others as they are
+//
+// Returns the power (channels x bands)
+pub fn band_power_topography<D>(
+    data: &D,
+    fs: f32,
+    bands: &[(f32, f32)],
+    mode: BandPowerMode,
+) -> Array2<f32>
+where
+    D: SignalSource + ?Sized,
+{
+    let segment_len = (SEGMENT_DURATION * fs).round().max(1.0) as usize;
+
+    let mut power = Array2::zeros((data.num_channels(), bands.len()));
+    for (index, mut row) in power.outer_iter_mut().enumerate() {
+        let (freqs, psd) = welch(&data.channel(index), fs, segment_len);
+        let total = match mode {
+            BandPowerMode::Absolute => 1.0,
+            BandPowerMode::Relative => band_power(&freqs, &psd, TOTAL_BAND),
+        };
+
+        row.assign(&Array1::from_iter(
+            bands
+                .iter()
+                .map(|&band| band_power(&freqs, &psd, band) / total),
+        ));
+    }
+
+    power
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use ndarray::s;
+
+    use super::*;
+    use crate::{
+        read::fixtures::{Recording, TempDir},
+        surrogate::Lcg,
+    };
+
+    const FS: f32 = 250.0;
+    const BANDS: [(f32, f32); 4] = [(1.0, 4.0), (4.0, 8.0), (8.0, 13.0), (13.0, 30.0)];
+
+    // Ten seconds of a 10 Hz tone of unit amplitude, NaN values, noise and a constant
+    fn data() -> Array2<f32> {
+        let mut generator = Lcg::new(4);
+        Array2::from_shape_fn((4, 2500), |(c, t)| match c {
+            0 => (TAU * 10.0 * t as f32 / FS).sin(),
+            1 => f32::NAN,
+            2 => generator.next_f32() - 0.5,
+            _ => 3.0,
+        })
+    }
+
+    #[test]
+    fn tone_holds_all_the_relative_power_of_its_band() {
+        let relative = band_power_topography(&data(), FS, &BANDS, BandPowerMode::Relative);
+
+        assert_eq!(relative.dim(), (4, 4));
+        assert!(relative[[0, 2]] > 0.99, "{}", relative[[0, 2]]);
+        assert!(relative.slice(s![0, ..2]).iter().all(|&p| p < 1e-3));
+        // Half the squared amplitude of the tone
+        let absolute = band_power_topography(&data(), FS, &BANDS, BandPowerMode::Absolute);
+        assert!(
+            (absolute[[0, 2]] - 0.5).abs() < 0.05,
+            "{}",
+            absolute[[0, 2]]
+        );
+    }
+
+    #[test]
+    fn nan_channels_do_not_poison_the_others() {
+        for mode in [BandPowerMode::Absolute, BandPowerMode::Relative] {
+            let power = band_power_topography(&data(), FS, &BANDS, mode);
+
+            assert!(power.row(1).iter().all(|p| p.is_nan()), "{mode:?}");
+            let alone = band_power_topography(&data().slice(s![2..3, ..]), FS, &BANDS, mode);
+            assert_eq!(power.row(2), alone.row(0));
+            assert!(power.row(2).iter().all(|p| p.is_finite() && *p > 0.0));
+        }
+        // A constant channel has no power to be a fraction of
+        let relative = band_power_topography(&data(), FS, &BANDS, BandPowerMode::Relative);
+        assert!(relative.row(3).iter().all(|p| p.is_nan()));
+    }
+
+    #[test]
+    fn topography_of_a_recording_carries_its_channel_names() {
+        let root = TempDir::new("band-power");
+        let data = data().slice(s![..1, ..]).to_owned();
+        let mut recording = Recording::new(&["Oz"], data.clone());
+        recording.sampling_interval = 4000.0;
+        let raw = Raw::load(&recording.write(&root)).unwrap();
+
+        let topography = BandPowerTopography::from_raw(&raw, &BANDS, BandPowerMode::Relative);
+        assert_eq!(topography.channel_names, vec!["Oz"]);
+        assert_eq!(topography.bands, BANDS.to_vec());
+        assert_eq!(
+            topography.power,
+            band_power_topography(&data, FS, &BANDS, BandPowerMode::Relative)
+        );
+    }
+}
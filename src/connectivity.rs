@@ -0,0 +1,250 @@
+use std::{error::Error, fmt::Display};
+
+use nalgebra::Complex;
+use ndarray::{s, Array1, Array2, ArrayBase, Data, Ix1, Ix2};
+
+use crate::{fft::analytic_signal, filter::FIRFilter};
+
+// Errors that can occur while computing connectivity
+#[derive(Debug)]
+pub enum ConnectivityError {
+    // The band is not within (0, fs / 2), or its low frequency is not below its high one
+    InvalidBand { low: f32, high: f32, fs: f32 },
+}
+
+impl Display for ConnectivityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectivityError::InvalidBand { low, high, fs } => write!(
+                f,
+                "band {low}..{high} Hz is not an increasing band within the \
+                 {} Hz Nyquist frequency of a sampling frequency of {fs} Hz",
+                fs / 2.0
+            ),
+        }
+    }
+}
+
+impl Error for ConnectivityError {}
+
+// Phase-based connectivity metric between two signals
+#[derive(Clone, Copy, Debug)]
+pub enum ConnectivityMetric {
+    // Phase locking value: consistency of the phase difference, in [0, 1]
+    Plv,
+    // Phase lag index: asymmetry of the phase difference about 0, in [0, 1], insensitive to
+    // zero-lag coupling such as volume conduction
+    Pli,
+}
+
+// Phase locking value between the signals `x` and `y`, sampled at `fs` Hz, within the `band`
+// (low, high) of frequencies in Hz
+// Fails unless 0 < low < high < fs / 2
+pub fn plv<S, T>(
+    x: &ArrayBase<S, Ix1>,
+    y: &ArrayBase<T, Ix1>,
+    fs: f32,
+    band: (f32, f32),
+) -> Result<f32, ConnectivityError>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+{
+    let filter = band_filter(fs, band)?;
+
+    Ok(phase_statistic(
+        &phases(x, &filter),
+        &phases(y, &filter),
+        ConnectivityMetric::Plv,
+    ))
+}
+
+// Phase lag index between the signals `x` and `y`, sampled at `fs` Hz, within the `band`
+// (low, high) of frequencies in Hz
+// Fails unless 0 < low < high < fs / 2
+pub fn pli<S, T>(
+    x: &ArrayBase<S, Ix1>,
+    y: &ArrayBase<T, Ix1>,
+    fs: f32,
+    band: (f32, f32),
+) -> Result<f32, ConnectivityError>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+{
+    let filter = band_filter(fs, band)?;
+
+    Ok(phase_statistic(
+        &phases(x, &filter),
+        &phases(y, &filter),
+        ConnectivityMetric::Pli,
+    ))
+}
+
+// Connectivity between every pair of channels of the `data` (channels x samples), sampled at `fs`
+// Hz, within the `band` (low, high) of frequencies in Hz
+//
+// Returns the symmetric matrix of the `metric` (channels x channels)
+// Fails unless 0 < low < high < fs / 2
+pub fn connectivity_matrix<S>(
+    data: &ArrayBase<S, Ix2>,
+    fs: f32,
+    band: (f32, f32),
+    metric: ConnectivityMetric,
+) -> Result<Array2<f32>, ConnectivityError>
+where
+    S: Data<Elem = f32>,
+{
+    let filter = band_filter(fs, band)?;
+    let phases = data
+        .outer_iter()
+        .map(|channel| phases(&channel, &filter))
+        .collect::<Vec<Array1<f32>>>();
+
+    let num_channels = data.nrows();
+    let mut matrix = Array2::zeros((num_channels, num_channels));
+    for i in 0..num_channels {
+        for j in i..num_channels {
+            let value = phase_statistic(&phases[i], &phases[j], metric);
+            matrix[[i, j]] = value;
+            matrix[[j, i]] = value;
+        }
+    }
+
+    Ok(matrix)
+}
+
+// Zero-phase band-pass filter spanning 3 cycles of the lowest frequency of the `band`
+fn band_filter(fs: f32, (low, high): (f32, f32)) -> Result<FIRFilter, ConnectivityError> {
+    // Also rejects NaN frequencies
+    if !(0.0 < low && low < high && high < fs / 2.0) {
+        return Err(ConnectivityError::InvalidBand { low, high, fs });
+    }
+    let num_taps = (3.0 * fs / low).round() as usize;
+
+    Ok(FIRFilter::bandpass(num_taps, low, high, fs))
+}
+
+// Instantaneous phase of the band-passed `signal`, leaving out the samples within half a filter
+// length of either edge, distorted by filtering
+fn phases<S>(signal: &ArrayBase<S, Ix1>, filter: &FIRFilter) -> Array1<f32>
+where
+    S: Data<Elem = f32>,
+{
+    let filtered = filter.process_zero_phase(signal);
+    let edge = (filter.num_taps() / 2).min(filtered.len() / 2);
+
+    analytic_signal(&filtered)
+        .slice(s![edge..filtered.len() - edge])
+        .mapv(|z| z.arg())
+}
+
+fn phase_statistic(x: &Array1<f32>, y: &Array1<f32>, metric: ConnectivityMetric) -> f32 {
+    if x.is_empty() {
+        return 0.0;
+    }
+    let differences = x - y;
+
+    match metric {
+        ConnectivityMetric::Plv => {
+            let sum = differences
+                .iter()
+                .map(|&d| Complex::new(d.cos(), d.sin()))
+                .sum::<Complex<f32>>();
+            sum.norm() / differences.len() as f32
+        }
+        ConnectivityMetric::Pli => {
+            // The sign of exactly zero-lag differences is 0, not 1 as per `f32::signum`
+            let sum = differences
+                .iter()
+                .map(|&d| match d.sin() {
+                    s if s > 0.0 => 1.0,
+                    s if s < 0.0 => -1.0,
+                    _ => 0.0,
+                })
+                .sum::<f32>();
+            sum.abs() / differences.len() as f32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, TAU};
+
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    const FS: f32 = 250.0;
+    const ALPHA: (f32, f32) = (8.0, 12.0);
+
+    // Ten seconds of a 10 Hz oscillation, starting at `phase`
+    fn oscillation(phase: f32) -> Array1<f32> {
+        Array1::from_shape_fn(2500, |t| (TAU * 10.0 * t as f32 / FS + phase).sin())
+    }
+
+    // Half a minute of uniform noise
+    fn noise(seed: u64) -> Array1<f32> {
+        let mut generator = Lcg::new(seed);
+        Array1::from_shape_fn(8192, |_| generator.next_f32() - 0.5)
+    }
+
+    #[test]
+    fn identical_signals_are_locked_at_zero_lag() {
+        let x = oscillation(0.0) + &(noise(1).slice(s![..2500]).to_owned() * 0.5);
+
+        assert!((plv(&x, &x, FS, ALPHA).unwrap() - 1.0).abs() < 1e-5);
+        assert_eq!(pli(&x, &x, FS, ALPHA).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn quadrature_signals_are_locked_with_a_lag() {
+        let (x, y) = (oscillation(0.0), oscillation(FRAC_PI_2));
+
+        assert!(plv(&x, &y, FS, ALPHA).unwrap() > 0.99);
+        assert_eq!(pli(&x, &y, FS, ALPHA).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn independent_noise_is_not_locked() {
+        let (x, y) = (noise(1), noise(2));
+
+        assert!(plv(&x, &y, FS, ALPHA).unwrap() < 0.2);
+        assert!(pli(&x, &y, FS, ALPHA).unwrap() < 0.2);
+    }
+
+    #[test]
+    fn connectivity_matrix_is_symmetric() {
+        let data = ndarray::stack![
+            ndarray::Axis(0),
+            oscillation(0.0),
+            oscillation(FRAC_PI_2),
+            noise(3).slice(s![..2500])
+        ];
+        let matrix = connectivity_matrix(&data, FS, ALPHA, ConnectivityMetric::Plv).unwrap();
+
+        assert_eq!(matrix, matrix.t());
+        assert!(matrix.diag().iter().all(|&d| (d - 1.0).abs() < 1e-5));
+        assert_eq!(
+            matrix[[0, 1]],
+            plv(&data.row(0), &data.row(1), FS, ALPHA).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_bands_fail() {
+        let x = oscillation(0.0);
+        for band in [
+            (0.0, 12.0),
+            (12.0, 8.0),
+            (8.0, 8.0),
+            (100.0, 125.0),
+            (f32::NAN, 12.0),
+        ] {
+            match plv(&x, &x, FS, band) {
+                Err(ConnectivityError::InvalidBand { fs, .. }) => assert_eq!(fs, FS),
+                other => panic!("Unexpected result {other:?} !"),
+            }
+        }
+    }
+}
@@ -1,31 +1,1559 @@
-use ndarray::{Array2, ArrayBase, Axis, Data, Ix2};
+use std::{error::Error, fmt::Display, marker::PhantomData};
+
+use nalgebra::{DMatrix, DVector, SymmetricEigen};
+use ndarray::{
+    s, Array1, Array2, Array3, ArrayBase, ArrayView2, Axis, Data, Ix1, Ix2, Ix3, LinalgScalar,
+    ScalarOperand,
+};
+use num_traits::{Float, FromPrimitive, One, Zero};
+
+use crate::filter::FIRFilter;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+// Relative change of the robust covariance estimate, in Frobenius norm, below which it has converged
+const ROBUST_TOLERANCE: f64 = 1e-6;
+
+// Elements of the data covariances can be computed of
+// Accumulating long recordings in f32 loses precision, which f64 data does not
+pub trait CovarianceElement:
+    Float + FromPrimitive + LinalgScalar + ScalarOperand + Send + Sync
+{
+}
+
+impl CovarianceElement for f32 {}
+impl CovarianceElement for f64 {}
 
 pub enum CovarianceType {
     Population = 0,
     Sample = 1,
 }
 
+// Estimator of the covariances of epochs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Estimator {
+    Population,
+    Sample,
+    LedoitWolf,
+    Oas,
+}
+
+// Layout of the data covariances are computed of
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Orientation {
+    // Channels x samples, as loaded
+    #[default]
+    VariablesInRows,
+    // Samples x channels, as read from dataframe-like sources, e.g. CSV files
+    VariablesInColumns,
+}
+
+// What to do with frequency bands exceeding the Nyquist frequency
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NyquistPolicy {
+    // Lower their upper edge to the Nyquist frequency
+    Clamp,
+    // Fail with `CovarianceError::BandOutOfRange`
+    #[default]
+    Error,
+}
+
+// Options of a covariance computation
+#[derive(Clone, Debug)]
+pub struct CovOptions {
+    assume_centered: bool,
+    ddof: usize,
+    orientation: Orientation,
+}
+
+impl Default for CovOptions {
+    fn default() -> Self {
+        Self {
+            assume_centered: false,
+            ddof: 1,
+            orientation: Orientation::default(),
+        }
+    }
+}
+
+impl CovOptions {
+    // Sample covariance, centering the data beforehand
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Skip the centering of data known to have a zero mean already, e.g. after high-pass
+    // filtering, saving a pass over the data and a copy of it
+    pub fn assume_centered(mut self, assume_centered: bool) -> Self {
+        self.assume_centered = assume_centered;
+        self
+    }
+
+    // Delta degrees of freedom: the scatter matrix is divided by `m_samples - ddof`
+    pub fn ddof(mut self, ddof: usize) -> Self {
+        self.ddof = ddof;
+        self
+    }
+
+    // Layout of the data, handled through a transposed view rather than a copy
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+}
+
+impl From<CovarianceType> for CovOptions {
+    fn from(cov_t: CovarianceType) -> Self {
+        Self::new().ddof(cov_t as usize)
+    }
+}
+
+// Covariance shrunk toward a scaled identity: (1 - shrinkage) * covariance + shrinkage * mu * I,
+// `mu` being the mean variance of the channels
+#[derive(Clone, Debug)]
+pub struct ShrunkCovariance<A = f32> {
+    pub covariance: Array2<A>,
+    // Shrinkage coefficient, between 0 (none) and 1 (scaled identity)
+    pub shrinkage: A,
+}
+
+// What to do with samples holding NaN values
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NanPolicy {
+    // Let them spread to the covariances of their channels
+    #[default]
+    Propagate,
+    // Leave out every sample holding a NaN value, on any channel
+    OmitSamples,
+}
+
+// Covariance of weighted samples
+#[derive(Clone, Debug)]
+pub struct WeightedCovariance<A = f32> {
+    pub covariance: Array2<A>,
+    // Number of samples left out for holding NaN values
+    pub dropped: usize,
+}
+
+// Covariance estimated robustly to outlying samples
+#[derive(Clone, Debug)]
+pub struct RobustCovariance<A = f32> {
+    pub covariance: Array2<A>,
+    // Final weight of each sample, between 0 and 1, low for outliers
+    pub weights: Array1<A>,
+    // Whether the estimate settled before the maximum number of iterations
+    pub converged: bool,
+    pub iterations: usize,
+}
+
+// What to do with the correlations of channels of zero variance, which are undefined
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ZeroVariance {
+    // Fill their rows and columns with NaN, diagonal included
+    #[default]
+    Nan,
+    // Fail with `CovarianceError::ZeroVariance`
+    Error,
+}
+
+// Errors that can occur while computing covariances
+#[derive(Debug)]
+pub enum CovarianceError {
+    // There are no more samples than delta degrees of freedom
+    NotEnoughSamples { num_samples: usize, ddof: usize },
+    // A channel is constant, so its correlations are undefined
+    ZeroVariance { channel: usize },
+    // There are not as many weights as samples
+    WeightsMismatch { weights: usize, samples: usize },
+    // A weight is negative or not a number
+    InvalidWeight { sample: usize, weight: f64 },
+    // The weights of the samples kept sum to zero
+    ZeroWeights,
+    // The covariance cannot be inverted
+    Singular,
+    // A frequency band is empty, or exceeds the Nyquist frequency
+    BandOutOfRange { low: f32, high: f32, nyquist: f32 },
+}
+
+impl Display for CovarianceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CovarianceError::NotEnoughSamples { num_samples, ddof } => write!(
+                f,
+                "{num_samples} samples are not enough for {ddof} delta degrees of freedom"
+            ),
+            CovarianceError::ZeroVariance { channel } => {
+                write!(f, "channel {channel} has zero variance")
+            }
+            CovarianceError::WeightsMismatch { weights, samples } => {
+                write!(f, "{weights} weights given for {samples} samples")
+            }
+            CovarianceError::InvalidWeight { sample, weight } => {
+                write!(f, "sample {sample} has an invalid weight of {weight}")
+            }
+            CovarianceError::ZeroWeights => f.write_str("weights of the samples sum to zero"),
+            CovarianceError::Singular => f.write_str("covariance is singular"),
+            CovarianceError::BandOutOfRange { low, high, nyquist } => write!(
+                f,
+                "band {low}-{high} Hz is empty or exceeds the Nyquist frequency of {nyquist} Hz"
+            ),
+        }
+    }
+}
+
+impl Error for CovarianceError {}
+
 pub trait Covariance<S>
 where
-    S: Data<Elem = f32>,
+    S: Data,
+    S::Elem: CovarianceElement,
 {
-    // TODO
-    // - Add `is_centered` parameter
-    fn compute_covariance(&self, cov_t: CovarianceType) -> Array2<f32>;
+    // Panics if there are no more samples than the delta degrees of freedom of `cov_t`
+    fn compute_covariance(&self, cov_t: CovarianceType) -> Array2<S::Elem>;
+
+    // The same covariance, failing instead of panicking
+    fn try_compute_covariance(
+        &self,
+        cov_t: CovarianceType,
+    ) -> Result<Array2<S::Elem>, CovarianceError>;
+
+    fn compute_covariance_opts(
+        &self,
+        options: &CovOptions,
+    ) -> Result<Array2<S::Elem>, CovarianceError>;
+
+    // Pearson correlation matrix (channels x channels): the covariance normalized by the standard
+    // deviations of the channels, with an exact unit diagonal
+    fn compute_correlation(
+        &self,
+        zero_variance: ZeroVariance,
+    ) -> Result<Array2<S::Elem>, CovarianceError>;
+
+    // Population covariance shrunk toward the scaled identity by the analytic Ledoit-Wolf
+    // coefficient, which is well-conditioned even with as few samples as channels
+    // * https://doi.org/10.1016/S0047-259X(03)00096-4
+    fn compute_covariance_ledoit_wolf(&self) -> Result<ShrunkCovariance<S::Elem>, CovarianceError>;
+
+    // Population covariance shrunk toward the scaled identity by the Oracle Approximating Shrinkage
+    // coefficient, typically closer to optimal than Ledoit-Wolf's for few Gaussian samples
+    // * https://doi.org/10.1109/TSP.2010.2053029
+    fn compute_covariance_oas(&self) -> Result<ShrunkCovariance<S::Elem>, CovarianceError>;
+
+    // Sample covariance loaded with `alpha` on its diagonal
+    fn compute_covariance_regularized(
+        &self,
+        alpha: S::Elem,
+    ) -> Result<Array2<S::Elem>, CovarianceError>;
+
+    // Unbiased covariance of samples of non-negative `weights`, e.g. zero for samples marked as
+    // artifacts, normalized internally so that only their ratios matter
+    // With equal weights, this is the sample covariance
+    fn compute_covariance_weighted<T>(
+        &self,
+        weights: &ArrayBase<T, Ix1>,
+        nan_policy: NanPolicy,
+    ) -> Result<WeightedCovariance<S::Elem>, CovarianceError>
+    where
+        T: Data<Elem = S::Elem>;
+
+    // Huber M-estimate of the covariance, iteratively down-weighting the samples whose
+    // Mahalanobis distance under the current estimate exceeds `c`, e.g. the square root of a
+    // high quantile of the chi-squared distribution of as many degrees of freedom as channels
+    // Stops after `max_iter` iterations at the latest, flagging the last estimate as unconverged
+    fn compute_covariance_robust(
+        &self,
+        max_iter: usize,
+        c: S::Elem,
+    ) -> Result<RobustCovariance<S::Elem>, CovarianceError>;
 }
 
 impl<S> Covariance<S> for ArrayBase<S, Ix2>
+where
+    S: Data,
+    S::Elem: CovarianceElement,
+{
+    fn compute_covariance(&self, cov_t: CovarianceType) -> Array2<S::Elem> {
+        self.try_compute_covariance(cov_t)
+            .expect("Not enough samples !")
+    }
+
+    fn try_compute_covariance(
+        &self,
+        cov_t: CovarianceType,
+    ) -> Result<Array2<S::Elem>, CovarianceError> {
+        self.compute_covariance_opts(&cov_t.into())
+    }
+
+    fn compute_covariance_opts(
+        &self,
+        options: &CovOptions,
+    ) -> Result<Array2<S::Elem>, CovarianceError> {
+        let data = match options.orientation {
+            Orientation::VariablesInRows => self.view(),
+            Orientation::VariablesInColumns => self.t(),
+        };
+
+        let m_samples = data.ncols();
+        if m_samples <= options.ddof {
+            return Err(CovarianceError::NotEnoughSamples {
+                num_samples: m_samples,
+                ddof: options.ddof,
+            });
+        }
+
+        let scatter = if options.assume_centered {
+            data.dot(&data.t())
+        } else {
+            let centered = centered(&data);
+            centered.dot(&centered.t())
+        };
+
+        Ok(scatter / from_usize::<S::Elem>(m_samples - options.ddof))
+    }
+
+    fn compute_correlation(
+        &self,
+        zero_variance: ZeroVariance,
+    ) -> Result<Array2<S::Elem>, CovarianceError> {
+        // Normalization cancels the degrees of freedom out
+        let covariance = self.compute_covariance_opts(&CovOptions::new().ddof(0))?;
+        let std_devs = covariance.diag().mapv(Float::sqrt);
+
+        if zero_variance == ZeroVariance::Error {
+            if let Some(channel) = std_devs.iter().position(|&sd| sd.is_zero()) {
+                return Err(CovarianceError::ZeroVariance { channel });
+            }
+        }
+
+        let n = covariance.nrows();
+        let mut correlation = Array2::zeros((n, n));
+        for i in 0..n {
+            for j in i..n {
+                let one = S::Elem::one();
+                let value = if std_devs[i].is_zero() || std_devs[j].is_zero() {
+                    S::Elem::nan()
+                } else if i == j {
+                    one
+                } else {
+                    (covariance[[i, j]] / (std_devs[i] * std_devs[j]))
+                        .max(-one)
+                        .min(one)
+                };
+                correlation[[i, j]] = value;
+                correlation[[j, i]] = value;
+            }
+        }
+
+        Ok(correlation)
+    }
+
+    fn compute_covariance_ledoit_wolf(&self) -> Result<ShrunkCovariance<S::Elem>, CovarianceError> {
+        let (p_channels, m_samples) = self.dim();
+        let (centered, covariance) = population_covariance(self)?;
+        let squared = centered.mapv(|x| x * x);
+
+        let (n, p) = (m_samples as f64, p_channels as f64);
+        let mu = sum_f64(covariance.diag().iter().copied()) / p;
+        // Squared Frobenius distance of the covariance to the scaled identity
+        let delta = covariance
+            .indexed_iter()
+            .map(|((i, j), &c)| {
+                let target = if i == j { mu } else { 0.0 };
+                (to_f64(c) - target).powi(2)
+            })
+            .sum::<f64>()
+            / p;
+        // Variance of the entries of the covariance, bounded by their distance to the target
+        let beta = ((sum_f64(squared.dot(&squared.t()).iter().copied()) / n
+            - sum_f64(covariance.iter().map(|&c| c * c)))
+            / (p * n))
+            .min(delta);
+        let shrinkage = if beta == 0.0 { 0.0 } else { beta / delta };
+
+        Ok(shrink(covariance, shrinkage, mu))
+    }
+
+    fn compute_covariance_oas(&self) -> Result<ShrunkCovariance<S::Elem>, CovarianceError> {
+        let (p_channels, m_samples) = self.dim();
+        let (_, covariance) = population_covariance(self)?;
+
+        let (n, p) = (m_samples as f64, p_channels as f64);
+        let mu = sum_f64(covariance.diag().iter().copied()) / p;
+        let alpha = sum_f64(covariance.iter().map(|&c| c * c)) / (p * p);
+
+        let numerator = alpha + mu * mu;
+        let denominator = (n + 1.0) * (alpha - mu * mu / p);
+        let shrinkage = if denominator == 0.0 {
+            1.0
+        } else {
+            (numerator / denominator).clamp(0.0, 1.0)
+        };
+
+        Ok(shrink(covariance, shrinkage, mu))
+    }
+
+    fn compute_covariance_regularized(
+        &self,
+        alpha: S::Elem,
+    ) -> Result<Array2<S::Elem>, CovarianceError> {
+        let mut covariance = self.compute_covariance_opts(&CovOptions::new())?;
+        covariance
+            .diag_mut()
+            .iter_mut()
+            .for_each(|v| *v = *v + alpha);
+
+        Ok(covariance)
+    }
+
+    fn compute_covariance_weighted<T>(
+        &self,
+        weights: &ArrayBase<T, Ix1>,
+        nan_policy: NanPolicy,
+    ) -> Result<WeightedCovariance<S::Elem>, CovarianceError>
+    where
+        T: Data<Elem = S::Elem>,
+    {
+        if weights.len() != self.ncols() {
+            return Err(CovarianceError::WeightsMismatch {
+                weights: weights.len(),
+                samples: self.ncols(),
+            });
+        }
+        if let Some((sample, &weight)) = weights
+            .iter()
+            .enumerate()
+            .find(|(_, w)| w.is_nan() || **w < S::Elem::zero())
+        {
+            return Err(CovarianceError::InvalidWeight {
+                sample,
+                weight: to_f64(weight),
+            });
+        }
+
+        let mut dropped = 0;
+        // Samples of zero weight are left out altogether, so that their values, NaN or not,
+        // cannot spread
+        let kept = (0..self.ncols())
+            .filter(|&i| {
+                if nan_policy == NanPolicy::OmitSamples && self.column(i).iter().any(|v| v.is_nan())
+                {
+                    dropped += 1;
+                    false
+                } else {
+                    weights[i] > S::Elem::zero()
+                }
+            })
+            .collect::<Vec<usize>>();
+
+        let samples = self.select(Axis(1), &kept);
+        let weights = weights.select(Axis(0), &kept).mapv(to_f64);
+        let v1 = weights.sum();
+        if v1 == 0.0 {
+            return Err(CovarianceError::ZeroWeights);
+        }
+        let weights = weights / v1;
+        // Unbiased for reliability weights: the scatter is divided by 1 - sum(w^2), for w summing
+        // to one
+        let denominator = 1.0 - weights.iter().map(|&w| w * w).sum::<f64>();
+        let weights = weights.mapv(from_f64::<S::Elem>);
+        if denominator <= 0.0 {
+            return Err(CovarianceError::NotEnoughSamples {
+                num_samples: kept.len(),
+                ddof: 1,
+            });
+        }
+
+        let mean = samples.dot(&weights).insert_axis(Axis(1));
+        let centered = &samples - &mean;
+        let weighted = &centered * &weights.insert_axis(Axis(0));
+
+        Ok(WeightedCovariance {
+            covariance: weighted.dot(&centered.t()) / from_f64::<S::Elem>(denominator),
+            dropped,
+        })
+    }
+
+    fn compute_covariance_robust(
+        &self,
+        max_iter: usize,
+        c: S::Elem,
+    ) -> Result<RobustCovariance<S::Elem>, CovarianceError> {
+        let (p_channels, m_samples) = self.dim();
+        if m_samples <= p_channels {
+            return Err(CovarianceError::NotEnoughSamples {
+                num_samples: m_samples,
+                ddof: p_channels,
+            });
+        }
+
+        let c = to_f64(c);
+        let samples = DMatrix::from_fn(p_channels, m_samples, |i, j| to_f64(self[[i, j]]));
+        let mut weights = DVector::from_element(m_samples, 1.0);
+        let (mut mean, mut covariance) = weighted_moments(&samples, &weights);
+
+        let mut iterations = 0;
+        let mut converged = false;
+        while iterations < max_iter && !converged {
+            let inverse = covariance
+                .clone()
+                .cholesky()
+                .ok_or(CovarianceError::Singular)?
+                .inverse();
+
+            for (j, sample) in samples.column_iter().enumerate() {
+                let deviation = sample - &mean;
+                let distance = deviation.dot(&(&inverse * &deviation)).max(0.0).sqrt();
+                weights[j] = if distance <= c { 1.0 } else { c / distance };
+            }
+
+            let previous = covariance;
+            (mean, covariance) = weighted_moments(&samples, &weights);
+            iterations += 1;
+            converged = (&covariance - &previous).norm() <= ROBUST_TOLERANCE * previous.norm();
+        }
+
+        Ok(RobustCovariance {
+            covariance: Array2::from_shape_fn((p_channels, p_channels), |(i, j)| {
+                from_f64(covariance[(i, j)])
+            }),
+            weights: weights.iter().copied().map(from_f64).collect(),
+            converged,
+            iterations,
+        })
+    }
+}
+
+// Mean of the `samples` (channels x samples) of Huber `weights`, along with their scatter of
+// squared weights, normalized as such
+fn weighted_moments(
+    samples: &DMatrix<f64>,
+    weights: &DVector<f64>,
+) -> (DVector<f64>, DMatrix<f64>) {
+    let mean = samples * weights / weights.sum();
+    let mut centered = samples.clone();
+    for (mut column, &w) in centered.column_iter_mut().zip(weights.iter()) {
+        column -= &mean;
+        column *= w;
+    }
+
+    let covariance = &centered * centered.transpose() / weights.map(|w| w * w).sum();
+
+    (mean, covariance)
+}
+
+// `data` minus the mean of each channel
+fn centered<S>(data: &ArrayBase<S, Ix2>) -> Array2<S::Elem>
+where
+    S: Data,
+    S::Elem: CovarianceElement,
+{
+    let mean = data.mean_axis(Axis(1)).unwrap().insert_axis(Axis(1));
+    data - &mean
+}
+
+// `data` minus the mean of each channel, along with its population covariance, shared by the
+// shrinkage estimators
+fn population_covariance<A, S>(
+    data: &ArrayBase<S, Ix2>,
+) -> Result<(Array2<A>, Array2<A>), CovarianceError>
+where
+    A: CovarianceElement,
+    S: Data<Elem = A>,
+{
+    let m_samples = data.ncols();
+    if m_samples == 0 {
+        return Err(CovarianceError::NotEnoughSamples {
+            num_samples: 0,
+            ddof: 0,
+        });
+    }
+
+    let centered = centered(data);
+    let covariance = centered.dot(&centered.t()) / from_usize::<A>(m_samples);
+
+    Ok((centered, covariance))
+}
+
+// `covariance` shrunk by `shrinkage` toward the identity scaled by `mu`
+fn shrink<A: CovarianceElement>(
+    covariance: Array2<A>,
+    shrinkage: f64,
+    mu: f64,
+) -> ShrunkCovariance<A> {
+    let mut covariance = covariance * from_f64::<A>(1.0 - shrinkage);
+    let loading = from_f64::<A>(shrinkage * mu);
+    covariance
+        .diag_mut()
+        .iter_mut()
+        .for_each(|v| *v = *v + loading);
+
+    ShrunkCovariance {
+        covariance,
+        shrinkage: from_f64(shrinkage),
+    }
+}
+
+fn to_f64<A: CovarianceElement>(value: A) -> f64 {
+    value.to_f64().unwrap()
+}
+
+fn from_f64<A: CovarianceElement>(value: f64) -> A {
+    A::from_f64(value).unwrap()
+}
+
+fn from_usize<A: CovarianceElement>(value: usize) -> A {
+    A::from_usize(value).unwrap()
+}
+
+fn sum_f64<A: CovarianceElement>(values: impl Iterator<Item = A>) -> f64 {
+    values.map(to_f64).sum()
+}
+
+// Covariance of data arriving in chunks (channels x samples), e.g. from a stream, without holding
+// it whole in memory
+// The mean and scatter matrix are updated in f64, whatever the element type `A` of the data,
+// merging those of each chunk as per
+// Chan et al., so that chunks of any size yield the covariance of the concatenated data
+// * https://doi.org/10.1007/978-3-642-51461-6_3
+#[derive(Clone, Debug)]
+pub struct IncrementalCovariance<A = f32> {
+    num_samples: usize,
+    mean: Array1<f64>,
+    scatter: Array2<f64>,
+    element: PhantomData<A>,
+}
+
+impl<A: CovarianceElement> IncrementalCovariance<A> {
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            num_samples: 0,
+            mean: Array1::zeros(num_channels),
+            scatter: Array2::zeros((num_channels, num_channels)),
+            element: PhantomData,
+        }
+    }
+
+    pub fn num_samples(&self) -> usize {
+        self.num_samples
+    }
+
+    pub fn update<S>(&mut self, chunk: &ArrayBase<S, Ix2>)
+    where
+        S: Data<Elem = A>,
+    {
+        assert_eq!(
+            chunk.nrows(),
+            self.mean.len(),
+            "Chunk must have one row per channel !"
+        );
+        if chunk.ncols() == 0 {
+            return;
+        }
+
+        let chunk = chunk.mapv(to_f64);
+        let mean = chunk.mean_axis(Axis(1)).unwrap();
+        let centered = &chunk - &mean.view().insert_axis(Axis(1));
+
+        self.merge(&IncrementalCovariance {
+            num_samples: chunk.ncols(),
+            scatter: centered.dot(&centered.t()),
+            mean,
+            element: PhantomData,
+        });
+    }
+
+    // Combine the partial estimate of `other`, e.g. computed by another worker
+    pub fn merge(&mut self, other: &IncrementalCovariance<A>) {
+        assert_eq!(
+            other.mean.len(),
+            self.mean.len(),
+            "Estimates must be of the same number of channels !"
+        );
+        if other.num_samples == 0 {
+            return;
+        }
+
+        let (n_a, n_b) = (self.num_samples as f64, other.num_samples as f64);
+        let n = n_a + n_b;
+        let delta = &other.mean - &self.mean;
+
+        let outer = delta
+            .view()
+            .insert_axis(Axis(1))
+            .dot(&delta.view().insert_axis(Axis(0)));
+        self.scatter = &self.scatter + &other.scatter + outer * (n_a * n_b / n);
+        self.mean = &self.mean + delta * (n_b / n);
+        self.num_samples += other.num_samples;
+    }
+
+    // Covariance of every sample seen so far
+    pub fn finalize(&self, cov_t: CovarianceType) -> Result<Array2<A>, CovarianceError> {
+        let ddof = cov_t as usize;
+        if self.num_samples <= ddof {
+            return Err(CovarianceError::NotEnoughSamples {
+                num_samples: self.num_samples,
+                ddof,
+            });
+        }
+
+        Ok((&self.scatter / (self.num_samples - ddof) as f64).mapv(from_f64))
+    }
+}
+
+// Channel that is constant over an epoch, e.g. disconnected or clipped
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConstantChannel {
+    pub epoch: usize,
+    pub channel: usize,
+}
+
+// Covariances of epochs
+#[derive(Clone, Debug)]
+pub struct EpochCovariances<A = f32> {
+    // Oriented epochs x channels x channels
+    pub covariances: Array3<A>,
+    // Channels of zero variance within an epoch, whose correlations are then undefined
+    pub constant: Vec<ConstantChannel>,
+}
+
+// Covariance of each of the `epochs` (epochs x channels x samples) by `estimator`, optionally
+// normalized by its trace, as needed by CSP or Riemannian classifiers
+// With the `rayon` feature, epochs are processed in parallel
+pub fn compute_covariances_epochs<S>(
+    epochs: &ArrayBase<S, Ix3>,
+    estimator: Estimator,
+    trace_normalize: bool,
+) -> Result<EpochCovariances<S::Elem>, CovarianceError>
+where
+    S: Data,
+    S::Elem: CovarianceElement,
+{
+    let (num_epochs, num_channels, _) = epochs.dim();
+    let epochs = epochs.view();
+    let covariance = |e: usize| {
+        estimate(&epochs.index_axis(Axis(0), e), estimator).map(|mut covariance| {
+            let trace = covariance.diag().sum();
+            if trace_normalize && trace > S::Elem::zero() {
+                covariance.mapv_inplace(|v| v / trace);
+            }
+            covariance
+        })
+    };
+
+    #[cfg(feature = "rayon")]
+    let estimates = (0..num_epochs)
+        .into_par_iter()
+        .map(covariance)
+        .collect::<Result<Vec<Array2<S::Elem>>, CovarianceError>>()?;
+    #[cfg(not(feature = "rayon"))]
+    let estimates = (0..num_epochs)
+        .map(covariance)
+        .collect::<Result<Vec<Array2<S::Elem>>, CovarianceError>>()?;
+
+    let mut covariances = Array3::zeros((num_epochs, num_channels, num_channels));
+    let mut constant = Vec::new();
+    for (e, estimate) in estimates.iter().enumerate() {
+        covariances.index_axis_mut(Axis(0), e).assign(estimate);
+        // Shrinkage toward the identity hides constant channels, so they are told by their data
+        let data = epochs.index_axis(Axis(0), e);
+        constant.extend(
+            data.rows()
+                .into_iter()
+                .enumerate()
+                .filter(|(_, row)| row.iter().all(|&v| v == row[0]))
+                .map(|(channel, _)| ConstantChannel { epoch: e, channel }),
+        );
+    }
+
+    Ok(EpochCovariances {
+        covariances,
+        constant,
+    })
+}
+
+fn estimate<A: CovarianceElement>(
+    data: &ArrayView2<A>,
+    estimator: Estimator,
+) -> Result<Array2<A>, CovarianceError> {
+    match estimator {
+        Estimator::Population => data.compute_covariance_opts(&CovarianceType::Population.into()),
+        Estimator::Sample => data.compute_covariance_opts(&CovarianceType::Sample.into()),
+        Estimator::LedoitWolf => data
+            .compute_covariance_ledoit_wolf()
+            .map(|shrunk| shrunk.covariance),
+        Estimator::Oas => data
+            .compute_covariance_oas()
+            .map(|shrunk| shrunk.covariance),
+    }
+}
+
+// Covariance loaded on its diagonal
+#[derive(Clone, Debug)]
+pub struct RegularizedCovariance<A = f32> {
+    pub covariance: Array2<A>,
+    // Value added to each variance
+    pub loading: A,
+}
+
+// Ratio of the largest to the smallest eigenvalue of the covariance `cov` (channels x channels),
+// infinite if it is singular
+// Inverting covariances of large condition numbers, e.g. for whitening or beamforming, amplifies
+// their noise
+pub fn condition_number<S>(cov: &ArrayBase<S, Ix2>) -> S::Elem
+where
+    S: Data,
+    S::Elem: CovarianceElement,
+{
+    let (smallest, largest) = eigenvalue_range(cov);
+    if smallest <= 0.0 {
+        S::Elem::infinity()
+    } else {
+        from_f64(largest / smallest)
+    }
+}
+
+// The covariance `cov` (channels x channels) loaded with the smallest value on its diagonal that
+// brings its condition number down to `max_cond`, if needed at all
+pub fn regularize_to_condition<S>(
+    cov: &ArrayBase<S, Ix2>,
+    max_cond: S::Elem,
+) -> RegularizedCovariance<S::Elem>
+where
+    S: Data,
+    S::Elem: CovarianceElement,
+{
+    let max_cond = to_f64(max_cond);
+    assert!(max_cond > 1.0, "Condition number must exceed 1 !");
+
+    // Loading shifts every eigenvalue, so (largest + loading) / (smallest + loading) <= max_cond
+    let (smallest, largest) = eigenvalue_range(cov);
+    let loading =
+        from_f64::<S::Elem>(((largest - max_cond * smallest) / (max_cond - 1.0)).max(0.0));
+
+    let mut covariance = cov.to_owned();
+    covariance
+        .diag_mut()
+        .iter_mut()
+        .for_each(|v| *v = *v + loading);
+
+    RegularizedCovariance {
+        covariance,
+        loading,
+    }
+}
+
+// Number of eigenvalues of the covariance `cov` (channels x channels) exceeding `tol` times the
+// largest one, e.g. one less than the number of channels after re-referencing to the average
+pub fn effective_rank<S>(cov: &ArrayBase<S, Ix2>, tol: S::Elem) -> usize
+where
+    S: Data,
+    S::Elem: CovarianceElement,
+{
+    let eigenvalues = eigenvalues(cov);
+    let largest = eigenvalues.iter().copied().fold(0.0, f64::max);
+
+    eigenvalues
+        .iter()
+        .filter(|&&l| l > largest * to_f64(tol))
+        .count()
+}
+
+// Smallest and largest eigenvalues of the symmetric matrix `cov`
+fn eigenvalue_range<S>(cov: &ArrayBase<S, Ix2>) -> (f64, f64)
+where
+    S: Data,
+    S::Elem: CovarianceElement,
+{
+    eigenvalues(cov).iter().fold(
+        (f64::INFINITY, f64::NEG_INFINITY),
+        |(smallest, largest), &l| (smallest.min(l), largest.max(l)),
+    )
+}
+
+fn eigenvalues<S>(cov: &ArrayBase<S, Ix2>) -> Vec<f64>
+where
+    S: Data,
+    S::Elem: CovarianceElement,
+{
+    assert_eq!(cov.nrows(), cov.ncols(), "Covariance must be square !");
+
+    let n = cov.nrows();
+    SymmetricEigen::new(DMatrix::from_fn(n, n, |i, j| to_f64(cov[[i, j]])))
+        .eigenvalues
+        .iter()
+        .copied()
+        .collect()
+}
+
+// Covariance of the `data` (channels x samples), sampled at `fs` Hz, within each of the frequency
+// `bands` (low, high), in Hz, e.g. for filter-bank CSP
+//
+// Each band is band-passed without phase shift, by a filter whose transition band is a quarter of
+// its lower edge (2 Hz at least), so that strong neighbouring rhythms do not leak into it
+// The samples within half a filter length of either edge, distorted by filtering, are then left
+// out of its covariance
+// Returns the covariances in the order of the `bands` (bands x channels x channels)
+pub fn band_covariances<S>(
+    data: &ArrayBase<S, Ix2>,
+    fs: f32,
+    bands: &[(f32, f32)],
+    estimator: Estimator,
+    nyquist_policy: NyquistPolicy,
+) -> Result<Array3<f32>, CovarianceError>
 where
     S: Data<Elem = f32>,
 {
-    fn compute_covariance(&self, cov_t: CovarianceType) -> Array2<f32> {
-        let m_samples = self.dim().1;
+    let nyquist = fs / 2.0;
+    let num_channels = data.nrows();
+    let mut covariances = Array3::zeros((bands.len(), num_channels, num_channels));
+
+    for (b, &(low, high)) in bands.iter().enumerate() {
+        let high = match nyquist_policy {
+            NyquistPolicy::Clamp => high.min(nyquist),
+            NyquistPolicy::Error => high,
+        };
+        if low <= 0.0 || low >= high || high > nyquist {
+            return Err(CovarianceError::BandOutOfRange { low, high, nyquist });
+        }
+
+        // Hamming windows have a transition band of about 3.3 / num_taps cycles per sample
+        let transition = (0.25 * low).max(2.0).min(low);
+        let filter = FIRFilter::bandpass((3.3 * fs / transition).ceil() as usize, low, high, fs);
+        let edge = (filter.num_taps() / 2).min(data.ncols() / 2);
+        let mut filtered = Array2::zeros((num_channels, data.ncols() - 2 * edge));
+        for (mut row, channel) in filtered.outer_iter_mut().zip(data.outer_iter()) {
+            let band = filter.process_zero_phase(&channel);
+            row.assign(&band.slice(s![edge..band.len() - edge]));
+        }
+
+        covariances
+            .index_axis_mut(Axis(0), b)
+            .assign(&estimate(&filtered.view(), estimator)?);
+    }
+
+    Ok(covariances)
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    // Standard normal values, by the Box-Muller transform
+    fn gaussian(generator: &mut Lcg) -> f64 {
+        let (u, v) = (
+            1.0 - generator.next_f32() as f64,
+            generator.next_f32() as f64,
+        );
+        (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * v).cos()
+    }
+
+    // Gaussian data (channels x samples) of 3 channels, the second correlated with the first
+    fn correlated(num_samples: usize, seed: u64) -> Array2<f64> {
+        let mut generator = Lcg::new(seed);
+        let mut data = Array2::from_shape_fn((3, num_samples), |_| gaussian(&mut generator));
+        let first = data.row(0).to_owned();
+        data.row_mut(1).scaled_add(0.8, &first);
+        data.row_mut(2).mapv_inplace(|x| 2.0 * x + 5.0);
+
+        data
+    }
+
+    // Independent standard normal data (channels x samples)
+    fn white(num_channels: usize, num_samples: usize, seed: u64) -> Array2<f64> {
+        let mut generator = Lcg::new(seed);
+        Array2::from_shape_fn((num_channels, num_samples), |_| gaussian(&mut generator))
+    }
+
+    fn max_difference<A: CovarianceElement>(a: &Array2<A>, b: &Array2<A>) -> f64 {
+        a.iter()
+            .zip(b)
+            .map(|(&x, &y)| to_f64(x - y).abs())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn centering_is_skipped_for_centered_data() {
+        let data = centered(&correlated(500, 1));
+        let centering = data.compute_covariance_opts(&CovOptions::new()).unwrap();
+        let assuming = data
+            .compute_covariance_opts(&CovOptions::new().assume_centered(true))
+            .unwrap();
+        assert!(max_difference(&centering, &assuming) < 1e-12);
+
+        // Which it does not for data of a non-zero mean
+        let data = correlated(500, 1);
+        let centering = data.compute_covariance_opts(&CovOptions::new()).unwrap();
+        let assuming = data
+            .compute_covariance_opts(&CovOptions::new().assume_centered(true))
+            .unwrap();
+        assert!((assuming[[2, 2]] - centering[[2, 2]] - 25.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn ddof_divides_the_scatter_matrix() {
+        let data = array![[1.0f64, 2.0, 3.0, 6.0], [0.0, 1.0, 0.0, 1.0]];
+        // Scatter: [[14, 2], [2, 1]]
+        for (ddof, denominator) in [(0, 4.0), (1, 3.0), (3, 1.0)] {
+            let covariance = data
+                .compute_covariance_opts(&CovOptions::new().ddof(ddof))
+                .unwrap();
+            assert!(
+                max_difference(
+                    &covariance,
+                    &(array![[14.0, 2.0], [2.0, 1.0]] / denominator)
+                ) < 1e-12
+            );
+        }
+
+        assert_eq!(
+            data.compute_covariance(CovarianceType::Sample),
+            data.compute_covariance_opts(&CovOptions::new().ddof(1))
+                .unwrap()
+        );
+        assert_eq!(
+            data.try_compute_covariance(CovarianceType::Population)
+                .unwrap(),
+            data.compute_covariance_opts(&CovOptions::new().ddof(0))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn too_few_samples_fail() {
+        let data = array![[1.0f32, 2.0], [3.0, 5.0]];
+
+        assert!(matches!(
+            data.compute_covariance_opts(&CovOptions::new().ddof(2)),
+            Err(CovarianceError::NotEnoughSamples {
+                num_samples: 2,
+                ddof: 2
+            })
+        ));
+        assert!(matches!(
+            Array2::<f32>::zeros((2, 1)).try_compute_covariance(CovarianceType::Sample),
+            Err(CovarianceError::NotEnoughSamples {
+                num_samples: 1,
+                ddof: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn correlation_of_proportional_channels_is_one() {
+        let mut data = correlated(2000, 2);
+        let first = data.row(0).to_owned();
+        data.row_mut(1).assign(&(&first * 3.0 - 1.0));
+        let correlation = data.compute_correlation(ZeroVariance::Error).unwrap();
+
+        assert_eq!(correlation.diag().to_vec(), vec![1.0; 3]);
+        assert_eq!(correlation, correlation.t());
+        assert!((correlation[[0, 1]] - 1.0).abs() < 1e-12);
+        // The third channel is independent of the others
+        assert!(correlation[[0, 2]].abs() < 0.1 && correlation[[1, 2]].abs() < 0.1);
+
+        // Anti-correlated channels
+        data.row_mut(1).assign(&(-&first));
+        let correlation = data.compute_correlation(ZeroVariance::Error).unwrap();
+        assert!((correlation[[0, 1]] + 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn correlations_of_constant_channels_are_undefined() {
+        let mut data = correlated(100, 3).mapv(|x| x as f32);
+        data.row_mut(1).fill(4.0);
+
+        let correlation = data.compute_correlation(ZeroVariance::Nan).unwrap();
+        for i in 0..3 {
+            assert!(correlation[[1, i]].is_nan() && correlation[[i, 1]].is_nan());
+        }
+        assert_eq!((correlation[[0, 0]], correlation[[2, 2]]), (1.0, 1.0));
+        assert!(matches!(
+            data.compute_correlation(ZeroVariance::Error),
+            Err(CovarianceError::ZeroVariance { channel: 1 })
+        ));
+    }
+
+    #[test]
+    fn ledoit_wolf_barely_shrinks_plenty_of_samples() {
+        // Unlike white data, whose covariance is the very target of the shrinkage
+        let shrunk = correlated(20000, 4)
+            .compute_covariance_ledoit_wolf()
+            .unwrap();
+        assert!(shrunk.shrinkage < 0.01, "{}", shrunk.shrinkage);
+
+        let shrunk = white(4, 20000, 4).compute_covariance_ledoit_wolf().unwrap();
+        assert!(shrunk.shrinkage > 0.5, "{}", shrunk.shrinkage);
+    }
+
+    #[test]
+    fn ledoit_wolf_conditions_few_samples() {
+        let data = white(20, 25, 5);
+        let sample = data.compute_covariance(CovarianceType::Population);
+        let shrunk = data.compute_covariance_ledoit_wolf().unwrap();
+
+        assert!(shrunk.shrinkage > 0.2 && shrunk.shrinkage <= 1.0);
+        let (before, after) = (
+            condition_number(&sample),
+            condition_number(&shrunk.covariance),
+        );
+        assert!(after * 10.0 < before, "{before} {after}");
+        // The trace is that of the population covariance
+        assert!((shrunk.covariance.diag().sum() - sample.diag().sum()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn regularization_loads_the_diagonal() {
+        let data = correlated(200, 6);
+        let covariance = data.compute_covariance(CovarianceType::Sample);
+        let regularized = data.compute_covariance_regularized(0.5).unwrap();
+
+        assert!(max_difference(&(regularized - &covariance), &(Array2::eye(3) * 0.5)) < 1e-12);
+    }
+
+    #[test]
+    fn oas_matches_the_scikit_learn_estimate() {
+        let data = array![
+            [0.5f64, -1.2, 0.3, 2.1, -0.7, 0.0, 1.4, -0.9],
+            [1.1, 0.4, -0.6, 0.8, -1.5, 0.2, 0.9, -0.3],
+            [-0.2, 0.7, 1.6, -1.1, 0.5, -0.8, 0.1, 1.3]
+        ];
+        let shrunk = data.compute_covariance_oas().unwrap();
+
+        // As per `sklearn.covariance.oas(data.T)`, in double precision
+        assert!((shrunk.shrinkage - 0.7516446524627474).abs() < 1e-12);
+        let expected = array![
+            [
+                0.9405267671832351,
+                0.12301350807704545,
+                -0.14105807629654898
+            ],
+            [
+                0.12301350807704545,
+                0.8246146698248331,
+                -0.10097197098311428
+            ],
+            [
+                -0.14105807629654898,
+                -0.10097197098311428,
+                0.8526710629919321
+            ]
+        ];
+        assert!(max_difference(&shrunk.covariance, &expected) < 1e-12);
+
+        let single = data.mapv(|x| x as f32).compute_covariance_oas().unwrap();
+        assert!((single.shrinkage - 0.7516446).abs() < 1e-5);
+    }
+
+    #[test]
+    fn oas_shrinkage_is_clamped_to_1() {
+        for (num_channels, num_samples) in [(10, 3), (20, 25), (3, 5000)] {
+            let shrunk = white(num_channels, num_samples, 7)
+                .compute_covariance_oas()
+                .unwrap();
+            assert!((0.0..=1.0).contains(&shrunk.shrinkage));
+        }
+        // Two samples of two channels yield a coefficient of 4 / 3
+        let shrunk = array![[0.0f64, 1.0], [0.0, 3.0]]
+            .compute_covariance_oas()
+            .unwrap();
+        assert_eq!(shrunk.shrinkage, 1.0);
+        // Covariances proportional to the identity are their own target
+        let shrunk = array![[1.0f64, -1.0, 1.0, -1.0], [1.0, 1.0, -1.0, -1.0]]
+            .compute_covariance_oas()
+            .unwrap();
+        assert_eq!(shrunk.shrinkage, 1.0);
+        assert_eq!(shrunk.covariance, Array2::<f64>::eye(2));
+    }
+
+    #[test]
+    fn zero_weights_leave_corrupted_samples_out() {
+        let clean = correlated(300, 8);
+        let mut corrupted = clean.clone();
+        corrupted.slice_mut(s![.., 100..150]).fill(1e6);
+        corrupted[[1, 120]] = f64::NAN;
+        let weights =
+            Array1::from_shape_fn(300, |i| if (100..150).contains(&i) { 0.0 } else { 1.0 });
+
+        let weighted = corrupted
+            .compute_covariance_weighted(&weights, NanPolicy::Propagate)
+            .unwrap();
+        let kept = (0..300)
+            .filter(|i| !(100..150).contains(i))
+            .collect::<Vec<usize>>();
+        let expected = clean
+            .select(Axis(1), &kept)
+            .compute_covariance(CovarianceType::Sample);
+        assert!(max_difference(&weighted.covariance, &expected) < 1e-9);
+        assert_eq!(weighted.dropped, 0);
+
+        // Equal weights, of whatever scale, give the sample covariance
+        let weighted = clean
+            .compute_covariance_weighted(&Array1::from_elem(300, 7.0), NanPolicy::Propagate)
+            .unwrap();
+        let expected = clean.compute_covariance(CovarianceType::Sample);
+        assert!(max_difference(&weighted.covariance, &expected) < 1e-9);
+    }
+
+    #[test]
+    fn samples_holding_nan_are_omitted_or_propagate() {
+        let mut data = correlated(100, 9);
+        data[[0, 10]] = f64::NAN;
+        data[[2, 50]] = f64::NAN;
+        let weights = Array1::ones(100);
+
+        let omitted = data
+            .compute_covariance_weighted(&weights, NanPolicy::OmitSamples)
+            .unwrap();
+        assert_eq!(omitted.dropped, 2);
+        assert!(omitted.covariance.iter().all(|c| c.is_finite()));
+
+        let propagated = data
+            .compute_covariance_weighted(&weights, NanPolicy::Propagate)
+            .unwrap();
+        assert!(propagated.covariance[[0, 0]].is_nan());
+    }
+
+    #[test]
+    fn invalid_weights_fail() {
+        let data = correlated(10, 10);
+
+        assert!(matches!(
+            data.compute_covariance_weighted(&Array1::zeros(10), NanPolicy::Propagate),
+            Err(CovarianceError::ZeroWeights)
+        ));
+        assert!(matches!(
+            data.compute_covariance_weighted(&Array1::ones(9), NanPolicy::Propagate),
+            Err(CovarianceError::WeightsMismatch {
+                weights: 9,
+                samples: 10
+            })
+        ));
+        let mut weights = Array1::ones(10);
+        weights[4] = -1.0;
+        assert!(matches!(
+            data.compute_covariance_weighted(&weights, NanPolicy::Propagate),
+            Err(CovarianceError::InvalidWeight { sample: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn chunks_of_any_size_give_the_batch_covariance() {
+        let data = correlated(1000, 11).mapv(|x| x as f32);
+        let mut incremental = IncrementalCovariance::<f32>::new(3);
+        let mut start = 0;
+        for size in [1, 7, 0, 250, 2, 500].into_iter().cycle() {
+            let end = (start + size).min(1000);
+            incremental.update(&data.slice(s![.., start..end]));
+            start = end;
+            if start == 1000 {
+                break;
+            }
+        }
+        assert_eq!(incremental.num_samples(), 1000);
+
+        for (cov_t, ddof) in [(CovarianceType::Population, 0), (CovarianceType::Sample, 1)] {
+            let expected = data
+                .compute_covariance_opts(&CovOptions::new().ddof(ddof))
+                .unwrap();
+            assert!(max_difference(&incremental.finalize(cov_t).unwrap(), &expected) < 1e-4);
+        }
+    }
+
+    #[test]
+    fn partial_estimates_merge_into_the_batch_covariance() {
+        let data = correlated(600, 12);
+        let (mut first, mut second) = (
+            IncrementalCovariance::<f64>::new(3),
+            IncrementalCovariance::<f64>::new(3),
+        );
+        first.update(&data.slice(s![.., ..100]));
+        second.update(&data.slice(s![.., 100..]));
+        first.merge(&second);
+        first.merge(&IncrementalCovariance::new(3));
+
+        let expected = data.compute_covariance(CovarianceType::Sample);
+        let merged = first.finalize(CovarianceType::Sample).unwrap();
+        assert!(max_difference(&merged, &expected) < 1e-10);
+
+        assert!(matches!(
+            IncrementalCovariance::<f64>::new(3).finalize(CovarianceType::Sample),
+            Err(CovarianceError::NotEnoughSamples {
+                num_samples: 0,
+                ddof: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn epoch_covariances_match_the_single_epoch_estimators() {
+        let epochs = Array3::from_shape_fn((10, 3, 200), |(e, c, t)| {
+            let x =
+                (t as f32 * 0.37 * (e + 1) as f32).sin() + (t as f32 * 0.11 * (c + 1) as f32).cos();
+            x * (c + 1) as f32
+        });
+
+        for estimator in [
+            Estimator::Population,
+            Estimator::Sample,
+            Estimator::LedoitWolf,
+            Estimator::Oas,
+        ] {
+            let result = compute_covariances_epochs(&epochs, estimator, false).unwrap();
+            assert_eq!(result.covariances.dim(), (10, 3, 3));
+            assert!(result.constant.is_empty());
+            for (e, epoch) in epochs.outer_iter().enumerate() {
+                let expected = match estimator {
+                    Estimator::Population => epoch.compute_covariance(CovarianceType::Population),
+                    Estimator::Sample => epoch.compute_covariance(CovarianceType::Sample),
+                    Estimator::LedoitWolf => {
+                        epoch.compute_covariance_ledoit_wolf().unwrap().covariance
+                    }
+                    Estimator::Oas => epoch.compute_covariance_oas().unwrap().covariance,
+                };
+                assert_eq!(result.covariances.index_axis(Axis(0), e), expected);
+            }
+
+            let normalized = compute_covariances_epochs(&epochs, estimator, true).unwrap();
+            for covariance in normalized.covariances.outer_iter() {
+                assert!((covariance.diag().sum() - 1.0).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn constant_channels_of_epochs_are_reported() {
+        let mut epochs =
+            Array3::from_shape_fn((4, 3, 50), |(e, c, t)| ((t * (c + 2) + e) % 7) as f64);
+        epochs.slice_mut(s![2, 1, ..]).fill(3.0);
+        let result = compute_covariances_epochs(&epochs, Estimator::LedoitWolf, false).unwrap();
+
+        assert_eq!(
+            result.constant,
+            vec![ConstantChannel {
+                epoch: 2,
+                channel: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn single_and_double_precision_agree() {
+        let data = correlated(2000, 14);
+        let single = data.mapv(|x| x as f32);
+
+        let (a, b) = (
+            data.compute_covariance(CovarianceType::Sample),
+            single.compute_covariance(CovarianceType::Sample),
+        );
+        assert!(a.iter().zip(&b).all(|(&a, &b)| (a - b as f64).abs() < 1e-4));
+
+        let (a, b) = (
+            data.compute_covariance_ledoit_wolf().unwrap(),
+            single.compute_covariance_ledoit_wolf().unwrap(),
+        );
+        assert!((a.shrinkage - b.shrinkage as f64).abs() < 1e-4);
+        assert!(a
+            .covariance
+            .iter()
+            .zip(&b.covariance)
+            .all(|(&a, &b)| (a - b as f64).abs() < 1e-4));
+    }
+
+    #[test]
+    fn both_orientations_give_the_same_covariance() {
+        let data = correlated(300, 15);
+        let rows = data.compute_covariance_opts(&CovOptions::new()).unwrap();
+
+        // Samples x channels, as a transposed view of the same memory
+        let columns = data.t();
+        assert_eq!(columns.as_ptr(), data.as_ptr());
+        let options = CovOptions::new().orientation(Orientation::VariablesInColumns);
+        assert_eq!(columns.compute_covariance_opts(&options).unwrap(), rows);
+        // Samples x channels in standard layout
+        let owned = columns.as_standard_layout().into_owned();
+        assert!(max_difference(&owned.compute_covariance_opts(&options).unwrap(), &rows) < 1e-12);
+
+        assert_eq!(CovOptions::new().orientation, Orientation::VariablesInRows);
+    }
+
+    #[test]
+    fn fewer_samples_than_channels_give_a_rank_deficient_covariance() {
+        // 3 samples of 5 channels span a plane once centered
+        let cov = white(5, 3, 16)
+            .compute_covariance_opts(&CovOptions::new())
+            .unwrap();
+        assert_eq!(effective_rank(&cov, 1e-10), 2);
+        assert_eq!(effective_rank(&Array2::<f64>::eye(5), 1e-10), 5);
+        assert!(condition_number(&cov) > 1e10);
+        assert_eq!(condition_number(&Array2::<f64>::eye(5)), 1.0);
+
+        let regularized = regularize_to_condition(&cov, 100.0);
+        assert!(regularized.loading > 0.0);
+        assert!((condition_number(&regularized.covariance) - 100.0).abs() < 1e-6);
+        assert_eq!(effective_rank(&regularized.covariance, 1e-10), 5);
+        let mut loaded = cov.clone();
+        loaded.diag_mut().mapv_inplace(|v| v + regularized.loading);
+        assert!(max_difference(&loaded, &regularized.covariance) == 0.0);
+        // Any less loading leaves the condition number above the target
+        let mut under = cov.clone();
+        under
+            .diag_mut()
+            .mapv_inplace(|v| v + 0.99 * regularized.loading);
+        assert!(condition_number(&under) > 100.0);
+    }
+
+    #[test]
+    fn well_conditioned_covariance_is_not_loaded() {
+        let cov = array![[2.0, 0.5], [0.5, 1.0]];
+        let regularized = regularize_to_condition(&cov, 100.0);
+        assert_eq!(regularized.loading, 0.0);
+        assert_eq!(regularized.covariance, cov);
+    }
+
+    // Frobenius norm of the difference of `a` and `b`
+    fn frobenius(a: &Array2<f64>, b: &Array2<f64>) -> f64 {
+        (a - b).mapv(|d| d * d).sum().sqrt()
+    }
+
+    #[test]
+    fn gross_outliers_barely_perturb_the_robust_covariance() {
+        let clean = correlated(1000, 17);
+        let reference = clean.compute_covariance_opts(&CovOptions::new()).unwrap();
+
+        // 2% of the samples replaced with artifacts of 50 standard deviations
+        let mut data = clean.clone();
+        let mut generator = Lcg::new(18);
+        for t in (0..1000).step_by(50) {
+            for c in 0..3 {
+                data[[c, t]] = 50.0
+                    * gaussian(&mut generator).signum()
+                    * (1.0 + gaussian(&mut generator).abs());
+            }
+        }
+
+        let sample = data.compute_covariance_opts(&CovOptions::new()).unwrap();
+        // Square root of the 0.99 quantile of the chi-squared distribution of 3 degrees of freedom
+        let robust = data
+            .compute_covariance_robust(100, 11.34f64.sqrt())
+            .unwrap();
+        assert!(robust.converged);
+        assert!(robust.iterations > 1);
+
+        let (sample_error, robust_error) = (
+            frobenius(&sample, &reference),
+            frobenius(&robust.covariance, &reference),
+        );
+        assert!(
+            robust_error < 0.1 * sample_error,
+            "{robust_error} {sample_error}"
+        );
+        // The outliers are down-weighted, most other samples left untouched
+        for (t, &weight) in robust.weights.iter().enumerate() {
+            if t % 50 == 0 {
+                assert!(weight < 0.2, "{t} {weight}");
+            }
+        }
+        assert!(robust.weights.iter().filter(|&&w| w == 1.0).count() > 900);
+    }
+
+    #[test]
+    fn robust_covariance_flags_unconverged_estimates() {
+        let mut data = correlated(200, 19);
+        data[[0, 0]] = 100.0;
+        let robust = data.compute_covariance_robust(1, 3.0).unwrap();
+        assert!(!robust.converged);
+        assert_eq!(robust.iterations, 1);
+        assert_eq!(robust.weights.len(), 200);
+
+        assert!(matches!(
+            white(3, 3, 20).compute_covariance_robust(10, 3.0),
+            Err(CovarianceError::NotEnoughSamples { .. })
+        ));
+    }
+
+    #[test]
+    fn band_covariances_show_the_coupling_within_its_band() {
+        // 4 channels of white noise at 250 Hz, the first two sharing a strong 10 Hz rhythm
+        let fs = 250.0;
+        let mut generator = Lcg::new(21);
+        let data = Array2::from_shape_fn((4, 1500), |(c, t)| {
+            let alpha = (std::f32::consts::TAU * 10.0 * t as f32 / fs).sin();
+            let noise = gaussian(&mut generator) as f32;
+            if c < 2 {
+                5.0 * alpha + noise
+            } else {
+                noise
+            }
+        });
+
+        let bands = [(8.0, 13.0), (15.0, 30.0)];
+        let covariances =
+            band_covariances(&data, fs, &bands, Estimator::Sample, NyquistPolicy::Error).unwrap();
+        assert_eq!(covariances.dim(), (2, 4, 4));
+
+        let correlation = |b: usize, i: usize, j: usize| {
+            let cov = covariances.index_axis(Axis(0), b);
+            cov[[i, j]] / (cov[[i, i]] * cov[[j, j]]).sqrt()
+        };
+        assert!(correlation(0, 0, 1) > 0.95, "{}", correlation(0, 0, 1));
+        assert!(correlation(1, 0, 1).abs() < 0.3, "{}", correlation(1, 0, 1));
+        assert!(correlation(0, 2, 3).abs() < 0.3, "{}", correlation(0, 2, 3));
+        // The rhythm dominates the alpha power of its channels only
+        let alpha = covariances.index_axis(Axis(0), 0);
+        let beta = covariances.index_axis(Axis(0), 1);
+        assert!(alpha[[0, 0]] > 10.0 * alpha[[2, 2]]);
+        assert!(beta[[0, 0]] < 2.0 * beta[[2, 2]]);
+    }
 
-        let mean = self.mean_axis(Axis(1)).unwrap().insert_axis(Axis(1));
-        let centered = self - &mean;
+    #[test]
+    fn bands_beyond_nyquist_are_clamped_or_rejected() {
+        let mut generator = Lcg::new(22);
+        let data = Array2::from_shape_fn((2, 600), |_| gaussian(&mut generator) as f32);
+        let bands = [(40.0, 200.0)];
 
-        let covariance_matrix = centered.dot(&centered.t()) / (m_samples - cov_t as usize) as f32;
+        assert!(matches!(
+            band_covariances(
+                &data,
+                250.0,
+                &bands,
+                Estimator::Sample,
+                NyquistPolicy::Error
+            ),
+            Err(CovarianceError::BandOutOfRange { nyquist: 125.0, .. })
+        ));
+        let clamped = band_covariances(
+            &data,
+            250.0,
+            &bands,
+            Estimator::Sample,
+            NyquistPolicy::Clamp,
+        )
+        .unwrap();
+        let explicit = band_covariances(
+            &data,
+            250.0,
+            &[(40.0, 125.0)],
+            Estimator::Sample,
+            NyquistPolicy::Error,
+        )
+        .unwrap();
+        assert_eq!(clamped, explicit);
 
-        covariance_matrix
+        // Bands of inverted or non-positive edges are always rejected
+        for band in [(20.0, 10.0), (0.0, 10.0)] {
+            assert!(matches!(
+                band_covariances(
+                    &data,
+                    250.0,
+                    &[band],
+                    Estimator::Sample,
+                    NyquistPolicy::Clamp
+                ),
+                Err(CovarianceError::BandOutOfRange { .. })
+            ));
+        }
     }
 }
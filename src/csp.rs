@@ -0,0 +1,220 @@
+use std::{error::Error, fmt::Display};
+
+use nalgebra::{DMatrix, SymmetricEigen};
+use ndarray::{Array2, ArrayBase, Axis, Data, Ix2, Ix3};
+
+// Errors that can occur while computing common spatial patterns
+#[derive(Debug)]
+pub enum CspError {
+    // The covariances are not square matrices of the same size
+    ShapeMismatch {
+        a: (usize, usize),
+        b: (usize, usize),
+    },
+    // More filters were requested than there are channels
+    TooManyFilters {
+        requested: usize,
+        channels: usize,
+    },
+    // The composite covariance of both classes is singular, e.g. after re-referencing to the
+    // average, and needs to be regularized or reduced beforehand
+    RankDeficient {
+        rank: usize,
+        channels: usize,
+    },
+}
+
+impl Display for CspError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CspError::ShapeMismatch { a, b } => {
+                write!(
+                    f,
+                    "covariances of shapes {a:?} and {b:?} are not comparable"
+                )
+            }
+            CspError::TooManyFilters {
+                requested,
+                channels,
+            } => write!(
+                f,
+                "{requested} filters requested for only {channels} channels"
+            ),
+            CspError::RankDeficient { rank, channels } => write!(
+                f,
+                "composite covariance of {channels} channels only has rank {rank}"
+            ),
+        }
+    }
+}
+
+impl Error for CspError {}
+
+// Common spatial patterns discriminating two classes by their channel covariances `cov_a` and
+// `cov_b` (channels x channels), solving the generalized eigenproblem `cov_a w = l (cov_a + cov_b) w`
+// through the whitening of the composite covariance
+//
+// Returns `n_filters` spatial filters (filters x channels), the half maximizing the variance of
+// class A first, then the half maximizing the variance of class B, sorted by decreasing eigenvalue
+pub fn csp<S, T>(
+    cov_a: &ArrayBase<S, Ix2>,
+    cov_b: &ArrayBase<T, Ix2>,
+    n_filters: usize,
+) -> Result<Array2<f32>, CspError>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+{
+    let num_channels = cov_a.nrows();
+    if cov_a.dim() != cov_b.dim() || cov_a.nrows() != cov_a.ncols() {
+        return Err(CspError::ShapeMismatch {
+            a: cov_a.dim(),
+            b: cov_b.dim(),
+        });
+    }
+    if n_filters > num_channels {
+        return Err(CspError::TooManyFilters {
+            requested: n_filters,
+            channels: num_channels,
+        });
+    }
+
+    let cov_b = DMatrix::from_fn(num_channels, num_channels, |i, j| cov_b[[i, j]] as f64);
+    let cov_a = DMatrix::from_fn(num_channels, num_channels, |i, j| cov_a[[i, j]] as f64);
+    let composite = &cov_a + cov_b;
+
+    // Whitening of the composite covariance: P = L^-1/2 U^T
+    let eigen = SymmetricEigen::new(composite);
+    let largest = eigen.eigenvalues.max();
+    let rank = eigen
+        .eigenvalues
+        .iter()
+        .filter(|&&l| l > largest * 1e-10)
+        .count();
+    if rank < num_channels {
+        return Err(CspError::RankDeficient {
+            rank,
+            channels: num_channels,
+        });
+    }
+    let whitening = DMatrix::from_diagonal(&eigen.eigenvalues.map(|l| 1.0 / l.sqrt()))
+        * eigen.eigenvectors.transpose();
+
+    // Eigenvectors of the whitened covariance of class A, rotated back into filters: W = B^T P
+    let whitened = SymmetricEigen::new(&whitening * cov_a * whitening.transpose());
+    let filters = whitened.eigenvectors.transpose() * &whitening;
+
+    let mut order = (0..num_channels).collect::<Vec<usize>>();
+    order.sort_by(|&a, &b| whitened.eigenvalues[b].total_cmp(&whitened.eigenvalues[a]));
+    let picked = order[..n_filters / 2]
+        .iter()
+        .chain(&order[num_channels - (n_filters - n_filters / 2)..])
+        .copied()
+        .collect::<Vec<usize>>();
+
+    Ok(Array2::from_shape_fn(
+        (n_filters, num_channels),
+        |(i, j)| filters[(picked[i], j)] as f32,
+    ))
+}
+
+// Log-variance features of the `epochs` (epochs x channels x samples) through the spatial
+// `filters` (filters x channels), the variance of each filtered signal being normalized by their
+// total
+//
+// Returns the features of each epoch (epochs x filters)
+pub fn apply_csp<S, T>(filters: &ArrayBase<S, Ix2>, epochs: &ArrayBase<T, Ix3>) -> Array2<f32>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+{
+    let mut features = Array2::zeros((epochs.len_of(Axis(0)), filters.nrows()));
+    for (mut row, epoch) in features.outer_iter_mut().zip(epochs.outer_iter()) {
+        let variances = filters.dot(&epoch).var_axis(Axis(1), 0.0);
+        let total = variances.sum();
+        row.assign(&variances.mapv(|v| (v / total).ln()));
+    }
+
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array3};
+
+    use super::*;
+    use crate::covariance::{Covariance, CovarianceType};
+    use crate::surrogate::Lcg;
+
+    // Epochs of 3 channels mixing 3 sources of uniform noise, the first source being 3 times
+    // larger in class A than in class B (epochs x channels x samples)
+    fn class(first_source_scale: f32, seed: u64) -> Array3<f32> {
+        let mixing = array![[1.0f32, 0.6, 0.2], [0.5, 1.0, 0.4], [0.3, 0.5, 1.0]];
+        let mut generator = Lcg::new(seed);
+        let mut epochs = Array3::zeros((20, 3, 200));
+        for mut epoch in epochs.outer_iter_mut() {
+            let sources = Array2::from_shape_fn((3, 200), |(i, _)| {
+                let scale = if i == 0 { first_source_scale } else { 1.0 };
+                scale * (generator.next_f32() - 0.5)
+            });
+            epoch.assign(&mixing.dot(&sources));
+        }
+
+        epochs
+    }
+
+    fn mean_covariance(epochs: &Array3<f32>) -> Array2<f32> {
+        epochs
+            .outer_iter()
+            .map(|epoch| epoch.compute_covariance(CovarianceType::Sample))
+            .fold(Array2::zeros((3, 3)), |sum, cov| sum + cov)
+            / epochs.len_of(Axis(0)) as f32
+    }
+
+    #[test]
+    fn top_filter_separates_the_classes() {
+        let (a, b) = (class(3.0, 1), class(1.0, 2));
+        let filters = csp(&mean_covariance(&a), &mean_covariance(&b), 2).unwrap();
+        assert_eq!(filters.dim(), (2, 3));
+
+        // The first filter maximizes the variance of class A, the last that of class B
+        let (features_a, features_b) = (apply_csp(&filters, &a), apply_csp(&filters, &b));
+        let smallest_a = features_a.column(0).fold(f32::INFINITY, |m, &x| m.min(x));
+        let largest_b = features_b
+            .column(0)
+            .fold(f32::NEG_INFINITY, |m, &x| m.max(x));
+        assert!(smallest_a > largest_b, "{smallest_a} {largest_b}");
+        assert!(features_a.column(1).mean().unwrap() < features_b.column(1).mean().unwrap());
+
+        // Log-variances normalized by their total are those of fractions of it
+        for row in features_a.outer_iter() {
+            assert!(row.iter().map(|f| f.exp()).sum::<f32>() <= 1.0 + 1e-5);
+        }
+    }
+
+    #[test]
+    fn invalid_covariances_fail() {
+        let cov = mean_covariance(&class(1.0, 3));
+        assert!(matches!(
+            csp(&cov, &cov.slice(ndarray::s![..2, ..2]), 2),
+            Err(CspError::ShapeMismatch { .. })
+        ));
+        assert!(matches!(
+            csp(&cov, &cov, 4),
+            Err(CspError::TooManyFilters {
+                requested: 4,
+                channels: 3
+            })
+        ));
+
+        // Rank 2 only, as after re-referencing to the average
+        let mut referenced = class(1.0, 4);
+        let mean = referenced.mean_axis(Axis(1)).unwrap().insert_axis(Axis(1));
+        referenced -= &mean;
+        let cov = mean_covariance(&referenced);
+        assert!(matches!(
+            csp(&cov, &cov, 2),
+            Err(CspError::RankDeficient { channels: 3, .. })
+        ));
+    }
+}
@@ -0,0 +1,779 @@
+use std::{error::Error, fmt::Display};
+
+use ndarray::{s, Array1, Array3, ArrayBase, ArrayView2, Axis, Data, Ix2, Ix3};
+
+use crate::evoked::{average_epochs, Evoked};
+use crate::read::{
+    brainvision_core::{Header, MarkerInfo},
+    events::Events,
+    raw::Raw,
+};
+
+// Errors that can occur while cutting, correcting or rejecting epochs
+#[derive(Debug)]
+pub enum EpochError {
+    // The window of samples around each event is empty, not ending after it starts
+    EmptyWindow {
+        tmin_samples: isize,
+        tmax_samples: usize,
+    },
+    // The window of samples around an event does not fit inside the recording
+    EpochOutOfBounds {
+        event: usize,
+        start: isize,
+        end: usize,
+        num_samples: usize,
+    },
+    // The baseline window of samples is empty or does not fit inside the epochs
+    BaselineOutOfBounds {
+        start: usize,
+        end: usize,
+        num_samples: usize,
+    },
+    // Rejecting epochs would keep fewer of them than required
+    TooManyRejected {
+        kept: usize,
+        total: usize,
+        min_keep_fraction: f32,
+    },
+}
+
+impl Display for EpochError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EpochError::EmptyWindow {
+                tmin_samples,
+                tmax_samples,
+            } => write!(
+                f,
+                "epoch window spans samples {tmin_samples}..{tmax_samples} around each event, \
+                 which is empty"
+            ),
+            EpochError::EpochOutOfBounds {
+                event,
+                start,
+                end,
+                num_samples,
+            } => write!(
+                f,
+                "epoch around event {event} spans samples {start}..{end}, \
+                 but the recording only has {num_samples} samples"
+            ),
+            EpochError::BaselineOutOfBounds {
+                start,
+                end,
+                num_samples,
+            } => write!(
+                f,
+                "baseline spans samples {start}..{end}, \
+                 which is empty or exceeds the {num_samples} samples of the epochs"
+            ),
+            EpochError::TooManyRejected {
+                kept,
+                total,
+                min_keep_fraction,
+            } => write!(
+                f,
+                "only {kept} of {total} epochs would be kept, \
+                 fewer than the required fraction of {min_keep_fraction}"
+            ),
+        }
+    }
+}
+
+impl Error for EpochError {}
+
+// What to do with an event whose epoch window exceeds the bounds of the recording
+#[derive(Clone, Copy, Debug)]
+pub enum OutOfBounds {
+    // Leave the event out of the resulting epochs
+    Skip,
+    // Fail with `EpochError::EpochOutOfBounds`
+    Error,
+}
+
+// Criteria for rejecting an epoch, each applied to every channel, in the unit of the data (e.g. µV)
+// Criteria left as `None` are not applied
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RejectionCriteria {
+    // Largest peak-to-peak amplitude allowed, e.g. 150 µV
+    pub max_peak_to_peak: Option<f32>,
+    // Smallest peak-to-peak amplitude allowed, under which a channel is flat, e.g. 0.5 µV
+    pub min_peak_to_peak: Option<f32>,
+    // Largest absolute amplitude allowed
+    pub max_abs: Option<f32>,
+    // Smallest fraction of the epochs to be kept, failing with `EpochError::TooManyRejected`
+    pub min_keep_fraction: Option<f32>,
+}
+
+// The criterion an epoch was rejected by
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Criterion {
+    PeakToPeak,
+    Flat,
+    Amplitude,
+}
+
+// Why an epoch was rejected: the first channel meeting a criterion, along with its offending value
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rejection {
+    pub epoch: usize,
+    pub channel: usize,
+    pub criterion: Criterion,
+    pub value: f32,
+}
+
+// The epochs retained by `reject_epochs`
+#[derive(Debug)]
+pub struct RetainedEpochs {
+    // Retained epochs (epochs x channels x samples)
+    pub epochs: Array3<f32>,
+    // Indices of the retained epochs
+    pub kept: Vec<usize>,
+    // Rejection of every dropped epoch
+    pub rejections: Vec<Rejection>,
+}
+
+// Epochs along with what is needed to interpret them, times being relative to their events
+#[derive(Clone, Debug)]
+pub struct Epochs {
+    // Epochs x channels x samples
+    pub data: Array3<f32>,
+    // Time of the first sample of each epoch, in seconds
+    pub tmin: f64,
+    // Sampling frequency, in Hz
+    pub sfreq: f64,
+    pub channel_names: Vec<String>,
+    // Label of the event of each epoch, e.g. the description of its marker
+    pub labels: Vec<String>,
+    // Window `[start, end)` in seconds the epochs were baseline-corrected over, if any
+    pub baseline: Option<(f64, f64)>,
+}
+
+impl Epochs {
+    pub fn new(
+        data: Array3<f32>,
+        tmin: f64,
+        sfreq: f64,
+        channel_names: Vec<String>,
+        labels: Vec<String>,
+    ) -> Self {
+        let (num_epochs, num_channels, _) = data.dim();
+        assert_eq!(
+            labels.len(),
+            num_epochs,
+            "One label per epoch is required !"
+        );
+        assert_eq!(
+            channel_names.len(),
+            num_channels,
+            "One name per channel is required !"
+        );
+
+        Epochs {
+            data,
+            tmin,
+            sfreq,
+            channel_names,
+            labels,
+            baseline: None,
+        }
+    }
+
+    // Cuts the data of the `raw` recording into epochs around each of its markers, labeled by
+    // their description, with the window `[marker + tmin, marker + tmax)` given in seconds
+    pub fn from_raw(
+        raw: &Raw,
+        tmin: f64,
+        tmax: f64,
+        out_of_bounds: OutOfBounds,
+    ) -> Result<Epochs, EpochError> {
+        let sfreq = raw.sfreq();
+        let tmin_samples = (tmin * sfreq).round() as isize;
+        let tmax_samples = (tmax * sfreq).round().max(0.0) as usize;
+
+        let markers = &raw.marker().markers;
+        let (data, used) = epochs(
+            &raw.data(),
+            markers,
+            tmin_samples,
+            tmax_samples,
+            out_of_bounds,
+        )?;
+
+        Ok(Epochs::new(
+            data,
+            tmin_samples as f64 / sfreq,
+            sfreq,
+            raw.channel_names().into_iter().map(String::from).collect(),
+            used.iter()
+                .map(|&i| markers[i].description.clone())
+                .collect(),
+        ))
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len_of(Axis(0))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn num_samples(&self) -> usize {
+        self.data.len_of(Axis(2))
+    }
+
+    // Time of each sample, in seconds
+    pub fn times(&self) -> Array1<f64> {
+        Array1::from_shape_fn(self.num_samples(), |i| self.tmin + i as f64 / self.sfreq)
+    }
+
+    // Index of the sample closest to the time `t` in seconds, clamped to the epochs, the end of
+    // the epochs included
+    pub fn time_to_index(&self, t: f64) -> usize {
+        self.offset(t).clamp(0, self.num_samples() as isize) as usize
+    }
+
+    // Index of the sample closest to the time `t` in seconds, wherever it lies
+    fn offset(&self, t: f64) -> isize {
+        ((t - self.tmin) * self.sfreq).round() as isize
+    }
+
+    // Label and data (channels x samples) of each epoch
+    pub fn iter(&self) -> impl Iterator<Item = (&str, ArrayView2<'_, f32>)> {
+        self.labels
+            .iter()
+            .map(String::as_str)
+            .zip(self.data.outer_iter())
+    }
+
+    // The epochs of the `label` only
+    pub fn select(&self, label: &str) -> Epochs {
+        let indices = (0..self.len())
+            .filter(|&i| self.labels[i] == label)
+            .collect::<Vec<usize>>();
+
+        Epochs {
+            data: self.data.select(Axis(0), &indices),
+            labels: vec![label.to_string(); indices.len()],
+            channel_names: self.channel_names.clone(),
+            ..*self
+        }
+    }
+
+    // Evoked response of all the epochs
+    pub fn average(&self) -> Evoked {
+        let (data, standard_error) = average_epochs(&self.data, None);
+
+        Evoked {
+            data,
+            standard_error,
+            tmin: self.tmin,
+            sfreq: self.sfreq,
+            channel_names: self.channel_names.clone(),
+            num_epochs: self.len(),
+        }
+    }
+
+    // Subtracts from each channel of each epoch its mean over the window `[start, end)` in seconds
+    pub fn apply_baseline(&mut self, (start, end): (f64, f64)) -> Result<(), EpochError> {
+        let (first, last) = (self.offset(start), self.offset(end));
+        if first < 0 || first >= last || last > self.num_samples() as isize {
+            return Err(EpochError::BaselineOutOfBounds {
+                start: first.max(0) as usize,
+                end: last.max(0) as usize,
+                num_samples: self.num_samples(),
+            });
+        }
+        let (first, last) = (first as usize, last as usize);
+
+        let means = self
+            .data
+            .slice(s![.., .., first..last])
+            .mean_axis(Axis(2))
+            .unwrap()
+            .insert_axis(Axis(2));
+        self.data -= &means;
+        self.baseline = Some((start, end));
+
+        Ok(())
+    }
+
+    // The epochs restricted to the window `[tmin, tmax)` in seconds, clamped to the epochs
+    pub fn crop(&self, tmin: f64, tmax: f64) -> Epochs {
+        let start = self.time_to_index(tmin);
+        let end = self.time_to_index(tmax).max(start);
+
+        Epochs {
+            data: self.data.slice(s![.., .., start..end]).to_owned(),
+            tmin: self.tmin + start as f64 / self.sfreq,
+            channel_names: self.channel_names.clone(),
+            labels: self.labels.clone(),
+            ..*self
+        }
+    }
+}
+
+// Cuts the `data` (channels x samples) into epochs around each of the zero-based `samples`
+// Each epoch spans the samples `[sample + tmin_samples, sample + tmax_samples)`
+// Fails with `EpochError::EmptyWindow` if `tmin_samples` is not below `tmax_samples`
+//
+// Returns the epochs (epochs x channels x samples) along with the indices of the samples they
+// were cut around
+pub fn epochs_at<S>(
+    data: &ArrayBase<S, Ix2>,
+    samples: &[usize],
+    tmin_samples: isize,
+    tmax_samples: usize,
+    out_of_bounds: OutOfBounds,
+) -> Result<(Array3<f32>, Vec<usize>), EpochError>
+where
+    S: Data<Elem = f32>,
+{
+    if tmin_samples >= tmax_samples as isize {
+        return Err(EpochError::EmptyWindow {
+            tmin_samples,
+            tmax_samples,
+        });
+    }
+    let (num_channels, num_samples) = data.dim();
+    let window = (tmax_samples as isize - tmin_samples) as usize;
+
+    let mut used = Vec::with_capacity(samples.len());
+    for (i, &sample) in samples.iter().enumerate() {
+        let start = sample as isize + tmin_samples;
+        let end = sample + tmax_samples;
+
+        if start < 0 || end > num_samples {
+            match out_of_bounds {
+                OutOfBounds::Skip => continue,
+                OutOfBounds::Error => {
+                    return Err(EpochError::EpochOutOfBounds {
+                        event: i,
+                        start,
+                        end,
+                        num_samples,
+                    })
+                }
+            }
+        }
+
+        used.push(i);
+    }
+
+    let mut result = Array3::zeros((used.len(), num_channels, window));
+    for (epoch, &i) in used.iter().enumerate() {
+        let start = (samples[i] as isize + tmin_samples) as usize;
+
+        result
+            .slice_mut(s![epoch, .., ..])
+            .assign(&data.slice(s![.., start..start + window]));
+    }
+
+    Ok((result, used))
+}
+
+// Cuts the `data` (channels x samples) into epochs around each of the `markers`
+// Each epoch spans the samples `[marker + tmin_samples, marker + tmax_samples)`
+//
+// Returns the epochs (epochs x channels x samples) along with the indices of the markers they
+// were cut around
+pub fn epochs<S>(
+    data: &ArrayBase<S, Ix2>,
+    markers: &[MarkerInfo],
+    tmin_samples: isize,
+    tmax_samples: usize,
+    out_of_bounds: OutOfBounds,
+) -> Result<(Array3<f32>, Vec<usize>), EpochError>
+where
+    S: Data<Elem = f32>,
+{
+    let samples = markers
+        .iter()
+        .map(MarkerInfo::sample)
+        .collect::<Vec<usize>>();
+
+    epochs_at(data, &samples, tmin_samples, tmax_samples, out_of_bounds)
+}
+
+// Cuts the `data` (channels x samples), sampled at `fs` Hz, into epochs around each of the
+// `events`, those of neither a sample nor an onset being left out
+// Each epoch spans the samples `[event + tmin_samples, event + tmax_samples)`
+//
+// Returns the epochs (epochs x channels x samples) along with the indices of the events they
+// were cut around
+pub fn epochs_events<S>(
+    data: &ArrayBase<S, Ix2>,
+    events: &Events,
+    fs: f64,
+    tmin_samples: isize,
+    tmax_samples: usize,
+    out_of_bounds: OutOfBounds,
+) -> Result<(Array3<f32>, Vec<usize>), EpochError>
+where
+    S: Data<Elem = f32>,
+{
+    let (indices, samples): (Vec<usize>, Vec<usize>) = events
+        .to_sample_indices(fs)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, sample)| sample.map(|sample| (i, sample)))
+        .unzip();
+
+    match epochs_at(data, &samples, tmin_samples, tmax_samples, out_of_bounds) {
+        Ok((epochs, used)) => Ok((epochs, used.into_iter().map(|i| indices[i]).collect())),
+        Err(EpochError::EpochOutOfBounds {
+            event,
+            start,
+            end,
+            num_samples,
+        }) => Err(EpochError::EpochOutOfBounds {
+            event: indices[event],
+            start,
+            end,
+            num_samples,
+        }),
+        Err(e) => Err(e),
+    }
+}
+
+// Cuts the `data` (channels x samples) into epochs around each of the `markers`, with the window
+// `[marker + tmin, marker + tmax)` given in seconds and converted using the sampling interval
+// of the `header`
+pub fn epochs_secs<S>(
+    data: &ArrayBase<S, Ix2>,
+    markers: &[MarkerInfo],
+    header: &Header,
+    tmin: f64,
+    tmax: f64,
+    out_of_bounds: OutOfBounds,
+) -> Result<(Array3<f32>, Vec<usize>), EpochError>
+where
+    S: Data<Elem = f32>,
+{
+    // The sampling interval is provided in microseconds
+    let to_samples = |t: f64| (t * header.sampling_rate_hz()).round();
+
+    epochs(
+        data,
+        markers,
+        to_samples(tmin) as isize,
+        to_samples(tmax).max(0.0) as usize,
+        out_of_bounds,
+    )
+}
+
+// Drops the `epochs` (epochs x channels x samples) meeting any of the rejection `criteria`
+pub fn reject_epochs<S>(
+    epochs: &ArrayBase<S, Ix3>,
+    criteria: &RejectionCriteria,
+) -> Result<RetainedEpochs, EpochError>
+where
+    S: Data<Elem = f32>,
+{
+    let mut kept = Vec::with_capacity(epochs.len_of(Axis(0)));
+    let mut rejections = Vec::new();
+    for (epoch, data) in epochs.outer_iter().enumerate() {
+        let rejection = data
+            .outer_iter()
+            .enumerate()
+            .find_map(|(channel, samples)| {
+                let max = samples.fold(f32::NEG_INFINITY, |acc, &x| acc.max(x));
+                let min = samples.fold(f32::INFINITY, |acc, &x| acc.min(x));
+                let peak_to_peak = max - min;
+                let abs = max.abs().max(min.abs());
+
+                let criterion = if criteria.max_abs.is_some_and(|limit| abs > limit) {
+                    Some((Criterion::Amplitude, abs))
+                } else if criteria
+                    .max_peak_to_peak
+                    .is_some_and(|limit| peak_to_peak > limit)
+                {
+                    Some((Criterion::PeakToPeak, peak_to_peak))
+                } else if criteria
+                    .min_peak_to_peak
+                    .is_some_and(|limit| peak_to_peak < limit)
+                {
+                    Some((Criterion::Flat, peak_to_peak))
+                } else {
+                    None
+                };
+
+                criterion.map(|(criterion, value)| Rejection {
+                    epoch,
+                    channel,
+                    criterion,
+                    value,
+                })
+            });
+
+        match rejection {
+            Some(rejection) => rejections.push(rejection),
+            None => kept.push(epoch),
+        }
+    }
+
+    let total = epochs.len_of(Axis(0));
+    if let Some(min_keep_fraction) = criteria.min_keep_fraction {
+        if (kept.len() as f32) < min_keep_fraction * total as f32 {
+            return Err(EpochError::TooManyRejected {
+                kept: kept.len(),
+                total,
+                min_keep_fraction,
+            });
+        }
+    }
+
+    Ok(RetainedEpochs {
+        epochs: epochs.select(Axis(0), &kept),
+        kept,
+        rejections,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array2;
+
+    use super::*;
+    use crate::read::fixtures::{Recording, TempDir};
+
+    fn marker(position: usize) -> MarkerInfo {
+        MarkerInfo {
+            marker_type: "Stimulus".into(),
+            description: "S  1".into(),
+            position,
+            size: 1,
+            channel: 0,
+            date: None,
+        }
+    }
+
+    // Two channels of 100 samples, the value of each being its index and the channel offset
+    fn ramp() -> Array2<f32> {
+        Array2::from_shape_fn((2, 100), |(c, t)| (c * 1000 + t) as f32)
+    }
+
+    #[test]
+    fn epochs_skip_markers_too_close_to_the_end() {
+        let markers = [marker(11), marker(51), marker(96)];
+        let (epochs, used) = epochs(&ramp(), &markers, -5, 10, OutOfBounds::Skip).unwrap();
+
+        assert_eq!(used, vec![0, 1]);
+        assert_eq!(epochs.dim(), (2, 2, 15));
+        assert_eq!(epochs[[0, 0, 0]], 5.0);
+        assert_eq!(epochs[[0, 1, 14]], 1019.0);
+        assert_eq!(epochs[[1, 0, 5]], 50.0);
+    }
+
+    #[test]
+    fn epochs_fail_on_markers_too_close_to_the_end() {
+        let markers = [marker(11), marker(51), marker(96)];
+
+        match epochs(&ramp(), &markers, -5, 10, OutOfBounds::Error) {
+            Err(EpochError::EpochOutOfBounds {
+                event,
+                start,
+                end,
+                num_samples,
+            }) => assert_eq!((event, start, end, num_samples), (2, 90, 105, 100)),
+            other => panic!("Unexpected result {other:?} !"),
+        }
+    }
+
+    #[test]
+    fn empty_windows_fail() {
+        // Past the end of the recording, which would otherwise pass the check of the window end
+        let markers = [marker(11), marker(96)];
+        for (tmin_samples, tmax_samples) in [(10, 10), (10, 5)] {
+            for out_of_bounds in [OutOfBounds::Skip, OutOfBounds::Error] {
+                assert!(matches!(
+                    epochs(&ramp(), &markers, tmin_samples, tmax_samples, out_of_bounds),
+                    Err(EpochError::EmptyWindow {
+                        tmin_samples: t0,
+                        tmax_samples: t1
+                    }) if (t0, t1) == (tmin_samples, tmax_samples)
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn epochs_secs_convert_with_the_sampling_interval() {
+        let root = TempDir::new("epochs-secs");
+        let path = Recording::new(&["Cz", "Pz"], ramp()).write(&root);
+        let header = Header::load(&path).unwrap();
+
+        // 500 Hz, so [-10 ms, 20 ms) spans 15 samples
+        let markers = [marker(11), marker(51), marker(96)];
+        let (secs, used) =
+            epochs_secs(&ramp(), &markers, &header, -0.01, 0.02, OutOfBounds::Skip).unwrap();
+        let (samples, _) = epochs(&ramp(), &markers, -5, 10, OutOfBounds::Skip).unwrap();
+
+        assert_eq!(used, vec![0, 1]);
+        assert_eq!(secs, samples);
+    }
+
+    // Ten epochs of 3 channels x 200 samples of a 20 µV oscillation, a 500 µV blink-like bump
+    // being injected on the first channel of epochs 3 and 7
+    fn blinks() -> Array3<f32> {
+        Array3::from_shape_fn((10, 3, 200), |(e, c, t)| {
+            let background = 20.0 * (t as f32 * 0.1 + e as f32 + c as f32).sin();
+            let blink = if c == 0 && (e == 3 || e == 7) {
+                500.0 * (-((t as f32 - 100.0) / 15.0).powi(2)).exp()
+            } else {
+                0.0
+            };
+            background + blink
+        })
+    }
+
+    #[test]
+    fn epochs_with_blinks_are_rejected() {
+        let criteria = RejectionCriteria {
+            max_peak_to_peak: Some(150.0),
+            min_peak_to_peak: Some(0.5),
+            ..Default::default()
+        };
+        let retained = reject_epochs(&blinks(), &criteria).unwrap();
+
+        assert_eq!(retained.kept, vec![0, 1, 2, 4, 5, 6, 8, 9]);
+        assert_eq!(retained.epochs, blinks().select(Axis(0), &retained.kept));
+        assert_eq!(
+            retained
+                .rejections
+                .iter()
+                .map(|r| (r.epoch, r.channel, r.criterion))
+                .collect::<Vec<_>>(),
+            vec![(3, 0, Criterion::PeakToPeak), (7, 0, Criterion::PeakToPeak)]
+        );
+        assert!(retained.rejections.iter().all(|r| r.value > 450.0));
+    }
+
+    #[test]
+    fn flat_and_large_epochs_are_rejected() {
+        let mut data = blinks();
+        data.slice_mut(s![5, 2, ..]).fill(1.0);
+        let criteria = RejectionCriteria {
+            min_peak_to_peak: Some(0.5),
+            max_abs: Some(400.0),
+            ..Default::default()
+        };
+        let retained = reject_epochs(&data, &criteria).unwrap();
+
+        assert_eq!(
+            retained
+                .rejections
+                .iter()
+                .map(|r| (r.epoch, r.channel, r.criterion))
+                .collect::<Vec<_>>(),
+            vec![
+                (3, 0, Criterion::Amplitude),
+                (5, 2, Criterion::Flat),
+                (7, 0, Criterion::Amplitude)
+            ]
+        );
+    }
+
+    #[test]
+    fn rejecting_too_many_epochs_fails() {
+        let criteria = RejectionCriteria {
+            max_peak_to_peak: Some(150.0),
+            min_keep_fraction: Some(0.9),
+            ..Default::default()
+        };
+
+        match reject_epochs(&blinks(), &criteria) {
+            Err(EpochError::TooManyRejected {
+                kept,
+                total,
+                min_keep_fraction,
+            }) => assert_eq!((kept, total, min_keep_fraction), (8, 10, 0.9)),
+            other => panic!("Unexpected result {other:?} !"),
+        }
+        let criteria = RejectionCriteria {
+            min_keep_fraction: Some(0.8),
+            ..criteria
+        };
+        assert_eq!(reject_epochs(&blinks(), &criteria).unwrap().kept.len(), 8);
+    }
+
+    #[test]
+    fn baseline_outside_the_epochs_fails() {
+        let data = Array3::from_shape_fn((2, 1, 10), |(e, _, t)| (e + t) as f32);
+        let mut epochs = Epochs::new(data, -0.1, 100.0, vec!["Cz".into()], vec!["a".into(); 2]);
+
+        match epochs.apply_baseline((-0.2, 0.0)) {
+            Err(EpochError::BaselineOutOfBounds {
+                start,
+                end,
+                num_samples,
+            }) => assert_eq!((start, end, num_samples), (0, 10, 10)),
+            other => panic!("Unexpected result {other:?} !"),
+        }
+        epochs.apply_baseline((-0.1, 0.0)).unwrap();
+        assert_eq!(
+            epochs.data.slice(s![0, 0, ..3]).to_vec(),
+            vec![-4.5, -3.5, -2.5]
+        );
+        assert_eq!(epochs.baseline, Some((-0.1, 0.0)));
+    }
+
+    // Four epochs of 2 channels x 50 samples at 100 Hz from -0.1 s, alternately labeled
+    fn labeled() -> Epochs {
+        let data =
+            Array3::from_shape_fn((4, 2, 50), |(e, c, t)| (e * 100 + c * 10) as f32 + t as f32);
+        Epochs::new(
+            data,
+            -0.1,
+            100.0,
+            vec!["Cz".into(), "Pz".into()],
+            ["target", "standard", "target", "standard"]
+                .map(String::from)
+                .to_vec(),
+        )
+    }
+
+    #[test]
+    fn selection_keeps_the_epochs_of_the_label() {
+        let epochs = labeled();
+        let targets = epochs.select("target");
+
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets.labels, vec!["target", "target"]);
+        assert_eq!(targets.data, epochs.data.select(Axis(0), &[0, 2]));
+        assert_eq!((targets.tmin, targets.sfreq), (epochs.tmin, epochs.sfreq));
+        assert_eq!(targets.channel_names, epochs.channel_names);
+        assert!(epochs.select("novel").is_empty());
+
+        for (i, (label, epoch)) in epochs.iter().enumerate() {
+            assert_eq!(label, epochs.labels[i]);
+            assert_eq!(epoch, epochs.data.index_axis(Axis(0), i));
+        }
+        // Average of epochs 0 and 2, 200 apart
+        let evoked = targets.average();
+        assert_eq!(evoked.num_epochs, 2);
+        assert_eq!(evoked.data[[1, 3]], 113.0);
+    }
+
+    #[test]
+    fn cropping_matches_manual_slicing() {
+        let epochs = labeled();
+        // Samples 10..30, from 0 s to 0.2 s
+        let cropped = epochs.crop(0.0, 0.2);
+
+        assert_eq!(epochs.time_to_index(0.0), 10);
+        assert_eq!(cropped.data, epochs.data.slice(s![.., .., 10..30]));
+        assert!(cropped.tmin.abs() < 1e-12);
+        assert_eq!(cropped.times().len(), 20);
+        assert!((cropped.times()[19] - epochs.times()[29]).abs() < 1e-12);
+        assert_eq!(cropped.labels, epochs.labels);
+
+        // Bounds beyond the epochs are clamped to them
+        assert_eq!(epochs.crop(-1.0, 1.0).data, epochs.data);
+        assert_eq!(epochs.crop(0.2, 0.1).num_samples(), 0);
+        // Cropping twice is cropping once to the intersection
+        assert_eq!(cropped.crop(0.05, 1.0).data, epochs.crop(0.05, 0.2).data);
+    }
+}
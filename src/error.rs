@@ -0,0 +1,216 @@
+use std::fmt::Display;
+
+use crate::connectivity::ConnectivityError;
+use crate::covariance::CovarianceError;
+use crate::csp::CspError;
+use crate::epochs::EpochError;
+use crate::evoked::ErpError;
+use crate::features::FeatureError;
+use crate::fft::FftError;
+use crate::ica::IcaError;
+use crate::line_noise::LineNoiseError;
+use crate::microstates::MicrostateError;
+use crate::pipeline::PipelineError;
+use crate::read::ReadError;
+use crate::s_transform::StError;
+use crate::spectral_fit::SpectralFitError;
+use crate::time_frequency::TfError;
+use crate::wavelet::WaveletError;
+
+// Errors of any module of the crate, for callers chaining several of them with `?`
+#[derive(Debug)]
+pub enum Error {
+    Read(ReadError),
+    Io(std::io::Error),
+    Epochs(EpochError),
+    Fourier(FftError),
+    Stockwell(StError),
+    Wavelet(WaveletError),
+    TimeFrequency(TfError),
+    Covariance(CovarianceError),
+    Csp(CspError),
+    Ica(IcaError),
+    Features(FeatureError),
+    Erp(ErpError),
+    SpectralFit(SpectralFitError),
+    Microstates(MicrostateError),
+    Connectivity(ConnectivityError),
+    Pipeline(PipelineError),
+    LineNoise(LineNoiseError),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Read(error) => error.fmt(f),
+            Error::Io(error) => error.fmt(f),
+            Error::Epochs(error) => error.fmt(f),
+            Error::Fourier(error) => error.fmt(f),
+            Error::Stockwell(error) => error.fmt(f),
+            Error::Wavelet(error) => error.fmt(f),
+            Error::TimeFrequency(error) => error.fmt(f),
+            Error::Covariance(error) => error.fmt(f),
+            Error::Csp(error) => error.fmt(f),
+            Error::Ica(error) => error.fmt(f),
+            Error::Features(error) => error.fmt(f),
+            Error::Erp(error) => error.fmt(f),
+            Error::SpectralFit(error) => error.fmt(f),
+            Error::Microstates(error) => error.fmt(f),
+            Error::Connectivity(error) => error.fmt(f),
+            Error::Pipeline(error) => error.fmt(f),
+            Error::LineNoise(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(match self {
+            Error::Read(error) => error,
+            Error::Io(error) => error,
+            Error::Epochs(error) => error,
+            Error::Fourier(error) => error,
+            Error::Stockwell(error) => error,
+            Error::Wavelet(error) => error,
+            Error::TimeFrequency(error) => error,
+            Error::Covariance(error) => error,
+            Error::Csp(error) => error,
+            Error::Ica(error) => error,
+            Error::Features(error) => error,
+            Error::Erp(error) => error,
+            Error::SpectralFit(error) => error,
+            Error::Microstates(error) => error,
+            Error::Connectivity(error) => error,
+            Error::Pipeline(error) => error,
+            Error::LineNoise(error) => error,
+        })
+    }
+}
+
+impl From<ReadError> for Error {
+    fn from(error: ReadError) -> Self {
+        Error::Read(error)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<EpochError> for Error {
+    fn from(error: EpochError) -> Self {
+        Error::Epochs(error)
+    }
+}
+
+impl From<FftError> for Error {
+    fn from(error: FftError) -> Self {
+        Error::Fourier(error)
+    }
+}
+
+impl From<StError> for Error {
+    fn from(error: StError) -> Self {
+        Error::Stockwell(error)
+    }
+}
+
+impl From<WaveletError> for Error {
+    fn from(error: WaveletError) -> Self {
+        Error::Wavelet(error)
+    }
+}
+
+impl From<TfError> for Error {
+    fn from(error: TfError) -> Self {
+        Error::TimeFrequency(error)
+    }
+}
+
+impl From<CovarianceError> for Error {
+    fn from(error: CovarianceError) -> Self {
+        Error::Covariance(error)
+    }
+}
+
+impl From<CspError> for Error {
+    fn from(error: CspError) -> Self {
+        Error::Csp(error)
+    }
+}
+
+impl From<IcaError> for Error {
+    fn from(error: IcaError) -> Self {
+        Error::Ica(error)
+    }
+}
+
+impl From<FeatureError> for Error {
+    fn from(error: FeatureError) -> Self {
+        Error::Features(error)
+    }
+}
+
+impl From<ErpError> for Error {
+    fn from(error: ErpError) -> Self {
+        Error::Erp(error)
+    }
+}
+
+impl From<SpectralFitError> for Error {
+    fn from(error: SpectralFitError) -> Self {
+        Error::SpectralFit(error)
+    }
+}
+
+impl From<MicrostateError> for Error {
+    fn from(error: MicrostateError) -> Self {
+        Error::Microstates(error)
+    }
+}
+
+impl From<ConnectivityError> for Error {
+    fn from(error: ConnectivityError) -> Self {
+        Error::Connectivity(error)
+    }
+}
+
+impl From<PipelineError> for Error {
+    fn from(error: PipelineError) -> Self {
+        Error::Pipeline(error)
+    }
+}
+
+impl From<LineNoiseError> for Error {
+    fn from(error: LineNoiseError) -> Self {
+        Error::LineNoise(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use ndarray::Array2;
+
+    use super::*;
+    use crate::covariance::{Covariance, CovarianceType};
+
+    fn covariance_of(data: &Array2<f32>) -> Result<Array2<f32>, Error> {
+        Ok(data.try_compute_covariance(CovarianceType::Sample)?)
+    }
+
+    #[test]
+    fn module_errors_convert_with_their_message() {
+        let error = covariance_of(&Array2::zeros((2, 1))).unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::Covariance(CovarianceError::NotEnoughSamples { .. })
+        ));
+        assert_eq!(error.to_string(), error.source().unwrap().to_string());
+        assert!(covariance_of(&Array2::zeros((2, 3))).is_ok());
+    }
+}
@@ -0,0 +1,536 @@
+use std::{error::Error, fmt::Display};
+
+use ndarray::{s, Array1, Array2, ArrayBase, Axis, Data, Ix1, Ix2, Ix3};
+
+// Slack, in seconds, of the bounds of a measurement window, absorbing the rounding of the times
+const TIME_TOLERANCE: f64 = 1e-9;
+
+// Errors that can occur while measuring ERP components
+#[derive(Debug)]
+pub enum ErpError {
+    // The window `[start, end]` does not lie within the times `[tmin, tmax]` of the response
+    WindowOutOfBounds {
+        start: f64,
+        end: f64,
+        tmin: f64,
+        tmax: f64,
+    },
+    // The window `[start, end]` holds none of the samples of the response
+    EmptyWindow {
+        start: f64,
+        end: f64,
+    },
+}
+
+impl Display for ErpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErpError::WindowOutOfBounds {
+                start,
+                end,
+                tmin,
+                tmax,
+            } => write!(
+                f,
+                "window [{start}, {end}] s does not lie within the response of [{tmin}, {tmax}] s"
+            ),
+            ErpError::EmptyWindow { start, end } => {
+                write!(f, "window [{start}, {end}] s holds no samples")
+            }
+        }
+    }
+}
+
+impl Error for ErpError {}
+
+// Sign of the deflection of an ERP component
+#[derive(Clone, Copy, Debug)]
+pub enum Polarity {
+    // e.g. the P300
+    Positive,
+    // e.g. the N200
+    Negative,
+}
+
+// Peak of an ERP component on a channel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PeakMeasure {
+    // Value at the peak, negative for negative components
+    pub amplitude: f32,
+    // Time of the peak, in seconds
+    pub latency: f64,
+}
+
+// Evoked response averaged from epochs, along with what is needed to interpret it
+#[derive(Clone, Debug)]
+pub struct Evoked {
+    // Mean across epochs (channels x samples)
+    pub data: Array2<f32>,
+    // Standard error of the mean (channels x samples), NaN for a single epoch
+    pub standard_error: Array2<f32>,
+    // Time of the first sample relative to the events, in seconds
+    pub tmin: f64,
+    // Sampling frequency, in Hz
+    pub sfreq: f64,
+    pub channel_names: Vec<String>,
+    // Number of epochs averaged
+    pub num_epochs: usize,
+}
+
+impl Evoked {
+    // Time of each sample relative to the events, in seconds
+    pub fn times(&self) -> Array1<f64> {
+        Array1::from_shape_fn(self.data.ncols(), |i| self.tmin + i as f64 / self.sfreq)
+    }
+
+    // Peak of each channel within the `window`, as by `peak_measure`
+    pub fn peak_measure(
+        &self,
+        window: (f64, f64),
+        polarity: Polarity,
+    ) -> Result<Vec<PeakMeasure>, ErpError> {
+        peak_measure(&self.data, &self.times(), window, polarity)
+    }
+
+    // Mean amplitude of each channel within the `window`, as by `mean_amplitude`
+    pub fn mean_amplitude(&self, window: (f64, f64)) -> Result<Array1<f32>, ErpError> {
+        mean_amplitude(&self.data, &self.times(), window)
+    }
+}
+
+// Peak of each channel of the `evoked` response (channels x samples), of the `times` in seconds,
+// within the `window` `[start, end]` in seconds: its most positive or negative sample, as per the
+// `polarity`
+// A peak that is a local extremum is refined to the vertex of the parabola through it and its two
+// neighbours, for a latency (and amplitude) between samples
+pub fn peak_measure<S, T>(
+    evoked: &ArrayBase<S, Ix2>,
+    times: &ArrayBase<T, Ix1>,
+    window: (f64, f64),
+    polarity: Polarity,
+) -> Result<Vec<PeakMeasure>, ErpError>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f64>,
+{
+    let (first, last) = window_indices(times, window)?;
+    let sign = match polarity {
+        Polarity::Positive => 1.0,
+        Polarity::Negative => -1.0,
+    };
+
+    Ok(evoked
+        .outer_iter()
+        .map(|channel| {
+            let value = |i: usize| sign * channel[i];
+            let index = (first..last)
+                .max_by(|&a, &b| value(a).total_cmp(&value(b)))
+                .unwrap();
+
+            let (mut amplitude, mut latency) = (value(index), times[index]);
+            if index > 0 && index + 1 < channel.len() {
+                let (before, peak, after) = (value(index - 1), value(index), value(index + 1));
+                let curvature = before - 2.0 * peak + after;
+                if before <= peak && after <= peak && curvature < 0.0 {
+                    let offset = 0.5 * (before - after) / curvature;
+                    amplitude = peak - 0.25 * (before - after) * offset;
+                    latency += offset as f64 * (times[index + 1] - times[index]);
+                }
+            }
+
+            PeakMeasure {
+                amplitude: sign * amplitude,
+                latency,
+            }
+        })
+        .collect())
+}
+
+// Mean of each channel of the `evoked` response (channels x samples), of the `times` in seconds,
+// over its samples within the `window` `[start, end]` in seconds
+pub fn mean_amplitude<S, T>(
+    evoked: &ArrayBase<S, Ix2>,
+    times: &ArrayBase<T, Ix1>,
+    window: (f64, f64),
+) -> Result<Array1<f32>, ErpError>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f64>,
+{
+    let (first, last) = window_indices(times, window)?;
+
+    Ok(evoked
+        .slice(s![.., first..last])
+        .mean_axis(Axis(1))
+        .unwrap())
+}
+
+// Range `[first, last)` of the indices of the `times` within the `window`, which must lie within
+// the `times` and hold at least one of them
+fn window_indices<T>(
+    times: &ArrayBase<T, Ix1>,
+    (start, end): (f64, f64),
+) -> Result<(usize, usize), ErpError>
+where
+    T: Data<Elem = f64>,
+{
+    let (tmin, tmax) = match (times.first(), times.last()) {
+        (Some(&tmin), Some(&tmax)) => (tmin, tmax),
+        _ => (f64::NAN, f64::NAN),
+    };
+    let first = times
+        .iter()
+        .take_while(|&&t| t < start - TIME_TOLERANCE)
+        .count();
+    let last = times
+        .iter()
+        .take_while(|&&t| t <= end + TIME_TOLERANCE)
+        .count();
+
+    if !(start >= tmin - TIME_TOLERANCE && end <= tmax + TIME_TOLERANCE) {
+        return Err(ErpError::WindowOutOfBounds {
+            start,
+            end,
+            tmin,
+            tmax,
+        });
+    }
+    if first >= last {
+        return Err(ErpError::EmptyWindow { start, end });
+    }
+
+    Ok((first, last))
+}
+
+// Averages the `epochs` (epochs x channels x samples) into an evoked response
+// When a `baseline` window `[start, end)` of samples is given, the mean of each channel of each
+// epoch over that window is subtracted beforehand, an empty window leaving the epochs as they are
+//
+// Returns the mean and its standard error across epochs (channels x samples), the standard error
+// being NaN for a single epoch
+pub fn average_epochs<S>(
+    epochs: &ArrayBase<S, Ix3>,
+    baseline: Option<(usize, usize)>,
+) -> (Array2<f32>, Array2<f32>)
+where
+    S: Data<Elem = f32>,
+{
+    let num_epochs = epochs.len_of(Axis(0));
+
+    average_epochs_weighted(epochs, &Array1::ones(num_epochs), baseline)
+}
+
+// Averages the `epochs` (epochs x channels x samples) into an evoked response, each epoch
+// contributing by its (non-negative) `weights`, e.g. to combine conditions of different sizes
+// When a `baseline` window `[start, end)` of samples is given, the mean of each channel of each
+// epoch over that window is subtracted beforehand, an empty window leaving the epochs as they are
+//
+// Returns the weighted mean and its standard error across epochs (channels x samples), the weights
+// being taken as reliability weights, so that the standard error is NaN for a single epoch
+pub fn average_epochs_weighted<S, T>(
+    epochs: &ArrayBase<S, Ix3>,
+    weights: &ArrayBase<T, Ix1>,
+    baseline: Option<(usize, usize)>,
+) -> (Array2<f32>, Array2<f32>)
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+{
+    let (num_epochs, num_channels, num_samples) = epochs.dim();
+    assert_eq!(
+        weights.len(),
+        num_epochs,
+        "One weight per epoch is required !"
+    );
+
+    let mut epochs = epochs.to_owned();
+    if let Some((start, end)) = baseline {
+        let end = end.min(num_samples);
+        if start < end {
+            let means = epochs
+                .slice(s![.., .., start..end])
+                .mean_axis(Axis(2))
+                .unwrap()
+                .insert_axis(Axis(2));
+            epochs -= &means;
+        }
+    }
+
+    let weights = weights / weights.sum();
+    let mut mean = Array2::<f32>::zeros((num_channels, num_samples));
+    for (epoch, &weight) in epochs.outer_iter().zip(&weights) {
+        mean.scaled_add(weight, &epoch);
+    }
+
+    // Weighted variance, unbiased for reliability weights, scaled into the variance of the mean
+    let mut squares = Array2::<f32>::zeros((num_channels, num_samples));
+    for (epoch, &weight) in epochs.outer_iter().zip(&weights) {
+        squares.scaled_add(weight, &(&epoch - &mean).mapv(|d| d * d));
+    }
+    let sum_squared_weights = weights.mapv(|w| w * w).sum();
+    let standard_error =
+        (squares * sum_squared_weights / (1.0 - sum_squared_weights)).mapv(f32::sqrt);
+
+    (mean, standard_error)
+}
+
+// Global field power of the `data` (channels x samples): the standard deviation across channels
+// at each sample, leaving out the channels at `exclude`
+pub fn global_field_power<S>(data: &ArrayBase<S, Ix2>, exclude: &[usize]) -> Array1<f32>
+where
+    S: Data<Elem = f32>,
+{
+    let included = (0..data.nrows())
+        .filter(|i| !exclude.contains(i))
+        .collect::<Vec<usize>>();
+
+    data.select(Axis(0), &included).std_axis(Axis(0), 0.0)
+}
+
+// Root mean square of the `signal` over a sliding window of `window_samples` samples, centered on
+// each sample, the `signal` being padded by reflection at both ends
+pub fn rms_envelope<S>(signal: &ArrayBase<S, Ix1>, window_samples: usize) -> Array1<f32>
+where
+    S: Data<Elem = f32>,
+{
+    assert!(window_samples > 0, "Window must be positive !");
+
+    let n = signal.len();
+    if n == 0 {
+        return Array1::zeros(0);
+    }
+    // Index of the padded sample `i` into the `signal`, reflecting about its first and last samples
+    let reflect = |i: isize| {
+        let period = 2 * (n as isize - 1);
+        if period == 0 {
+            return 0;
+        }
+        let i = i.rem_euclid(period);
+        (if i < n as isize { i } else { period - i }) as usize
+    };
+
+    // Cumulative sums of squares of the padded signal, in f64 to keep long signals accurate
+    let left = (window_samples / 2) as isize;
+    let mut cumulative = Vec::with_capacity(n + window_samples);
+    cumulative.push(0.0f64);
+    for i in -left..(n + window_samples) as isize - left - 1 {
+        let x = signal[reflect(i)] as f64;
+        cumulative.push(cumulative.last().unwrap() + x * x);
+    }
+
+    Array1::from_iter((0..n).map(|i| {
+        ((cumulative[i + window_samples] - cumulative[i]) / window_samples as f64).sqrt() as f32
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use ndarray::{Array3, Axis};
+
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    // A positive then negative deflection over 100 samples, flat over its first 20
+    fn template(t: usize) -> f32 {
+        let t = t as f32;
+        if t < 20.0 {
+            0.0
+        } else {
+            10.0 * ((t - 20.0) / 80.0 * TAU).sin()
+        }
+    }
+
+    // `num_epochs` copies of the template on 2 channels, with uniform noise of unit amplitude and
+    // an offset distinct for each epoch
+    fn noisy_copies(num_epochs: usize) -> Array3<f32> {
+        let mut generator = Lcg::new(3);
+        Array3::from_shape_fn((num_epochs, 2, 100), |(e, c, t)| {
+            let offset = e as f32 - num_epochs as f32 / 2.0;
+            (c + 1) as f32 * template(t) + offset + 2.0 * (generator.next_f32() - 0.5)
+        })
+    }
+
+    #[test]
+    fn averaging_noisy_copies_converges_to_the_template() {
+        let (mean, standard_error) = average_epochs(&noisy_copies(400), Some((0, 20)));
+
+        for ((c, t), &value) in mean.indexed_iter() {
+            assert!((value - (c + 1) as f32 * template(t)).abs() < 0.15);
+        }
+        // The standard error of uniform noise of variance 1/3, over 400 epochs
+        let expected = (1.0f32 / 3.0 / 400.0).sqrt();
+        assert!(standard_error
+            .iter()
+            .all(|&se| (se - expected).abs() < 0.01));
+
+        let baseline = mean.slice(s![.., 0..20]).mean_axis(Axis(1)).unwrap();
+        assert!(baseline.iter().all(|m| m.abs() < 1e-5));
+    }
+
+    #[test]
+    fn empty_baseline_leaves_the_epochs_as_they_are() {
+        let epochs = noisy_copies(5);
+        let (mean, _) = average_epochs(&epochs, Some((20, 20)));
+        let (uncorrected, _) = average_epochs(&epochs, None);
+
+        assert_eq!(mean, uncorrected);
+        let plain = epochs.mean_axis(Axis(0)).unwrap();
+        assert!(mean.iter().zip(&plain).all(|(a, b)| (a - b).abs() < 1e-5));
+    }
+
+    #[test]
+    fn single_epoch_has_a_nan_standard_error() {
+        let epochs = noisy_copies(1);
+        let (mean, standard_error) = average_epochs(&epochs, None);
+
+        assert_eq!(mean, epochs.index_axis(Axis(0), 0));
+        assert!(standard_error.iter().all(|se| se.is_nan()));
+    }
+
+    #[test]
+    fn weighted_average_combines_conditions() {
+        let a = Array3::from_elem((1, 1, 4), 1.0f32);
+        let b = Array3::from_elem((3, 1, 4), 5.0f32);
+        let epochs = ndarray::concatenate(Axis(0), &[a.view(), b.view()]).unwrap();
+
+        // Each condition weighted by the inverse of its number of epochs counts as much
+        let weights = Array1::from(vec![1.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0]);
+        let (mean, _) = average_epochs_weighted(&epochs, &weights, None);
+        assert!(mean.iter().all(|&m| (m - 3.0).abs() < 1e-6));
+
+        let (mean, _) = average_epochs_weighted(&epochs, &Array1::ones(4), None);
+        assert!(mean.iter().all(|&m| (m - 4.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn global_field_power_of_identical_channels_is_zero() {
+        let data = Array2::from_shape_fn((4, 50), |(_, t)| (t as f32 * 0.3).sin());
+
+        assert!(global_field_power(&data, &[])
+            .iter()
+            .all(|&g| g.abs() < 1e-6));
+    }
+
+    #[test]
+    fn global_field_power_of_antiphase_channels_is_their_amplitude() {
+        let data = Array2::from_shape_fn((3, 50), |(c, t)| match c {
+            0 => 5.0 * (t as f32 * 0.3).sin(),
+            1 => -5.0 * (t as f32 * 0.3).sin(),
+            _ => 100.0,
+        });
+        let gfp = global_field_power(&data, &[2]);
+
+        for (t, &g) in gfp.iter().enumerate() {
+            assert!((g - 5.0 * (t as f32 * 0.3).sin().abs()).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn rms_envelope_of_a_sinusoid_is_its_amplitude_over_sqrt_2() {
+        // Windows of whole periods of 20 samples, away from the reflections at the edges
+        let signal = Array1::from_shape_fn(400, |t| 3.0 * (t as f32 * TAU / 20.0).sin());
+        let envelope = rms_envelope(&signal, 40);
+
+        let expected = 3.0 / 2f32.sqrt();
+        let interior = envelope.slice(s![20..380]);
+        assert!(interior.iter().all(|&e| (e - expected).abs() < 1e-3));
+    }
+
+    #[test]
+    fn rms_envelope_reflects_at_the_edges() {
+        let signal = Array1::from(vec![1.0f32, 2.0, 3.0]);
+
+        // Padded as 2, 1, 2, 3, 2
+        let expected = [3f32.sqrt(), (14.0f32 / 3.0).sqrt(), (17.0f32 / 3.0).sqrt()];
+        let envelope = rms_envelope(&signal, 3);
+        for (e, expected) in envelope.iter().zip(expected) {
+            assert!((e - expected).abs() < 1e-6);
+        }
+        assert_eq!(rms_envelope(&Array1::<f32>::zeros(0), 3).len(), 0);
+    }
+
+    // Evoked response from -0.2 to 0.8 s at 250 Hz: a Gaussian bump of 5 µV peaking at 301.5 ms,
+    // between two samples, on the first channel, its opposite on the second, and a ramp of the
+    // time on the third
+    fn bump() -> Evoked {
+        let (fs, tmin, latency) = (250.0, -0.2, 0.3015);
+        let data = Array2::from_shape_fn((3, 250), |(c, i)| {
+            let t = tmin + i as f64 / fs;
+            let gaussian = 5.0 * (-0.5 * ((t - latency) / 0.03).powi(2)).exp() as f32;
+            match c {
+                0 => gaussian,
+                1 => -gaussian,
+                _ => t as f32,
+            }
+        });
+
+        Evoked {
+            standard_error: Array2::from_elem(data.dim(), f32::NAN),
+            data,
+            tmin,
+            sfreq: fs,
+            channel_names: vec!["Pz".into(), "Fz".into(), "Cz".into()],
+            num_epochs: 1,
+        }
+    }
+
+    #[test]
+    fn interpolated_peak_latency_is_within_a_millisecond() {
+        let evoked = bump();
+        let positive = evoked.peak_measure((0.2, 0.4), Polarity::Positive).unwrap();
+        let negative = evoked.peak_measure((0.2, 0.4), Polarity::Negative).unwrap();
+
+        // The highest sample, at 0.3 s, is 1.5 ms away
+        let highest = (0..250)
+            .max_by(|&a, &b| evoked.data[[0, a]].total_cmp(&evoked.data[[0, b]]))
+            .unwrap();
+        assert!((evoked.times()[highest] - 0.3).abs() < 1e-9);
+        for (peak, amplitude) in [(positive[0], 5.0), (negative[1], -5.0)] {
+            assert!((peak.latency - 0.3015).abs() < 1e-3, "{peak:?}");
+            assert!((peak.amplitude - amplitude).abs() < 1e-2, "{peak:?}");
+        }
+        // A ramp peaks at the end of the window, where it is no local extremum
+        assert!((positive[2].latency - 0.4).abs() < 1e-9);
+        assert!((negative[2].latency - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn mean_amplitude_spans_the_samples_of_the_window() {
+        let evoked = bump();
+        let means = evoked.mean_amplitude((0.0, 0.1)).unwrap();
+
+        // Samples from 0 to 0.1 s included
+        let expected = evoked
+            .data
+            .slice(s![.., 50..=75])
+            .mean_axis(Axis(1))
+            .unwrap();
+        assert_eq!(means, expected);
+        assert!((means[2] - 0.05).abs() < 1e-6);
+    }
+
+    #[test]
+    fn windows_outside_the_response_fail() {
+        let evoked = bump();
+
+        for window in [(-0.3, 0.1), (0.5, 0.9)] {
+            assert!(matches!(
+                evoked.peak_measure(window, Polarity::Positive),
+                Err(ErpError::WindowOutOfBounds { .. })
+            ));
+            assert!(matches!(
+                evoked.mean_amplitude(window),
+                Err(ErpError::WindowOutOfBounds { .. })
+            ));
+        }
+        // Between two samples
+        assert!(matches!(
+            evoked.mean_amplitude((0.301, 0.302)),
+            Err(ErpError::EmptyWindow { .. })
+        ));
+        // The whole response
+        assert!(evoked.mean_amplitude((-0.2, 0.796)).is_ok());
+    }
+}
@@ -0,0 +1,508 @@
+use std::{error::Error, fmt::Display};
+
+use ndarray::{s, Array1, Array2, Array3, ArrayBase, ArrayView1, Data, Ix1, Ix2};
+
+// Errors that can occur while computing features
+#[derive(Debug)]
+pub enum FeatureError {
+    // The signal is too short for templates of `m` samples to be compared
+    TooShort { len: usize, m: usize },
+    // The signal exceeds the length cap of a quadratic computation
+    TooLong { len: usize, max_len: usize },
+}
+
+impl Display for FeatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeatureError::TooShort { len, m } => write!(
+                f,
+                "signal of {len} samples is too short for templates of {m} samples"
+            ),
+            FeatureError::TooLong { len, max_len } => write!(
+                f,
+                "signal of {len} samples exceeds the cap of {max_len} samples"
+            ),
+        }
+    }
+}
+
+impl Error for FeatureError {}
+
+// Tolerance within which two templates of a signal match
+#[derive(Clone, Copy, Debug)]
+pub enum Tolerance {
+    // In the unit of the signal
+    Absolute(f32),
+    // In standard deviations of the signal, e.g. 0.2
+    StdDevs(f32),
+}
+
+// Handling of the samples past the last full window of a signal
+#[derive(Clone, Copy, Debug, Default)]
+pub enum PartialWindow {
+    // Left out
+    #[default]
+    Drop,
+    // Gathered into a final shorter window, when not already covered by the last full window
+    Keep,
+}
+
+// Iterator over the windows of `window` samples of a signal, starting every `hop` samples, along
+// with the index of their first sample
+#[derive(Clone, Debug)]
+pub struct SlidingWindows<'a> {
+    signal: ArrayView1<'a, f32>,
+    window: usize,
+    hop: usize,
+    num_windows: usize,
+    index: usize,
+}
+
+impl<'a> SlidingWindows<'a> {
+    // Panics if the window or the hop is empty
+    pub fn new<S>(
+        signal: &'a ArrayBase<S, Ix1>,
+        window: usize,
+        hop: usize,
+        partial: PartialWindow,
+    ) -> Self
+    where
+        S: Data<Elem = f32>,
+    {
+        assert!(window > 0 && hop > 0, "Empty window or hop !");
+
+        SlidingWindows {
+            signal: signal.view(),
+            window,
+            hop,
+            num_windows: num_windows(signal.len(), window, hop, partial),
+            index: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for SlidingWindows<'a> {
+    type Item = (usize, ArrayView1<'a, f32>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.num_windows {
+            return None;
+        }
+
+        let start = self.index * self.hop;
+        let end = (start + self.window).min(self.signal.len());
+        self.index += 1;
+
+        Some((start, self.signal.slice_move(s![start..end])))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.num_windows - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for SlidingWindows<'_> {}
+
+// Number of windows of `window` samples, every `hop` samples, over a signal of `len` samples
+fn num_windows(len: usize, window: usize, hop: usize, partial: PartialWindow) -> usize {
+    let full = if len >= window {
+        (len - window) / hop + 1
+    } else {
+        0
+    };
+
+    let uncovered = full == 0 || (full - 1) * hop + window < len;
+    match partial {
+        PartialWindow::Keep if full * hop < len && uncovered => full + 1,
+        _ => full,
+    }
+}
+
+// Feature of a window of samples, e.g. its RMS or Hjorth activity
+pub type WindowFeature = dyn Fn(ArrayView1<f32>) -> f32;
+
+// Features of each channel of the `data` (channels x samples) over its sliding windows of `window`
+// samples every `hop` samples, each of the `features` mapping a window to a value
+//
+// Returns the features of each window and channel (windows x channels x features)
+pub fn extract_features<S>(
+    data: &ArrayBase<S, Ix2>,
+    window: usize,
+    hop: usize,
+    partial: PartialWindow,
+    features: &[&WindowFeature],
+) -> Array3<f32>
+where
+    S: Data<Elem = f32>,
+{
+    assert!(window > 0 && hop > 0, "Empty window or hop !");
+    let num_windows = num_windows(data.ncols(), window, hop, partial);
+
+    let mut result = Array3::zeros((num_windows, data.nrows(), features.len()));
+    for (channel, signal) in data.outer_iter().enumerate() {
+        for (w, (_, samples)) in SlidingWindows::new(&signal, window, hop, partial).enumerate() {
+            for (f, feature) in features.iter().enumerate() {
+                result[[w, channel, f]] = feature(samples.view());
+            }
+        }
+    }
+
+    result
+}
+
+// Hjorth parameters of the `signal`:
+// - activity: its variance
+// - mobility: the standard deviation of its first difference over its own, with the difference
+//   taken per second rather than per sample when the sampling frequency `fs` is given
+// - complexity: the mobility of its first difference over its own
+//
+// Parameters that would divide by zero, e.g. the mobility and complexity of a constant signal, are 0
+pub fn hjorth<S>(signal: &ArrayBase<S, Ix1>, fs: Option<f32>) -> (f32, f32, f32)
+where
+    S: Data<Elem = f32>,
+{
+    if signal.len() < 3 {
+        return (signal.var(0.0), 0.0, 0.0);
+    }
+    let ratio = |numerator: f32, denominator: f32| {
+        if denominator > 0.0 {
+            numerator / denominator
+        } else {
+            0.0
+        }
+    };
+
+    let first = difference(signal) * fs.unwrap_or(1.0);
+    let second = difference(&first) * fs.unwrap_or(1.0);
+    let (activity, first_var, second_var) = (signal.var(0.0), first.var(0.0), second.var(0.0));
+
+    let mobility = ratio(first_var, activity).sqrt();
+    let complexity = ratio(ratio(second_var, first_var).sqrt(), mobility);
+
+    (activity, mobility, complexity)
+}
+
+// Hjorth parameters of each channel of the `data` (channels x samples), as computed by `hjorth`
+//
+// Returns the activity, mobility and complexity of each channel (channels x 3)
+pub fn hjorth_channels<S>(data: &ArrayBase<S, Ix2>, fs: Option<f32>) -> Array2<f32>
+where
+    S: Data<Elem = f32>,
+{
+    let mut parameters = Array2::zeros((data.nrows(), 3));
+    for (mut row, channel) in parameters.outer_iter_mut().zip(data.outer_iter()) {
+        let (activity, mobility, complexity) = hjorth(&channel, fs);
+        row.assign(&Array1::from(vec![activity, mobility, complexity]));
+    }
+
+    parameters
+}
+
+fn difference<S>(signal: &ArrayBase<S, Ix1>) -> Array1<f32>
+where
+    S: Data<Elem = f32>,
+{
+    &signal.slice(s![1..]) - &signal.slice(s![..-1])
+}
+
+// S. M. Pincus, "Approximate entropy as a measure of system complexity," Proceedings of the
+// National Academy of Sciences, vol. 88, no. 6, pp. 2297-2301, 1991.
+// J. S. Richman and J. R. Moorman, "Physiological time-series analysis using approximate entropy
+// and sample entropy," American Journal of Physiology, vol. 278, no. 6, pp. H2039-H2049, 2000.
+
+// Sample entropy of the `signal`: -ln(A / B), B and A being the numbers of pairs of distinct
+// templates of `m` and `m + 1` samples matching within the tolerance `r` (in Chebyshev distance),
+// both over the first n - m templates
+// Every pair of templates is compared, in O(n^2) time, comparisons stopping at the first sample
+// exceeding the tolerance: signals longer than `max_len` are rejected rather than computed
+//
+// Returns 0 for a constant signal, every template matching, and infinity when no templates of
+// `m + 1` samples match
+pub fn sample_entropy<S>(
+    signal: &ArrayBase<S, Ix1>,
+    m: usize,
+    r: Tolerance,
+    max_len: usize,
+) -> Result<f32, FeatureError>
+where
+    S: Data<Elem = f32>,
+{
+    let (x, r) = templates(signal, m, r, max_len)?;
+    let n = x.len();
+
+    let (mut a, mut b) = (0u64, 0u64);
+    for i in 0..n - m {
+        for j in i + 1..n - m {
+            if matches(&x[i..i + m], &x[j..j + m], r) {
+                b += 1;
+                if (x[i + m] - x[j + m]).abs() <= r {
+                    a += 1;
+                }
+            }
+        }
+    }
+
+    Ok(if a == 0 {
+        f32::INFINITY
+    } else {
+        (b as f64 / a as f64).ln() as f32
+    })
+}
+
+// Approximate entropy of the `signal`: phi_m - phi_(m + 1), phi_k being the mean over the
+// n - k + 1 templates of k samples of the logarithm of the fraction of templates matching each of
+// them within the tolerance `r` (in Chebyshev distance), itself included
+// Every pair of templates is compared, in O(n^2) time, comparisons stopping at the first sample
+// exceeding the tolerance: signals longer than `max_len` are rejected rather than computed
+//
+// Returns 0 for a constant signal
+pub fn approximate_entropy<S>(
+    signal: &ArrayBase<S, Ix1>,
+    m: usize,
+    r: Tolerance,
+    max_len: usize,
+) -> Result<f32, FeatureError>
+where
+    S: Data<Elem = f32>,
+{
+    let (x, r) = templates(signal, m, r, max_len)?;
+
+    let phi = |k: usize| {
+        let count = x.len() - k + 1;
+        let mut matching = vec![1u64; count];
+        for i in 0..count {
+            for j in i + 1..count {
+                if matches(&x[i..i + k], &x[j..j + k], r) {
+                    matching[i] += 1;
+                    matching[j] += 1;
+                }
+            }
+        }
+
+        matching
+            .iter()
+            .map(|&c| (c as f64 / count as f64).ln())
+            .sum::<f64>()
+            / count as f64
+    };
+
+    Ok((phi(m) - phi(m + 1)) as f32)
+}
+
+// Samples of the `signal` along with the tolerance `r` in its unit, checked to hold at least two
+// templates of `m + 1` samples and at most `max_len` samples
+fn templates<S>(
+    signal: &ArrayBase<S, Ix1>,
+    m: usize,
+    r: Tolerance,
+    max_len: usize,
+) -> Result<(Vec<f32>, f32), FeatureError>
+where
+    S: Data<Elem = f32>,
+{
+    let len = signal.len();
+    if len < m + 2 {
+        return Err(FeatureError::TooShort { len, m });
+    }
+    if len > max_len {
+        return Err(FeatureError::TooLong { len, max_len });
+    }
+
+    let r = match r {
+        Tolerance::Absolute(r) => r,
+        Tolerance::StdDevs(k) => k * signal.std(0.0),
+    };
+
+    Ok((signal.iter().copied().collect(), r))
+}
+
+// Whether the templates `a` and `b` are within the tolerance `r` of each other at every sample
+fn matches(a: &[f32], b: &[f32], r: f32) -> bool {
+    a.iter().zip(b).all(|(x, y)| (x - y).abs() <= r)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{PI, TAU};
+
+    use super::*;
+
+    // `periods` whole periods of a sinusoid of amplitude 2 and frequency `f`, sampled at `fs` Hz
+    fn sinusoid(f: f32, fs: f32, periods: usize) -> Array1<f32> {
+        let n = (periods as f32 * fs / f).round() as usize;
+        Array1::from_shape_fn(n, |t| 2.0 * (TAU * f * t as f32 / fs).sin())
+    }
+
+    #[test]
+    fn hjorth_mobility_of_a_sinusoid_follows_its_frequency() {
+        let (f, fs) = (10.0, 1000.0);
+        let (activity, mobility, complexity) = hjorth(&sinusoid(f, fs, 20), Some(fs));
+
+        // The variance of a sinusoid is half its squared amplitude, its difference being a
+        // sinusoid scaled by 2 sin(pi f / fs)
+        assert!((activity - 2.0).abs() < 1e-3);
+        let expected = 2.0 * fs * (PI * f / fs).sin();
+        assert!((mobility - expected).abs() / expected < 1e-3, "{mobility}");
+        assert!((mobility - TAU * f).abs() / (TAU * f) < 1e-3);
+        assert!((complexity - 1.0).abs() < 1e-3, "{complexity}");
+
+        // Per sample, the mobility is in radians per sample
+        let (_, per_sample, _) = hjorth(&sinusoid(f, fs, 20), None);
+        assert!((per_sample - mobility / fs).abs() < 1e-6);
+    }
+
+    #[test]
+    fn hjorth_of_a_constant_signal_does_not_divide_by_zero() {
+        assert_eq!(
+            hjorth(&Array1::from_elem(50, 3.0f32), Some(250.0)),
+            (0.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            hjorth(&Array1::from(vec![1.0f32, 3.0]), None),
+            (1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn hjorth_channels_match_hjorth() {
+        let data = ndarray::stack![
+            ndarray::Axis(0),
+            sinusoid(10.0, 500.0, 5),
+            sinusoid(50.0, 500.0, 25),
+            Array1::zeros(250)
+        ];
+        let parameters = hjorth_channels(&data, Some(500.0));
+
+        assert_eq!(parameters.dim(), (3, 3));
+        for (row, channel) in parameters.outer_iter().zip(data.outer_iter()) {
+            let (activity, mobility, complexity) = hjorth(&channel, Some(500.0));
+            assert_eq!(row.to_vec(), vec![activity, mobility, complexity]);
+        }
+    }
+
+    // Logistic map x <- 3.9 x (1 - x) from 0.4, iterated in double precision
+    fn logistic(n: usize) -> Array1<f32> {
+        let mut x = 0.4f64;
+        Array1::from_shape_fn(n, |_| {
+            let value = x as f32;
+            x = 3.9 * x * (1.0 - x);
+            value
+        })
+    }
+
+    #[test]
+    fn entropies_of_the_logistic_map_match_their_definitions() {
+        // Reference values from a direct evaluation of the definitions of Richman and Moorman and
+        // of Pincus over the same 500 samples, in double precision
+        let signal = logistic(500);
+        let r = Tolerance::Absolute(0.2);
+        for (m, sample, approximate) in
+            [(1, 0.451_820_3, 0.465_819_7), (2, 0.431_165_7, 0.431_468_9)]
+        {
+            let entropy = sample_entropy(&signal, m, r, 10_000).unwrap();
+            assert!((entropy - sample).abs() < 1e-5, "{m} {entropy}");
+            let entropy = approximate_entropy(&signal, m, r, 10_000).unwrap();
+            assert!((entropy - approximate).abs() < 1e-5, "{m} {entropy}");
+        }
+
+        // A tolerance in standard deviations is one in the unit of the signal
+        let std = signal.std(0.0);
+        assert_eq!(
+            sample_entropy(&signal, 2, Tolerance::StdDevs(0.2), 10_000).unwrap(),
+            sample_entropy(&signal, 2, Tolerance::Absolute(0.2 * std), 10_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn entropies_of_degenerate_signals() {
+        let constant = Array1::from_elem(100, 1.5f32);
+        for r in [Tolerance::Absolute(0.1), Tolerance::StdDevs(0.2)] {
+            assert_eq!(sample_entropy(&constant, 2, r, 1000).unwrap(), 0.0);
+            assert_eq!(approximate_entropy(&constant, 2, r, 1000).unwrap(), 0.0);
+        }
+        // No two templates match
+        let ramp = Array1::from_shape_fn(10, |t| t as f32);
+        assert_eq!(
+            sample_entropy(&ramp, 2, Tolerance::Absolute(0.5), 1000).unwrap(),
+            f32::INFINITY
+        );
+
+        assert!(matches!(
+            sample_entropy(&ramp.slice(s![..3]), 2, Tolerance::Absolute(0.5), 1000),
+            Err(FeatureError::TooShort { len: 3, m: 2 })
+        ));
+        assert!(matches!(
+            approximate_entropy(&ramp, 2, Tolerance::Absolute(0.5), 9),
+            Err(FeatureError::TooLong {
+                len: 10,
+                max_len: 9
+            })
+        ));
+    }
+
+    fn windows(len: usize, window: usize, hop: usize, partial: PartialWindow) -> Vec<Vec<f32>> {
+        let signal = Array1::from_shape_fn(len, |t| t as f32);
+        let windows = SlidingWindows::new(&signal, window, hop, partial);
+        let expected = windows.len();
+
+        let windows = windows
+            .map(|(start, samples)| {
+                assert_eq!(samples[0], start as f32);
+                samples.to_vec()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(windows.len(), expected);
+        windows
+    }
+
+    #[test]
+    fn partial_windows_are_kept_only_when_uncovered() {
+        let mut full = vec![
+            vec![0.0, 1.0, 2.0, 3.0],
+            vec![3.0, 4.0, 5.0, 6.0],
+            vec![6.0, 7.0, 8.0, 9.0],
+        ];
+        // The last full window ends with the signal
+        assert_eq!(windows(10, 4, 3, PartialWindow::Drop), full);
+        assert_eq!(windows(10, 4, 3, PartialWindow::Keep), full);
+        // Or a sample before it
+        assert_eq!(windows(11, 4, 3, PartialWindow::Drop), full);
+        full.push(vec![9.0, 10.0]);
+        assert_eq!(windows(11, 4, 3, PartialWindow::Keep), full);
+
+        // Samples between windows hopping further than their length are no partial window
+        assert_eq!(windows(11, 2, 4, PartialWindow::Keep).len(), 3);
+        // Signals shorter than a window
+        assert!(windows(3, 4, 1, PartialWindow::Drop).is_empty());
+        assert_eq!(
+            windows(3, 4, 1, PartialWindow::Keep),
+            vec![vec![0.0, 1.0, 2.0]]
+        );
+    }
+
+    #[test]
+    fn features_are_extracted_per_window_and_channel() {
+        let data = Array2::from_shape_fn((2, 11), |(c, t)| (t * (1 + 9 * c)) as f32);
+        let mean = |w: ArrayView1<f32>| w.mean().unwrap();
+        let max = |w: ArrayView1<f32>| w.fold(f32::MIN, |max, &x| max.max(x));
+
+        let features = extract_features(&data, 4, 3, PartialWindow::Keep, &[&mean, &max]);
+        assert_eq!(features.dim(), (4, 2, 2));
+        assert_eq!(
+            features.slice(s![.., 0, 0]).to_vec(),
+            vec![1.5, 4.5, 7.5, 9.5]
+        );
+        assert_eq!(
+            features.slice(s![.., 0, 1]).to_vec(),
+            vec![3.0, 6.0, 9.0, 10.0]
+        );
+        assert_eq!(
+            features.slice(s![.., 1, ..]),
+            features.slice(s![.., 0, ..]).mapv(|f| 10.0 * f)
+        );
+
+        let dropped = extract_features(&data, 4, 3, PartialWindow::Drop, &[&mean, &max]);
+        assert_eq!(dropped, features.slice(s![..3, .., ..]));
+    }
+}
@@ -1,15 +1,57 @@
 use core::f32;
+use std::error::Error;
+use std::fmt::{Debug, Display};
+
 use nalgebra::Complex;
-use ndarray::{s, Array1, Array2, ArrayBase, Data, Ix1};
-use num_traits::identities::Zero;
+use ndarray::{s, Array1, Array2, ArrayBase, Data, Ix1, ScalarOperand};
+use num_traits::{identities::Zero, Float, FloatConst, FromPrimitive, NumAssign};
 use std::f32::consts::PI;
 
+// Floating-point types of the complex-valued data of which Fourier Transforms are computed, `f32`
+// being the default throughout the crate and `f64` trading memory for accuracy
+pub trait FftElement:
+    Float + FloatConst + FromPrimitive + NumAssign + ScalarOperand + Debug + Send + Sync
+{
+}
+
+impl FftElement for f32 {}
+impl FftElement for f64 {}
+
+// Errors that can occur while computing Fourier transforms
+#[derive(Debug)]
+pub enum FftError {
+    // The window of a short-time transform is empty
+    ZeroWindow,
+    // The hop between the windows of a short-time transform is zero
+    ZeroHop,
+    // The window of a short-time transform is longer than the signal
+    WindowTooLong { window_size: usize, len: usize },
+}
+
+impl Display for FftError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FftError::ZeroWindow => f.write_str("window must hold at least one sample"),
+            FftError::ZeroHop => f.write_str("hop between windows must be at least one sample"),
+            FftError::WindowTooLong { window_size, len } => write!(
+                f,
+                "window of {window_size} samples is longer than the signal of {len} samples"
+            ),
+        }
+    }
+}
+
+impl Error for FftError {}
+
 // Trait which implements different FFT algorithms, from complex-valued time-domain data to
 // complex-valued frequency-domain
 pub trait FourierTransform {
-    fn dft(&self) -> Array1<Complex<f32>>;
+    type Elem;
+
+    fn dft(&self) -> Array1<Complex<Self::Elem>>;
     // Cooley-Tukey radix-2 algorithm
-    fn fft(&self) -> Array1<Complex<f32>>;
+    // Lengths other than powers of 2 go through Bluestein's algorithm instead
+    fn fft(&self) -> Array1<Complex<Self::Elem>>;
 }
 
 // Trait which implements different FFT algorithms, from real-valued time-domain data to
@@ -22,15 +64,24 @@ pub trait RealFourierTransform {
     // Does the same thing as normal FFT, just maps the signal to complex values
     fn rfft(&self) -> Array1<Complex<f32>>;
     // Short-time FT implementation using a sine window
+    // Panics if the window or the hop is empty, or the window longer than the signal
     fn stft(&self, window_size: usize, hop_size: usize) -> Array2<Complex<f32>>;
+    // The same transform, failing instead of panicking
+    fn try_stft(
+        &self,
+        window_size: usize,
+        hop_size: usize,
+    ) -> Result<Array2<Complex<f32>>, FftError>;
 }
 
 // Trait which implements an inverse FFT algorithm, from complex-valued frequency-domain to
 // complex-valued time-domain
 pub trait InverseFourierTransform {
-    fn idft(&self) -> Array1<Complex<f32>>;
+    type Elem;
+
+    fn idft(&self) -> Array1<Complex<Self::Elem>>;
     // Conjugate trick for computing the inverse FFT
-    fn ifft(&self) -> Array1<Complex<f32>>;
+    fn ifft(&self) -> Array1<Complex<Self::Elem>>;
 }
 
 // Trait which implements different inverse FFT algorithms, from complex-valued frequency-domain to
@@ -42,11 +93,14 @@ pub trait RealInverseFourierTransform {
     fn irfft(&self) -> Array1<f32>;
 }
 
-impl<S> FourierTransform for ArrayBase<S, Ix1>
+impl<A, S> FourierTransform for ArrayBase<S, Ix1>
 where
-    S: Data<Elem = Complex<f32>>,
+    A: FftElement,
+    S: Data<Elem = Complex<A>>,
 {
-    fn dft(&self) -> Array1<Complex<f32>> {
+    type Elem = A;
+
+    fn dft(&self) -> Array1<Complex<A>> {
         let n = self.len();
         let mut result = Array1::zeros(n);
 
@@ -54,7 +108,7 @@ where
             let mut sum = Complex::zero();
 
             for t in 0..n {
-                let angle = -2.0f32 * PI * k as f32 * t as f32 / n as f32;
+                let angle = -A::TAU() * from_usize::<A>(k) * from_usize(t) / from_usize(n);
                 let twiddle = Complex::new(angle.cos(), angle.sin());
 
                 sum += twiddle * self[t];
@@ -66,9 +120,13 @@ where
         result
     }
 
-    fn fft(&self) -> Array1<Complex<f32>> {
+    fn fft(&self) -> Array1<Complex<A>> {
         let n = self.len();
 
+        if !n.is_power_of_two() {
+            return bluestein(self);
+        }
+
         // Base case
         if n == 1 {
             return Array1::from_elem(1, self[0]);
@@ -85,7 +143,7 @@ where
         // Return the full spectrum of frequencies
         let mut result = Array1::zeros(n);
         for k in 0..n / 2 {
-            let angle = -2.0 * PI * k as f32 / n as f32;
+            let angle = -A::TAU() * from_usize::<A>(k) / from_usize(n);
             let twiddle = Complex::new(angle.cos(), angle.sin());
 
             result[k] = fft_even[k] + twiddle * fft_odd[k];
@@ -125,6 +183,28 @@ where
     }
 
     fn stft(&self, window_size: usize, hop_size: usize) -> Array2<Complex<f32>> {
+        self.try_stft(window_size, hop_size)
+            .expect("Invalid STFT window !")
+    }
+
+    fn try_stft(
+        &self,
+        window_size: usize,
+        hop_size: usize,
+    ) -> Result<Array2<Complex<f32>>, FftError> {
+        if window_size == 0 {
+            return Err(FftError::ZeroWindow);
+        }
+        if hop_size == 0 {
+            return Err(FftError::ZeroHop);
+        }
+        if window_size > self.len() {
+            return Err(FftError::WindowTooLong {
+                window_size,
+                len: self.len(),
+            });
+        }
+
         // Pad the window size to be of power-of-2 length
         let next_pow_2 = window_size.next_power_of_two();
         // Construct the sine window function
@@ -145,15 +225,18 @@ where
             result.slice_mut(s![i, ..]).assign(&spectrum);
         }
 
-        result
+        Ok(result)
     }
 }
 
-impl<S> InverseFourierTransform for ArrayBase<S, Ix1>
+impl<A, S> InverseFourierTransform for ArrayBase<S, Ix1>
 where
-    S: Data<Elem = Complex<f32>>,
+    A: FftElement,
+    S: Data<Elem = Complex<A>>,
 {
-    fn idft(&self) -> Array1<Complex<f32>> {
+    type Elem = A;
+
+    fn idft(&self) -> Array1<Complex<A>> {
         let n = self.len();
         let mut result = Array1::zeros(n);
 
@@ -161,7 +244,7 @@ where
             let mut sum = Complex::zero();
 
             for t in 0..n {
-                let angle = 2.0f32 * PI * k as f32 * t as f32 / n as f32;
+                let angle = A::TAU() * from_usize::<A>(k) * from_usize(t) / from_usize(n);
                 let twiddle = Complex::new(angle.cos(), angle.sin());
 
                 sum += twiddle * self[t];
@@ -170,12 +253,12 @@ where
             result[k] = sum;
         }
 
-        result / n as f32
+        result / from_usize::<A>(n)
     }
 
-    fn ifft(&self) -> Array1<Complex<f32>> {
+    fn ifft(&self) -> Array1<Complex<A>> {
         // Conjugates the data, applies a forward FFT then conjugates the output back
-        self.map(|x| x.conj()).fft().map(|x| x.conj()) / self.len() as f32
+        self.map(|x| x.conj()).fft().map(|x| x.conj()) / from_usize::<A>(self.len())
     }
 }
 
@@ -210,6 +293,50 @@ where
     }
 }
 
+// Bluestein's algorithm, computing the FFT of a `signal` of any length as the convolution of two
+// chirps, itself computed by radix-2 FFTs of a power-of-2 length
+//
+// X[k] = w*[k] sum_j (x[j] w*[j]) w[k - j], where w[j] = exp(i pi j^2 / n)
+fn bluestein<A, S>(signal: &ArrayBase<S, Ix1>) -> Array1<Complex<A>>
+where
+    A: FftElement,
+    S: Data<Elem = Complex<A>>,
+{
+    let n = signal.len();
+    if n == 0 {
+        return Array1::zeros(0);
+    }
+
+    // j^2 is reduced modulo 2n beforehand, so that the angle of large indices stays accurate
+    let chirp = Array1::from_shape_fn(n, |j| {
+        let angle = std::f64::consts::PI * ((j * j) % (2 * n)) as f64 / n as f64;
+        Complex::new(
+            A::from_f64(angle.cos()).unwrap(),
+            A::from_f64(angle.sin()).unwrap(),
+        )
+    });
+
+    let m = (2 * n - 1).next_power_of_two();
+    let mut a = Array1::zeros(m);
+    let mut b = Array1::zeros(m);
+    for j in 0..n {
+        a[j] = signal[j] * chirp[j].conj();
+        b[j] = chirp[j];
+        if j > 0 {
+            b[m - j] = chirp[j];
+        }
+    }
+
+    let products = &a.fft() * &b.fft();
+    let convolution = products.ifft();
+
+    Array1::from_shape_fn(n, |k| convolution[k] * chirp[k].conj())
+}
+
+pub(crate) fn from_usize<A: FftElement>(value: usize) -> A {
+    A::from_usize(value).unwrap()
+}
+
 // Computes the FFT frequencies for an n-point FFT with the `sampling_freq` in Hz
 pub fn freqs(n: usize, sampling_freq: f32) -> Array1<f32> {
     let df = sampling_freq / n as f32;
@@ -221,3 +348,138 @@ pub fn rfreqs(n: usize, sampling_freq: f32) -> Array1<f32> {
     let n_pos = n / 2 + 1;
     Array1::from_iter((0..n_pos).map(|i| i as f32 * sampling_freq / n as f32))
 }
+
+// P. Welch, "The use of fast Fourier transform for the estimation of power spectra: A method
+// based on time averaging over short, modified periodograms," IEEE Transactions on Audio and
+// Electroacoustics, vol. 15, no. 2, pp. 70-73, 1967.
+
+// Power spectral density of the `signal`, sampled at `fs` Hz, by Welch's method: the one-sided
+// periodograms of its segments of `segment_len` samples (the whole signal if shorter), overlapping
+// by half, each demeaned and Hann-windowed, are averaged, as scipy's `welch` does
+//
+// Returns the frequencies in Hz and the PSD in units^2/Hz, of `segment_len / 2 + 1` values each
+// Panics if the segments are empty
+pub fn welch<S>(
+    signal: &ArrayBase<S, Ix1>,
+    fs: f32,
+    segment_len: usize,
+) -> (Array1<f32>, Array1<f32>)
+where
+    S: Data<Elem = f32>,
+{
+    assert!(segment_len > 0, "Empty Welch segments !");
+    let segment_len = segment_len.min(signal.len()).max(1);
+    let hop = (segment_len / 2).max(1);
+
+    // Periodic Hann window
+    let window = Array1::from_shape_fn(segment_len, |n| {
+        0.5 - 0.5 * (2.0 * PI * n as f32 / segment_len as f32).cos()
+    });
+    let scale = fs * window.mapv(|w| w * w).sum();
+
+    let num_freqs = segment_len / 2 + 1;
+    let mut psd = Array1::<f32>::zeros(num_freqs);
+    let mut num_segments = 0;
+    let mut start = 0;
+    while start + segment_len <= signal.len() {
+        let segment = signal.slice(s![start..start + segment_len]);
+        let mean = segment.mean().unwrap();
+        let windowed = Array1::from_shape_fn(segment_len, |n| (segment[n] - mean) * window[n]);
+        let spectrum = windowed.rfft();
+
+        for (k, value) in psd.iter_mut().enumerate() {
+            // Negative frequencies folded onto the positive ones, but for DC and Nyquist
+            let factor = if k == 0 || 2 * k == segment_len {
+                1.0
+            } else {
+                2.0
+            };
+            *value += factor * spectrum[k].norm_sqr() / scale;
+        }
+        num_segments += 1;
+        start += hop;
+    }
+    if num_segments > 0 {
+        psd /= num_segments as f32;
+    }
+
+    (rfreqs(segment_len, fs), psd)
+}
+
+// Computes the analytic signal of the real-valued `signal` through the Hilbert transform, whose
+// argument is the instantaneous phase of the `signal`
+// The `signal` is zero-padded to a power of 2 for the FFT, which only distorts its edges
+pub fn analytic_signal<S>(signal: &ArrayBase<S, Ix1>) -> Array1<Complex<f32>>
+where
+    S: Data<Elem = f32>,
+{
+    let n = signal.len();
+    let padded_len = n.next_power_of_two();
+    let mut padded = Array1::zeros(padded_len);
+    padded.slice_mut(s![..n]).assign(signal);
+
+    // Keep the DC (and Nyquist) components, double the positive frequencies, drop the negative ones
+    let mut spectrum = padded.rfft();
+    for (k, value) in spectrum.iter_mut().enumerate() {
+        if k == 0 || 2 * k == padded_len {
+            continue;
+        }
+        *value *= if 2 * k < padded_len { 2.0 } else { 0.0 };
+    }
+
+    spectrum.ifft().slice(s![..n]).to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    fn noise(n: usize, seed: u64) -> Array1<Complex<f64>> {
+        let mut generator = Lcg::new(seed);
+        Array1::from_shape_fn(n, |_| {
+            Complex::new(
+                generator.next_f32() as f64 - 0.5,
+                generator.next_f32() as f64 - 0.5,
+            )
+        })
+    }
+
+    #[test]
+    fn fft_of_any_length_matches_the_dft() {
+        for n in [1, 2, 3, 7, 64, 100, 300, 1023] {
+            let signal = noise(n, n as u64);
+            let (fast, direct) = (signal.fft(), signal.dft());
+            let error = fast
+                .iter()
+                .zip(&direct)
+                .map(|(a, b)| (a - b).norm())
+                .fold(0.0, f64::max);
+            assert!(error < 1e-9 * n as f64, "{n} {error}");
+
+            let round_trip = fast.ifft();
+            let error = round_trip
+                .iter()
+                .zip(&signal)
+                .map(|(a, b)| (a - b).norm())
+                .fold(0.0, f64::max);
+            assert!(error < 1e-12, "{n} {error}");
+        }
+    }
+
+    #[test]
+    fn invalid_stft_windows_fail() {
+        let signal = Array1::<f32>::zeros(5);
+
+        assert!(matches!(signal.try_stft(0, 1), Err(FftError::ZeroWindow)));
+        assert!(matches!(signal.try_stft(2, 0), Err(FftError::ZeroHop)));
+        assert!(matches!(
+            signal.try_stft(10, 1),
+            Err(FftError::WindowTooLong {
+                window_size: 10,
+                len: 5
+            })
+        ));
+        assert_eq!(signal.try_stft(5, 1).unwrap().nrows(), 1);
+    }
+}
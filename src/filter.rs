@@ -1,7 +1,12 @@
+use std::f32::consts::PI;
+
 use nalgebra::Complex;
-use ndarray::{s, Array1, ArrayBase, Data, Ix1};
+use ndarray::{s, Array1, Array2, ArrayBase, Data, Ix1};
 
-use crate::fft::{RealFourierTransform, RealInverseFourierTransform};
+use crate::{
+    fft::{RealFourierTransform, RealInverseFourierTransform},
+    source::SignalSource,
+};
 
 pub struct FIRFilter {
     coefficients: Array1<f32>,
@@ -14,6 +19,91 @@ impl FIRFilter {
         }
     }
 
+    // Windowed-sinc (Hamming) low-pass filter of `num_taps` coefficients, cutting off at `cutoff`
+    // Hz for data sampled at `fs` Hz
+    // An even `num_taps` is rounded up, so that the filter is symmetric about its middle coefficient
+    pub fn lowpass(num_taps: usize, cutoff: f32, fs: f32) -> Self {
+        Self::windowed_sinc(num_taps, |t| ideal_lowpass(t, cutoff / fs))
+    }
+
+    // Windowed-sinc (Hamming) high-pass filter of `num_taps` coefficients, cutting off at `cutoff`
+    // Hz for data sampled at `fs` Hz
+    // An even `num_taps` is rounded up, so that the filter is symmetric about its middle coefficient
+    pub fn highpass(num_taps: usize, cutoff: f32, fs: f32) -> Self {
+        Self::windowed_sinc(num_taps, |t| {
+            ideal_lowpass(t, 0.5) - ideal_lowpass(t, cutoff / fs)
+        })
+    }
+
+    // Windowed-sinc (Hamming) band-pass filter of `num_taps` coefficients, passing `low` to `high`
+    // Hz for data sampled at `fs` Hz
+    // An even `num_taps` is rounded up, so that the filter is symmetric about its middle coefficient
+    pub fn bandpass(num_taps: usize, low: f32, high: f32, fs: f32) -> Self {
+        Self::windowed_sinc(num_taps, |t| {
+            ideal_lowpass(t, high / fs) - ideal_lowpass(t, low / fs)
+        })
+    }
+
+    // Windowed-sinc (Hamming) band-stop filter of `num_taps` coefficients, rejecting `low` to
+    // `high` Hz for data sampled at `fs` Hz, e.g. as a notch around the power line frequency
+    // An even `num_taps` is rounded up, so that the filter is symmetric about its middle coefficient
+    pub fn bandstop(num_taps: usize, low: f32, high: f32, fs: f32) -> Self {
+        Self::windowed_sinc(num_taps, |t| {
+            ideal_lowpass(t, 0.5) - ideal_lowpass(t, high / fs) + ideal_lowpass(t, low / fs)
+        })
+    }
+
+    // Filter of the `ideal` impulse response, a function of the offset from the middle coefficient,
+    // under a Hamming window
+    fn windowed_sinc(num_taps: usize, ideal: impl Fn(f32) -> f32) -> Self {
+        let num_taps = num_taps | 1;
+        let middle = (num_taps / 2) as f32;
+
+        let coefficients = (0..num_taps)
+            .map(|i| {
+                let window =
+                    0.54 - 0.46 * (2.0 * PI * i as f32 / (num_taps - 1).max(1) as f32).cos();
+                ideal(i as f32 - middle) * window
+            })
+            .collect::<Vec<f32>>();
+
+        Self::new(coefficients)
+    }
+
+    pub fn num_taps(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    // Filters the `signal` without shifting it in time, returning as many samples as it has
+    // Only meant for symmetric filters of an odd number of coefficients, such as the windowed-sinc
+    // ones, whose delay of half their length is then compensated for exactly
+    pub fn process_zero_phase<S>(&self, signal: &ArrayBase<S, Ix1>) -> Array1<f32>
+    where
+        S: Data<Elem = f32>,
+    {
+        let delay = self.coefficients.len() / 2;
+
+        self.process(signal)
+            .slice(s![delay..delay + signal.len()])
+            .to_owned()
+    }
+
+    // Filters each channel of the `data` (channels x samples) without shifting it in time, as by
+    // `process_zero_phase`
+    // The `data` is any `SignalSource`, e.g. an array or a `ScaledView` of Int16 data, converted a
+    // channel at a time
+    pub fn process_zero_phase_channels<D>(&self, data: &D) -> Array2<f32>
+    where
+        D: SignalSource + ?Sized,
+    {
+        let mut filtered = Array2::zeros((data.num_channels(), data.num_samples()));
+        for (index, mut row) in filtered.outer_iter_mut().enumerate() {
+            row.assign(&self.process_zero_phase(&data.channel(index)));
+        }
+
+        filtered
+    }
+
     #[allow(non_snake_case)]
     pub fn process<S>(&self, signal: &ArrayBase<S, Ix1>) -> Array1<f32>
     where
@@ -53,3 +143,110 @@ impl FIRFilter {
         output
     }
 }
+
+// Impulse response of an ideal low-pass filter cutting off at the normalized frequency `cutoff`
+// (cycles per sample), `t` samples away from its middle
+fn ideal_lowpass(t: f32, cutoff: f32) -> f32 {
+    if t == 0.0 {
+        2.0 * cutoff
+    } else {
+        (2.0 * PI * cutoff * t).sin() / (PI * t)
+    }
+}
+
+// Largest denominator of the rational factor a signal is resampled by between two rates
+const MAX_RESAMPLING_DENOMINATOR: usize = 1000;
+
+// Half-length, in samples of the lower of the two rates, of the anti-aliasing filter of
+// `resample_poly`, as scipy's
+const RESAMPLE_HALF_LEN: usize = 10;
+
+// Resamples the `signal` by the rational factor `up / down`: it is up-sampled by inserting
+// `up - 1` zeros between its samples, low-pass filtered (zero-phase) below the lower of the two
+// Nyquist frequencies, then down-sampled by keeping every `down`-th sample
+//
+// Returns `ceil(n * up / down)` samples, sample `i` of the `signal` landing at `i * up / down`
+// Panics if either factor is zero
+pub fn resample_poly<S>(signal: &ArrayBase<S, Ix1>, up: usize, down: usize) -> Array1<f32>
+where
+    S: Data<Elem = f32>,
+{
+    assert!(up > 0 && down > 0, "Resampling factors must be positive !");
+    let divisor = gcd(up, down);
+    let (up, down) = (up / divisor, down / divisor);
+    if up == 1 && down == 1 {
+        return signal.to_owned();
+    }
+
+    let mut upsampled = Array1::zeros(signal.len() * up);
+    upsampled.slice_mut(s![..;up]).assign(signal);
+
+    // The gain of `up` restores the amplitude spread out by the inserted zeros
+    let max_factor = up.max(down);
+    let filter = FIRFilter::lowpass(
+        2 * RESAMPLE_HALF_LEN * max_factor + 1,
+        0.5 / max_factor as f32,
+        1.0,
+    );
+    let filtered = filter.process_zero_phase(&upsampled) * up as f32;
+
+    filtered.slice(s![..;down]).to_owned()
+}
+
+// Decimates the `signal` by the integer `factor`, low-pass filtering it beforehand, as
+// `resample_poly` with an up-sampling factor of 1
+pub fn decimate<S>(signal: &ArrayBase<S, Ix1>, factor: usize) -> Array1<f32>
+where
+    S: Data<Elem = f32>,
+{
+    resample_poly(signal, 1, factor)
+}
+
+// Resamples the `signal` from `fs_in` to `fs_out` Hz, by `resample_poly` of the factor `up / down`
+// nearest to the ratio of the rates, of a denominator up to 1000 (exact for any two integer rates
+// of such a ratio)
+//
+// Returns the resampled signal, along with its actual rate `fs_in * up / down`
+// Panics if either rate is not positive
+pub fn resample<S>(signal: &ArrayBase<S, Ix1>, fs_in: f64, fs_out: f64) -> (Array1<f32>, f64)
+where
+    S: Data<Elem = f32>,
+{
+    assert!(
+        fs_in > 0.0 && fs_out > 0.0,
+        "Sampling frequencies must be positive !"
+    );
+    let (up, down) = rational(fs_out / fs_in);
+
+    (
+        resample_poly(signal, up, down),
+        fs_in * up as f64 / down as f64,
+    )
+}
+
+// Fraction `up / down` nearest to the positive `ratio`, of a denominator up to
+// `MAX_RESAMPLING_DENOMINATOR`, the smallest one when exact
+pub(crate) fn rational(ratio: f64) -> (usize, usize) {
+    let mut best = (1, 1);
+    let mut best_error = f64::INFINITY;
+    for down in 1..=MAX_RESAMPLING_DENOMINATOR {
+        let up = (ratio * down as f64).round().max(1.0) as usize;
+        let error = (up as f64 / down as f64 - ratio).abs();
+        if error < best_error {
+            (best, best_error) = ((up, down), error);
+        }
+        if error <= 1e-9 * ratio {
+            break;
+        }
+    }
+
+    best
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
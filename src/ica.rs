@@ -0,0 +1,339 @@
+// A. Hyvärinen, "Fast and robust fixed-point algorithms for independent component analysis," in IEEE Transactions on Neural Networks, vol. 10, no. 3, pp. 626-634, May 1999, doi: 10.1109/72.761722.
+
+use std::{error::Error, fmt::Display};
+
+use nalgebra::{DMatrix, DVector, SymmetricEigen};
+use ndarray::{Array1, Array2, ArrayBase, Axis, Data, Ix2};
+
+use crate::{
+    covariance::{CovOptions, Covariance, CovarianceError},
+    whitening::{apply_whitening, whitening_matrix, Whitening},
+};
+
+// Independent components of multichannel data, separated by symmetric FastICA with the logcosh
+// nonlinearity
+#[derive(Clone, Debug)]
+pub struct FastIca {
+    // Mean of each channel, removed before separation
+    mean: Array1<f32>,
+    // Maps the centered channels to the components (components x channels)
+    unmixing: Array2<f32>,
+    // Maps the components back to the centered channels (channels x components)
+    mixing: Array2<f32>,
+    // Components of the data the separation was fit on (components x samples)
+    sources: Array2<f32>,
+    iterations: usize,
+}
+
+// Errors that can occur while fitting a `FastIca`
+#[derive(Debug)]
+pub enum IcaError {
+    // More components were requested than the data has channels
+    TooManyComponents { requested: usize, channels: usize },
+    // The data does not span as many dimensions as the requested components
+    RankDeficient { rank: usize, requested: usize },
+    // The unmixing matrix did not converge within the allowed iterations
+    NotConverged { iterations: usize, change: f64 },
+    // The covariance of the data cannot be computed to whiten it, e.g. for lack of samples
+    Covariance(CovarianceError),
+}
+
+impl Display for IcaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IcaError::TooManyComponents {
+                requested,
+                channels,
+            } => write!(
+                f,
+                "{requested} components requested from data of only {channels} channels"
+            ),
+            IcaError::RankDeficient { rank, requested } => write!(
+                f,
+                "{requested} components requested from data of rank {rank}"
+            ),
+            IcaError::NotConverged { iterations, change } => write!(
+                f,
+                "FastICA did not converge in {iterations} iterations (last change {change:e})"
+            ),
+            IcaError::Covariance(error) => write!(f, "cannot whiten the data: {error}"),
+        }
+    }
+}
+
+impl Error for IcaError {}
+
+impl FastIca {
+    // Separate the `data` (channels x samples) into `n_components` independent components, the
+    // data being centered and PCA-whitened (as by `whitening_matrix`) onto its `n_components`
+    // principal components first
+    // The unmixing matrix is updated at most `max_iter` times, until the change of its rows falls
+    // below `tol`
+    pub fn fit<S>(
+        data: &ArrayBase<S, Ix2>,
+        n_components: usize,
+        max_iter: usize,
+        tol: f64,
+    ) -> Result<FastIca, IcaError>
+    where
+        S: Data<Elem = f32>,
+    {
+        let (num_channels, num_samples) = data.dim();
+        if n_components == 0 || n_components > num_channels {
+            return Err(IcaError::TooManyComponents {
+                requested: n_components,
+                channels: num_channels,
+            });
+        }
+
+        // PCA whitening of the population covariance onto its leading components: Z = L^-1/2 U^T X
+        // The covariance maps whitened components back to the channels: C W^T = U L^1/2
+        let data = data.mapv(|x| x as f64);
+        let covariance = data
+            .compute_covariance_opts(&CovOptions::new().ddof(0))
+            .map_err(IcaError::Covariance)?;
+        let mean = data.mean_axis(Axis(1)).unwrap();
+        let centered = &data - &mean.view().insert_axis(Axis(1));
+        let whitening = whitening_matrix(&covariance, Whitening::Pca, Some(n_components), 0.0);
+        if whitening.nrows() < n_components {
+            return Err(IcaError::RankDeficient {
+                rank: whitening.nrows(),
+                requested: n_components,
+            });
+        }
+        let dewhitening = covariance.dot(&whitening.t());
+        let whitened = to_matrix(&apply_whitening(&whitening, &centered));
+        let (whitening, dewhitening) = (to_matrix(&whitening), to_matrix(&dewhitening));
+
+        // Deterministic, well-spread initial guess
+        let mut seed = 0x853c_49e6_748f_ea9bu64;
+        let mut w =
+            symmetric_decorrelation(&DMatrix::from_fn(n_components, n_components, |_, _| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+            }));
+
+        let mut iterations = 0;
+        let mut change = f64::INFINITY;
+        while change > tol {
+            if iterations == max_iter {
+                return Err(IcaError::NotConverged { iterations, change });
+            }
+
+            // w+ = E{z g(w^T z)} - E{g'(w^T z)} w, with g = tanh
+            let projections = (&w * &whitened).map(f64::tanh);
+            let derivatives = DVector::from_fn(n_components, |i, _| {
+                projections.row(i).iter().map(|g| 1.0 - g * g).sum::<f64>() / num_samples as f64
+            });
+            let updated = &projections * whitened.transpose() / num_samples as f64
+                - DMatrix::from_diagonal(&derivatives) * &w;
+            let updated = symmetric_decorrelation(&updated);
+
+            // Rows converge up to their sign, once aligned with their previous value
+            change = (&updated * w.transpose())
+                .diagonal()
+                .iter()
+                .map(|d| (d.abs() - 1.0).abs())
+                .fold(0.0, f64::max);
+            w = updated;
+            iterations += 1;
+        }
+
+        let unmixing = &w * &whitening;
+        // `w` being orthogonal, its inverse is its transpose
+        let mixing = &dewhitening * w.transpose();
+        let sources = &w * &whitened;
+
+        Ok(FastIca {
+            mean: mean.mapv(|x| x as f32),
+            unmixing: to_array(&unmixing),
+            mixing: to_array(&mixing),
+            sources: to_array(&sources),
+            iterations,
+        })
+    }
+
+    // Maps the centered channels to the components (components x channels)
+    pub fn unmixing_matrix(&self) -> &Array2<f32> {
+        &self.unmixing
+    }
+
+    // Maps the components back to the centered channels (channels x components)
+    pub fn mixing_matrix(&self) -> &Array2<f32> {
+        &self.mixing
+    }
+
+    // Components of the data the separation was fit on (components x samples)
+    pub fn sources(&self) -> &Array2<f32> {
+        &self.sources
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    // Components of other `data` (channels x samples), through the fitted unmixing matrix
+    pub fn transform<S>(&self, data: &ArrayBase<S, Ix2>) -> Array2<f32>
+    where
+        S: Data<Elem = f32>,
+    {
+        self.unmixing
+            .dot(&(data - &self.mean.view().insert_axis(Axis(1))))
+    }
+
+    // Reconstruct the data the separation was fit on without the components at
+    // `exclude_components`, e.g. those of blinks or heartbeats (channels x samples)
+    pub fn inverse_transform(&self, exclude_components: &[usize]) -> Array2<f32> {
+        let mut sources = self.sources.clone();
+        for &component in exclude_components {
+            sources.row_mut(component).fill(0.0);
+        }
+
+        self.mixing.dot(&sources) + self.mean.view().insert_axis(Axis(1))
+    }
+}
+
+// W (W^T W)^-1/2, the closest orthogonal matrix to W, keeping its rows decorrelated
+fn symmetric_decorrelation(w: &DMatrix<f64>) -> DMatrix<f64> {
+    let eigen = SymmetricEigen::new(w * w.transpose());
+    let inverse_sqrt = eigen.eigenvalues.map(|l| 1.0 / l.max(f64::EPSILON).sqrt());
+
+    &eigen.eigenvectors * DMatrix::from_diagonal(&inverse_sqrt) * eigen.eigenvectors.transpose() * w
+}
+
+fn to_matrix(array: &Array2<f64>) -> DMatrix<f64> {
+    DMatrix::from_fn(array.nrows(), array.ncols(), |i, j| array[[i, j]])
+}
+
+fn to_array(matrix: &DMatrix<f64>) -> Array2<f32> {
+    Array2::from_shape_fn(matrix.shape(), |(i, j)| matrix[(i, j)] as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use ndarray::{array, Array2};
+
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    // A sinusoid, a sawtooth and uniform noise, over 2000 samples (sources x samples)
+    fn sources() -> Array2<f32> {
+        let mut generator = Lcg::new(5);
+        Array2::from_shape_fn((3, 2000), |(i, t)| match i {
+            0 => (TAU * t as f32 / 50.0).sin(),
+            1 => (t % 37) as f32 / 18.0 - 1.0,
+            _ => 2.0 * generator.next_f32() - 1.0,
+        })
+    }
+
+    fn mixing() -> Array2<f32> {
+        array![
+            [1.0, 0.5, 0.3],
+            [0.4, 1.0, 0.6],
+            [0.7, 0.2, 1.0],
+            [0.3, 0.8, 0.5]
+        ]
+    }
+
+    fn correlation(a: &[f32], b: &[f32]) -> f32 {
+        let (ma, mb) = (
+            a.iter().sum::<f32>() / a.len() as f32,
+            b.iter().sum::<f32>() / b.len() as f32,
+        );
+        let cov = a
+            .iter()
+            .zip(b)
+            .map(|(x, y)| (x - ma) * (y - mb))
+            .sum::<f32>();
+        let va = a.iter().map(|x| (x - ma).powi(2)).sum::<f32>();
+        let vb = b.iter().map(|y| (y - mb).powi(2)).sum::<f32>();
+        cov / (va * vb).sqrt()
+    }
+
+    #[test]
+    fn independent_sources_are_recovered() {
+        let data = mixing().dot(&sources()) + 3.0;
+        let ica = FastIca::fit(&data, 3, 200, 1e-6).unwrap();
+
+        assert_eq!(ica.unmixing_matrix().dim(), (3, 4));
+        assert_eq!(ica.mixing_matrix().dim(), (4, 3));
+        // Each source correlates with a distinct component, up to its sign
+        let mut matched = Vec::new();
+        for source in sources().outer_iter() {
+            let (component, r) = ica
+                .sources()
+                .outer_iter()
+                .map(|c| correlation(&c.to_vec(), &source.to_vec()).abs())
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            assert!(r > 0.95, "{r}");
+            matched.push(component);
+        }
+        matched.sort_unstable();
+        assert_eq!(matched, vec![0, 1, 2]);
+
+        let transformed = ica.transform(&data);
+        assert!(transformed
+            .iter()
+            .zip(ica.sources())
+            .all(|(a, b)| (a - b).abs() < 1e-3));
+    }
+
+    #[test]
+    fn inverse_transform_removes_excluded_components() {
+        let data = mixing().dot(&sources());
+        let ica = FastIca::fit(&data, 3, 200, 1e-6).unwrap();
+
+        let reconstructed = ica.inverse_transform(&[]);
+        assert!(reconstructed
+            .iter()
+            .zip(&data)
+            .all(|(a, b)| (a - b).abs() < 1e-3));
+
+        // Without the component of the sinusoid, the channels no longer hold it
+        let sinusoid = sources().row(0).to_vec();
+        let component = (0..3)
+            .max_by(|&a, &b| {
+                let r = |i: usize| correlation(&ica.sources().row(i).to_vec(), &sinusoid).abs();
+                r(a).total_cmp(&r(b))
+            })
+            .unwrap();
+        let cleaned = ica.inverse_transform(&[component]);
+        for channel in cleaned.outer_iter() {
+            assert!(correlation(&channel.to_vec(), &sinusoid).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn invalid_requests_fail() {
+        let data = mixing().dot(&sources());
+        assert!(matches!(
+            FastIca::fit(&data, 5, 200, 1e-6),
+            Err(IcaError::TooManyComponents {
+                requested: 5,
+                channels: 4
+            })
+        ));
+        // Four channels mixing three sources span only three dimensions
+        assert!(matches!(
+            FastIca::fit(&data, 4, 200, 1e-6),
+            Err(IcaError::RankDeficient {
+                rank: 3,
+                requested: 4
+            })
+        ));
+        assert!(matches!(
+            FastIca::fit(&data, 3, 1, 1e-12),
+            Err(IcaError::NotConverged { iterations: 1, .. })
+        ));
+        assert!(matches!(
+            FastIca::fit(&Array2::<f32>::zeros((3, 0)), 2, 200, 1e-6),
+            Err(IcaError::Covariance(_))
+        ));
+    }
+}
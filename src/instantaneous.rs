@@ -0,0 +1,289 @@
+use std::f64::consts::{PI, TAU};
+
+use nalgebra::DMatrix;
+use ndarray::{Array1, ArrayBase, Data, Ix1};
+
+use crate::fft::analytic_signal;
+
+// B. Boashash, "Estimating and interpreting the instantaneous frequency of a signal," Proceedings
+// of the IEEE, vol. 80, no. 4, pp. 520-538, 1992.
+// A. Savitzky and M. J. E. Golay, "Smoothing and differentiation of data by simplified least
+// squares procedures," Analytical Chemistry, vol. 36, no. 8, pp. 1627-1639, 1964.
+
+// Smoothing of the raw instantaneous frequency, over windows centered on each sample, masked
+// samples being left out of them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Smoothing {
+    // The raw estimate, as is
+    None,
+    // Median over windows of `duration` seconds, robust to the spikes of phase slips
+    Median { duration: f32 },
+    // Least-squares polynomial of degree `order` over windows of `duration` seconds, evaluated at
+    // their center, which preserves gradual changes of frequency
+    SavitzkyGolay { duration: f32, order: usize },
+}
+
+// Parameters of the instantaneous frequency estimation
+#[derive(Clone, Debug)]
+pub struct InstantaneousFrequencyOptions {
+    pub smoothing: Smoothing,
+    // Fraction of the median envelope under which the phase of a sample is deemed unreliable and
+    // the sample masked
+    pub envelope_threshold: f32,
+    // Duration, in seconds, at both edges of the signal masked for the distortion of the Hilbert
+    // transform
+    pub edge_duration: f32,
+}
+
+impl Default for InstantaneousFrequencyOptions {
+    fn default() -> Self {
+        InstantaneousFrequencyOptions {
+            smoothing: Smoothing::Median { duration: 0.25 },
+            envelope_threshold: 0.2,
+            edge_duration: 0.5,
+        }
+    }
+}
+
+impl InstantaneousFrequencyOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn smoothing(mut self, smoothing: Smoothing) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+
+    pub fn envelope_threshold(mut self, threshold: f32) -> Self {
+        self.envelope_threshold = threshold;
+        self
+    }
+
+    pub fn edge_duration(mut self, duration: f32) -> Self {
+        self.edge_duration = duration;
+        self
+    }
+}
+
+// Instantaneous frequency of the `signal`, sampled at `fs` Hz, as by
+// `instantaneous_frequency_with` with the default options: a median over 0.25 s, samples of an
+// envelope under a fifth of its median and 0.5 s at both edges being masked
+pub fn instantaneous_frequency<S>(signal: &ArrayBase<S, Ix1>, fs: f32) -> Array1<f32>
+where
+    S: Data<Elem = f32>,
+{
+    instantaneous_frequency_with(signal, fs, &InstantaneousFrequencyOptions::default())
+}
+
+// Instantaneous frequency of the `signal`, sampled at `fs` Hz, as the derivative of the unwrapped
+// phase of its analytic signal (by central differences), smoothed as per the `options`
+// The `signal` is expected narrow-band, e.g. band-passed around the alpha band
+//
+// Returns the frequency of each sample in Hz, NaN for the masked ones
+// Panics if the Savitzky-Golay windows are not longer than the degree of their polynomial
+pub fn instantaneous_frequency_with<S>(
+    signal: &ArrayBase<S, Ix1>,
+    fs: f32,
+    options: &InstantaneousFrequencyOptions,
+) -> Array1<f32>
+where
+    S: Data<Elem = f32>,
+{
+    let n = signal.len();
+    let analytic = analytic_signal(signal);
+    let envelope = analytic.mapv(|z| z.norm());
+
+    let mut phase = analytic
+        .iter()
+        .map(|z| (z.im as f64).atan2(z.re as f64))
+        .collect::<Vec<f64>>();
+    unwrap(&mut phase);
+
+    let fs = fs as f64;
+    let mut raw = (0..n)
+        .map(|t| {
+            let (before, after) = (t.saturating_sub(1), (t + 1).min(n - 1));
+            if before == after {
+                return f64::NAN;
+            }
+            (phase[after] - phase[before]) / (after - before) as f64 * fs / TAU
+        })
+        .collect::<Vec<f64>>();
+
+    // Samples whose difference involves an unreliable phase, or too close to the edges
+    let mut sorted = envelope.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let threshold = options.envelope_threshold * sorted.get(n / 2).copied().unwrap_or(0.0);
+    let edge = (options.edge_duration as f64 * fs).round() as usize;
+    let masked = (0..n)
+        .map(|t| {
+            let low = (t.saturating_sub(1)..(t + 2).min(n)).any(|i| envelope[i] < threshold);
+            t < edge || t + edge >= n || low
+        })
+        .collect::<Vec<bool>>();
+    for (value, &masked) in raw.iter_mut().zip(&masked) {
+        if masked {
+            *value = f64::NAN;
+        }
+    }
+
+    let half_window = |duration: f32| (duration as f64 * fs / 2.0).round() as usize;
+    let smoothed = match options.smoothing {
+        Smoothing::None => raw,
+        Smoothing::Median { duration } => smooth(&raw, half_window(duration), median),
+        Smoothing::SavitzkyGolay { duration, order } => {
+            let half_window = half_window(duration);
+            assert!(
+                2 * half_window + 1 > order,
+                "Smoothing window must be longer than the degree of its polynomial !"
+            );
+            let offsets = (0..=2 * half_window)
+                .map(|i| i as f64 - half_window as f64)
+                .collect::<Vec<f64>>();
+            let full = polynomial_weights(&offsets, order);
+
+            smooth(&raw, half_window, |window: &[(f64, f64)]| {
+                if window.len() == offsets.len() {
+                    if let Some(full) = &full {
+                        return window.iter().zip(full).map(|(&(_, v), w)| v * w).sum();
+                    }
+                }
+                let offsets = window.iter().map(|&(x, _)| x).collect::<Vec<f64>>();
+                polynomial_weights(&offsets, order).map_or(f64::NAN, |weights| {
+                    window.iter().zip(&weights).map(|(&(_, v), w)| v * w).sum()
+                })
+            })
+        }
+    };
+
+    Array1::from_iter(
+        smoothed
+            .iter()
+            .zip(&masked)
+            .map(|(&f, &masked)| if masked { f32::NAN } else { f as f32 }),
+    )
+}
+
+// Unwraps the `phase` in place, removing its jumps of more than pi between consecutive samples
+fn unwrap(phase: &mut [f64]) {
+    let mut offset = 0.0;
+    for t in 1..phase.len() {
+        let wrapped = phase[t] + offset;
+        let jump = wrapped - phase[t - 1];
+        if jump.abs() > PI {
+            offset -= TAU * (jump / TAU).round();
+        }
+        phase[t] += offset;
+    }
+}
+
+// Reduction of the finite `values` within `half_window` samples of each sample, as (offset,
+// value) pairs, NaN where there are none
+fn smooth(values: &[f64], half_window: usize, reduce: impl Fn(&[(f64, f64)]) -> f64) -> Vec<f64> {
+    let n = values.len();
+    (0..n)
+        .map(|t| {
+            let window = (t.saturating_sub(half_window)..(t + half_window + 1).min(n))
+                .filter(|&i| values[i].is_finite())
+                .map(|i| (i as f64 - t as f64, values[i]))
+                .collect::<Vec<(f64, f64)>>();
+            if window.is_empty() {
+                return f64::NAN;
+            }
+            reduce(&window)
+        })
+        .collect()
+}
+
+fn median(window: &[(f64, f64)]) -> f64 {
+    let mut values = window.iter().map(|&(_, v)| v).collect::<Vec<f64>>();
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    }
+}
+
+// Weights of the values at the `offsets` whose sum is the value at 0 of their least-squares
+// polynomial of degree `order`, None if there are too few of them to fit it
+fn polynomial_weights(offsets: &[f64], order: usize) -> Option<Vec<f64>> {
+    if offsets.len() <= order {
+        return None;
+    }
+
+    // Offsets scaled to [-1, 1] for the conditioning of the fit, its value at 0 being the same
+    let scale = offsets.iter().fold(1.0, |max: f64, x| max.max(x.abs()));
+    let design = DMatrix::from_fn(offsets.len(), order + 1, |i, j| {
+        (offsets[i] / scale).powi(j as i32)
+    });
+    let normal = design.transpose() * &design;
+    let weights = normal.cholesky()?.solve(&design.transpose());
+
+    Some(weights.row(0).iter().copied().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FS: f32 = 250.0;
+
+    // Linear chirp from 8 to 12 Hz over 10 s, of instantaneous frequency 8 + 0.4 t
+    fn chirp() -> Array1<f32> {
+        Array1::from_shape_fn(2500, |i| {
+            let t = i as f64 / FS as f64;
+            (TAU * (8.0 * t + 0.2 * t * t)).sin() as f32
+        })
+    }
+
+    #[test]
+    fn estimate_tracks_a_chirp_away_from_the_edges() {
+        for smoothing in [
+            Smoothing::None,
+            Smoothing::Median { duration: 0.25 },
+            Smoothing::SavitzkyGolay {
+                duration: 0.3,
+                order: 2,
+            },
+        ] {
+            let options = InstantaneousFrequencyOptions::new().smoothing(smoothing);
+            let frequency = instantaneous_frequency_with(&chirp(), FS, &options);
+
+            assert_eq!(frequency.len(), 2500);
+            // 0.5 s at both edges
+            assert!(frequency.iter().take(125).all(|f| f.is_nan()));
+            assert!(frequency.iter().skip(2375).all(|f| f.is_nan()));
+            for (i, &f) in frequency.iter().enumerate().take(2375).skip(125) {
+                let expected = 8.0 + 0.4 * i as f32 / FS;
+                assert!((f - expected).abs() < 0.3, "{smoothing:?} {i} {f}");
+            }
+        }
+        let default = instantaneous_frequency_with(&chirp(), FS, &Default::default());
+        assert!(instantaneous_frequency(&chirp(), FS)
+            .iter()
+            .zip(&default)
+            .all(|(a, b)| a == b || (a.is_nan() && b.is_nan())));
+    }
+
+    #[test]
+    fn samples_of_a_low_envelope_are_masked() {
+        // A 10 Hz tone silenced from 4 to 6 s
+        let signal = Array1::from_shape_fn(2500, |i| {
+            let t = i as f32 / FS;
+            let amplitude = if (4.0..6.0).contains(&t) { 0.0 } else { 1.0 };
+            amplitude * (std::f32::consts::TAU * 10.0 * t).sin()
+        });
+        let options = InstantaneousFrequencyOptions::new().edge_duration(0.0);
+        let frequency = instantaneous_frequency_with(&signal, FS, &options);
+
+        assert!(frequency.iter().skip(1050).take(400).all(|f| f.is_nan()));
+        assert!(frequency
+            .iter()
+            .skip(250)
+            .take(500)
+            .all(|f| (f - 10.0).abs() < 0.3));
+    }
+}
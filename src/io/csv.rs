@@ -0,0 +1,306 @@
+// Comma-separated (or otherwise delimited) values of multichannel data, one sample per row:
+//
+// time,<channel 1>,<channel 2>,...
+// 0,<value>,<value>,...
+// 0.002,<value>,<value>,...
+//
+// Fields holding the delimiter, quotes or line breaks are quoted, as per RFC 4180, and missing
+// values are written as `NaN`
+
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use ndarray::{Array2, ArrayBase, Data, Ix2};
+
+const TIME_COLUMN: &str = "time";
+
+// Formatting of CSV files
+#[derive(Clone, Debug)]
+pub struct CsvOptions {
+    delimiter: char,
+    precision: Option<usize>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            precision: None,
+        }
+    }
+}
+
+impl CsvOptions {
+    // Comma-separated values, written with as many digits as needed to be read back exactly
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Character separating the fields of a row, e.g. `;` or `\t`
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    // Number of digits written after the decimal point of the values, times being written in full
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+}
+
+// Write `data` (channels x samples), sampled at `fs` Hz, as comma-separated values along with the
+// time of each sample, in seconds
+pub fn write_csv<P, S>(
+    path: P,
+    data: &ArrayBase<S, Ix2>,
+    channel_names: &[&str],
+    fs: f64,
+) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    S: Data<Elem = f32>,
+{
+    write_csv_with(path, data, channel_names, fs, &CsvOptions::new())
+}
+
+// Write `data` (channels x samples), sampled at `fs` Hz, formatted as per `options`, along with
+// the time of each sample, in seconds
+// The file is written one sample at a time, never holding it whole in memory
+pub fn write_csv_with<P, S>(
+    path: P,
+    data: &ArrayBase<S, Ix2>,
+    channel_names: &[&str],
+    fs: f64,
+    options: &CsvOptions,
+) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    S: Data<Elem = f32>,
+{
+    assert_eq!(
+        data.nrows(),
+        channel_names.len(),
+        "Data must have one row per channel name !"
+    );
+
+    let delimiter = options.delimiter;
+    let mut writer = BufWriter::new(fs::File::create(path)?);
+
+    write!(writer, "{TIME_COLUMN}")?;
+    for name in channel_names {
+        write!(writer, "{delimiter}{}", quote(name, delimiter))?;
+    }
+    writeln!(writer)?;
+
+    for (i, sample) in data.columns().into_iter().enumerate() {
+        write!(writer, "{}", i as f64 / fs)?;
+
+        for value in sample {
+            write!(writer, "{delimiter}")?;
+            match options.precision {
+                _ if value.is_nan() => write!(writer, "NaN")?,
+                Some(precision) => write!(writer, "{value:.precision$}")?,
+                None => write!(writer, "{value}")?,
+            }
+        }
+        writeln!(writer)?;
+    }
+
+    writer.flush()
+}
+
+// Read the channel names and the data (channels x samples) of comma-separated values, as
+// written by `write_csv`
+pub fn read_csv<P: AsRef<Path>>(path: P) -> io::Result<(Vec<String>, Array2<f32>)> {
+    read_csv_with(path, &CsvOptions::new())
+}
+
+// Read the channel names and the data (channels x samples) of values delimited as per `options`
+// A leading `time` column is left out, while empty and `NaN` cells are read as NaN
+// Fails with `io::ErrorKind::InvalidData` on rows of the wrong length or values that are not numbers
+pub fn read_csv_with<P: AsRef<Path>>(
+    path: P,
+    options: &CsvOptions,
+) -> io::Result<(Vec<String>, Array2<f32>)> {
+    let mut lines = BufReader::new(fs::File::open(path)?).lines();
+
+    let mut names = match lines.next() {
+        Some(line) => split(&line?, options.delimiter),
+        None => return Err(invalid_data("file is empty".into())),
+    };
+    let skip = usize::from(names.first().is_some_and(|name| name == TIME_COLUMN));
+    names.drain(..skip);
+
+    // Values are read sample by sample, i.e. samples x channels
+    let mut values = Vec::new();
+    let mut num_samples = 0;
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+        if line.is_empty() {
+            continue;
+        }
+
+        let fields = split(&line, options.delimiter);
+        if fields.len() != names.len() + skip {
+            return Err(invalid_data(format!(
+                "row {} has {} fields, but the header has {}",
+                i + 2,
+                fields.len(),
+                names.len() + skip
+            )));
+        }
+
+        for field in &fields[skip..] {
+            let field = field.trim();
+            let value = if field.is_empty() || field.eq_ignore_ascii_case("nan") {
+                f32::NAN
+            } else {
+                field.parse().map_err(|_| {
+                    invalid_data(format!("row {} holds '{field}', not a number", i + 2))
+                })?
+            };
+            values.push(value);
+        }
+        num_samples += 1;
+    }
+
+    let data = Array2::from_shape_vec((num_samples, names.len()), values)
+        .map_err(|e| invalid_data(e.to_string()))?
+        .reversed_axes()
+        .as_standard_layout()
+        .into_owned();
+
+    Ok((names, data))
+}
+
+// `field`, quoted if it holds the `delimiter`, quotes or line breaks
+fn quote(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Fields of a `line`, unquoting those that are
+fn split(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+fn invalid_data(reason: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+    use crate::read::fixtures::TempDir;
+
+    #[test]
+    fn non_ascii_channel_names_round_trip() {
+        let root = TempDir::new("csv");
+        let path = root.path().join("data.csv");
+        let names = ["Fp1 (µV)", "Cz; \"ref\" µV", "EKG,µV"];
+        let data = array![
+            [1.5f32, -2.25, 1e-7],
+            [f32::NAN, 0.0, 3.0],
+            [123456.78, -0.001, f32::MAX]
+        ];
+        write_csv(&path, &data, &names, 500.0).unwrap();
+
+        let (read_names, read) = read_csv(&path).unwrap();
+        assert_eq!(read_names, names);
+        assert_eq!(read.dim(), (3, 3));
+        for (a, b) in read.iter().zip(&data) {
+            assert!(a == b || (a.is_nan() && b.is_nan()));
+        }
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let mut lines = contents.lines();
+        assert_eq!(
+            lines.next(),
+            Some("time,Fp1 (µV),\"Cz; \"\"ref\"\" µV\",\"EKG,µV\"")
+        );
+        assert_eq!(lines.next(), Some("0,1.5,NaN,123456.78"));
+        assert_eq!(lines.next(), Some("0.002,-2.25,0,-0.001"));
+    }
+
+    #[test]
+    fn delimiter_and_precision_are_applied() {
+        let root = TempDir::new("csv-options");
+        let path = root.path().join("data.tsv");
+        let options = CsvOptions::new().delimiter('\t').precision(2);
+        let data = array![[1.0f32, 2.345], [f32::NAN, -0.5]];
+        write_csv_with(&path, &data, &["a", "b"], 4.0, &options).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "time\ta\tb\n0\t1.00\tNaN\n0.25\t2.35\t-0.50\n"
+        );
+        let (names, read) = read_csv_with(&path, &options).unwrap();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(read[[0, 1]], 2.35);
+        assert!(read[[1, 0]].is_nan());
+    }
+
+    #[test]
+    fn files_without_a_time_column_are_read() {
+        let root = TempDir::new("csv-no-time");
+        let path = root.write("data.csv", "O1,O2\n1,2\n,4\n\n5,nan\n");
+
+        let (names, data) = read_csv(&path).unwrap();
+        assert_eq!(names, ["O1", "O2"]);
+        assert_eq!(data.dim(), (2, 3));
+        assert_eq!(
+            (data[[0, 0]], data[[0, 2]], data[[1, 0]], data[[1, 1]]),
+            (1.0, 5.0, 2.0, 4.0)
+        );
+        assert!(data[[0, 1]].is_nan() && data[[1, 2]].is_nan());
+    }
+
+    #[test]
+    fn invalid_rows_fail() {
+        let root = TempDir::new("csv-invalid");
+
+        let path = root.write("short.csv", "time,a,b\n0,1,2\n0.1,3\n");
+        let error = read_csv(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            "row 3 has 2 fields, but the header has 3"
+        );
+
+        let path = root.write("text.csv", "time,a\n0,high\n");
+        assert_eq!(
+            read_csv(&path).unwrap_err().to_string(),
+            "row 2 holds 'high', not a number"
+        );
+
+        let path = root.write("empty.csv", "");
+        assert_eq!(read_csv(&path).unwrap_err().to_string(), "file is empty");
+    }
+}
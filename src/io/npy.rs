@@ -0,0 +1,353 @@
+// numpy's `.npy` format, version 1.0
+// * https://numpy.org/doc/stable/reference/generated/numpy.lib.format.html
+//
+// magic string `\x93NUMPY` | major version | minor version | header length (u16, little endian)
+// header: a Python dict literal, padded with spaces and ended by a newline so that the data
+// starts at a multiple of 64 bytes
+// data: the values, in C (row-major) order
+
+use std::{
+    fs,
+    io::{self, BufWriter, Read, Write},
+    path::Path,
+};
+
+use nalgebra::Complex;
+use ndarray::{ArrayBase, ArrayD, Data, Dimension, Ix1, Ix2, IxDyn, ShapeBuilder};
+
+const MAGIC: &[u8; 6] = b"\x93NUMPY";
+const ALIGNMENT: usize = 64;
+
+// Element types that can be stored in, and loaded from, a `.npy` file
+pub trait NpyElement: Copy {
+    // numpy's little-endian `dtype` descriptor
+    const DESCR: &'static str;
+    const SIZE: usize;
+
+    fn write_le<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+    fn from_le(bytes: &[u8]) -> Self;
+}
+
+impl NpyElement for f32 {
+    const DESCR: &'static str = "<f4";
+    const SIZE: usize = 4;
+
+    fn write_le<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.to_le_bytes())
+    }
+
+    fn from_le(bytes: &[u8]) -> Self {
+        f32::from_le_bytes(bytes.try_into().unwrap())
+    }
+}
+
+impl NpyElement for f64 {
+    const DESCR: &'static str = "<f8";
+    const SIZE: usize = 8;
+
+    fn write_le<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.to_le_bytes())
+    }
+
+    fn from_le(bytes: &[u8]) -> Self {
+        f64::from_le_bytes(bytes.try_into().unwrap())
+    }
+}
+
+// Complex values are stored as their real part followed by their imaginary part
+impl NpyElement for Complex<f32> {
+    const DESCR: &'static str = "<c8";
+    const SIZE: usize = 8;
+
+    fn write_le<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.re.write_le(writer)?;
+        self.im.write_le(writer)
+    }
+
+    fn from_le(bytes: &[u8]) -> Self {
+        Complex::new(f32::from_le(&bytes[..4]), f32::from_le(&bytes[4..]))
+    }
+}
+
+impl NpyElement for Complex<f64> {
+    const DESCR: &'static str = "<c16";
+    const SIZE: usize = 16;
+
+    fn write_le<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.re.write_le(writer)?;
+        self.im.write_le(writer)
+    }
+
+    fn from_le(bytes: &[u8]) -> Self {
+        Complex::new(f64::from_le(&bytes[..8]), f64::from_le(&bytes[8..]))
+    }
+}
+
+// Write 2-dimensional `data`, e.g. channels x samples or channels x frequencies, to a `.npy` file
+pub fn write_npy<P, S, T>(path: P, data: &ArrayBase<S, Ix2>) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    S: Data<Elem = T>,
+    T: NpyElement,
+{
+    write(path, data)
+}
+
+// Write 1-dimensional `data`, e.g. a single signal or spectrum, to a `.npy` file
+pub fn write_npy1<P, S, T>(path: P, data: &ArrayBase<S, Ix1>) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    S: Data<Elem = T>,
+    T: NpyElement,
+{
+    write(path, data)
+}
+
+// Read a `.npy` file of values of type `T`, whatever its number of dimensions
+// Use `into_dimensionality` to get an array of a known number of dimensions back
+// Fails with `io::ErrorKind::InvalidData` if the file is not a `.npy` file of little-endian `T`
+pub fn read_npy<P, T>(path: P) -> io::Result<ArrayD<T>>
+where
+    P: AsRef<Path>,
+    T: NpyElement,
+{
+    let mut bytes = Vec::new();
+    fs::File::open(path)?.read_to_end(&mut bytes)?;
+
+    if bytes.len() < 10 || &bytes[..6] != MAGIC {
+        return Err(invalid_data("not a .npy file".into()));
+    }
+    // Versions 2.0 and 3.0 only differ by a 4 bytes header length
+    let (header_start, header_len) = match bytes[6] {
+        1 => (10, u16::from_le_bytes([bytes[8], bytes[9]]) as usize),
+        2 | 3 if bytes.len() >= 12 => (
+            12,
+            u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize,
+        ),
+        version => return Err(invalid_data(format!("unsupported version {version}"))),
+    };
+    let data_start = header_start + header_len;
+    let header = bytes
+        .get(header_start..data_start)
+        .and_then(|header| std::str::from_utf8(header).ok())
+        .ok_or_else(|| invalid_data("truncated or non-textual header".into()))?;
+
+    let descr = dict_value(header, "descr")
+        .map(|value| value.trim_matches(|c| c == '\'' || c == '"'))
+        .ok_or_else(|| invalid_data("header is missing 'descr'".into()))?;
+    if descr != T::DESCR {
+        return Err(invalid_data(format!(
+            "values are '{descr}', but '{}' were expected",
+            T::DESCR
+        )));
+    }
+    let fortran_order = match dict_value(header, "fortran_order") {
+        Some("False") => false,
+        Some("True") => true,
+        _ => return Err(invalid_data("header is missing 'fortran_order'".into())),
+    };
+    let shape = dict_value(header, "shape")
+        .and_then(parse_shape)
+        .ok_or_else(|| invalid_data("header is missing 'shape'".into()))?;
+
+    let num_values = shape.iter().product::<usize>();
+    let data = &bytes[data_start..];
+    if data.len() < num_values * T::SIZE {
+        return Err(invalid_data(format!(
+            "data has {} bytes, but {num_values} values of {} bytes were expected",
+            data.len(),
+            T::SIZE
+        )));
+    }
+    let values = data
+        .chunks_exact(T::SIZE)
+        .take(num_values)
+        .map(T::from_le)
+        .collect::<Vec<T>>();
+
+    ArrayD::from_shape_vec(IxDyn(&shape).set_f(fortran_order), values)
+        .map_err(|e| invalid_data(e.to_string()))
+}
+
+fn write<P, S, T, D>(path: P, data: &ArrayBase<S, D>) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    S: Data<Elem = T>,
+    T: NpyElement,
+    D: Dimension,
+{
+    let mut writer = BufWriter::new(fs::File::create(path)?);
+    writer.write_all(&header::<T>(data.shape()))?;
+    // Iteration is in logical, i.e. C, order whatever the memory layout of `data`
+    for value in data {
+        value.write_le(&mut writer)?;
+    }
+
+    writer.flush()
+}
+
+// Magic string, version and header of a C-ordered `.npy` file of values of type `T`
+fn header<T: NpyElement>(shape: &[usize]) -> Vec<u8> {
+    let shape = match shape {
+        [length] => format!("({length},)"),
+        shape => format!(
+            "({})",
+            shape
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+    };
+    let mut dict = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {shape}, }}",
+        T::DESCR
+    );
+    // Pad so that the preamble, the header and its newline span a multiple of `ALIGNMENT` bytes
+    let preamble_len = MAGIC.len() + 4;
+    let padding = (ALIGNMENT - (preamble_len + dict.len() + 1) % ALIGNMENT) % ALIGNMENT;
+    dict.extend(std::iter::repeat_n(' ', padding));
+    dict.push('\n');
+
+    let mut bytes = Vec::with_capacity(preamble_len + dict.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(dict.as_bytes());
+
+    bytes
+}
+
+// Raw value of `key` in the header dict, up to the next top-level comma
+fn dict_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let start = header
+        .find(&format!("'{key}'"))
+        .or_else(|| header.find(&format!("\"{key}\"")))?
+        + key.len()
+        + 2;
+    let value = header[start..].trim_start().strip_prefix(':')?.trim_start();
+
+    // The shape tuple holds commas of its own
+    let end = if value.starts_with('(') {
+        value.find(')')? + 1
+    } else {
+        value.find([',', '}'])?
+    };
+
+    Some(value[..end].trim())
+}
+
+// Dimensions of a shape tuple, e.g. `(2, 3)`, `(3,)` or `()`
+fn parse_shape(tuple: &str) -> Option<Vec<usize>> {
+    tuple
+        .strip_prefix('(')?
+        .strip_suffix(')')?
+        .split(',')
+        .map(str::trim)
+        .filter(|dimension| !dimension.is_empty())
+        .map(|dimension| dimension.parse().ok())
+        .collect()
+}
+
+fn invalid_data(reason: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array1, Array2, Ix1, Ix2};
+
+    use super::*;
+    use crate::read::fixtures::TempDir;
+
+    #[test]
+    fn arrays_round_trip() {
+        let root = TempDir::new("npy");
+        let data = Array2::from_shape_fn((3, 5), |(i, j)| i as f32 * 10.0 - j as f32 / 4.0);
+        let path = root.path().join("data.npy");
+        write_npy(&path, &data).unwrap();
+        let read = read_npy::<_, f32>(&path).unwrap();
+        assert_eq!(read.into_dimensionality::<Ix2>().unwrap(), data);
+
+        // Whatever the memory layout
+        write_npy(&path, &data.t()).unwrap();
+        let read = read_npy::<_, f32>(&path).unwrap();
+        assert_eq!(read.into_dimensionality::<Ix2>().unwrap(), data.t());
+
+        let signal = Array1::from(vec![0.5f64, -1.0, f64::MAX]);
+        write_npy1(&path, &signal).unwrap();
+        let read = read_npy::<_, f64>(&path).unwrap();
+        assert_eq!(read.into_dimensionality::<Ix1>().unwrap(), signal);
+
+        let spectrum = array![[Complex::new(1.0f32, -2.0), Complex::new(0.0, 3.5)]];
+        write_npy(&path, &spectrum).unwrap();
+        let read = read_npy::<_, Complex<f32>>(&path).unwrap();
+        assert_eq!(read.into_dimensionality::<Ix2>().unwrap(), spectrum);
+    }
+
+    #[test]
+    fn header_is_aligned_to_64_bytes() {
+        let root = TempDir::new("npy-header");
+        let path = root.path().join("small.npy");
+        write_npy(&path, &array![[1.0f32, 2.0, 3.0], [4.0, 5.0, 6.0]]).unwrap();
+        let bytes = fs::read(&path).unwrap();
+
+        let dict = "{'descr': '<f4', 'fortran_order': False, 'shape': (2, 3), }";
+        let mut expected = b"\x93NUMPY\x01\x00\x76\x00".to_vec();
+        expected.extend_from_slice(dict.as_bytes());
+        expected.extend(std::iter::repeat_n(b' ', 118 - dict.len() - 1));
+        expected.push(b'\n');
+        for value in 1..=6 {
+            expected.extend_from_slice(&(value as f32).to_le_bytes());
+        }
+        assert_eq!(bytes, expected);
+        assert_eq!(bytes.len(), 128 + 24);
+    }
+
+    #[test]
+    fn fortran_ordered_files_are_read() {
+        let root = TempDir::new("npy-fortran");
+        let dict = "{'descr': '<f4', 'fortran_order': True, 'shape': (2, 3), }\n";
+        let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+        bytes.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(dict.as_bytes());
+        // Column after column
+        for value in [1.0f32, 4.0, 2.0, 5.0, 3.0, 6.0] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let path = root.write("fortran.npy", bytes);
+
+        let read = read_npy::<_, f32>(&path).unwrap();
+        assert_eq!(
+            read.into_dimensionality::<Ix2>().unwrap(),
+            array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]
+        );
+    }
+
+    #[test]
+    fn invalid_files_fail() {
+        let root = TempDir::new("npy-invalid");
+        let path = root.path().join("data.npy");
+        write_npy1(&path, &Array1::from(vec![1.0f32, 2.0])).unwrap();
+
+        let error = read_npy::<_, f64>(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            "values are '<f4', but '<f8' were expected"
+        );
+
+        let truncated = fs::read(&path).unwrap()[..66].to_vec();
+        let path = root.write("truncated.npy", truncated);
+        assert_eq!(
+            read_npy::<_, f32>(&path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let path = root.write("text.npy", "not numpy at all");
+        assert_eq!(
+            read_npy::<_, f32>(&path).unwrap_err().to_string(),
+            "not a .npy file"
+        );
+    }
+}
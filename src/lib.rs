@@ -1,7 +1,34 @@
+pub mod alignment;
+pub mod bad_channels;
+pub mod band_power;
+pub mod connectivity;
 pub mod covariance;
+pub mod csp;
+pub mod epochs;
+pub mod error;
+pub mod evoked;
+pub mod features;
 pub mod fft;
 pub mod filter;
+pub mod ica;
+pub mod instantaneous;
+pub mod io;
+pub mod line_noise;
+pub mod microstates;
+pub mod peaks;
+pub mod pipeline;
+pub mod quality;
 #[allow(dead_code)]
 pub mod read;
+pub mod reference;
+pub mod regression;
 pub mod s_transform;
+pub mod source;
+pub mod spectral_fit;
+pub mod surrogate;
+pub mod time_frequency;
 pub mod wavelet;
+pub mod whitening;
+pub mod write;
+
+pub use error::Error;
@@ -0,0 +1,254 @@
+use std::{error::Error, fmt::Display};
+
+use ndarray::{Array1, Array2};
+
+use crate::{
+    fft::welch,
+    filter::FIRFilter,
+    quality::{psd_where, quantile, ratio, LINE_FREQUENCIES, LINE_HALF_WIDTH, NEIGHBOURS},
+    source::SignalSource,
+};
+
+// Duration, in seconds, of the Welch segments of the PSD
+const SEGMENT_DURATION: f32 = 2.0;
+// Harmonics of a line frequency, the fundamental included, its peak is searched at
+const HARMONICS: usize = 3;
+// Ratio of the peak PSD at a line frequency over its background, above which it is line noise
+const MIN_PEAK_RATIO: f32 = 10.0;
+// Width, in Hz, of the band rejected around a line frequency and each of its harmonics
+const NOTCH_WIDTH: f32 = 4.0;
+// Duration, in seconds, of the notch filters
+const NOTCH_DURATION: f32 = 2.0;
+
+// Errors that can occur while removing power line noise
+#[derive(Debug)]
+pub enum LineNoiseError {
+    // The line frequency to remove is not positive, in Hz
+    InvalidLineFrequency { frequency: f32 },
+}
+
+impl Display for LineNoiseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LineNoiseError::InvalidLineFrequency { frequency } => {
+                write!(f, "line frequency of {frequency} Hz is not positive")
+            }
+        }
+    }
+}
+
+impl Error for LineNoiseError {}
+
+// Power line frequency of a recording, as estimated from its PSD
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineFrequency {
+    // 50 or 60 Hz
+    pub frequency: f32,
+    // How much more the line frequency stands out than the other one, in [0, 1): 1 minus the ratio
+    // of their peak ratios, 0 when both stand out as much
+    pub confidence: f32,
+}
+
+// Power line frequency of the `data` (channels x samples), sampled at `fs` Hz, from its Welch PSD
+// averaged over channels
+// The peak ratio of 50 and 60 Hz is the highest, over their first 3 harmonics below Nyquist, of the
+// peak PSD within 1 Hz of the harmonic over the median PSD 2 to 5 Hz away from it, as by the
+// line-noise ratio of `channel_quality`
+//
+// Returns the line frequency of the higher peak ratio, or None when neither is above 10, e.g. for
+// data already cleaned of it or sampled too slowly to hold it
+pub fn estimate_line_frequency<D>(data: &D, fs: f32) -> Option<LineFrequency>
+where
+    D: SignalSource + ?Sized,
+{
+    let segment_len = (SEGMENT_DURATION * fs).round().max(1.0) as usize;
+    let mut mean_psd = None::<Array1<f32>>;
+    let mut freqs = Array1::zeros(0);
+    for index in 0..data.num_channels() {
+        let (f, psd) = welch(&data.channel(index), fs, segment_len);
+        freqs = f;
+        mean_psd = Some(match mean_psd {
+            Some(sum) => sum + psd,
+            None => psd,
+        });
+    }
+    let psd = mean_psd? / data.num_channels() as f32;
+    let nyquist = fs / 2.0;
+
+    let peak_ratio = |line: f32| {
+        (1..=HARMONICS)
+            .map(|h| h as f32 * line)
+            .filter(|&harmonic| harmonic + NEIGHBOURS.1 <= nyquist)
+            .map(|harmonic| {
+                let peak = psd_where(&freqs, &psd, |f| (f - harmonic).abs() <= LINE_HALF_WIDTH)
+                    .into_iter()
+                    .fold(0.0, f32::max);
+                let mut background = psd_where(&freqs, &psd, |f| {
+                    (NEIGHBOURS.0..=NEIGHBOURS.1).contains(&(f - harmonic).abs())
+                });
+                background.sort_by(f32::total_cmp);
+
+                ratio(peak, quantile(&background, 0.5))
+            })
+            .fold(0.0, f32::max)
+    };
+    let [(low, low_ratio), (high, high_ratio)] =
+        LINE_FREQUENCIES.map(|line| (line, peak_ratio(line)));
+    let ((frequency, best), (_, other)) = if low_ratio >= high_ratio {
+        ((low, low_ratio), (high, high_ratio))
+    } else {
+        ((high, high_ratio), (low, low_ratio))
+    };
+    if best.is_nan() || best <= MIN_PEAK_RATIO {
+        return None;
+    }
+
+    Some(LineFrequency {
+        frequency,
+        confidence: 1.0 - other.max(1.0) / best,
+    })
+}
+
+// Each channel of the `data` (channels x samples), sampled at `fs` Hz, rid of the power line noise
+// at `line_frequency` Hz and its first 3 harmonics below Nyquist, the fundamental included, by
+// zero-phase windowed-sinc band-stop filters of 2 seconds, rejecting 4 Hz around each
+// The line frequency is estimated by `estimate_line_frequency` when not given, the data being
+// left as it is when none is found
+// Fails if the given line frequency is not positive
+//
+// Returns the filtered data, along with the line frequency removed, if any
+pub fn remove_line_noise<D>(
+    data: &D,
+    fs: f32,
+    line_frequency: Option<f32>,
+) -> Result<(Array2<f32>, Option<f32>), LineNoiseError>
+where
+    D: SignalSource + ?Sized,
+{
+    if let Some(frequency) = line_frequency.filter(|&f| !(f > 0.0 && f.is_finite())) {
+        return Err(LineNoiseError::InvalidLineFrequency { frequency });
+    }
+    let line_frequency = line_frequency
+        .or_else(|| estimate_line_frequency(data, fs).map(|estimate| estimate.frequency));
+
+    let mut filtered = Array2::zeros((data.num_channels(), data.num_samples()));
+    for (index, mut row) in filtered.outer_iter_mut().enumerate() {
+        row.assign(&data.channel(index));
+    }
+    let Some(line) = line_frequency else {
+        return Ok((filtered, None));
+    };
+
+    let num_taps = (NOTCH_DURATION * fs).round() as usize;
+    for harmonic in (1..=HARMONICS)
+        .map(|h| h as f32 * line)
+        .take_while(|&harmonic| harmonic + NOTCH_WIDTH / 2.0 < fs / 2.0)
+    {
+        let notch = FIRFilter::bandstop(
+            num_taps,
+            harmonic - NOTCH_WIDTH / 2.0,
+            harmonic + NOTCH_WIDTH / 2.0,
+            fs,
+        );
+        filtered = notch.process_zero_phase_channels(&filtered);
+    }
+
+    Ok((filtered, Some(line)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use ndarray::{s, Axis};
+
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    const FS: f32 = 500.0;
+
+    // Four channels of 20 s of white noise, uniform in [-0.5, 0.5)
+    fn noise() -> Array2<f32> {
+        let mut generator = Lcg::new(11);
+        Array2::from_shape_fn((4, 10_000), |_| generator.next_f32() - 0.5)
+    }
+
+    // The `noise` plus a sine at each of the `frequencies`, of an amplitude of 0.2 on every channel
+    fn contaminated(noise: &Array2<f32>, frequencies: &[f32]) -> Array2<f32> {
+        let mut data = noise.clone();
+        for (t, mut column) in data.axis_iter_mut(Axis(1)).enumerate() {
+            let time = t as f32 / FS;
+            column += frequencies
+                .iter()
+                .map(|&f| 0.2 * (TAU * f * time).sin())
+                .sum::<f32>();
+        }
+
+        data
+    }
+
+    #[test]
+    fn line_frequency_is_estimated_from_its_peak() {
+        for line in [50.0, 60.0] {
+            let data = contaminated(&noise(), &[line, 2.0 * line]);
+            let estimate = estimate_line_frequency(&data, FS).unwrap();
+            assert_eq!(estimate.frequency, line);
+            assert!(estimate.confidence > 0.9, "{line} {estimate:?}");
+        }
+        // A peak at the second harmonic only is enough
+        let estimate = estimate_line_frequency(&contaminated(&noise(), &[120.0]), FS).unwrap();
+        assert_eq!(estimate.frequency, 60.0);
+    }
+
+    #[test]
+    fn clean_noise_has_no_line_frequency() {
+        assert_eq!(estimate_line_frequency(&noise(), FS), None);
+        // Nor any sampled too slowly to hold one
+        assert_eq!(
+            estimate_line_frequency(&contaminated(&noise(), &[20.0]), 100.0),
+            None
+        );
+
+        let (filtered, line) = remove_line_noise(&noise(), FS, None).unwrap();
+        assert_eq!(line, None);
+        assert_eq!(filtered, noise());
+    }
+
+    #[test]
+    fn line_noise_is_removed() {
+        let noise = noise();
+        let data = contaminated(&noise, &[50.0, 100.0]);
+        let (filtered, line) = remove_line_noise(&data, FS, None).unwrap();
+
+        assert_eq!(line, Some(50.0));
+        assert_eq!(estimate_line_frequency(&filtered, FS), None);
+        // Away from the edges of the filters, only the noise within the notches is lost
+        let middle = s![.., 1000..9000];
+        let residual = (&filtered.slice(middle) - &noise.slice(middle)).mapv(|v| v * v);
+        let power = noise.slice(middle).mapv(|v| v * v);
+        assert!(residual.sum() < 0.1 * power.sum(), "{}", residual.sum());
+    }
+
+    #[test]
+    fn only_the_first_harmonics_are_removed() {
+        let data = contaminated(&noise(), &[200.0]);
+        let (filtered, line) = remove_line_noise(&data, FS, Some(50.0)).unwrap();
+
+        assert_eq!(line, Some(50.0));
+        // The 4th harmonic at 200 Hz is left as it is
+        let (_, psd) = welch(&filtered.row(0), FS, 1000);
+        let (_, original) = welch(&data.row(0), FS, 1000);
+        assert!((psd[400] / original[400] - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn invalid_line_frequency_fails() {
+        for frequency in [0.0, -50.0, f32::NAN, f32::INFINITY] {
+            assert!(matches!(
+                remove_line_noise(&noise(), FS, Some(frequency)),
+                Err(LineNoiseError::InvalidLineFrequency { frequency: f })
+                    if f.to_bits() == frequency.to_bits()
+            ));
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use std::{error::Error, fmt::Display};
+
+use nalgebra::{DMatrix, SymmetricEigen};
+use ndarray::{Array1, Array2, ArrayBase, Axis, Data, Ix2};
+
+use crate::{evoked::global_field_power, peaks::find_peaks, surrogate::Lcg};
+
+// R. D. Pascual-Marqui, C. M. Michel and D. Lehmann, "Segmentation of brain electrical activity
+// into microstates: model estimation and validation," IEEE Transactions on Biomedical Engineering,
+// vol. 42, no. 7, pp. 658-665, 1995.
+
+// Change of the explained variance between iterations under which the clustering has converged
+const TOLERANCE: f64 = 1e-6;
+// Iterations of each run of the clustering
+const MAX_ITERATIONS: usize = 100;
+
+// Errors that can occur while clustering topographies into microstates
+#[derive(Debug)]
+pub enum MicrostateError {
+    // There are fewer GFP peaks than states to draw the initial templates from
+    NotEnoughPeaks { peaks: usize, states: usize },
+}
+
+impl Display for MicrostateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MicrostateError::NotEnoughPeaks { peaks, states } => {
+                write!(
+                    f,
+                    "{peaks} GFP peaks are not enough for {states} microstates"
+                )
+            }
+        }
+    }
+}
+
+impl Error for MicrostateError {}
+
+// Microstate segmentation of a recording
+#[derive(Clone, Debug)]
+pub struct Microstates {
+    // Topographies of the states (states x channels), average-referenced and of unit norm, their
+    // polarity being arbitrary
+    pub templates: Array2<f32>,
+    // State of each sample, the template its topography correlates best with regardless of polarity
+    pub labels: Vec<usize>,
+    // Global explained variance of all samples by their templates, in [0, 1]
+    pub gev: f32,
+}
+
+// Clusters the topographies of the `data` (channels x samples) at the peaks of its global field
+// power into `n_states` microstates, by modified k-means, the distance being the polarity-invariant
+// spatial correlation
+// The clustering is run `n_init` times from distinct peak topographies drawn from the `seed`,
+// keeping the run explaining the most variance of the peaks, and every sample is then back-fitted
+// to the template it correlates best with
+// Fails if there are fewer GFP peaks than states
+// Panics if `n_states` or `n_init` is zero
+pub fn microstates<S>(
+    data: &ArrayBase<S, Ix2>,
+    n_states: usize,
+    n_init: usize,
+    seed: u64,
+) -> Result<Microstates, MicrostateError>
+where
+    S: Data<Elem = f32>,
+{
+    assert!(n_states > 0, "Number of microstates must be positive !");
+    assert!(n_init > 0, "Number of runs must be positive !");
+
+    let data = data.mapv(|x| x as f64);
+    let maps = &data - &data.mean_axis(Axis(0)).unwrap().insert_axis(Axis(0));
+    let gfp = global_field_power(&maps.mapv(|x| x as f32), &[]);
+    let peaks = find_peaks(&gfp, None, None, 1)
+        .into_iter()
+        .map(|peak| peak.index)
+        .collect::<Vec<usize>>();
+    if peaks.len() < n_states {
+        return Err(MicrostateError::NotEnoughPeaks {
+            peaks: peaks.len(),
+            states: n_states,
+        });
+    }
+
+    let peak_maps = maps.select(Axis(1), &peaks);
+    let mut generator = Lcg::new(seed);
+    let mut best: Option<(Array2<f64>, f64)> = None;
+    for _ in 0..n_init {
+        // Distinct peaks drawn by a partial Fisher-Yates shuffle
+        let mut indices = (0..peaks.len()).collect::<Vec<usize>>();
+        for i in 0..n_states {
+            let j = i + (generator.next_u64() >> 33) as usize % (indices.len() - i);
+            indices.swap(i, j);
+        }
+        let mut templates = normalized(&peak_maps.select(Axis(1), &indices[..n_states]).t());
+
+        let mut gev = 0.0;
+        for _ in 0..MAX_ITERATIONS {
+            let (labels, _) = backfit(&templates, &peak_maps);
+            templates = updated(&templates, &peak_maps, &labels);
+
+            let previous = gev;
+            gev = explained_variance(&templates, &peak_maps);
+            if (gev - previous).abs() < TOLERANCE {
+                break;
+            }
+        }
+
+        if best.as_ref().is_none_or(|(_, best_gev)| gev > *best_gev) {
+            best = Some((templates, gev));
+        }
+    }
+
+    let (templates, _) = best.unwrap();
+    let (labels, _) = backfit(&templates, &maps);
+
+    Ok(Microstates {
+        gev: explained_variance(&templates, &maps) as f32,
+        templates: templates.mapv(|x| x as f32),
+        labels,
+    })
+}
+
+// Rows of the `maps` of unit norm, those null being left as they are
+fn normalized<S>(maps: &ArrayBase<S, Ix2>) -> Array2<f64>
+where
+    S: Data<Elem = f64>,
+{
+    let mut maps = maps.to_owned();
+    for mut map in maps.outer_iter_mut() {
+        let norm = map.dot(&map).sqrt();
+        if norm > 0.0 {
+            map /= norm;
+        }
+    }
+
+    maps
+}
+
+// Template of each of the `maps` (channels x samples) along with the absolute spatial correlation
+// with it, the templates being of unit norm and the maps average-referenced
+// Null maps correlate with no template, their label being arbitrary
+fn backfit(templates: &Array2<f64>, maps: &Array2<f64>) -> (Vec<usize>, Array1<f64>) {
+    let products = templates.dot(maps);
+    let norms = maps.map_axis(Axis(0), |map| map.dot(&map).sqrt());
+
+    let mut labels = Vec::with_capacity(maps.ncols());
+    let mut correlations = Array1::zeros(maps.ncols());
+    for (t, column) in products.axis_iter(Axis(1)).enumerate() {
+        let (label, product) = column
+            .iter()
+            .map(|p| p.abs())
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        labels.push(label);
+        if norms[t] > 0.0 {
+            correlations[t] = product / norms[t];
+        }
+    }
+
+    (labels, correlations)
+}
+
+// Templates updated to the first principal component of the maps assigned to each, which
+// maximizes the sum of their squared projections regardless of polarity
+// Templates assigned no map are left as they are
+fn updated(templates: &Array2<f64>, maps: &Array2<f64>, labels: &[usize]) -> Array2<f64> {
+    let mut updated = templates.clone();
+    for (k, mut template) in updated.outer_iter_mut().enumerate() {
+        let assigned = (0..labels.len())
+            .filter(|&t| labels[t] == k)
+            .collect::<Vec<usize>>();
+        if assigned.is_empty() {
+            continue;
+        }
+
+        let members = maps.select(Axis(1), &assigned);
+        let scatter = members.dot(&members.t());
+        let n = scatter.nrows();
+        let eigen = SymmetricEigen::new(DMatrix::from_fn(n, n, |i, j| scatter[[i, j]]));
+        let principal = eigen.eigenvalues.imax();
+        template.assign(&Array1::from_iter(
+            eigen.eigenvectors.column(principal).iter().copied(),
+        ));
+    }
+
+    updated
+}
+
+// Global explained variance of the `maps` by the `templates` they are back-fitted to: the sum of
+// their squared GFP weighted by their squared correlation over that of their squared GFP
+fn explained_variance(templates: &Array2<f64>, maps: &Array2<f64>) -> f64 {
+    let (_, correlations) = backfit(templates, maps);
+    let gfp2 = maps.map_axis(Axis(0), |map| map.dot(&map));
+    let total = gfp2.sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+
+    (&gfp2 * &correlations.mapv(|c| c * c)).sum() / total
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use ndarray::Array1;
+
+    use super::*;
+
+    // Two random topographies of 16 channels, average-referenced and of unit norm
+    fn topographies() -> Array2<f32> {
+        let mut generator = Lcg::new(2);
+        let maps = Array2::from_shape_fn((2, 16), |_| generator.next_f32() - 0.5);
+        let maps = &maps - &maps.mean_axis(Axis(1)).unwrap().insert_axis(Axis(1));
+        normalized(&maps.mapv(|x| x as f64)).mapv(|x| x as f32)
+    }
+
+    // The topographies alternating every 40 samples, each waxing and waning over its segment with
+    // a polarity flipping every other segment, plus noise, along with the generating state of
+    // each sample
+    fn alternating() -> (Array2<f32>, Vec<usize>) {
+        let maps = topographies();
+        let mut generator = Lcg::new(5);
+        let states = (0..2000).map(|t| (t / 40) % 2).collect::<Vec<usize>>();
+        let data = Array2::from_shape_fn((16, 2000), |(c, t)| {
+            let segment = t / 40;
+            let polarity = if (segment / 2) % 2 == 0 { 1.0 } else { -1.0 };
+            let envelope = 10.0 * (PI * ((t % 40) as f32 + 0.5) / 40.0).sin();
+            polarity * envelope * maps[[states[t], c]] + 0.5 * (generator.next_f32() - 0.5)
+        });
+
+        (data, states)
+    }
+
+    fn correlation(a: &Array1<f32>, b: &Array1<f32>) -> f32 {
+        a.dot(b) / (a.dot(a) * b.dot(b)).sqrt()
+    }
+
+    #[test]
+    fn alternating_topographies_are_recovered() {
+        let (data, states) = alternating();
+        let maps = topographies();
+        let result = microstates(&data, 2, 5, 7).unwrap();
+
+        // Template of each generating topography, whatever their order and polarity
+        let assigned = (0..2)
+            .map(|k| {
+                let r = |j: usize| {
+                    correlation(&result.templates.row(j).to_owned(), &maps.row(k).to_owned()).abs()
+                };
+                let j = if r(0) > r(1) { 0 } else { 1 };
+                assert!(r(j) > 0.95, "{}", r(j));
+                j
+            })
+            .collect::<Vec<usize>>();
+        assert_ne!(assigned[0], assigned[1]);
+
+        let matching = states
+            .iter()
+            .zip(&result.labels)
+            .filter(|&(&state, &label)| assigned[state] == label)
+            .count();
+        assert!(matching as f32 > 0.9 * states.len() as f32, "{matching}");
+        assert!(result.gev > 0.8 && result.gev <= 1.0, "{}", result.gev);
+
+        // The same seed gives the same segmentation
+        let again = microstates(&data, 2, 5, 7).unwrap();
+        assert_eq!(
+            (again.templates, again.labels),
+            (result.templates, result.labels)
+        );
+    }
+
+    #[test]
+    fn too_few_peaks_fail() {
+        // Three noiseless segments, of a single GFP peak each
+        let maps = topographies();
+        let data = Array2::from_shape_fn((16, 120), |(c, t)| {
+            10.0 * (PI * ((t % 40) as f32 + 0.5) / 40.0).sin() * maps[[(t / 40) % 2, c]]
+        });
+        assert!(matches!(
+            microstates(&data, 5, 1, 0),
+            Err(MicrostateError::NotEnoughPeaks {
+                peaks: 3,
+                states: 5
+            })
+        ));
+        assert!(microstates(&data, 3, 1, 0).is_ok());
+        assert!(matches!(
+            microstates(&Array2::<f32>::zeros((4, 100)), 1, 1, 0),
+            Err(MicrostateError::NotEnoughPeaks {
+                peaks: 0,
+                states: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,224 @@
+use ndarray::{ArrayBase, Data, Ix1};
+
+// A local maximum of a signal, along with its properties
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Peak {
+    // Index of the sample of the peak, the midpoint of a flat top rounded down
+    pub index: usize,
+    // Value of the signal at the peak
+    pub height: f32,
+    // Height of the peak above the higher of its two bases
+    pub prominence: f32,
+    // Indices of the lowest samples of the signal between the peak and the nearest higher sample
+    // on either side, or the edge of the signal when there is none
+    pub left_base: usize,
+    pub right_base: usize,
+}
+
+// Peaks of the `signal`, its local maxima (flat tops included, the edges of the signal excluded)
+// filtered in turn, as scipy's `find_peaks` does:
+// - by their `min_height`, if any
+// - by their `min_distance` in samples, the higher of two peaks closer than it being kept
+// - by their `min_prominence`, if any, the prominence of each peak not depending on the others
+//
+// Returns the peaks in the order of the signal
+pub fn find_peaks<S>(
+    signal: &ArrayBase<S, Ix1>,
+    min_height: Option<f32>,
+    min_prominence: Option<f32>,
+    min_distance: usize,
+) -> Vec<Peak>
+where
+    S: Data<Elem = f32>,
+{
+    let mut indices = local_maxima(signal);
+
+    if let Some(min_height) = min_height {
+        indices.retain(|&i| signal[i] >= min_height);
+    }
+
+    if min_distance > 1 {
+        indices = select_by_distance(signal, &indices, min_distance);
+    }
+
+    indices
+        .into_iter()
+        .map(|index| {
+            let (left_base, right_base) = bases(signal, index);
+            let height = signal[index];
+
+            Peak {
+                index,
+                height,
+                prominence: height - signal[left_base].max(signal[right_base]),
+                left_base,
+                right_base,
+            }
+        })
+        .filter(|peak| min_prominence.is_none_or(|min| peak.prominence >= min))
+        .collect()
+}
+
+// Indices of the local maxima of the `signal`, those of flat tops being their midpoints
+fn local_maxima<S>(signal: &ArrayBase<S, Ix1>) -> Vec<usize>
+where
+    S: Data<Elem = f32>,
+{
+    let n = signal.len();
+    let mut maxima = Vec::new();
+
+    let mut i = 1;
+    while i + 1 < n {
+        if signal[i - 1] < signal[i] {
+            // End of the flat top, if any
+            let mut ahead = i + 1;
+            while ahead + 1 < n && signal[ahead] == signal[i] {
+                ahead += 1;
+            }
+
+            if signal[ahead] < signal[i] {
+                maxima.push((i + ahead - 1) / 2);
+                i = ahead;
+                continue;
+            }
+        }
+        i += 1;
+    }
+
+    maxima
+}
+
+// The `peaks` of the `signal` no closer than `min_distance` to a higher one, visited from the
+// highest down
+fn select_by_distance<S>(
+    signal: &ArrayBase<S, Ix1>,
+    peaks: &[usize],
+    min_distance: usize,
+) -> Vec<usize>
+where
+    S: Data<Elem = f32>,
+{
+    let mut order = (0..peaks.len()).collect::<Vec<usize>>();
+    order.sort_by(|&a, &b| signal[peaks[b]].total_cmp(&signal[peaks[a]]));
+
+    let mut keep = vec![true; peaks.len()];
+    for &j in &order {
+        if !keep[j] {
+            continue;
+        }
+
+        for k in (0..j)
+            .rev()
+            .take_while(|&k| peaks[j] - peaks[k] < min_distance)
+        {
+            keep[k] = false;
+        }
+        for k in (j + 1..peaks.len()).take_while(|&k| peaks[k] - peaks[j] < min_distance) {
+            keep[k] = false;
+        }
+    }
+
+    peaks
+        .iter()
+        .zip(keep)
+        .filter_map(|(&peak, keep)| keep.then_some(peak))
+        .collect()
+}
+
+// Left and right bases of the `peak` of the `signal`: the lowest samples, the closest to the peak
+// among equal ones, before a sample higher than the peak or the edge of the signal
+fn bases<S>(signal: &ArrayBase<S, Ix1>, peak: usize) -> (usize, usize)
+where
+    S: Data<Elem = f32>,
+{
+    let height = signal[peak];
+    let lowest = |range: &mut dyn Iterator<Item = usize>| {
+        range
+            .take_while(|&i| signal[i] <= height)
+            .fold(
+                peak,
+                |base, i| if signal[i] < signal[base] { i } else { base },
+            )
+    };
+
+    (
+        lowest(&mut (0..=peak).rev()),
+        lowest(&mut (peak..signal.len())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array1};
+
+    use super::*;
+
+    // Two close peaks (1, 3), a small bump (7), a flat top (10..=12) and the highest peak (14)
+    fn signal() -> Array1<f32> {
+        array![0.0, 5.0, 1.0, 4.0, 0.0, 0.0, 1.0, 1.5, 1.0, 0.0, 3.0, 3.0, 3.0, 0.0, 8.0, 2.0, 0.0]
+    }
+
+    fn indices(peaks: &[Peak]) -> Vec<usize> {
+        peaks.iter().map(|peak| peak.index).collect()
+    }
+
+    #[test]
+    fn peaks_report_their_prominence_and_bases() {
+        let peaks = find_peaks(&signal(), None, None, 1);
+
+        assert_eq!(indices(&peaks), vec![1, 3, 7, 11, 14]);
+        let properties = peaks
+            .iter()
+            .map(|p| (p.height, p.prominence, p.left_base, p.right_base))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            properties,
+            vec![
+                (5.0, 5.0, 0, 4),
+                (4.0, 3.0, 2, 4),
+                (1.5, 1.5, 5, 9),
+                (3.0, 3.0, 9, 13),
+                (8.0, 8.0, 13, 16)
+            ]
+        );
+    }
+
+    #[test]
+    fn close_peaks_and_small_bumps_are_filtered() {
+        let signal = signal();
+
+        // The lower of the two peaks 2 samples apart is dropped, the flat top 3 samples from the
+        // highest peak is kept
+        assert_eq!(
+            indices(&find_peaks(&signal, None, None, 3)),
+            vec![1, 7, 11, 14]
+        );
+        assert_eq!(
+            indices(&find_peaks(&signal, None, Some(2.0), 1)),
+            vec![1, 3, 11, 14]
+        );
+        assert_eq!(
+            indices(&find_peaks(&signal, None, Some(2.0), 3)),
+            vec![1, 11, 14]
+        );
+        assert_eq!(
+            indices(&find_peaks(&signal, Some(3.5), None, 1)),
+            vec![1, 3, 14]
+        );
+    }
+
+    #[test]
+    fn edges_and_rising_plateaus_are_not_peaks() {
+        assert!(find_peaks(&array![3.0, 1.0, 2.0], None, None, 1).is_empty());
+        assert!(find_peaks(&array![0.0, 1.0, 1.0], None, None, 1).is_empty());
+        assert_eq!(
+            indices(&find_peaks(&array![0.0, 2.0, 2.0, 3.0, 0.0], None, None, 1)),
+            vec![3]
+        );
+        // Midpoint of an even plateau rounded down
+        assert_eq!(
+            indices(&find_peaks(&array![0.0, 2.0, 2.0, 0.0], None, None, 1)),
+            vec![1]
+        );
+    }
+}
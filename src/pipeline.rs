@@ -0,0 +1,581 @@
+use std::{
+    error,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use ndarray::{Array2, Array3, Axis};
+
+use crate::{
+    bad_channels::interpolate_channels,
+    epochs::Epochs,
+    filter::{resample, FIRFilter},
+    read::raw::Raw,
+    reference::{rereference_average, rereference_to},
+    Error,
+};
+
+// Errors that can occur while running the provided steps of a pipeline
+#[derive(Debug)]
+pub enum PipelineError {
+    // The band of a filter is not within (0, fs / 2), or its low frequency is not below its high one
+    InvalidBand { low: f32, high: f32, fs: f32 },
+}
+
+impl Display for PipelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PipelineError::InvalidBand { low, high, fs } => write!(
+                f,
+                "band {low}..{high} Hz is not an increasing band within the \
+                 {} Hz Nyquist frequency of a sampling frequency of {fs} Hz",
+                fs / 2.0
+            ),
+        }
+    }
+}
+
+impl error::Error for PipelineError {}
+
+// What the steps of a pipeline know about the data they process, updated along with it
+#[derive(Clone, Debug)]
+pub struct Meta {
+    // Sampling frequency, in Hz
+    pub sfreq: f64,
+    pub channel_names: Vec<String>,
+    // Cartesian positions of the channels (channels x 3), NaN for those unknown
+    pub positions: Array2<f64>,
+    // Indices of the channels deemed bad
+    pub bad_channels: Vec<usize>,
+}
+
+impl Meta {
+    // Sampling frequency, channel names and positions of the `raw` recording, without bad channels
+    pub fn from_raw(raw: &Raw) -> Meta {
+        Meta {
+            sfreq: raw.sfreq(),
+            channel_names: raw.channel_names().into_iter().map(Into::into).collect(),
+            positions: raw.header().channel_positions().positions,
+            bad_channels: Vec::new(),
+        }
+    }
+
+    // Sampling frequency and channel names of the `epochs`, their positions being unknown, without
+    // bad channels
+    pub fn from_epochs(epochs: &Epochs) -> Meta {
+        Meta {
+            sfreq: epochs.sfreq,
+            channel_names: epochs.channel_names.clone(),
+            positions: Array2::from_elem((epochs.channel_names.len(), 3), f64::NAN),
+            bad_channels: Vec::new(),
+        }
+    }
+
+    // Indices of the channels called `names`, in the order of `names`
+    // Names not matching any channel are left out
+    pub fn channel_indices(&self, names: &[String]) -> Vec<usize> {
+        names
+            .iter()
+            .filter_map(|name| self.channel_names.iter().position(|c| c == name))
+            .collect()
+    }
+}
+
+// A processing step of a pipeline, of the data (channels x samples) and its metadata in place
+// Implemented by the provided steps below, as well as by any step of the user's own
+pub trait Step {
+    // Name of the step, as logged
+    fn name(&self) -> String;
+
+    // Parameters of the step, as logged
+    fn parameters(&self) -> String;
+
+    fn apply(&self, data: &mut Array2<f32>, meta: &mut Meta) -> Result<(), Error>;
+}
+
+// A step of a pipeline as it was run
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    pub name: String,
+    pub parameters: String,
+    pub duration: Duration,
+}
+
+// Data (channels x samples) and metadata at the end of a pipeline, along with the log of its steps
+#[derive(Clone, Debug)]
+pub struct Processed {
+    pub data: Array2<f32>,
+    pub meta: Meta,
+    pub log: Vec<LogEntry>,
+}
+
+// Epochs and metadata at the end of a pipeline, along with the log of its steps, the duration of
+// each being summed over the epochs
+#[derive(Clone, Debug)]
+pub struct ProcessedEpochs {
+    pub epochs: Epochs,
+    pub meta: Meta,
+    pub log: Vec<LogEntry>,
+}
+
+// Processing steps applied in order, e.g.
+// `Pipeline::new().step(BandPass { .. }).step(Notch { .. }).step(Rereference::Average { .. })`
+#[derive(Default)]
+pub struct Pipeline {
+    steps: Vec<Box<dyn Step>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The pipeline followed by the `step`
+    pub fn step(mut self, step: impl Step + 'static) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    // Runs the steps on the data of the `raw` recording, leaving it as it is
+    pub fn run(&self, raw: &Raw) -> Result<Processed, Error> {
+        self.run_data(raw.data().to_owned(), Meta::from_raw(raw))
+    }
+
+    // Runs the steps on the `data` (channels x samples) of the `meta`, stopping at the first step
+    // failing
+    pub fn run_data(&self, mut data: Array2<f32>, mut meta: Meta) -> Result<Processed, Error> {
+        let mut log = Vec::with_capacity(self.steps.len());
+        for step in &self.steps {
+            let start = Instant::now();
+            step.apply(&mut data, &mut meta)?;
+            log.push(LogEntry {
+                name: step.name(),
+                parameters: step.parameters(),
+                duration: start.elapsed(),
+            });
+        }
+
+        Ok(Processed { data, meta, log })
+    }
+
+    // Runs the steps on each of the `epochs` apart, as by `run_epochs_with` with the metadata of
+    // the epochs
+    pub fn run_epochs(&self, epochs: &Epochs) -> Result<ProcessedEpochs, Error> {
+        self.run_epochs_with(epochs, Meta::from_epochs(epochs))
+    }
+
+    // Runs the steps on the data (channels x samples) of each of the `epochs` apart, every epoch
+    // starting from the `meta`, e.g. for the positions of the channels to interpolate bad ones
+    // The times of the epochs still start at their `tmin`, the sampling frequency and channel
+    // names being those at the end of the steps
+    // No step is run on empty epochs, returned as they are with an empty log
+    pub fn run_epochs_with(&self, epochs: &Epochs, meta: Meta) -> Result<ProcessedEpochs, Error> {
+        let mut processed = Vec::with_capacity(epochs.len());
+        let mut log: Vec<LogEntry> = Vec::new();
+        let mut last = meta.clone();
+        for (_, epoch) in epochs.iter() {
+            let Processed {
+                data,
+                meta,
+                log: epoch_log,
+            } = self.run_data(epoch.to_owned(), meta.clone())?;
+            if log.is_empty() {
+                log = epoch_log;
+            } else {
+                for (entry, epoch_entry) in log.iter_mut().zip(epoch_log) {
+                    entry.duration += epoch_entry.duration;
+                }
+            }
+            processed.push(data);
+            last = meta;
+        }
+
+        let Some(first) = processed.first() else {
+            return Ok(ProcessedEpochs {
+                epochs: epochs.clone(),
+                meta,
+                log,
+            });
+        };
+        let (num_channels, num_samples) = first.dim();
+        let mut data = Array3::zeros((processed.len(), num_channels, num_samples));
+        for (mut epoch, processed) in data.outer_iter_mut().zip(&processed) {
+            epoch.assign(processed);
+        }
+
+        Ok(ProcessedEpochs {
+            epochs: Epochs {
+                data,
+                sfreq: last.sfreq,
+                channel_names: last.channel_names.clone(),
+                labels: epochs.labels.clone(),
+                ..*epochs
+            },
+            meta: last,
+            log,
+        })
+    }
+}
+
+// Zero-phase windowed-sinc band-pass filtering of each channel, passing `low` to `high` Hz
+#[derive(Clone, Debug)]
+pub struct BandPass {
+    pub low: f32,
+    pub high: f32,
+    pub num_taps: usize,
+}
+
+impl Step for BandPass {
+    fn name(&self) -> String {
+        "band-pass".into()
+    }
+
+    fn parameters(&self) -> String {
+        format!("{}-{} Hz, {} taps", self.low, self.high, self.num_taps)
+    }
+
+    fn apply(&self, data: &mut Array2<f32>, meta: &mut Meta) -> Result<(), Error> {
+        let fs = meta.sfreq as f32;
+        check_band(self.low, self.high, fs)?;
+        let filter = FIRFilter::bandpass(self.num_taps, self.low, self.high, fs);
+        filter_channels(data, &filter);
+
+        Ok(())
+    }
+}
+
+// Zero-phase windowed-sinc band-stop filtering of each channel, rejecting `width` Hz around `freq`
+// Hz, e.g. the power line frequency
+#[derive(Clone, Debug)]
+pub struct Notch {
+    pub freq: f32,
+    pub width: f32,
+    pub num_taps: usize,
+}
+
+impl Step for Notch {
+    fn name(&self) -> String {
+        "notch".into()
+    }
+
+    fn parameters(&self) -> String {
+        format!(
+            "{} Hz, {} Hz wide, {} taps",
+            self.freq, self.width, self.num_taps
+        )
+    }
+
+    fn apply(&self, data: &mut Array2<f32>, meta: &mut Meta) -> Result<(), Error> {
+        let fs = meta.sfreq as f32;
+        let (low, high) = (self.freq - self.width / 2.0, self.freq + self.width / 2.0);
+        check_band(low, high, fs)?;
+        let filter = FIRFilter::bandstop(self.num_taps, low, high, fs);
+        filter_channels(data, &filter);
+
+        Ok(())
+    }
+}
+
+// Resampling of each channel to `sfreq` Hz, as by `resample`, the actual new rate being recorded
+#[derive(Clone, Debug)]
+pub struct Resample {
+    pub sfreq: f64,
+}
+
+impl Step for Resample {
+    fn name(&self) -> String {
+        "resample".into()
+    }
+
+    fn parameters(&self) -> String {
+        format!("{} Hz", self.sfreq)
+    }
+
+    fn apply(&self, data: &mut Array2<f32>, meta: &mut Meta) -> Result<(), Error> {
+        let mut channels = Vec::with_capacity(data.nrows());
+        let mut sfreq = meta.sfreq;
+        for channel in data.outer_iter() {
+            let (resampled, actual) = resample(&channel, meta.sfreq, self.sfreq);
+            channels.push(resampled);
+            sfreq = actual;
+        }
+
+        let num_samples = channels.first().map_or(0, |channel| channel.len());
+        let mut resampled = Array2::zeros((channels.len(), num_samples));
+        for (mut row, channel) in resampled.outer_iter_mut().zip(&channels) {
+            row.assign(channel);
+        }
+        *data = resampled;
+        meta.sfreq = sfreq;
+
+        Ok(())
+    }
+}
+
+// Re-referencing of the data, names not matching any channel being ignored
+#[derive(Clone, Debug)]
+pub enum Rereference {
+    // To the common average of all channels, except those called as given
+    Average { exclude: Vec<String> },
+    // To the mean of the channels called as given, e.g. both mastoids
+    Channels(Vec<String>),
+}
+
+impl Step for Rereference {
+    fn name(&self) -> String {
+        "re-reference".into()
+    }
+
+    fn parameters(&self) -> String {
+        match self {
+            Rereference::Average { exclude } if exclude.is_empty() => "average".into(),
+            Rereference::Average { exclude } => {
+                format!("average, excluding {}", exclude.join(", "))
+            }
+            Rereference::Channels(names) => names.join("+"),
+        }
+    }
+
+    fn apply(&self, data: &mut Array2<f32>, meta: &mut Meta) -> Result<(), Error> {
+        match self {
+            Rereference::Average { exclude } => {
+                rereference_average(data, &meta.channel_indices(exclude))
+            }
+            Rereference::Channels(names) => rereference_to(data, &meta.channel_indices(names)),
+        }
+
+        Ok(())
+    }
+}
+
+// Interpolation of the bad channels of the metadata from their `neighbors` nearest good channels,
+// as by `interpolate_channels`, those of a known position being no longer deemed bad
+#[derive(Clone, Debug)]
+pub struct InterpolateBad {
+    pub neighbors: usize,
+}
+
+impl Step for InterpolateBad {
+    fn name(&self) -> String {
+        "interpolate-bad".into()
+    }
+
+    fn parameters(&self) -> String {
+        format!("{} neighbors", self.neighbors)
+    }
+
+    fn apply(&self, data: &mut Array2<f32>, meta: &mut Meta) -> Result<(), Error> {
+        interpolate_channels(data, &meta.bad_channels, &meta.positions, self.neighbors);
+        let positions = &meta.positions;
+        meta.bad_channels
+            .retain(|&i| !positions.row(i).iter().all(|p| p.is_finite()));
+
+        Ok(())
+    }
+}
+
+// Validation of the band `low` to `high` Hz of a filter at the sampling frequency `fs` in Hz
+fn check_band(low: f32, high: f32, fs: f32) -> Result<(), PipelineError> {
+    // Also rejects NaN frequencies
+    if !(0.0 < low && low < high && high < fs / 2.0) {
+        return Err(PipelineError::InvalidBand { low, high, fs });
+    }
+
+    Ok(())
+}
+
+// Filters each channel of the `data` with the zero-phase `filter`
+fn filter_channels(data: &mut Array2<f32>, filter: &FIRFilter) {
+    for mut channel in data.axis_iter_mut(Axis(0)) {
+        let filtered = filter.process_zero_phase(&channel);
+        channel.assign(&filtered);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::*;
+    use crate::{
+        read::fixtures::{Recording, TempDir},
+        surrogate::Lcg,
+    };
+
+    const FS: f32 = 500.0;
+
+    // Three channels of 2 s of a 10 Hz rhythm, 50 Hz line noise and a little noise
+    fn data() -> Array2<f32> {
+        let mut generator = Lcg::new(8);
+        Array2::from_shape_fn((3, 1000), |(c, t)| {
+            let time = t as f32 / FS;
+            (c + 1) as f32 * (TAU * 10.0 * time).sin()
+                + 2.0 * (TAU * 50.0 * time).sin()
+                + (generator.next_f32() - 0.5)
+        })
+    }
+
+    fn three_steps() -> Pipeline {
+        Pipeline::new()
+            .step(BandPass {
+                low: 1.0,
+                high: 40.0,
+                num_taps: 201,
+            })
+            .step(Notch {
+                freq: 50.0,
+                width: 4.0,
+                num_taps: 201,
+            })
+            .step(Rereference::Average {
+                exclude: vec!["Pz".into()],
+            })
+    }
+
+    // The steps of `three_steps` applied by hand
+    fn manually(data: &Array2<f32>) -> Array2<f32> {
+        let mut expected = data.clone();
+        filter_channels(&mut expected, &FIRFilter::bandpass(201, 1.0, 40.0, FS));
+        filter_channels(&mut expected, &FIRFilter::bandstop(201, 48.0, 52.0, FS));
+        rereference_average(&mut expected, &[2]);
+
+        expected
+    }
+
+    #[test]
+    fn pipeline_matches_the_steps_applied_in_sequence() {
+        let root = TempDir::new("pipeline");
+        let raw = Raw::load(&Recording::new(&["Fz", "Cz", "Pz"], data()).write(&root)).unwrap();
+        let processed = three_steps().run(&raw).unwrap();
+
+        assert_eq!(processed.data, manually(&raw.data().to_owned()));
+        assert_eq!(raw.data(), data());
+        assert_eq!(
+            processed
+                .log
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.parameters.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("band-pass", "1-40 Hz, 201 taps"),
+                ("notch", "50 Hz, 4 Hz wide, 201 taps"),
+                ("re-reference", "average, excluding Pz")
+            ]
+        );
+    }
+
+    // A step of the user's own, scaling the data
+    struct Scale(f32);
+
+    impl Step for Scale {
+        fn name(&self) -> String {
+            "scale".into()
+        }
+
+        fn parameters(&self) -> String {
+            format!("x{}", self.0)
+        }
+
+        fn apply(&self, data: &mut Array2<f32>, _: &mut Meta) -> Result<(), Error> {
+            *data *= self.0;
+            Ok(())
+        }
+    }
+
+    fn meta() -> Meta {
+        Meta {
+            sfreq: FS as f64,
+            channel_names: vec!["Fz".into(), "Cz".into(), "Pz".into()],
+            positions: Array2::from_elem((3, 3), f64::NAN),
+            bad_channels: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn custom_steps_are_run_in_order() {
+        let pipeline = three_steps().step(Scale(2.0));
+        let processed = pipeline.run_data(data(), meta()).unwrap();
+
+        assert_eq!(pipeline.len(), 4);
+        assert_eq!(processed.data, manually(&data()) * 2.0);
+        assert_eq!(processed.log[3].name, "scale");
+    }
+
+    #[test]
+    fn bands_beyond_nyquist_fail() {
+        let steps: [Box<dyn Step>; 4] = [
+            Box::new(BandPass {
+                low: 1.0,
+                high: 250.0,
+                num_taps: 101,
+            }),
+            Box::new(BandPass {
+                low: 40.0,
+                high: 1.0,
+                num_taps: 101,
+            }),
+            Box::new(Notch {
+                freq: 249.0,
+                width: 4.0,
+                num_taps: 101,
+            }),
+            Box::new(Notch {
+                freq: 1.0,
+                width: 4.0,
+                num_taps: 101,
+            }),
+        ];
+
+        for step in steps {
+            let mut data = data();
+            match step.apply(&mut data, &mut meta()) {
+                Err(Error::Pipeline(PipelineError::InvalidBand { fs, .. })) => {
+                    assert_eq!(fs, FS)
+                }
+                other => panic!("Unexpected result {other:?} !"),
+            }
+            assert_eq!(data, self::data());
+        }
+    }
+
+    #[test]
+    fn epochs_are_processed_apart() {
+        let data = data();
+        let epochs = Epochs::new(
+            Array3::from_shape_fn((4, 3, 250), |(e, c, t)| data[[c, e * 250 + t]]),
+            -0.1,
+            FS as f64,
+            meta().channel_names,
+            ["a", "b", "a", "b"].map(String::from).to_vec(),
+        );
+        let pipeline = three_steps().step(Resample { sfreq: 250.0 });
+        let processed = pipeline.run_epochs(&epochs).unwrap();
+
+        assert_eq!(processed.epochs.data.dim(), (4, 3, 125));
+        assert_eq!(
+            (processed.epochs.sfreq, processed.meta.sfreq),
+            (250.0, 250.0)
+        );
+        assert_eq!(
+            (processed.epochs.tmin, &processed.epochs.labels),
+            (-0.1, &epochs.labels)
+        );
+        assert_eq!(processed.log.len(), 4);
+        for (processed, (_, epoch)) in processed.epochs.iter().zip(epochs.iter()) {
+            let expected = pipeline.run_data(epoch.to_owned(), meta()).unwrap();
+            assert_eq!(processed.1, expected.data);
+        }
+
+        let empty = epochs.select("c");
+        let processed = pipeline.run_epochs(&empty).unwrap();
+        assert!(processed.epochs.is_empty() && processed.log.is_empty());
+    }
+}
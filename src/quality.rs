@@ -0,0 +1,414 @@
+use ndarray::{Array1, ArrayBase, Data, Ix1, Ix2};
+
+use crate::fft::welch;
+
+// Frequencies of the power line, in Hz, depending on the region
+pub(crate) const LINE_FREQUENCIES: [f32; 2] = [50.0, 60.0];
+// Half-width, in Hz, of the band around a line frequency its peak is searched within
+pub(crate) const LINE_HALF_WIDTH: f32 = 1.0;
+// Distances, in Hz, from a line frequency of the neighbouring band its peak is compared to
+pub(crate) const NEIGHBOURS: (f32, f32) = (2.0, 5.0);
+// Band of the EEG, in Hz, the high-frequency power is compared to
+const EEG_BAND: (f32, f32) = (1.0, 40.0);
+// Duration, in seconds, of the Welch segments of the PSD
+const SEGMENT_DURATION: f32 = 2.0;
+// Duration, in seconds, of the shortest run of identical samples deemed flat
+const FLAT_DURATION: f32 = 0.1;
+// Length of the shortest run of identical extreme samples deemed clipped
+const CLIPPING_RUN: usize = 3;
+// Quantiles the robust amplitude range spans
+const RANGE_QUANTILES: (f32, f32) = (0.025, 0.975);
+
+// Quality criterion a channel can fail
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QualityIssue {
+    LineNoise,
+    Flatline,
+    Clipping,
+    HighFrequencyNoise,
+    AmplitudeRange,
+}
+
+// Limits each quality metric of a channel must stay within for it to pass
+#[derive(Clone, Debug)]
+pub struct QualityThresholds {
+    // Of the line-noise ratio
+    pub max_line_noise_ratio: f32,
+    // Of the fraction of flat samples
+    pub max_flat_fraction: f32,
+    // Of the fraction of clipped samples
+    pub max_clipping_fraction: f32,
+    // Of the high-frequency noise ratio
+    pub max_high_frequency_ratio: f32,
+    // Of the robust amplitude range, in the unit of the data (e.g. microvolts)
+    pub amplitude_range: (f32, f32),
+}
+
+impl Default for QualityThresholds {
+    fn default() -> Self {
+        QualityThresholds {
+            max_line_noise_ratio: 10.0,
+            max_flat_fraction: 0.1,
+            max_clipping_fraction: 0.01,
+            max_high_frequency_ratio: 0.5,
+            amplitude_range: (0.5, 500.0),
+        }
+    }
+}
+
+impl QualityThresholds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_line_noise_ratio(mut self, ratio: f32) -> Self {
+        self.max_line_noise_ratio = ratio;
+        self
+    }
+
+    pub fn max_flat_fraction(mut self, fraction: f32) -> Self {
+        self.max_flat_fraction = fraction;
+        self
+    }
+
+    pub fn max_clipping_fraction(mut self, fraction: f32) -> Self {
+        self.max_clipping_fraction = fraction;
+        self
+    }
+
+    pub fn max_high_frequency_ratio(mut self, ratio: f32) -> Self {
+        self.max_high_frequency_ratio = ratio;
+        self
+    }
+
+    pub fn amplitude_range(mut self, min: f32, max: f32) -> Self {
+        self.amplitude_range = (min, max);
+        self
+    }
+}
+
+// Quality metrics of a channel, along with the criteria it fails
+#[derive(Clone, Debug)]
+pub struct ChannelQuality {
+    // Peak PSD within 1 Hz of 50 or 60 Hz (the higher ratio of the two) over the median PSD 2 to
+    // 5 Hz away from it, about 1 without line noise
+    pub line_noise_ratio: f32,
+    // Fraction of the samples within runs of identical values lasting at least 0.1 s
+    pub flat_fraction: f32,
+    // Fraction of the samples within runs of at least 3 identical values at the minimum or maximum
+    // of the channel, 0 for a constant channel
+    pub clipping_fraction: f32,
+    // Power above 40 Hz (the line frequencies and their harmonics left out) over the power within
+    // 1 to 40 Hz
+    pub high_frequency_ratio: f32,
+    // Range between the 2.5th and 97.5th percentiles of the samples
+    pub amplitude_range: f32,
+    pub issues: Vec<QualityIssue>,
+}
+
+impl ChannelQuality {
+    pub fn passed(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+// Quality metrics of each channel of the `data` (channels x samples), sampled at `fs` Hz, checked
+// against the default thresholds
+pub fn channel_quality<S>(data: &ArrayBase<S, Ix2>, fs: f32) -> Vec<ChannelQuality>
+where
+    S: Data<Elem = f32>,
+{
+    channel_quality_with(data, fs, &QualityThresholds::default())
+}
+
+// Quality metrics of each channel of the `data` (channels x samples), sampled at `fs` Hz, checked
+// against the `thresholds`
+// Ratios that would divide by zero, e.g. those of a constant channel, are 0
+pub fn channel_quality_with<S>(
+    data: &ArrayBase<S, Ix2>,
+    fs: f32,
+    thresholds: &QualityThresholds,
+) -> Vec<ChannelQuality>
+where
+    S: Data<Elem = f32>,
+{
+    let segment_len = (SEGMENT_DURATION * fs).round().max(1.0) as usize;
+    let flat_run = ((FLAT_DURATION * fs).round() as usize).max(2);
+
+    data.outer_iter()
+        .map(|channel| {
+            let (freqs, psd) = welch(&channel, fs, segment_len);
+
+            let line_noise_ratio = line_noise_ratio(&freqs, &psd);
+            let flat_fraction = flat_fraction(&channel, flat_run);
+            let clipping_fraction = clipping_fraction(&channel);
+            let high_frequency_ratio = high_frequency_ratio(&freqs, &psd);
+            let amplitude_range = amplitude_range(&channel);
+
+            let (min_range, max_range) = thresholds.amplitude_range;
+            let issues = [
+                (
+                    line_noise_ratio > thresholds.max_line_noise_ratio,
+                    QualityIssue::LineNoise,
+                ),
+                (
+                    flat_fraction > thresholds.max_flat_fraction,
+                    QualityIssue::Flatline,
+                ),
+                (
+                    clipping_fraction > thresholds.max_clipping_fraction,
+                    QualityIssue::Clipping,
+                ),
+                (
+                    high_frequency_ratio > thresholds.max_high_frequency_ratio,
+                    QualityIssue::HighFrequencyNoise,
+                ),
+                (
+                    amplitude_range < min_range || amplitude_range > max_range,
+                    QualityIssue::AmplitudeRange,
+                ),
+            ]
+            .into_iter()
+            .filter_map(|(failed, issue)| failed.then_some(issue))
+            .collect();
+
+            ChannelQuality {
+                line_noise_ratio,
+                flat_fraction,
+                clipping_fraction,
+                high_frequency_ratio,
+                amplitude_range,
+                issues,
+            }
+        })
+        .collect()
+}
+
+// Higher ratio, over the line frequencies below Nyquist, of the peak PSD around the line frequency
+// over the median PSD of its neighbouring bands
+fn line_noise_ratio(freqs: &Array1<f32>, psd: &Array1<f32>) -> f32 {
+    let nyquist = freqs[freqs.len() - 1];
+
+    LINE_FREQUENCIES
+        .iter()
+        .filter(|&&line| line + NEIGHBOURS.1 <= nyquist)
+        .map(|&line| {
+            let peak = psd_where(freqs, psd, |f| (f - line).abs() <= LINE_HALF_WIDTH)
+                .into_iter()
+                .fold(0.0, f32::max);
+            let mut background = psd_where(freqs, psd, |f| {
+                (NEIGHBOURS.0..=NEIGHBOURS.1).contains(&(f - line).abs())
+            });
+            background.sort_by(f32::total_cmp);
+
+            ratio(peak, quantile(&background, 0.5))
+        })
+        .fold(0.0, f32::max)
+}
+
+// Power above the EEG band (around the line frequencies and their harmonics left out) over the
+// power within it
+fn high_frequency_ratio(freqs: &Array1<f32>, psd: &Array1<f32>) -> f32 {
+    let near_line = |f: f32| {
+        LINE_FREQUENCIES.iter().any(|&line| {
+            let harmonic = (f / line).round().max(1.0) * line;
+            (f - harmonic).abs() <= NEIGHBOURS.0
+        })
+    };
+
+    let high = psd_where(freqs, psd, |f| f > EEG_BAND.1 && !near_line(f));
+    let eeg = psd_where(freqs, psd, |f| (EEG_BAND.0..=EEG_BAND.1).contains(&f));
+
+    ratio(high.iter().sum(), eeg.iter().sum())
+}
+
+// Values of the `psd` at the `freqs` satisfying the `predicate`
+pub(crate) fn psd_where(
+    freqs: &Array1<f32>,
+    psd: &Array1<f32>,
+    predicate: impl Fn(f32) -> bool,
+) -> Vec<f32> {
+    freqs
+        .iter()
+        .zip(psd)
+        .filter(|&(&f, _)| predicate(f))
+        .map(|(_, &p)| p)
+        .collect()
+}
+
+// Fraction of the samples of the `signal` within runs of at least `min_run` identical values
+fn flat_fraction<S>(signal: &ArrayBase<S, Ix1>, min_run: usize) -> f32
+where
+    S: Data<Elem = f32>,
+{
+    fraction_in_runs(signal, min_run, |_| true)
+}
+
+// Fraction of the samples of the `signal` within runs of identical values at its minimum or
+// maximum, the signal not being constant
+fn clipping_fraction<S>(signal: &ArrayBase<S, Ix1>) -> f32
+where
+    S: Data<Elem = f32>,
+{
+    let min = signal.fold(f32::INFINITY, |a, &b| a.min(b));
+    let max = signal.fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+    if min >= max {
+        return 0.0;
+    }
+
+    fraction_in_runs(signal, CLIPPING_RUN, |x| x == min || x == max)
+}
+
+// Fraction of the samples of the `signal` within runs of at least `min_run` identical values
+// satisfying the `predicate`
+fn fraction_in_runs<S>(
+    signal: &ArrayBase<S, Ix1>,
+    min_run: usize,
+    predicate: impl Fn(f32) -> bool,
+) -> f32
+where
+    S: Data<Elem = f32>,
+{
+    let n = signal.len();
+    if n == 0 {
+        return 0.0;
+    }
+
+    let mut count = 0;
+    let mut start = 0;
+    while start < n {
+        let value = signal[start];
+        let mut end = start + 1;
+        while end < n && signal[end] == value {
+            end += 1;
+        }
+        if end - start >= min_run && predicate(value) {
+            count += end - start;
+        }
+        start = end;
+    }
+
+    count as f32 / n as f32
+}
+
+// Range between the lower and upper `RANGE_QUANTILES` of the `signal`
+fn amplitude_range<S>(signal: &ArrayBase<S, Ix1>) -> f32
+where
+    S: Data<Elem = f32>,
+{
+    let mut sorted = signal.to_vec();
+    sorted.sort_by(f32::total_cmp);
+
+    quantile(&sorted, RANGE_QUANTILES.1) - quantile(&sorted, RANGE_QUANTILES.0)
+}
+
+// Quantile `q` of the `sorted` values, linearly interpolated, 0 when there are none
+pub(crate) fn quantile(sorted: &[f32], q: f32) -> f32 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+
+    let position = q * (sorted.len() - 1) as f32;
+    let (below, fraction) = (position.floor() as usize, position.fract());
+    let above = (below + 1).min(sorted.len() - 1);
+
+    sorted[below] + fraction * (sorted[above] - sorted[below])
+}
+
+pub(crate) fn ratio(numerator: f32, denominator: f32) -> f32 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use ndarray::{s, Array2};
+
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    const FS: f32 = 250.0;
+
+    // Ten seconds of EEG-like activity, in microvolts: a 10 Hz rhythm over noise of a power
+    // decaying with frequency (an autoregressive process of the first order)
+    fn eeg(seed: u64) -> Array1<f32> {
+        let mut generator = Lcg::new(seed);
+        let mut state = 0.0;
+        Array1::from_shape_fn(2500, |t| {
+            state = 0.95 * state + 20.0 * (generator.next_f32() - 0.5);
+            state + 10.0 * (TAU * 10.0 * t as f32 / FS).sin()
+        })
+    }
+
+    fn sinusoid(frequency: f32, amplitude: f32) -> Array1<f32> {
+        Array1::from_shape_fn(2500, |t| {
+            amplitude * (TAU * frequency * t as f32 / FS).sin()
+        })
+    }
+
+    #[test]
+    fn each_pathology_is_flagged_by_its_metric() {
+        let mut generator = Lcg::new(99);
+        let white = Array1::from_shape_fn(2500, |_| 100.0 * (generator.next_f32() - 0.5));
+        let mut flat = eeg(3);
+        flat.slice_mut(s![500..1000]).fill(1.0);
+        let clipped = eeg(4).mapv(|x| x.clamp(-30.0, 30.0));
+
+        let channels = [
+            eeg(1),
+            &eeg(2) + &sinusoid(50.0, 20.0),
+            flat,
+            clipped,
+            &eeg(5) + &white,
+            eeg(6) * 1e-3,
+            eeg(7) * 100.0,
+        ];
+        let views = channels.iter().map(|c| c.view()).collect::<Vec<_>>();
+        let data = ndarray::stack(ndarray::Axis(0), &views).unwrap();
+        let quality = channel_quality(&data, FS);
+
+        let issues = quality.iter().map(|q| q.issues.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            issues,
+            vec![
+                vec![],
+                vec![QualityIssue::LineNoise],
+                vec![QualityIssue::Flatline],
+                vec![QualityIssue::Clipping],
+                vec![QualityIssue::HighFrequencyNoise],
+                vec![QualityIssue::AmplitudeRange],
+                vec![QualityIssue::AmplitudeRange],
+            ]
+        );
+        assert!(quality[0].passed());
+        assert!(quality[1].line_noise_ratio > 100.0 * quality[0].line_noise_ratio);
+        assert_eq!(quality[2].flat_fraction, 0.2);
+        assert_eq!(quality[3].amplitude_range, 60.0);
+        assert!(quality[4].high_frequency_ratio > 10.0 * quality[0].high_frequency_ratio);
+    }
+
+    #[test]
+    fn constant_channel_is_flat_without_dividing_by_zero() {
+        let quality = channel_quality(&Array2::from_elem((1, 2500), 4.0), FS);
+
+        assert_eq!(quality[0].flat_fraction, 1.0);
+        assert_eq!(quality[0].clipping_fraction, 0.0);
+        assert_eq!(quality[0].line_noise_ratio, 0.0);
+        assert_eq!(quality[0].high_frequency_ratio, 0.0);
+        assert_eq!(
+            quality[0].issues,
+            vec![QualityIssue::Flatline, QualityIssue::AmplitudeRange]
+        );
+        // Unless tolerated
+        let thresholds = QualityThresholds::new()
+            .max_flat_fraction(1.0)
+            .amplitude_range(0.0, 500.0);
+        let quality = channel_quality_with(&Array2::from_elem((1, 2500), 4.0), FS, &thresholds);
+        assert!(quality[0].passed());
+    }
+}
@@ -1,11 +1,55 @@
 // * https://www.brainproducts.com/download/specification-of-brainvision-core-data-format-1-0/
 
 use core::{f32, str};
-use std::{fmt::Debug, fs, io::Read, path::Path, str::Split};
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    fs,
+    io::{BufReader, ErrorKind, Read, Seek, SeekFrom},
+    marker::PhantomData,
+    ops::Range,
+    path::Path,
+};
 
-use ndarray::{Array2, ArrayView1};
+use memmap2::Mmap;
+use ndarray::{aview1, s, Array1, Array2, Array3, ArrayView1, ArrayView2, Axis};
 
-use super::BIDSPath;
+use super::{montage::standard_position, BIDSPath, ReadError};
+
+// Values are taken verbatim, quotes included, while escape sequences are left to `Fields`, as the
+// only one of the specification (`\1`) is unknown to INI
+fn ini_options() -> ini::ParseOption {
+    ini::ParseOption {
+        enabled_quote: false,
+        enabled_escape: false,
+        enabled_indented_mutiline_value: false,
+        enabled_preserve_key_leading_whitespace: false,
+    }
+}
+
+// Value of the mandatory `key` of a `section` of a header, failing with the error built by `invalid`
+fn required<'a>(
+    section: &'a ini::Properties,
+    key: &str,
+    invalid: &dyn Fn(String) -> ReadError,
+) -> Result<&'a str, ReadError> {
+    section
+        .get(key)
+        .ok_or_else(|| invalid(format!("missing {key}")))
+}
+
+// Value of the mandatory numeric `key` of a `section` of a header
+fn required_number<T: str::FromStr>(
+    section: &ini::Properties,
+    key: &str,
+    invalid: &dyn Fn(String) -> ReadError,
+) -> Result<T, ReadError> {
+    let value = required(section, key, invalid)?;
+
+    value
+        .parse::<T>()
+        .map_err(|_| invalid(format!("invalid {key} '{value}'")))
+}
 
 mod locked {
     // Trait used to restrict the data type the raw data can be formatted to
@@ -16,16 +60,21 @@ mod locked {
     impl Locked for i16 {}
 }
 
-// Struct containing all of the information provided in the header file of the associated `task`,
-// `acquisition` and `run`, provided to the `Header::load` method.
+// Struct containing all of the information provided in the header file of a recording, provided to
+// the `Header::load` method.
 //
 // sub-<subject>[_ses-<session>]_task-<task>[_acq-<acquisition>][_run-<run>]_eeg.vhdr
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
     // Name of the EEG data file
     pub data_file: String,
-    // Name of marker file
-    pub marker_file: String,
+    // Name of marker file, if any
+    pub marker_file: Option<String>,
+    // Data orientation of the EEG data file
+    // - MULTIPLEXED: The values of all channels are stored sample by sample
+    // - VECTORIZED: All the values of a channel are stored before the next channel
+    pub data_orientation: DataOrientation,
     // Number of channels in the EEG data file
     pub num_channels: u32,
     pub sampling_interval: f64,
@@ -33,16 +82,18 @@ pub struct Header {
     pub averaged: bool,
     // Number of segments included in the average
     pub averaged_segms: u32,
-    // Number of samples per channel
+    // Number of samples per channel, in each segment
     pub segment_data_points: u32,
     // Type of segmentation
     // - NOTSEGMENTED: The data set is not segmented
-    // - MARKERBASED: The data set is segmented based
+    // - MARKERBASED: The data set is segmented based on markers
     pub segmentation_type: String,
     // Encoding of data in EEG data file
     // - IEEE_FLOAT_32: IEEE floating-point format, single precision, 4 bytes per value
     // - INT_16: 16-bit signed integer
     pub binary_format: BinaryFormatType,
+    // Byte order of the values in the EEG data file, provided by `UseBigEndianOrder`
+    pub byte_order: Endianness,
     // Stores information about each channel, provided in the `[Channel Infos]` section
     pub channels: Vec<ChannelInfo>,
     // Stores information about each channel's coordinates, provided in the `Coordinates` section
@@ -53,61 +104,57 @@ pub struct Header {
 
 impl Header {
     // Load a header file by providing the `path` to a BIDS-compliant data recording
-    pub fn load<P: AsRef<Path>>(
-        path: &BIDSPath<P>,
-        task: &str,
-        acquisition: Option<&str>,
-        run: Option<&str>,
-    ) -> Header {
-        let mut buf = String::new();
-        let _ = fs::File::open(path.path.join(format!(
-            "sub-{}{}_task-{}{}{}_{}.vhdr",
-            path.subject,
-            if let Some(session) = path.session {
-                format!("_ses-{session}")
-            } else {
-                String::default()
-            },
-            task,
-            if let Some(acquisition) = acquisition {
-                format!("_acq-{acquisition}")
-            } else {
-                String::default()
-            },
-            if let Some(run) = run {
-                format!("_run-{run}")
-            } else {
-                String::default()
-            },
-            path.datatype
-        )))
-        .unwrap()
-        .read_to_string(&mut buf);
-        // Extract the `[Comment]` section
-        let comment = buf
-            .match_indices("[Comment]")
-            .next()
-            .map(|(idx, _)| buf[idx + "[Comment]".len()..buf.len()].to_string());
+    // The `path` is validated beforehand, failing with the first level of it not found
+    // Fails with `ReadError::InvalidHeader` if the header file cannot be read, is not valid INI,
+    // lacks or holds invalid mandatory keys, or holds a channel or coordinates line of invalid
+    // numbers
+    pub fn load<P: AsRef<Path>>(path: &BIDSPath<P>) -> Result<Header, ReadError> {
+        path.validate()?;
+
+        let filename = path.filename(path.datatype, "vhdr");
+        let invalid = |reason: String| ReadError::InvalidHeader {
+            file: filename
+                .file_name()
+                .map_or_else(String::new, |f| f.to_string_lossy().into_owned()),
+            reason,
+        };
+
+        let mut buf = fs::read_to_string(&filename).map_err(|e| invalid(e.to_string()))?;
+        // Extract the `[Comment]` section, free text which is not INI
+        let comment = buf.find("[Comment]").map(|idx| {
+            let comment = buf[idx + "[Comment]".len()..].to_string();
+            buf.truncate(idx);
+            comment
+        });
         // And skip the first line (identification line)
         buf = buf.lines().skip(1).collect::<Vec<&str>>().join("\n");
 
-        let file = ini::Ini::load_from_str(&buf).unwrap();
+        let file =
+            ini::Ini::load_from_str_opt(&buf, ini_options()).map_err(|e| invalid(e.to_string()))?;
 
-        let common_infos = file.section(Some("Common Infos")).unwrap();
-        let binary_infos = file.section(Some("Binary Infos")).unwrap();
-        let channel_infos = file.section(Some("Channel Infos")).unwrap();
+        let section = |name: &str| {
+            file.section(Some(name))
+                .ok_or_else(|| invalid(format!("missing [{name}] section")))
+        };
+        let common_infos = section("Common Infos")?;
+        let binary_infos = section("Binary Infos")?;
+        let channel_infos = section("Channel Infos")?;
         let coordinates = file.section(Some("Coordinates"));
 
-        let data_file = common_infos.get("DataFile").unwrap().into();
-        let marker_file = common_infos.get("MarkerFile").unwrap().into();
-        let num_channels = common_infos
-            .get("NumberOfChannels")
-            .map(|s| s.parse::<u32>().unwrap())
-            .unwrap();
-        let sampling_interval = common_infos
-            .get("SamplingInterval")
-            .map(|s| s.parse::<f64>().unwrap())
-            .unwrap();
+        let data_file = required(common_infos, "DataFile", &invalid)?.into();
+        let marker_file = common_infos.get("MarkerFile").map(Into::into);
+        let data_orientation = match common_infos.get("DataOrientation") {
+            None | Some("MULTIPLEXED") => DataOrientation::Multiplexed,
+            Some("VECTORIZED") => DataOrientation::Vectorized,
+            Some(orientation) => {
+                return Err(invalid(format!("invalid DataOrientation '{orientation}'")))
+            }
+        };
+        let num_channels = required_number::<u32>(common_infos, "NumberOfChannels", &invalid)?;
+        if num_channels == 0 {
+            return Err(invalid("invalid NumberOfChannels '0'".into()));
+        }
+        let sampling_interval = required_number::<f64>(common_infos, "SamplingInterval", &invalid)?;
         let averaged = common_infos.get("Averaged").map_or_else(
             || false,
             |s| match s {
@@ -118,46 +165,54 @@ impl Header {
         );
         let averaged_segms = match averaged {
             false => 0u32,
-            true => common_infos
-                .get("AveragedSegments")
-                .map(|s| s.parse::<u32>().unwrap())
-                .unwrap(),
+            true => required_number(common_infos, "AveragedSegments", &invalid)?,
         };
-        let segmentation_type = match averaged {
-            false => "NOTSEGMENTED",
-            true => common_infos.get("SegmentationType").unwrap(),
-        }
-        .into();
-        let segment_data_points = if averaged && segmentation_type == "MARKERBASED" {
-            common_infos
-                .get("SegmentDataPoints")
-                .map(|s| s.parse::<u32>().unwrap())
-                .unwrap()
+        let segmentation_type = common_infos
+            .get("SegmentationType")
+            .unwrap_or("NOTSEGMENTED")
+            .into();
+        let segment_data_points = if segmentation_type == "MARKERBASED" {
+            required_number(common_infos, "SegmentDataPoints", &invalid)?
         } else {
             0
         };
 
-        let binary_format = match binary_infos.get("BinaryFormat").unwrap() {
+        let binary_format = match required(binary_infos, "BinaryFormat", &invalid)? {
             "IEEE_FLOAT_32" => BinaryFormatType::IeeeFloat32,
             "INT_16" => BinaryFormatType::Int16,
-            _ => panic!("Invalid binary format !"),
+            format => return Err(invalid(format!("invalid BinaryFormat '{format}'"))),
+        };
+        let byte_order = match binary_infos.get("UseBigEndianOrder") {
+            Some("YES") => Endianness::Big,
+            _ => Endianness::Little,
         };
 
         let channels = channel_infos
             .iter()
-            .map(|(_, v)| ChannelInfo::from(v.split(',')))
-            .collect::<Vec<ChannelInfo>>();
+            .map(|(key, v)| ChannelInfo::try_from(v).map_err(|e| invalid(format!("{key}: {e}"))))
+            .collect::<Result<Vec<ChannelInfo>, ReadError>>()?;
+        if channels.len() != num_channels as usize {
+            return Err(ReadError::ChannelCountMismatch {
+                declared: num_channels as usize,
+                listed: channels.len(),
+            });
+        }
 
-        let channel_coords = coordinates.map(|coords| {
-            coords
-                .iter()
-                .map(|(_, v)| Coordinates::from(v.split(',')))
-                .collect::<Vec<Coordinates>>()
-        });
+        let channel_coords = coordinates
+            .map(|coords| {
+                coords
+                    .iter()
+                    .map(|(key, v)| {
+                        Coordinates::try_from(v).map_err(|e| invalid(format!("{key}: {e}")))
+                    })
+                    .collect::<Result<Vec<Coordinates>, ReadError>>()
+            })
+            .transpose()?;
 
-        Header {
+        Ok(Header {
             data_file,
             marker_file,
+            data_orientation,
             num_channels,
             sampling_interval,
             averaged,
@@ -165,47 +220,205 @@ impl Header {
             segment_data_points,
             segmentation_type,
             binary_format,
+            byte_order,
             channels,
             channel_coords,
             comment,
+        })
+    }
+
+    // Sampling rate, in Hz, from the sampling interval in microseconds
+    pub fn sampling_rate_hz(&self) -> f64 {
+        1e6 / self.sampling_interval
+    }
+
+    // Duration of `num_samples` samples, in seconds
+    pub fn duration_secs(&self, num_samples: usize) -> f64 {
+        num_samples as f64 * self.sampling_interval / 1e6
+    }
+
+    // Time of each of `num_samples` samples, in seconds from the first one
+    // Each time is computed from its own index, so that fractional sampling intervals do not
+    // accumulate rounding errors over long recordings
+    pub fn times(&self, num_samples: usize) -> Array1<f64> {
+        Array1::from_iter((0..num_samples).map(|i| self.duration_secs(i)))
+    }
+
+    // Number of segments included in the average, if the data set is averaged
+    pub fn averaged_segments(&self) -> Option<u32> {
+        self.averaged.then_some(self.averaged_segms)
+    }
+
+    // Whether the data set is made of segments of `segment_data_points` samples each
+    pub fn is_segmented(&self) -> bool {
+        self.segmentation_type == "MARKERBASED"
+    }
+
+    // Cartesian positions of the channels (channels x 3), as given by the `[Coordinates]` section
+    // Channels without a (known, i.e. of a finite non-zero radius) position there fall back to the standard 10-20/10-10 position of
+    // their name, on a unit sphere, while those not part of it either are left as NaN
+    pub fn channel_positions(&self) -> ChannelPositions {
+        let mut positions = Array2::from_elem((self.channels.len(), 3), f64::NAN);
+        let mut templated = Vec::new();
+        let mut missing = Vec::new();
+        for (i, channel) in self.channels.iter().enumerate() {
+            let coords = self
+                .channel_coords
+                .as_ref()
+                .and_then(|coords| coords.get(i))
+                .filter(|coords| coords.radius.is_finite() && coords.radius != 0.0)
+                .copied()
+                .or_else(|| {
+                    let standard = standard_position(&channel.name);
+                    if standard.is_some() {
+                        templated.push(i);
+                    }
+                    standard
+                });
+            match coords {
+                Some(coords) => positions.row_mut(i).assign(&aview1(&coords.to_cartesian())),
+                None => missing.push(i),
+            }
+        }
+
+        ChannelPositions {
+            positions,
+            templated,
+            missing,
         }
     }
+
+    // Indices of the channels with the given `names`, in the order of `names`
+    // Names not matching any channel are left out
+    pub fn channel_indices(&self, names: &[&str]) -> Vec<usize> {
+        names
+            .iter()
+            .filter_map(|name| self.channels.iter().position(|c| c.name == *name))
+            .collect()
+    }
+}
+
+// <N> channels, <sampling frequency> Hz, <binary format>, <data orientation>, <byte order>
+// [, <segmentation>][, averaged over <N> segments]
+// channels: <first channels>[, ... (<N> more)]
+impl Display for Header {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const SHOWN_CHANNELS: usize = 8;
+
+        write!(
+            f,
+            "{} channels, {} Hz, {}, {}, {} endian",
+            self.num_channels,
+            self.sampling_rate_hz(),
+            match self.binary_format {
+                BinaryFormatType::IeeeFloat32 => "IEEE_FLOAT_32",
+                BinaryFormatType::Int16 => "INT_16",
+            },
+            match self.data_orientation {
+                DataOrientation::Multiplexed => "MULTIPLEXED",
+                DataOrientation::Vectorized => "VECTORIZED",
+            },
+            match self.byte_order {
+                Endianness::Little => "little",
+                Endianness::Big => "big",
+            }
+        )?;
+        if self.is_segmented() {
+            write!(f, ", segments of {} samples", self.segment_data_points)?;
+        }
+        if let Some(segments) = self.averaged_segments() {
+            write!(f, ", averaged over {segments} segments")?;
+        }
+
+        let names = self
+            .channels
+            .iter()
+            .take(SHOWN_CHANNELS)
+            .map(ChannelInfo::name)
+            .collect::<Vec<&str>>();
+        write!(f, "\nchannels: {}", names.join(", "))?;
+        if self.channels.len() > SHOWN_CHANNELS {
+            write!(f, ", ... ({} more)", self.channels.len() - SHOWN_CHANNELS)?;
+        }
+
+        Ok(())
+    }
 }
 
 pub(crate) trait BinaryFormat: locked::Locked + Sized {
     const BYTES: usize;
 
-    fn from_bytes(bytes: &[u8]) -> Self;
+    fn from_bytes(bytes: &[u8], byte_order: Endianness) -> Self;
 }
 
 impl BinaryFormat for f32 {
     const BYTES: usize = 4;
 
-    fn from_bytes(bytes: &[u8]) -> Self {
+    fn from_bytes(bytes: &[u8], byte_order: Endianness) -> Self {
         let mut rep = [0u8; 4];
         rep.copy_from_slice(bytes);
-        f32::from_le_bytes(rep)
+        match byte_order {
+            Endianness::Little => f32::from_le_bytes(rep),
+            Endianness::Big => f32::from_be_bytes(rep),
+        }
     }
 }
 
 impl BinaryFormat for i16 {
     const BYTES: usize = 2;
 
-    fn from_bytes(bytes: &[u8]) -> Self {
+    fn from_bytes(bytes: &[u8], byte_order: Endianness) -> Self {
         let mut rep = [0u8; 2];
         rep.copy_from_slice(bytes);
-        i16::from_le_bytes(rep)
+        match byte_order {
+            Endianness::Little => i16::from_le_bytes(rep),
+            Endianness::Big => i16::from_be_bytes(rep),
+        }
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinaryFormatType {
     IeeeFloat32,
     Int16,
 }
 
+impl BinaryFormatType {
+    // Number of bytes a single value takes in the EEG data file
+    pub fn bytes(&self) -> usize {
+        match self {
+            BinaryFormatType::IeeeFloat32 => f32::BYTES,
+            BinaryFormatType::Int16 => i16::BYTES,
+        }
+    }
+
+    // Decode a single value as `f32`, regardless of the binary format
+    fn decode(&self, bytes: &[u8], byte_order: Endianness) -> f32 {
+        match self {
+            BinaryFormatType::IeeeFloat32 => f32::from_bytes(bytes, byte_order),
+            BinaryFormatType::Int16 => i16::from_bytes(bytes, byte_order).into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DataOrientation {
+    Multiplexed,
+    Vectorized,
+}
+
 // Information about a channel
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChannelInfo {
     name: String,
     ref_name: String,
@@ -213,57 +426,480 @@ pub struct ChannelInfo {
     unit: String,
 }
 
-impl From<Split<'_, char>> for ChannelInfo {
-    fn from(mut value: Split<char>) -> Self {
-        let name = value.next().unwrap().into();
-        let ref_name = value
-            .next()
-            .map(|s| if s.is_empty() { "Cz" } else { s })
-            .unwrap()
-            .into();
-        let resolution = value
-            .next()
-            .map(|s| {
-                if s.is_empty() {
-                    1.0f64
-                } else {
-                    s.parse::<f64>().unwrap()
-                }
-            })
-            .unwrap();
-        let unit = value
+impl ChannelInfo {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn ref_name(&self) -> &str {
+        &self.ref_name
+    }
+
+    pub(crate) fn set_ref_name(&mut self, ref_name: String) {
+        self.ref_name = ref_name;
+    }
+
+    pub fn resolution(&self) -> f64 {
+        self.resolution
+    }
+
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
+
+    // Factor converting values of the channel's unit to microvolts, `None` for non-voltage units
+    pub fn scale_to_microvolts(&self) -> Option<f64> {
+        match self.unit.as_str() {
+            "V" => Some(1e6),
+            "mV" => Some(1e3),
+            MICROVOLTS => Some(1.0),
+            "nV" => Some(1e-3),
+            _ => None,
+        }
+    }
+}
+
+// Default unit of a channel, spelled with the micro sign (U+00B5) as in the specification
+pub const MICROVOLTS: &str = "\u{b5}V";
+
+// Normalize the spellings of voltage units, e.g. `uV` or `μV` (Greek mu, U+03BC) to `µV`
+fn normalize_unit(unit: &str) -> String {
+    match unit {
+        "uV" | "uv" | "\u{3bc}V" | "\u{3bc}v" | "\u{b5}v" => MICROVOLTS.into(),
+        "v" => "V".into(),
+        "mv" => "mV".into(),
+        "nv" => "nV".into(),
+        unit => unit.into(),
+    }
+}
+
+// Ch<Channel number>=<Name>,<Reference channel name>,<Resolution in "Unit">,<Unit>
+// Fails with the reason if the resolution is not a number
+impl TryFrom<&str> for ChannelInfo {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut fields = Fields::new(value);
+        let name = fields.next().unwrap_or_default();
+        let ref_name = fields.next().unwrap_or_else(|| "Cz".into());
+        let resolution = fields.next_number("resolution")?.unwrap_or(1.0f64);
+        let unit = fields
             .next()
-            .map(|s| if s.is_empty() { "μV" } else { s })
-            .unwrap()
-            .into();
+            .map_or_else(|| MICROVOLTS.into(), |u| normalize_unit(&u));
 
-        Self {
+        Ok(Self {
             name,
             ref_name,
             resolution,
             unit,
-        }
+        })
     }
 }
 
-// Coordinates of a channel
+// Cartesian positions of the channels of a header
 #[derive(Debug)]
+pub struct ChannelPositions {
+    // Positions of the channels (channels x 3), NaN for those missing
+    pub positions: Array2<f64>,
+    // Indices of the channels positioned after the standard 10-20/10-10 template
+    pub templated: Vec<usize>,
+    // Indices of the channels without any position
+    pub missing: Vec<usize>,
+}
+
+// Coordinates of a channel, in spherical form
+// - radius: distance from the center of the head, where 0 marks an unknown position
+// - theta: inclination from the vertex (Cz), in degrees, negative over the left hemisphere
+// - phi: angle from the right (T8) towards the front (Fpz), in degrees
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coordinates {
     radius: f64,
     theta: f64,
     phi: f64,
 }
 
-impl From<Split<'_, char>> for Coordinates {
-    fn from(mut value: Split<'_, char>) -> Self {
-        Coordinates {
-            radius: value.next().map(|s| s.parse::<f64>().unwrap()).unwrap(),
-            theta: value.next().map(|s| s.parse::<f64>().unwrap()).unwrap(),
-            phi: value.next().map(|s| s.parse::<f64>().unwrap()).unwrap(),
+impl Coordinates {
+    pub fn new(radius: f64, theta: f64, phi: f64) -> Self {
+        Coordinates { radius, theta, phi }
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub fn theta(&self) -> f64 {
+        self.theta
+    }
+
+    pub fn phi(&self) -> f64 {
+        self.phi
+    }
+
+    // Cartesian coordinates `[x, y, z]`, with x pointing right, y to the front and z to the vertex
+    pub fn to_cartesian(&self) -> [f64; 3] {
+        let (theta, phi) = (self.theta.to_radians(), self.phi.to_radians());
+
+        [
+            self.radius * theta.sin() * phi.cos(),
+            self.radius * theta.sin() * phi.sin(),
+            self.radius * theta.cos(),
+        ]
+    }
+}
+
+// Ch<Channel number>=<Radius>,<Theta>,<Phi>
+// Fails with the reason if any of them is missing or not a number
+impl TryFrom<&str> for Coordinates {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut fields = Fields::new(value);
+        let mut coordinate = |field: &str| {
+            fields
+                .next_number::<f64>(field)?
+                .ok_or_else(|| format!("missing {field}"))
+        };
+
+        Ok(Coordinates {
+            radius: coordinate("radius")?,
+            theta: coordinate("theta")?,
+            phi: coordinate("phi")?,
+        })
+    }
+}
+
+// Tokenizer over the comma-separated fields of a line of the `[Channel Infos]`, `[Coordinates]` or
+// `[Marker Infos]` sections
+// Whitespace around each field is trimmed, escaped commas (`\1`) are restored and empty or missing
+// trailing fields are `None`
+struct Fields<'a> {
+    fields: std::str::Split<'a, char>,
+}
+
+impl<'a> Fields<'a> {
+    fn new(line: &'a str) -> Self {
+        Fields {
+            fields: line.split(','),
+        }
+    }
+
+    fn next(&mut self) -> Option<String> {
+        self.fields
+            .next()
+            .map(|f| f.trim().replace("\\1", ","))
+            .filter(|f| !f.is_empty())
+    }
+
+    // Next field parsed as a number, failing with the name of the `field` if it is not one
+    fn next_number<T: str::FromStr>(&mut self, field: &str) -> Result<Option<T>, String> {
+        self.next()
+            .map(|value| {
+                value
+                    .parse::<T>()
+                    .map_err(|_| format!("invalid {field} '{value}'"))
+            })
+            .transpose()
+    }
+}
+
+// Struct containing all of the markers provided in the marker file referenced by a `Header`
+//
+// sub-<subject>[_ses-<session>]_task-<task>[_acq-<acquisition>][_run-<run>]_eeg.vmrk
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Marker {
+    // Name of the EEG data file the markers refer to
+    pub data_file: String,
+    // Stores every marker, provided in the `[Marker Infos]` section, in the order of their number
+    pub markers: Vec<MarkerInfo>,
+}
+
+impl Marker {
+    // Load the marker file referenced by the `header` of a BIDS-compliant data recording
+    // The header not referencing any marker file, or the marker file not existing, is not an error,
+    // as marker files are optional in practice, and results in no markers at all
+    // Fails with `ReadError::InvalidMarkerFile` if the marker file cannot be read or parsed, or holds
+    // a marker line without a position or of invalid numbers
+    pub fn load<P: AsRef<Path>>(
+        path: &BIDSPath<P>,
+        header: &Header,
+    ) -> Result<Option<Marker>, ReadError> {
+        let Some(marker_file) = &header.marker_file else {
+            return Ok(None);
+        };
+        let invalid = |reason: String| ReadError::InvalidMarkerFile {
+            file: marker_file.clone(),
+            reason,
+        };
+
+        let mut buf = match fs::read_to_string(path.directory().join(marker_file)) {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(invalid(e.to_string())),
+        };
+        // Skip the first line (identification line)
+        buf = buf.lines().skip(1).collect::<Vec<&str>>().join("\n");
+
+        let file =
+            ini::Ini::load_from_str_opt(&buf, ini_options()).map_err(|e| invalid(e.to_string()))?;
+
+        let common_infos = file
+            .section(Some("Common Infos"))
+            .ok_or_else(|| invalid("missing [Common Infos] section".into()))?;
+        let marker_infos = file.section(Some("Marker Infos"));
+
+        let data_file = common_infos
+            .get("DataFile")
+            .ok_or_else(|| invalid("missing DataFile".into()))?
+            .into();
+        let markers = marker_infos.map_or_else(
+            || Ok(Vec::new()),
+            |markers| {
+                markers
+                    .iter()
+                    .map(|(key, v)| {
+                        MarkerInfo::try_from(v).map_err(|e| invalid(format!("{key}: {e}")))
+                    })
+                    .collect::<Result<Vec<MarkerInfo>, ReadError>>()
+            },
+        )?;
+
+        Ok(Some(Marker { data_file, markers }))
+    }
+}
+
+// Information about a marker
+//
+// Mk<Marker number>=<Type>,<Description>,<Position in data points>,<Size in data points>,
+// <Channel number (0 = marker is related to all channels)>[,<Date (YYYYMMDDhhmmssuuuuuu)>]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarkerInfo {
+    pub marker_type: String,
+    pub description: String,
+    // One-based position of the marker in data points
+    pub position: usize,
+    // Number of data points the marker spans
+    pub size: usize,
+    // One-based channel number the marker refers to, 0 meaning all channels
+    pub channel: usize,
+    // Only provided by `New Segment` markers
+    pub date: Option<Date>,
+}
+
+impl MarkerInfo {
+    // Zero-based index of the sample the marker points to
+    pub fn sample(&self) -> usize {
+        self.position.saturating_sub(1)
+    }
+
+    // Time the marker points to, in seconds from the first sample
+    pub fn onset_secs(&self, header: &Header) -> f64 {
+        header.duration_secs(self.sample())
+    }
+}
+
+// Fails with the reason if the position is missing, or the position, size or channel is not a
+// number
+impl TryFrom<&str> for MarkerInfo {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut fields = Fields::new(value);
+        let marker_type = fields.next().unwrap_or_default();
+        let description = fields.next().unwrap_or_default();
+        let position = fields
+            .next_number("position")?
+            .ok_or_else(|| "missing position".to_string())?;
+        let size = fields.next_number("size")?.unwrap_or(1);
+        let channel = fields.next_number("channel")?.unwrap_or(0);
+        // A malformed date is left out rather than failing the whole marker
+        let date = fields.next().and_then(|s| Date::parse(&s).ok().flatten());
+
+        Ok(Self {
+            marker_type,
+            description,
+            position,
+            size,
+            channel,
+            date,
+        })
+    }
+}
+
+// Date of a `New Segment` marker
+//
+// YYYYMMDDhhmmssuuuuuu
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub microsecond: u32,
+}
+
+impl Date {
+    // Parse the date of a marker, where an empty date, or one of only zeros, is no date at all
+    pub fn parse(value: &str) -> Result<Option<Date>, DateError> {
+        let value = value.trim();
+        if value.bytes().all(|b| b == b'0') {
+            return Ok(None);
+        }
+
+        Date::try_from(value).map(Some)
+    }
+
+    // Microseconds elapsed since the Unix epoch (1970-01-01 00:00:00), the date being taken as UTC
+    pub fn to_unix_micros(&self) -> i64 {
+        // Days since the epoch of the proleptic Gregorian calendar date, shifting the year to
+        // start in March so that leap days fall at its end
+        let (month, year) = match self.month {
+            1 | 2 => (self.month as i64 + 9, self.year as i64 - 1),
+            _ => (self.month as i64 - 3, self.year as i64),
+        };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * month + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146097 + day_of_era - 719468;
+
+        let seconds =
+            days * 86400 + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64;
+
+        seconds * 1_000_000 + self.microsecond as i64
+    }
+}
+
+// YYYYMMDDhhmmssuuuuuu
+impl TryFrom<&str> for Date {
+    type Error = DateError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if value.len() != 20 {
+            return Err(DateError::Length(value.len()));
+        }
+        if !value.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(DateError::NotNumeric(value.into()));
+        }
+        let field = |range: Range<usize>| value[range].parse::<u32>().unwrap();
+
+        let date = Date {
+            year: field(0..4) as u16,
+            month: field(4..6) as u8,
+            day: field(6..8) as u8,
+            hour: field(8..10) as u8,
+            minute: field(10..12) as u8,
+            second: field(12..14) as u8,
+            microsecond: field(14..20),
+        };
+
+        let leap = date.year.is_multiple_of(4)
+            && (!date.year.is_multiple_of(100) || date.year.is_multiple_of(400));
+        let days_in_month = match date.month {
+            2 if leap => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        };
+        let checks = [
+            ("month", date.month, 1..=12),
+            ("day", date.day, 1..=days_in_month),
+            ("hour", date.hour, 0..=23),
+            ("minute", date.minute, 0..=59),
+            ("second", date.second, 0..=59),
+        ];
+        for (field, value, range) in checks {
+            if !range.contains(&value) {
+                return Err(DateError::OutOfRange { field, value });
+            }
+        }
+
+        Ok(date)
+    }
+}
+
+// Errors that can occur while parsing the date of a marker
+#[derive(Debug)]
+pub enum DateError {
+    // The date is not exactly 20 bytes long
+    Length(usize),
+    // The date contains characters other than digits
+    NotNumeric(String),
+    // A field of the date is outside its valid range
+    OutOfRange { field: &'static str, value: u8 },
+}
+
+impl Display for DateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DateError::Length(len) => {
+                write!(
+                    f,
+                    "date is {len} bytes long instead of 20 (YYYYMMDDhhmmssuuuuuu)"
+                )
+            }
+            DateError::NotNumeric(value) => write!(f, "date '{value}' is not only made of digits"),
+            DateError::OutOfRange { field, value } => write!(f, "{field} {value} is out of range"),
         }
     }
 }
 
+impl Error for DateError {}
+
+// How thoroughly the size of a data file is checked against its header, when loading it
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Validation {
+    // Fail with `ReadError::DataSizeMismatch` or `ReadError::SegmentMismatch`
+    #[default]
+    Strict,
+    // Load the whole samples of the data file, dropping any trailing bytes
+    Lenient,
+}
+
+// What to load of a data file, so that only the requested samples and channels are ever read
+#[derive(Clone, Debug, Default)]
+pub struct LoadOptions {
+    // Zero-based range of samples, clamped to those of the data file, all of them by default
+    samples: Option<Range<usize>>,
+    // Zero-based indices of the channels, in the order they are loaded, all of them by default
+    channels: Option<Vec<usize>>,
+    validation: Validation,
+}
+
+impl LoadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn samples(mut self, samples: Range<usize>) -> Self {
+        self.samples = Some(samples);
+        self
+    }
+
+    pub fn channels(mut self, channels: &[usize]) -> Self {
+        self.channels = Some(channels.to_vec());
+        self
+    }
+
+    pub fn validation(mut self, validation: Validation) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    pub(super) fn sample_range(&self) -> Option<&Range<usize>> {
+        self.samples.as_ref()
+    }
+
+    pub(super) fn channel_indices(&self) -> Option<&[usize]> {
+        self.channels.as_deref()
+    }
+}
+
 // The formated data associated with a header
 //
 // sub-<subject>[_ses-<session>]_task-<task>[_acq-<acquisition>][_run-<run>]_eeg.eeg
@@ -275,28 +911,1337 @@ pub struct Data<T: BinaryFormat> {
 
 #[allow(private_bounds)]
 impl<T: BinaryFormat + Clone> Data<T> {
-    pub fn load<P: AsRef<Path>>(path: &BIDSPath<P>, header: &Header) -> Data<T> {
-        // Load the raw data
-        let rawdata = fs::read(path.path.join(header.data_file.as_str())).unwrap();
+    // Load the data file associated with a header
+    // With `Validation::Strict`, a data file not holding a whole number of samples (or segments,
+    // for segmented data) is rejected, while `Validation::Lenient` truncates it to its whole samples
+    pub fn load<P: AsRef<Path>>(
+        path: &BIDSPath<P>,
+        header: &Header,
+        validation: Validation,
+    ) -> Result<Data<T>, ReadError> {
+        Self::load_with(path, header, &LoadOptions::new().validation(validation))
+    }
+
+    // Load only the samples and channels of the data file selected by `options`
+    // Multiplexed data files are read from the first to the last selected sample at once, while
+    // vectorized data files are read channel by channel, only ever for the selected samples
+    // Fails with `ReadError::InvalidDataFile` if the data file cannot be opened or read
+    pub fn load_with<P: AsRef<Path>>(
+        path: &BIDSPath<P>,
+        header: &Header,
+        options: &LoadOptions,
+    ) -> Result<Data<T>, ReadError> {
+        let invalid = |e: std::io::Error| ReadError::InvalidDataFile {
+            file: header.data_file.clone(),
+            reason: e.to_string(),
+        };
+        let mut file =
+            fs::File::open(path.directory().join(header.data_file.as_str())).map_err(invalid)?;
+        let file_bytes = file.metadata().map_err(invalid)?.len() as usize;
         let num_channels = header.num_channels as usize;
-        // Cut the raw data into chunks based on the binary format of the dataset's data type
-        let chunks = rawdata.chunks_exact(T::BYTES);
-        // Actual number of data points is `effective_data_points` + 1
-        let effective_data_points = chunks.len() / num_channels;
+        let num_samples = Self::num_samples(file_bytes, header, options.validation)?;
+
+        let samples = options.samples.clone().map_or(0..num_samples, |samples| {
+            let start = samples.start.min(num_samples);
+            start..samples.end.clamp(start, num_samples)
+        });
+        let channels = options
+            .channels
+            .clone()
+            .unwrap_or_else(|| (0..num_channels).collect());
+        assert!(
+            channels.iter().all(|&c| c < num_channels),
+            "Channel index out of bounds !"
+        );
+        let decode = |bytes: &[u8]| {
+            bytes
+                .chunks_exact(T::BYTES)
+                .map(|c| T::from_bytes(c, header.byte_order))
+                .collect::<Vec<T>>()
+        };
+
         // Format the raw data according to the binary representation
         // Data orientation is N x M, where N is the number of channels and M is number of samples
-        let data = Array2::from_shape_vec(
-            (effective_data_points, num_channels),
-            chunks.map(|c| T::from_bytes(c)).collect::<Vec<T>>(),
-        )
-        .unwrap()
-        .t()
-        .to_owned();
+        let data = match header.data_orientation {
+            DataOrientation::Multiplexed => {
+                let mut buf = vec![0; samples.len() * num_channels * T::BYTES];
+                file.seek(SeekFrom::Start(
+                    (samples.start * num_channels * T::BYTES) as u64,
+                ))
+                .map_err(invalid)?;
+                file.read_exact(&mut buf).map_err(invalid)?;
+
+                let data =
+                    Array2::from_shape_vec((samples.len(), num_channels), decode(&buf)).unwrap();
+                match &options.channels {
+                    Some(_) => data.select(Axis(1), &channels),
+                    None => data,
+                }
+                .reversed_axes()
+            }
+            DataOrientation::Vectorized => {
+                let mut buf = vec![0; samples.len() * T::BYTES];
+                let mut values = Vec::with_capacity(channels.len() * samples.len());
+                for &channel in &channels {
+                    file.seek(SeekFrom::Start(
+                        ((channel * num_samples + samples.start) * T::BYTES) as u64,
+                    ))
+                    .map_err(invalid)?;
+                    file.read_exact(&mut buf).map_err(invalid)?;
+                    values.extend(decode(&buf));
+                }
+
+                Array2::from_shape_vec((channels.len(), samples.len()), values).unwrap()
+            }
+        };
+
+        Ok(Data { data })
+    }
+
+    // Number of samples of a data file of `file_bytes` bytes, validated as per `validation`
+    // Trailing bytes not amounting to a whole sample are never read
+    fn num_samples(
+        file_bytes: usize,
+        header: &Header,
+        validation: Validation,
+    ) -> Result<usize, ReadError> {
+        let num_channels = header.num_channels as usize;
+        let num_samples = file_bytes / T::BYTES / num_channels;
+        if let Validation::Strict = validation {
+            if !file_bytes.is_multiple_of(num_channels * T::BYTES) {
+                return Err(ReadError::DataSizeMismatch {
+                    file_bytes,
+                    num_channels,
+                    value_bytes: T::BYTES,
+                });
+            }
+            let segment_data_points = header.segment_data_points as usize;
+            if segment_data_points > 0 && !num_samples.is_multiple_of(segment_data_points) {
+                return Err(ReadError::SegmentMismatch {
+                    num_samples,
+                    segment_data_points,
+                });
+            }
+        }
 
-        Data { data }
+        Ok(num_samples)
     }
 
     pub fn channel(&self, index: usize) -> ArrayView1<'_, T> {
         self.data.row(index)
     }
+
+    // View of the whole data, oriented channels x samples
+    pub fn view(&self) -> ArrayView2<'_, T> {
+        self.data.view()
+    }
+
+    // Data of a segmented data set, cut into its segments (segments x channels x samples)
+    // Each segment spans `SegmentDataPoints` samples, or, when the header does not provide it, the
+    // distance between the `New Segment` markers of `marker`, which must then be equally spaced
+    // The `New Segment` markers, if any, must lie at the start of each segment
+    pub fn segments(&self, header: &Header, marker: &Marker) -> Result<Array3<T>, ReadError> {
+        let (num_channels, num_samples) = self.data.dim();
+        let starts = marker
+            .markers
+            .iter()
+            .filter(|m| m.marker_type == "New Segment")
+            .map(MarkerInfo::sample)
+            .collect::<Vec<usize>>();
+        let segment_data_points = match header.segment_data_points {
+            0 if starts.len() > 1 => starts[1] - starts[0],
+            0 => return Err(ReadError::NotSegmented),
+            n => n as usize,
+        };
+        if segment_data_points == 0 || !num_samples.is_multiple_of(segment_data_points) {
+            return Err(ReadError::SegmentMismatch {
+                num_samples,
+                segment_data_points,
+            });
+        }
+
+        let num_segments = num_samples / segment_data_points;
+        if !starts.is_empty() {
+            if starts.len() != num_segments {
+                return Err(ReadError::SegmentCountMismatch {
+                    expected: num_segments,
+                    found: starts.len(),
+                });
+            }
+            for (segment, &start) in starts.iter().enumerate() {
+                if start != segment * segment_data_points {
+                    return Err(ReadError::SegmentBoundaryMismatch {
+                        segment,
+                        expected: segment * segment_data_points,
+                        found: start,
+                    });
+                }
+            }
+        }
+
+        // channels x (segments x samples) -> channels x segments x samples
+        //                                 -> segments x channels x samples
+        let segments = self
+            .data
+            .to_shape((num_channels, num_segments, segment_data_points))
+            .unwrap()
+            .permuted_axes([1, 0, 2])
+            .as_standard_layout()
+            .into_owned();
+
+        Ok(segments)
+    }
+
+    // Data restricted to the channels at `indices`, in the order of `indices`
+    pub fn select_channels(&self, indices: &[usize]) -> Data<T> {
+        Data {
+            data: self.data.select(Axis(0), indices),
+        }
+    }
+
+    // Memory-map the data file associated with a header instead of reading it whole
+    // Samples are only decoded when requested through the returned `MmapData`
+    // The data file is validated as by `Data::load`, failing with `ReadError::InvalidDataFile` if it
+    // cannot be opened or mapped
+    pub fn open_mmap<P: AsRef<Path>>(
+        path: &BIDSPath<P>,
+        header: &Header,
+        validation: Validation,
+    ) -> Result<MmapData<T>, ReadError> {
+        let invalid = |e: std::io::Error| ReadError::InvalidDataFile {
+            file: header.data_file.clone(),
+            reason: e.to_string(),
+        };
+        let file =
+            fs::File::open(path.directory().join(header.data_file.as_str())).map_err(invalid)?;
+        // SAFETY: the data file is only ever read, modifying it while mapped is undefined behaviour
+        let mmap = unsafe { Mmap::map(&file) }.map_err(invalid)?;
+        let num_channels = header.num_channels as usize;
+        let num_samples = Self::num_samples(mmap.len(), header, validation)?;
+
+        Ok(MmapData {
+            mmap,
+            data_orientation: header.data_orientation,
+            byte_order: header.byte_order,
+            num_channels,
+            num_samples,
+            format: PhantomData,
+        })
+    }
+}
+
+#[allow(private_bounds)]
+impl<T: BinaryFormat + Clone + Into<f32>> Data<T> {
+    // Data converted to physical values, by multiplying each channel with its resolution
+    pub fn scaled(&self, header: &Header) -> Array2<f32> {
+        let mut data = self.data.mapv(Into::into);
+        scale(&mut data, &header.channels);
+
+        data
+    }
+
+    // Data converted to physical values in microvolts, so that channels recorded in different
+    // voltage units are consistent
+    // Channels of non-voltage units are only multiplied with their resolution
+    pub fn to_microvolts(&self, header: &Header) -> Array2<f32> {
+        let mut data = self.scaled(header);
+        for (mut row, channel) in data.axis_iter_mut(Axis(0)).zip(&header.channels) {
+            row *= channel.scale_to_microvolts().unwrap_or(1.0) as f32;
+        }
+
+        data
+    }
+}
+
+impl Data<i16> {
+    // View of the data converted to physical values lazily, as per the resolutions of the `header`
+    pub fn scaled_view(&self, header: &Header) -> ScaledView<'_> {
+        ScaledView::new(self, header)
+    }
+}
+
+// Int16 data along with the resolutions of its channels, converted to physical values on each
+// access rather than at once, so as not to hold an `f32` copy twice the size of the data
+// Values are those of `Data::scaled`, to the bit
+pub struct ScaledView<'a> {
+    data: ArrayView2<'a, i16>,
+    resolutions: Vec<f32>,
+}
+
+impl<'a> ScaledView<'a> {
+    // Panics if the `header` does not describe as many channels as the `data` holds
+    pub fn new(data: &'a Data<i16>, header: &Header) -> Self {
+        assert_eq!(
+            data.data.nrows(),
+            header.channels.len(),
+            "One channel of the header per channel of the data expected !"
+        );
+
+        ScaledView {
+            data: data.view(),
+            resolutions: header
+                .channels
+                .iter()
+                .map(|channel| channel.resolution as f32)
+                .collect(),
+        }
+    }
+
+    pub fn num_channels(&self) -> usize {
+        self.data.nrows()
+    }
+
+    pub fn num_samples(&self) -> usize {
+        self.data.ncols()
+    }
+
+    // Physical value of the channel at `channel` at the `sample`
+    pub fn get(&self, channel: usize, sample: usize) -> f32 {
+        f32::from(self.data[[channel, sample]]) * self.resolutions[channel]
+    }
+
+    // Physical values of the channel at `channel`, sample by sample
+    pub fn channel_iter(&self, channel: usize) -> impl Iterator<Item = f32> + '_ {
+        let resolution = self.resolutions[channel];
+        self.data
+            .row(channel)
+            .into_iter()
+            .map(move |&value| f32::from(value) * resolution)
+    }
+
+    // Physical values of every channel within the `samples` only (channels x samples)
+    pub fn to_array_range(&self, samples: Range<usize>) -> Array2<f32> {
+        let mut data = self.data.slice(s![.., samples]).mapv(f32::from);
+        for (mut row, &resolution) in data.axis_iter_mut(Axis(0)).zip(&self.resolutions) {
+            row *= resolution;
+        }
+
+        data
+    }
+}
+
+// The memory-mapped data associated with a header, decoded lazily on each access
+//
+// sub-<subject>[_ses-<session>]_task-<task>[_acq-<acquisition>][_run-<run>]_eeg.eeg
+#[allow(private_bounds)]
+pub struct MmapData<T: BinaryFormat> {
+    mmap: Mmap,
+    data_orientation: DataOrientation,
+    byte_order: Endianness,
+    num_channels: usize,
+    num_samples: usize,
+    format: PhantomData<T>,
+}
+
+#[allow(private_bounds)]
+impl<T: BinaryFormat + Clone> MmapData<T> {
+    pub fn num_channels(&self) -> usize {
+        self.num_channels
+    }
+
+    pub fn num_samples(&self) -> usize {
+        self.num_samples
+    }
+
+    // Decode the `samples` of the channel at `index`
+    pub fn channel(&self, index: usize, samples: Range<usize>) -> Array1<T> {
+        self.slice(index..index + 1, samples).remove_axis(Axis(0))
+    }
+
+    // Decode the `samples` of the `channels`, oriented channels x samples
+    pub fn slice(&self, channels: Range<usize>, samples: Range<usize>) -> Array2<T> {
+        assert!(channels.end <= self.num_channels && samples.end <= self.num_samples);
+
+        // Index of the value of a channel at a sample, as laid out in the data file
+        let index = |c: usize, s: usize| match self.data_orientation {
+            DataOrientation::Multiplexed => s * self.num_channels + c,
+            DataOrientation::Vectorized => c * self.num_samples + s,
+        };
+
+        Array2::from_shape_fn((channels.len(), samples.len()), |(c, s)| {
+            let offset = index(channels.start + c, samples.start + s) * T::BYTES;
+            T::from_bytes(&self.mmap[offset..offset + T::BYTES], self.byte_order)
+        })
+    }
+}
+
+#[allow(private_bounds)]
+impl<T: BinaryFormat + Clone + Into<f32>> MmapData<T> {
+    // Decode the `samples` of the `channels` and convert them to physical values, by multiplying
+    // each channel with its resolution
+    pub fn slice_scaled(
+        &self,
+        header: &Header,
+        channels: Range<usize>,
+        samples: Range<usize>,
+    ) -> Array2<f32> {
+        let mut data = self.slice(channels.clone(), samples).mapv(Into::into);
+        scale(&mut data, &header.channels[channels]);
+
+        data
+    }
+}
+
+// Incremental reader over the data associated with a header, yielding successive blocks of
+// samples (channels x samples) decoded as `f32`, the last one possibly being shorter
+// A block failing to be read, e.g. of a data file truncated since it was opened, is yielded as a
+// `ReadError::InvalidDataFile`, after which the reader is exhausted
+//
+// Multiplexed data is read sequentially, while vectorized data requires a seek per channel for
+// every block, which discards the read buffer
+pub struct DataReader {
+    // Name of the EEG data file, for the errors of its blocks
+    data_file: String,
+    reader: BufReader<fs::File>,
+    binary_format: BinaryFormatType,
+    data_orientation: DataOrientation,
+    byte_order: Endianness,
+    num_channels: usize,
+    num_samples: usize,
+    block_size: usize,
+    // Index of the first sample of the next block
+    position: usize,
+}
+
+impl DataReader {
+    // Open the data file associated with a header, to be read in blocks of `block_size` samples
+    // Fails with `ReadError::InvalidDataFile` if the data file cannot be opened
+    // Panics if `block_size` is zero
+    pub fn open<P: AsRef<Path>>(
+        path: &BIDSPath<P>,
+        header: &Header,
+        block_size: usize,
+    ) -> Result<Self, ReadError> {
+        assert!(block_size > 0, "Block size must be positive !");
+
+        let invalid = |e: std::io::Error| ReadError::InvalidDataFile {
+            file: header.data_file.clone(),
+            reason: e.to_string(),
+        };
+        let file =
+            fs::File::open(path.directory().join(header.data_file.as_str())).map_err(invalid)?;
+        let num_channels = header.num_channels as usize;
+        let num_samples = file.metadata().map_err(invalid)?.len() as usize
+            / header.binary_format.bytes()
+            / num_channels;
+
+        Ok(DataReader {
+            data_file: header.data_file.clone(),
+            reader: BufReader::new(file),
+            binary_format: header.binary_format,
+            data_orientation: header.data_orientation,
+            byte_order: header.byte_order,
+            num_channels,
+            num_samples,
+            block_size,
+            position: 0,
+        })
+    }
+
+    pub fn num_samples(&self) -> usize {
+        self.num_samples
+    }
+
+    // Next block of samples, as read from the current position
+    fn read_block(&mut self, len: usize) -> std::io::Result<Array2<f32>> {
+        let bytes = self.binary_format.bytes();
+        let mut buf = vec![0u8; len * self.num_channels * bytes];
+
+        let block = match self.data_orientation {
+            DataOrientation::Multiplexed => {
+                self.reader.read_exact(&mut buf)?;
+                let values = buf
+                    .chunks_exact(bytes)
+                    .map(|c| self.binary_format.decode(c, self.byte_order));
+
+                Array2::from_shape_vec((len, self.num_channels), values.collect())
+                    .unwrap()
+                    .t()
+                    .to_owned()
+            }
+            DataOrientation::Vectorized => {
+                for (channel, chunk) in buf.chunks_exact_mut(len * bytes).enumerate() {
+                    let offset = (channel * self.num_samples + self.position) * bytes;
+                    self.reader.seek(SeekFrom::Start(offset as u64))?;
+                    self.reader.read_exact(chunk)?;
+                }
+                let values = buf
+                    .chunks_exact(bytes)
+                    .map(|c| self.binary_format.decode(c, self.byte_order));
+
+                Array2::from_shape_vec((self.num_channels, len), values.collect()).unwrap()
+            }
+        };
+
+        Ok(block)
+    }
+}
+
+impl Iterator for DataReader {
+    type Item = Result<Array2<f32>, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.num_samples {
+            return None;
+        }
+
+        let len = self.block_size.min(self.num_samples - self.position);
+        match self.read_block(len) {
+            Ok(block) => {
+                self.position += len;
+                Some(Ok(block))
+            }
+            Err(e) => {
+                self.position = self.num_samples;
+                Some(Err(ReadError::InvalidDataFile {
+                    file: self.data_file.clone(),
+                    reason: e.to_string(),
+                }))
+            }
+        }
+    }
+}
+
+// Multiply each channel (row) of the `data` with the resolution of the matching `channels` entry
+fn scale(data: &mut Array2<f32>, channels: &[ChannelInfo]) {
+    for (mut row, channel) in data.axis_iter_mut(Axis(0)).zip(channels) {
+        row *= channel.resolution as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{s, Array2};
+
+    use super::*;
+    use std::path::PathBuf;
+
+    use crate::read::fixtures::{Format, Recording, TempDir};
+    use crate::{
+        band_power::{band_power_topography, BandPowerMode},
+        filter::FIRFilter,
+    };
+
+    // Four channels of 50 samples, the value of each being its channel and sample indices
+    fn values() -> Array2<f32> {
+        Array2::from_shape_fn((4, 50), |(c, t)| c as f32 * 100.0 + t as f32 - 20.0)
+    }
+
+    #[test]
+    fn mmap_slices_match_the_eager_loader() {
+        for vectorized in [false, true] {
+            let root = TempDir::new("mmap");
+            let mut recording = Recording::new(&["Fp1", "Fp2", "Cz", "Pz"], values());
+            recording.vectorized = vectorized;
+            let path = recording.write(&root);
+            let header = Header::load(&path).unwrap();
+
+            let eager = Data::<f32>::load(&path, &header, Validation::Strict).unwrap();
+            let mmap = Data::<f32>::open_mmap(&path, &header, Validation::Strict).unwrap();
+            assert_eq!((mmap.num_channels(), mmap.num_samples()), (4, 50));
+
+            for (channels, samples) in [(0..2, 0..10), (2..4, 10..30), (1..3, 45..50)] {
+                assert_eq!(
+                    mmap.slice(channels.clone(), samples.clone()),
+                    eager.view().slice(s![channels, samples])
+                );
+            }
+            assert_eq!(mmap.channel(3, 5..8), eager.channel(3).slice(s![5..8]));
+        }
+    }
+
+    #[test]
+    fn invalid_mapped_data_files_fail() {
+        let root = TempDir::new("mmap-invalid");
+        let path = Recording::new(&["Fp1", "Fp2", "Cz", "Pz"], values()).write(&root);
+        let header = Header::load(&path).unwrap();
+        let data_file = path.filename("eeg", "eeg");
+
+        // A value short of the last sample
+        let mut bytes = fs::read(&data_file).unwrap();
+        bytes.truncate(bytes.len() - 4);
+        fs::write(&data_file, bytes).unwrap();
+        assert!(matches!(
+            Data::<f32>::open_mmap(&path, &header, Validation::Strict),
+            Err(ReadError::DataSizeMismatch {
+                file_bytes: 796,
+                num_channels: 4,
+                value_bytes: 4
+            })
+        ));
+        let lenient = Data::<f32>::open_mmap(&path, &header, Validation::Lenient).unwrap();
+        assert_eq!(lenient.num_samples(), 49);
+        assert_eq!(lenient.slice(0..4, 0..49), values().slice(s![.., ..49]));
+
+        fs::remove_file(&data_file).unwrap();
+        assert!(matches!(
+            Data::<f32>::open_mmap(&path, &header, Validation::Strict),
+            Err(ReadError::InvalidDataFile { file, .. }) if file == "sub-01_task-test_eeg.eeg"
+        ));
+    }
+
+    #[test]
+    fn streamed_blocks_match_the_eager_loader() {
+        for vectorized in [false, true] {
+            let root = TempDir::new("stream");
+            let mut recording = Recording::new(&["Fp1", "Fp2", "Cz", "Pz"], values());
+            recording.vectorized = vectorized;
+            let path = recording.write(&root);
+            let header = Header::load(&path).unwrap();
+
+            let eager = Data::<f32>::load(&path, &header, Validation::Strict).unwrap();
+            let reader = DataReader::open(&path, &header, 16).unwrap();
+            assert_eq!(reader.num_samples(), 50);
+
+            let blocks = reader
+                .collect::<Result<Vec<Array2<f32>>, ReadError>>()
+                .unwrap();
+            assert_eq!(
+                blocks.iter().map(|b| b.ncols()).collect::<Vec<usize>>(),
+                vec![16, 16, 16, 2]
+            );
+            let views = blocks.iter().map(|b| b.view()).collect::<Vec<_>>();
+            assert_eq!(ndarray::concatenate(Axis(1), &views).unwrap(), eager.view());
+        }
+    }
+
+    #[test]
+    fn truncated_streams_fail_instead_of_panicking() {
+        for (vectorized, blocks) in [(false, 1), (true, 0)] {
+            let root = TempDir::new("stream-truncated");
+            let mut recording = Recording::new(&["Fp1", "Fp2", "Cz", "Pz"], values());
+            recording.vectorized = vectorized;
+            let path = recording.write(&root);
+            let header = Header::load(&path).unwrap();
+
+            let mut reader = DataReader::open(&path, &header, 16).unwrap();
+            // Truncated after opening, within the second block of multiplexed data and the third
+            // channel of vectorized data
+            fs::File::options()
+                .write(true)
+                .open(path.filename("eeg", "eeg"))
+                .unwrap()
+                .set_len(300)
+                .unwrap();
+            for _ in 0..blocks {
+                assert!(reader.next().unwrap().is_ok());
+            }
+            assert!(matches!(
+                reader.next(),
+                Some(Err(ReadError::InvalidDataFile { file, .. }))
+                    if file == "sub-01_task-test_eeg.eeg"
+            ));
+            assert!(reader.next().is_none());
+        }
+
+        let root = TempDir::new("stream-missing");
+        let path = Recording::new(&["Cz"], Array2::zeros((1, 4))).write(&root);
+        let header = Header::load(&path).unwrap();
+        fs::remove_file(path.filename("eeg", "eeg")).unwrap();
+        assert!(matches!(
+            DataReader::open(&path, &header, 16),
+            Err(ReadError::InvalidDataFile { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_headers_fail_instead_of_panicking() {
+        let base = Recording::new(&["Cz"], Array2::zeros((1, 4))).header();
+        for (header, expected) in [
+            (
+                base.replace("[Binary Infos]", "[Other]"),
+                "missing [Binary Infos] section",
+            ),
+            (
+                base.replace("[Channel Infos]", "[Other]"),
+                "missing [Channel Infos] section",
+            ),
+            (base.replace("DataFile=", "Data="), "missing DataFile"),
+            (
+                base.replace("NumberOfChannels=1", "NumberOfChannels=one"),
+                "invalid NumberOfChannels 'one'",
+            ),
+            (
+                base.replace("SamplingInterval=2000\n", ""),
+                "missing SamplingInterval",
+            ),
+            (
+                base.replace("MULTIPLEXED", "DIAGONAL"),
+                "invalid DataOrientation 'DIAGONAL'",
+            ),
+            (
+                base.replace("IEEE_FLOAT_32", "INT_32"),
+                "invalid BinaryFormat 'INT_32'",
+            ),
+            (
+                base.replace("NumberOfChannels=1", "NumberOfChannels=0")
+                    .replace("Ch1=Cz,,1,µV\n", ""),
+                "invalid NumberOfChannels '0'",
+            ),
+            (
+                base.replace("Ch1=Cz,,1,µV", "Ch1=Cz,,abc,uV"),
+                "Ch1: invalid resolution 'abc'",
+            ),
+            (
+                format!("{base}\n[Coordinates]\nCh1=1,90\n"),
+                "Ch1: missing phi",
+            ),
+            (
+                format!("{base}\n[Coordinates]\nCh1=1,north,0\n"),
+                "Ch1: invalid theta 'north'",
+            ),
+        ] {
+            let root = TempDir::new("header-invalid");
+            let path = Recording::new(&["Cz"], Array2::zeros((1, 4))).write(&root);
+            fs::write(path.filename("eeg", "vhdr"), header).unwrap();
+
+            match Header::load(&path) {
+                Err(ReadError::InvalidHeader { file, reason }) => assert_eq!(
+                    (file.as_str(), reason.as_str()),
+                    ("sub-01_task-test_eeg.vhdr", expected)
+                ),
+                other => panic!("Unexpected result {other:?} !"),
+            }
+        }
+    }
+
+    #[test]
+    fn big_endian_data_loads_as_little_endian_data() {
+        let data = values();
+        for vectorized in [false, true] {
+            let load = |format: Format, big_endian: bool| {
+                let root = TempDir::new("byte-order");
+                let mut recording = Recording::new(&["Fp1", "Fp2", "Cz", "Pz"], data.clone());
+                recording.format = format;
+                recording.vectorized = vectorized;
+                recording.big_endian = big_endian;
+                let path = recording.write(&root);
+                let header = Header::load(&path).unwrap();
+                assert_eq!(
+                    header.byte_order,
+                    if big_endian {
+                        Endianness::Big
+                    } else {
+                        Endianness::Little
+                    }
+                );
+
+                match format {
+                    Format::Float32 => Data::<f32>::load(&path, &header, Validation::Strict)
+                        .unwrap()
+                        .view()
+                        .to_owned(),
+                    Format::Int16 => Data::<i16>::load(&path, &header, Validation::Strict)
+                        .unwrap()
+                        .scaled(&header),
+                }
+            };
+
+            for format in [Format::Float32, Format::Int16] {
+                let little = load(format, false);
+                assert_eq!(little, data);
+                assert_eq!(load(format, true), little);
+            }
+        }
+    }
+
+    #[test]
+    fn channel_fields_are_trimmed_and_unescaped() {
+        let root = TempDir::new("fields");
+        let mut recording = Recording::new(&["Fp1", "Fp2", "C3"], Array2::zeros((3, 2)));
+        recording.channels = vec![
+            " Fp1\\1a , Cz\\1Fz , 0.5 , µV ".into(),
+            "Fp2,,,".into(),
+            "C3 , , 2 ,".into(),
+        ];
+        let path = recording.write(&root);
+        let header = Header::load(&path).unwrap();
+
+        assert_eq!(
+            header
+                .channels
+                .iter()
+                .map(|c| (c.name(), c.ref_name(), c.resolution(), c.unit()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Fp1,a", "Cz,Fz", 0.5, MICROVOLTS),
+                // Empty trailing fields take their defaults
+                ("Fp2", "Cz", 1.0, MICROVOLTS),
+                ("C3", "Cz", 2.0, MICROVOLTS),
+            ]
+        );
+    }
+
+    #[test]
+    fn mixed_units_convert_to_microvolts() {
+        let root = TempDir::new("units");
+        let mut recording =
+            Recording::new(&["Fp1", "Fp2", "Cz", "Pz", "GSR"], Array2::ones((5, 3)));
+        recording.channels = vec![
+            "Fp1,,0.5,mV".into(),
+            "Fp2,,0.1,uV".into(),
+            "Cz,,2,\u{3bc}V".into(),
+            "Pz".into(),
+            "GSR,,0.25,S".into(),
+        ];
+        let path = recording.write(&root);
+        let header = Header::load(&path).unwrap();
+
+        assert_eq!(
+            header
+                .channels
+                .iter()
+                .map(ChannelInfo::unit)
+                .collect::<Vec<&str>>(),
+            vec!["mV", MICROVOLTS, MICROVOLTS, MICROVOLTS, "S"]
+        );
+        assert_eq!(header.channels[3].ref_name(), "Cz");
+        assert_eq!(header.channels[3].resolution(), 1.0);
+        assert_eq!(header.channels[4].scale_to_microvolts(), None);
+
+        let data = Data::<f32>::load(&path, &header, Validation::Strict).unwrap();
+        let scaled = data.scaled(&header);
+        let microvolts = data.to_microvolts(&header);
+        for (channel, (resolution, factor)) in [
+            (0.5, 1000.0),
+            (0.1, 1.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (0.25, 1.0),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            assert!(scaled.row(channel).iter().all(|&v| v == resolution));
+            assert!(microvolts
+                .row(channel)
+                .iter()
+                .all(|&v| (v - resolution * factor).abs() < 1e-4));
+        }
+    }
+
+    #[test]
+    fn channel_count_must_match_the_listed_channels() {
+        let root = TempDir::new("corrupt-channels");
+        let recording = Recording::new(&["Fp1", "Fp2"], Array2::zeros((2, 4)));
+        let path = recording.write(&root);
+        fs::write(
+            path.filename("eeg", "vhdr"),
+            recording
+                .header()
+                .replace("NumberOfChannels=2", "NumberOfChannels=3"),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            Header::load(&path),
+            Err(ReadError::ChannelCountMismatch {
+                declared: 3,
+                listed: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn data_size_must_amount_to_whole_samples() {
+        let root = TempDir::new("corrupt-size");
+        let data = Array2::from_shape_fn((2, 4), |(c, t)| (c * 4 + t) as f32);
+        let recording = Recording::new(&["Fp1", "Fp2"], data.clone());
+        let path = recording.write(&root);
+        let header = Header::load(&path).unwrap();
+        // Half a sample of trailing bytes
+        let mut bytes = recording.data_bytes();
+        bytes.extend([0; 4]);
+        fs::write(path.directory().join(&header.data_file), bytes).unwrap();
+
+        assert!(matches!(
+            Data::<f32>::load(&path, &header, Validation::Strict),
+            Err(ReadError::DataSizeMismatch {
+                file_bytes: 36,
+                num_channels: 2,
+                value_bytes: 4
+            })
+        ));
+        let lenient = Data::<f32>::load(&path, &header, Validation::Lenient).unwrap();
+        assert_eq!(lenient.view(), data);
+    }
+
+    #[test]
+    fn segmented_data_must_amount_to_whole_segments() {
+        let root = TempDir::new("corrupt-segments");
+        let mut recording = Recording::new(&["Cz"], Array2::zeros((1, 10)));
+        recording.common_infos = vec![
+            "Averaged=YES".into(),
+            "AveragedSegments=2".into(),
+            "SegmentationType=MARKERBASED".into(),
+            "SegmentDataPoints=4".into(),
+        ];
+        let path = recording.write(&root);
+        let header = Header::load(&path).unwrap();
+
+        assert!(matches!(
+            Data::<f32>::load(&path, &header, Validation::Strict),
+            Err(ReadError::SegmentMismatch {
+                num_samples: 10,
+                segment_data_points: 4
+            })
+        ));
+        assert!(Data::<f32>::load(&path, &header, Validation::Lenient).is_ok());
+    }
+
+    // Header, data and markers of two channels of 3 segments of 100 samples, the value of each
+    // being its channel and sample indices, segmented by the `common_infos` and delimited by the
+    // `markers`, along with the directory holding them
+    fn segmented(common_infos: &[&str], markers: &[&str]) -> (TempDir, Header, Data<f32>, Marker) {
+        let root = TempDir::new("segments");
+        let data = Array2::from_shape_fn((2, 300), |(c, t)| (c * 1000 + t) as f32);
+        let mut recording = Recording::new(&["Fp1", "Cz"], data).markers(markers);
+        recording.common_infos = common_infos.iter().map(|&info| info.into()).collect();
+        let path = recording.write(&root);
+        let header = Header::load(&path).unwrap();
+        let data = Data::<f32>::load(&path, &header, Validation::Strict).unwrap();
+        let marker = Marker::load(&path, &header).unwrap().unwrap();
+
+        (root, header, data, marker)
+    }
+
+    const SEGMENTED: [&str; 2] = ["SegmentationType=MARKERBASED", "SegmentDataPoints=100"];
+
+    #[test]
+    fn segments_are_reshaped_from_the_header_or_the_markers() {
+        let boundaries = [
+            "New Segment,,1,1,0",
+            "Stimulus,S  1,50,1,0",
+            "New Segment,,101,1,0",
+            "New Segment,,201,1,0",
+        ];
+        for (common_infos, markers) in [
+            (&SEGMENTED[..], &[][..]),
+            (&SEGMENTED[..], &boundaries[..]),
+            // Without `SegmentDataPoints`, from the distance between the `New Segment` markers
+            (&[][..], &boundaries[..]),
+        ] {
+            let (_root, header, data, marker) = segmented(common_infos, markers);
+            let segments = data.segments(&header, &marker).unwrap();
+
+            assert_eq!(segments.dim(), (3, 2, 100));
+            assert_eq!(segments[[0, 0, 0]], 0.0);
+            assert_eq!(segments[[1, 0, 42]], 142.0);
+            assert_eq!(segments[[2, 1, 99]], 1299.0);
+            for segment in 0..3 {
+                assert_eq!(
+                    segments.slice(s![segment, .., ..]),
+                    data.view()
+                        .slice(s![.., segment * 100..(segment + 1) * 100])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn mismatched_segments_fail() {
+        let (_root, header, data, marker) =
+            segmented(&SEGMENTED, &["New Segment,,1,1,0", "New Segment,,101,1,0"]);
+        assert!(matches!(
+            data.segments(&header, &marker),
+            Err(ReadError::SegmentCountMismatch {
+                expected: 3,
+                found: 2
+            })
+        ));
+
+        let (_root, header, data, marker) = segmented(
+            &SEGMENTED,
+            &[
+                "New Segment,,1,1,0",
+                "New Segment,,101,1,0",
+                "New Segment,,202,1,0",
+            ],
+        );
+        assert!(matches!(
+            data.segments(&header, &marker),
+            Err(ReadError::SegmentBoundaryMismatch {
+                segment: 2,
+                expected: 200,
+                found: 201
+            })
+        ));
+
+        // Markers 120 samples apart, which do not divide the 300 samples
+        let (_root, header, data, marker) =
+            segmented(&[], &["New Segment,,1,1,0", "New Segment,,121,1,0"]);
+        assert!(matches!(
+            data.segments(&header, &marker),
+            Err(ReadError::SegmentMismatch {
+                num_samples: 300,
+                segment_data_points: 120
+            })
+        ));
+
+        for markers in [&[][..], &["New Segment,,1,1,0"][..]] {
+            let (_root, header, data, marker) = segmented(&[], markers);
+            assert!(matches!(
+                data.segments(&header, &marker),
+                Err(ReadError::NotSegmented)
+            ));
+        }
+    }
+
+    fn assert_close(a: ArrayView1<f64>, b: [f64; 3]) {
+        assert!(
+            a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-9),
+            "{a} differs from {b:?} !"
+        );
+    }
+
+    #[test]
+    fn coordinates_convert_to_cartesian_positions() {
+        let root = TempDir::new("coordinates");
+        let mut recording = Recording::new(&["Cz", "C3", "C4", "Fpz"], Array2::zeros((4, 2)));
+        recording.extra =
+            "\n[Coordinates]\nCh1=1,0,0\nCh2=1,-90,0\nCh3=1,90,0\nCh4=1,90,90\n".into();
+        let header = Header::load(&recording.write(&root)).unwrap();
+
+        let positions = header.channel_positions();
+        assert!(positions.templated.is_empty() && positions.missing.is_empty());
+        assert_close(positions.positions.row(0), [0.0, 0.0, 1.0]);
+        assert_close(positions.positions.row(1), [-1.0, 0.0, 0.0]);
+        assert_close(positions.positions.row(2), [1.0, 0.0, 0.0]);
+        assert_close(positions.positions.row(3), [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn channels_without_coordinates_fall_back_to_the_standard_positions() {
+        let root = TempDir::new("coordinates-standard");
+        let mut recording = Recording::new(&["Cz", "C3", "C4", "Aux1"], Array2::zeros((4, 2)));
+        // A null radius stands for an unknown position
+        recording.extra = "\n[Coordinates]\nCh1=0,0,0\nCh2=0,0,0\n".into();
+        let header = Header::load(&recording.write(&root)).unwrap();
+
+        let ChannelPositions {
+            positions,
+            templated,
+            missing,
+        } = header.channel_positions();
+        assert_eq!((templated, missing), (vec![0, 1, 2], vec![3]));
+        assert_close(positions.row(0), [0.0, 0.0, 1.0]);
+        let (c3, c4) = (positions.row(1), positions.row(2));
+        assert!(c3[0] < 0.0);
+        assert_close(c4, [-c3[0], c3[1], c3[2]]);
+        assert!(positions.row(3).iter().all(|v| v.is_nan()));
+    }
+
+    #[test]
+    fn dates_parse_when_valid_and_fail_otherwise() {
+        assert!(Date::parse("").unwrap().is_none());
+        assert!(Date::parse("00000000000000000000").unwrap().is_none());
+
+        let date = Date::parse("20240229235959123456").unwrap().unwrap();
+        assert_eq!(
+            (
+                date.year,
+                date.month,
+                date.day,
+                date.hour,
+                date.minute,
+                date.second
+            ),
+            (2024, 2, 29, 23, 59, 59)
+        );
+        assert_eq!(date.microsecond, 123456);
+        assert_eq!(
+            Date::parse("19700101000001000002")
+                .unwrap()
+                .unwrap()
+                .to_unix_micros(),
+            1_000_002
+        );
+
+        assert!(matches!(
+            Date::parse("2024022923"),
+            Err(DateError::Length(10))
+        ));
+        assert!(matches!(
+            Date::parse("2024-02-29T235959123"),
+            Err(DateError::NotNumeric(_))
+        ));
+        for (value, field, out_of_range) in [
+            ("20241301000000000000", "month", 13),
+            ("20230229000000000000", "day", 29),
+            ("20240431000000000000", "day", 31),
+            ("20240101240000000000", "hour", 24),
+            ("20240101006000000000", "minute", 60),
+            ("20240101000060000000", "second", 60),
+        ] {
+            match Date::parse(value) {
+                Err(DateError::OutOfRange { field: f, value: v }) => {
+                    assert_eq!((f, v), (field, out_of_range))
+                }
+                other => panic!("Unexpected result {other:?} for {value} !"),
+            }
+        }
+    }
+
+    #[test]
+    fn markers_drop_invalid_dates() {
+        let valid = MarkerInfo::try_from("New Segment,,1,1,0,20240101120000000000").unwrap();
+        assert_eq!(valid.date.unwrap().hour, 12);
+        assert!(MarkerInfo::try_from("New Segment,,1,1,0,2024010112")
+            .unwrap()
+            .date
+            .is_none());
+        assert!(
+            MarkerInfo::try_from("New Segment,,1,1,0,20241399120000000000")
+                .unwrap()
+                .date
+                .is_none()
+        );
+        assert!(MarkerInfo::try_from("New Segment,,1,1,0")
+            .unwrap()
+            .date
+            .is_none());
+        assert!(MarkerInfo::try_from("New Segment,,1,1,0,")
+            .unwrap()
+            .date
+            .is_none());
+    }
+
+    #[test]
+    fn malformed_markers_fail_instead_of_panicking() {
+        for (marker, expected) in [
+            ("Stimulus,S  1", "Mk1: missing position"),
+            ("Stimulus,S  1,,1,0", "Mk1: missing position"),
+            ("Stimulus,S  1,first,1,0", "Mk1: invalid position 'first'"),
+            ("Stimulus,S  1,3,-1,0", "Mk1: invalid size '-1'"),
+            ("Stimulus,S  1,3,1,Cz", "Mk1: invalid channel 'Cz'"),
+        ] {
+            let root = TempDir::new("markers-malformed");
+            let path = Recording::new(&["Cz"], Array2::zeros((1, 4)))
+                .markers(&[marker])
+                .write(&root);
+            let header = Header::load(&path).unwrap();
+
+            match Marker::load(&path, &header) {
+                Err(ReadError::InvalidMarkerFile { file, reason }) => assert_eq!(
+                    (file.as_str(), reason.as_str()),
+                    ("sub-01_task-test_eeg.vmrk", expected)
+                ),
+                other => panic!("Unexpected result {other:?} for {marker} !"),
+            }
+        }
+    }
+
+    // Values of the data file selected by `options`, whatever its binary format
+    fn load_values(
+        path: &BIDSPath<PathBuf>,
+        header: &Header,
+        options: &LoadOptions,
+    ) -> Array2<f32> {
+        match header.binary_format {
+            BinaryFormatType::IeeeFloat32 => Data::<f32>::load_with(path, header, options)
+                .unwrap()
+                .view()
+                .to_owned(),
+            BinaryFormatType::Int16 => Data::<i16>::load_with(path, header, options)
+                .unwrap()
+                .view()
+                .mapv(f32::from),
+        }
+    }
+
+    #[test]
+    fn load_options_match_slicing_a_full_load() {
+        for vectorized in [false, true] {
+            for format in [Format::Float32, Format::Int16] {
+                let root = TempDir::new("load-options");
+                let mut recording = Recording::new(&["Fp1", "Fp2", "Cz", "Pz"], values());
+                recording.vectorized = vectorized;
+                recording.format = format;
+                let path = recording.write(&root);
+                let header = Header::load(&path).unwrap();
+
+                let full = load_values(&path, &header, &LoadOptions::new());
+                assert_eq!(full, values());
+
+                let options = LoadOptions::new().samples(10..35).channels(&[3, 1]);
+                let partial = load_values(&path, &header, &options);
+                assert_eq!(partial.dim(), (2, 25));
+                assert_eq!(partial, full.select(Axis(0), &[3, 1]).slice(s![.., 10..35]));
+
+                // Ranges are clamped to the samples of the data file
+                let clamped = load_values(&path, &header, &LoadOptions::new().samples(40..100));
+                assert_eq!(clamped, full.slice(s![.., 40..]));
+            }
+        }
+    }
+
+    #[test]
+    fn int16_data_decodes_as_signed() {
+        let data = Array2::from_shape_vec(
+            (2, 4),
+            vec![-32768.0, -1.0, 0.0, 32767.0, -2.0, 1.0, -256.0, 255.0],
+        )
+        .unwrap();
+        for big_endian in [false, true] {
+            for vectorized in [false, true] {
+                let root = TempDir::new("int16");
+                let mut recording = Recording::new(&["Fp1", "Fp2"], data.clone());
+                recording.format = Format::Int16;
+                recording.big_endian = big_endian;
+                recording.vectorized = vectorized;
+                recording.channels[1] = "Fp2,,0.1,µV".into();
+                let path = recording.write(&root);
+                let header = Header::load(&path).unwrap();
+
+                let loaded = Data::<i16>::load(&path, &header, Validation::Strict).unwrap();
+                assert_eq!(loaded.view(), data.mapv(|v| v as i16));
+                let scaled = loaded.scaled(&header);
+                assert_eq!(scaled[[0, 0]], -32768.0);
+                assert!((scaled[[1, 2]] + 25.6).abs() < 1e-4);
+
+                let raw = crate::read::raw::Raw::load(&path).unwrap();
+                assert_eq!(raw.data(), scaled);
+            }
+        }
+    }
+
+    // Header of 10 Int16 channels, with coordinates, a comment and dated markers
+    fn described() -> (TempDir, Header, Marker) {
+        let root = TempDir::new("described");
+        let names = ["Fp1", "Fp2", "F3", "F4", "C3", "C4", "P3", "P4", "O1", "O2"];
+        let mut recording = Recording::new(&names, Array2::zeros((10, 4))).markers(&[
+            "New Segment,,1,1,0,20240131235959123456",
+            "Stimulus,S\\1 1,3,1,2",
+        ]);
+        recording.format = Format::Int16;
+        recording.big_endian = true;
+        recording.channels[0] = "Fp1,Cz,0.1,mV".into();
+        recording.extra = "\n[Coordinates]\nCh1=1,-90,-72\n\n[Comment]\nImpedances fine\n".into();
+        let path = recording.write(&root);
+        let header = Header::load(&path).unwrap();
+        let marker = Marker::load(&path, &header).unwrap().unwrap();
+
+        (root, header, marker)
+    }
+
+    #[test]
+    fn header_summary_lists_its_layout_and_channels() {
+        let (_root, header, _) = described();
+
+        assert_eq!(
+            header.to_string(),
+            "10 channels, 500 Hz, INT_16, MULTIPLEXED, big endian\n\
+             channels: Fp1, Fp2, F3, F4, C3, C4, P3, P4, ... (2 more)"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn header_and_markers_round_trip_through_serde() {
+        let (_root, header, marker) = described();
+
+        let json = serde_json::to_string(&header).unwrap();
+        let deserialized = serde_json::from_str::<Header>(&json).unwrap();
+        assert_eq!(format!("{deserialized:?}"), format!("{header:?}"));
+        assert_eq!(deserialized.comment.as_deref(), Some("\nImpedances fine\n"));
+        assert_eq!(deserialized.channels[0].unit(), "mV");
+
+        let json = serde_json::to_string(&marker).unwrap();
+        let deserialized = serde_json::from_str::<Marker>(&json).unwrap();
+        assert_eq!(format!("{deserialized:?}"), format!("{marker:?}"));
+        assert_eq!(deserialized.markers[1].description, "S, 1");
+        assert_eq!(
+            deserialized.markers[0].date.as_ref().unwrap().microsecond,
+            123456
+        );
+    }
+
+    #[test]
+    fn sampling_rate_and_times_follow_the_sampling_interval() {
+        let root = TempDir::new("rate-512");
+        let mut recording = Recording::new(&["Cz"], Array2::zeros((1, 4)));
+        recording.sampling_interval = 1953.125;
+        let header = Header::load(&recording.write(&root)).unwrap();
+
+        assert_eq!(header.sampling_rate_hz(), 512.0);
+        assert_eq!(header.duration_secs(1024), 2.0);
+        let times = header.times(513);
+        assert_eq!((times[0], times[256], times[512]), (0.0, 0.5, 1.0));
+
+        // 333.33... Hz
+        let root = TempDir::new("rate-fractional");
+        recording.sampling_interval = 3000.0;
+        let header = Header::load(&recording.write(&root)).unwrap();
+
+        assert!((header.sampling_rate_hz() - 1000.0 / 3.0).abs() < 1e-9);
+        assert_eq!(header.duration_secs(1000), 3.0);
+        let times = header.times(100_001);
+        assert_eq!(times.len(), 100_001);
+        // Computed from their own index, so that no rounding error accumulates
+        assert!((times[100_000] - 300.0).abs() < 1e-12);
+        assert!((times[1] - 0.003).abs() < 1e-15);
+    }
+
+    #[test]
+    fn scaled_view_matches_the_eager_conversion() {
+        let mut generator = crate::surrogate::Lcg::new(3);
+        let data = Array2::from_shape_fn((3, 1000), |_| {
+            (generator.next_f32() * 6000.0 - 3000.0).round()
+        });
+        let root = TempDir::new("scaled-view");
+        let mut recording = Recording::new(&["Fp1", "Fp2", "Cz"], data);
+        recording.format = Format::Int16;
+        recording.channels[0] = "Fp1,,0.5,µV".into();
+        recording.channels[2] = "Cz,,0.0488281,µV".into();
+        let path = recording.write(&root);
+        let header = Header::load(&path).unwrap();
+        let loaded = Data::<i16>::load(&path, &header, Validation::Strict).unwrap();
+
+        let eager = loaded.scaled(&header);
+        let view = loaded.scaled_view(&header);
+        assert_eq!((view.num_channels(), view.num_samples()), (3, 1000));
+        for channel in 0..3 {
+            for sample in [0, 1, 500, 999] {
+                assert_eq!(view.get(channel, sample), eager[[channel, sample]]);
+            }
+            let values = view.channel_iter(channel).collect::<Vec<f32>>();
+            assert_eq!(values, eager.row(channel).to_vec());
+        }
+        assert_eq!(view.to_array_range(5..15), eager.slice(s![.., 5..15]));
+        assert_eq!(view.to_array_range(0..1000), eager);
+
+        // Accepted in place of the eager array, to the same results
+        let bands = [(1.0, 4.0), (8.0, 13.0)];
+        for mode in [BandPowerMode::Absolute, BandPowerMode::Relative] {
+            assert_eq!(
+                band_power_topography(&view, 500.0, &bands, mode),
+                band_power_topography(&eager, 500.0, &bands, mode)
+            );
+        }
+        let filter = FIRFilter::lowpass(51, 30.0, 500.0);
+        assert_eq!(
+            filter.process_zero_phase_channels(&view),
+            filter.process_zero_phase_channels(&eager)
+        );
+    }
+
+    #[test]
+    fn unreadable_data_files_fail_instead_of_panicking() {
+        let root = TempDir::new("data-unreadable");
+        let path = Recording::new(&["Cz"], Array2::zeros((1, 4))).write(&root);
+        let header = Header::load(&path).unwrap();
+        let data_file = path.filename("eeg", "eeg");
+
+        fs::remove_file(&data_file).unwrap();
+        assert!(matches!(
+            Data::<f32>::load(&path, &header, Validation::Strict),
+            Err(ReadError::InvalidDataFile { file, .. }) if file == "sub-01_task-test_eeg.eeg"
+        ));
+        // A directory opens, but cannot be read
+        fs::create_dir(&data_file).unwrap();
+        assert!(matches!(
+            Data::<f32>::load(&path, &header, Validation::Lenient),
+            Err(ReadError::InvalidDataFile { .. })
+        ));
+    }
 }
@@ -0,0 +1,262 @@
+// * https://bids-specification.readthedocs.io/en/stable/modality-specific-files/electroencephalography.html#channels-description-_channelstsv
+
+use std::path::Path;
+
+use super::{brainvision_core::Header, tsv::Tsv, ReadError};
+
+// Struct containing the description of every channel of a recording, provided in the
+// `channels.tsv` sidecar
+//
+// sub-<subject>[_ses-<session>]_task-<task>[_acq-<acquisition>][_run-<run>]_channels.tsv
+#[derive(Debug)]
+pub struct ChannelsTsv {
+    pub channels: Vec<ChannelDescription>,
+}
+
+// Description of a channel, a row of the `channels.tsv` sidecar
+// Every column but `name` is optional
+#[derive(Debug)]
+pub struct ChannelDescription {
+    pub name: String,
+    // Type of the channel, e.g. EEG, EOG, ECG, MISC
+    pub channel_type: Option<String>,
+    pub units: Option<String>,
+    pub status: Option<ChannelStatus>,
+    pub status_description: Option<String>,
+    pub sampling_frequency: Option<f64>,
+    pub low_cutoff: Option<f64>,
+    pub high_cutoff: Option<f64>,
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChannelStatus {
+    Good,
+    Bad,
+}
+
+// Disagreement between the channels of a `channels.tsv` sidecar and the ones of a `Header`
+#[derive(Debug, PartialEq)]
+pub enum ChannelMismatch {
+    // Channel listed in the header but not in the sidecar
+    MissingInTsv(String),
+    // Channel listed in the sidecar but not in the header
+    MissingInHeader(String),
+    // Channel listed in both, but at different positions
+    Order {
+        name: String,
+        header_index: usize,
+        tsv_index: usize,
+    },
+}
+
+impl ChannelsTsv {
+    // Load the `channels.tsv` sidecar at `path`
+    // Fails with `ReadError::InvalidSidecar` if it cannot be read, lacks the `name` of a channel
+    // or holds a non-numeric frequency
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ChannelsTsv, ReadError> {
+        let tsv = Tsv::load(path)?;
+
+        let channels = (0..tsv.num_rows())
+            .map(|row| {
+                Ok(ChannelDescription {
+                    name: tsv.require(row, "name")?.into(),
+                    channel_type: tsv.get(row, "type").map(|s| s.to_uppercase()),
+                    units: tsv.get(row, "units").map(Into::into),
+                    status: tsv.get(row, "status").map(|s| match s {
+                        "bad" => ChannelStatus::Bad,
+                        _ => ChannelStatus::Good,
+                    }),
+                    status_description: tsv.get(row, "status_description").map(Into::into),
+                    sampling_frequency: tsv.parse_value(row, "sampling_frequency")?,
+                    low_cutoff: tsv.parse_value(row, "low_cutoff")?,
+                    high_cutoff: tsv.parse_value(row, "high_cutoff")?,
+                    description: tsv.get(row, "description").map(Into::into),
+                })
+            })
+            .collect::<Result<Vec<ChannelDescription>, ReadError>>()?;
+
+        Ok(ChannelsTsv { channels })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ChannelDescription> {
+        self.channels.iter().find(|c| c.name == name)
+    }
+
+    // Names of the channels marked as bad
+    pub fn bad_channels(&self) -> Vec<&str> {
+        self.channels
+            .iter()
+            .filter(|c| c.status == Some(ChannelStatus::Bad))
+            .map(|c| c.name.as_str())
+            .collect()
+    }
+
+    // Mark the channels called `names` as bad, e.g. as found by `detect_bad_channels`, with the
+    // given `description` of why
+    // Names not matching any channel are ignored
+    pub fn mark_bad(&mut self, names: &[&str], description: Option<&str>) {
+        for channel in self
+            .channels
+            .iter_mut()
+            .filter(|c| names.contains(&c.name.as_str()))
+        {
+            channel.status = Some(ChannelStatus::Bad);
+            channel.status_description = description.map(Into::into);
+        }
+    }
+
+    // Names of the channels of the given `channel_type`, e.g. "EEG"
+    pub fn channels_of_type(&self, channel_type: &str) -> Vec<&str> {
+        self.channels
+            .iter()
+            .filter(|c| {
+                c.channel_type
+                    .as_deref()
+                    .is_some_and(|t| t.eq_ignore_ascii_case(channel_type))
+            })
+            .map(|c| c.name.as_str())
+            .collect()
+    }
+
+    // Names of the channels of the given `channel_type` not marked as bad
+    pub fn good_channels_of_type(&self, channel_type: &str) -> Vec<&str> {
+        let bad = self.bad_channels();
+
+        self.channels_of_type(channel_type)
+            .into_iter()
+            .filter(|c| !bad.contains(c))
+            .collect()
+    }
+
+    // Compare the channels of the sidecar against the ones of the `header`, returning every
+    // mismatched name or ordering
+    pub fn reconcile(&self, header: &Header) -> Vec<ChannelMismatch> {
+        let mut mismatches = Vec::new();
+
+        for (header_index, channel) in header.channels.iter().enumerate() {
+            match self.channels.iter().position(|c| c.name == channel.name()) {
+                None => mismatches.push(ChannelMismatch::MissingInTsv(channel.name().into())),
+                Some(tsv_index) if tsv_index != header_index => {
+                    mismatches.push(ChannelMismatch::Order {
+                        name: channel.name().into(),
+                        header_index,
+                        tsv_index,
+                    })
+                }
+                _ => {}
+            }
+        }
+        for channel in &self.channels {
+            if !header.channels.iter().any(|c| c.name() == channel.name) {
+                mismatches.push(ChannelMismatch::MissingInHeader(channel.name.clone()));
+            }
+        }
+
+        mismatches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array2;
+
+    use super::*;
+    use crate::read::{
+        brainvision_core::{Data, Validation},
+        fixtures::{Recording, TempDir},
+    };
+
+    const CHANNELS: &str = "name\ttype\tunits\tstatus\tstatus_description\tsampling_frequency\n\
+                            Fp1\tEEG\tµV\tgood\tn/a\t500\n\
+                            Fp2\tEEG\tµV\tbad\tflat\t500\n\
+                            Cz\teeg\tµV\tn/a\tn/a\t500\n\
+                            VEOG\tEOG\tµV\tgood\tn/a\tn/a\n";
+
+    #[test]
+    fn good_eeg_channels_drive_the_selection() {
+        let root = TempDir::new("channels");
+        let data = Array2::from_shape_fn((4, 10), |(c, t)| (c * 10 + t) as f32);
+        let path = Recording::new(&["Fp1", "Fp2", "Cz", "VEOG"], data).write(&root);
+        let tsv = root.write("sub-01/eeg/sub-01_task-test_channels.tsv", CHANNELS);
+
+        let channels = ChannelsTsv::load(tsv).unwrap();
+        assert_eq!(channels.channels.len(), 4);
+        assert_eq!(channels.bad_channels(), vec!["Fp2"]);
+        assert_eq!(channels.channels_of_type("EOG"), vec!["VEOG"]);
+        assert_eq!(
+            channels.get("Cz").unwrap().channel_type.as_deref(),
+            Some("EEG")
+        );
+        assert_eq!(channels.get("VEOG").unwrap().sampling_frequency, None);
+        assert_eq!(
+            channels.get("Fp2").unwrap().status_description.as_deref(),
+            Some("flat")
+        );
+
+        let header = Header::load(&path).unwrap();
+        assert!(channels.reconcile(&header).is_empty());
+
+        let good = channels.good_channels_of_type("EEG");
+        assert_eq!(good, vec!["Fp1", "Cz"]);
+        let data = Data::<f32>::load(&path, &header, Validation::Strict).unwrap();
+        let selected = data.select_channels(&header.channel_indices(&good));
+        assert_eq!(selected.view().row(0), data.channel(0));
+        assert_eq!(selected.view().row(1), data.channel(2));
+    }
+
+    #[test]
+    fn reconcile_reports_missing_and_misordered_channels() {
+        let root = TempDir::new("channels-reconcile");
+        let path = Recording::new(&["Fp2", "Fp1", "O1"], Array2::zeros((3, 4))).write(&root);
+        let tsv = root.write("sub-01/eeg/sub-01_task-test_channels.tsv", CHANNELS);
+
+        let mismatches = ChannelsTsv::load(tsv)
+            .unwrap()
+            .reconcile(&Header::load(&path).unwrap());
+        assert_eq!(
+            mismatches,
+            vec![
+                ChannelMismatch::Order {
+                    name: "Fp2".into(),
+                    header_index: 0,
+                    tsv_index: 1,
+                },
+                ChannelMismatch::Order {
+                    name: "Fp1".into(),
+                    header_index: 1,
+                    tsv_index: 0,
+                },
+                ChannelMismatch::MissingInTsv("O1".into()),
+                ChannelMismatch::MissingInHeader("Cz".into()),
+                ChannelMismatch::MissingInHeader("VEOG".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_sidecars_fail_instead_of_panicking() {
+        let root = TempDir::new("channels-invalid");
+        for (contents, expected) in [
+            ("type\tunits\nEEG\tµV\n", "missing name column"),
+            ("name\ttype\nFp1\tEEG\nn/a\tEEG\n", "missing name in row 2"),
+            (
+                "name\tlow_cutoff\nFp1\t0.1\nFp2\thigh\n",
+                "invalid low_cutoff 'high' in row 2",
+            ),
+        ] {
+            let tsv = root.write("channels.tsv", contents);
+            match ChannelsTsv::load(tsv) {
+                Err(ReadError::InvalidSidecar { file, reason }) => {
+                    assert_eq!((file.as_str(), reason.as_str()), ("channels.tsv", expected))
+                }
+                other => panic!("Unexpected result {other:?} !"),
+            }
+        }
+
+        assert!(matches!(
+            ChannelsTsv::load(root.path().join("missing.tsv")),
+            Err(ReadError::InvalidSidecar { .. })
+        ));
+    }
+}
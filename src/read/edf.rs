@@ -0,0 +1,892 @@
+// * https://www.edfplus.info/specs/edf.html
+// * https://www.edfplus.info/specs/edfplus.html
+// * https://www.biosemi.com/faq/file_format.htm
+
+use std::{fs, path::Path};
+
+use ndarray::{Array1, Array2, ArrayBase, ArrayView1, ArrayView2, Data, Ix1};
+
+use super::{
+    brainvision_core::{Marker, MarkerInfo},
+    BIDSPath, ReadError,
+};
+
+// Labels of the signal holding the EDF+/BDF+ annotations instead of recorded data
+const ANNOTATIONS_LABELS: [&str; 2] = ["EDF Annotations", "BDF Annotations"];
+// Label of the BioSemi signal holding the trigger and system bits
+const STATUS_LABEL: &str = "Status";
+// Bits of the BioSemi `Status` signal holding the trigger values
+pub const TRIGGER_MASK: i32 = 0xFFFF;
+
+// Flavour of the file, which determines the width of the samples
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EdfFormat {
+    // 16-bit samples
+    Edf,
+    // BioSemi 24-bit samples, identified by a version starting with the 0xFF byte
+    Bdf,
+}
+
+impl EdfFormat {
+    // Number of bytes a single sample takes in the data records
+    pub fn sample_bytes(&self) -> usize {
+        match self {
+            EdfFormat::Edf => 2,
+            EdfFormat::Bdf => 3,
+        }
+    }
+
+    // Decode a single little-endian two's complement sample
+    fn decode(&self, bytes: &[u8]) -> i32 {
+        match self {
+            EdfFormat::Edf => i16::from_le_bytes([bytes[0], bytes[1]]).into(),
+            EdfFormat::Bdf => {
+                // Sign-extend the 24-bit value
+                let sign = if bytes[2] & 0x80 != 0 { 0xFF } else { 0x00 };
+                i32::from_le_bytes([bytes[0], bytes[1], bytes[2], sign])
+            }
+        }
+    }
+}
+
+// Struct containing all of the information provided in the header record of the EDF(+) or BDF(+)
+// file of a recording, provided to the `EdfHeader::load` and `EdfHeader::load_bdf` methods.
+//
+// sub-<subject>[_ses-<session>]_task-<task>[_acq-<acquisition>][_run-<run>]_eeg.{edf,bdf}
+#[derive(Debug)]
+pub struct EdfHeader {
+    // Name of the EDF/BDF file
+    pub data_file: String,
+    pub format: EdfFormat,
+    // Version of the data format, `0` for EDF and `BIOSEMI` for BDF
+    pub version: String,
+    // Local patient identification
+    pub patient: String,
+    // Local recording identification
+    pub recording: String,
+    // Start date of the recording, formatted as dd.mm.yy
+    pub start_date: String,
+    // Start time of the recording, formatted as hh.mm.ss
+    pub start_time: String,
+    // Number of bytes of the whole header record
+    pub header_bytes: usize,
+    // Indicates whether the file is EDF+, `EDF+C` (continuous) or `EDF+D` (discontinuous)
+    pub reserved: String,
+    // Number of data records
+    pub num_records: usize,
+    // Duration of a data record, in seconds
+    pub record_duration: f64,
+    // Stores information about each signal, including the annotations signal of EDF+ files
+    pub signals: Vec<EdfSignal>,
+}
+
+impl EdfHeader {
+    // Load the header record of an EDF(+) file by providing the `path` to a BIDS-compliant data
+    // recording
+    // The `path` is validated beforehand, failing with the first level of it not found
+    // Fails with `ReadError::InvalidHeader` if the file cannot be read, or its header record is
+    // truncated or holds invalid numbers
+    pub fn load<P: AsRef<Path>>(path: &BIDSPath<P>) -> Result<EdfHeader, ReadError> {
+        path.validate()?;
+        Self::load_file(path, "edf")
+    }
+
+    // Load the header record of a BDF(+) file by providing the `path` to a BIDS-compliant data
+    // recording
+    // The `path` is validated beforehand, failing with the first level of it not found
+    // Fails with `ReadError::InvalidHeader` if the file cannot be read, or its header record is
+    // truncated or holds invalid numbers
+    pub fn load_bdf<P: AsRef<Path>>(path: &BIDSPath<P>) -> Result<EdfHeader, ReadError> {
+        path.validate()?;
+        Self::load_file(path, "bdf")
+    }
+
+    fn load_file<P: AsRef<Path>>(
+        path: &BIDSPath<P>,
+        extension: &str,
+    ) -> Result<EdfHeader, ReadError> {
+        let file = path.filename(path.datatype, extension);
+        let data_file = file
+            .file_name()
+            .map_or_else(String::new, |f| f.to_string_lossy().into_owned());
+        let invalid = |reason: String| ReadError::InvalidHeader {
+            file: data_file.clone(),
+            reason,
+        };
+        let raw = fs::read(&file).map_err(|e| invalid(e.to_string()))?;
+
+        // The version of BDF files is the 0xFF byte followed by `BIOSEMI`, which is not ASCII
+        let first = *raw.first().ok_or_else(|| invalid("empty file".into()))?;
+        let format = if first == 0xFF {
+            EdfFormat::Bdf
+        } else {
+            EdfFormat::Edf
+        };
+        let mut fields = Fields { raw: &raw, idx: 1 };
+        let version = match format {
+            EdfFormat::Edf => format!("{}{}", first as char, fields.next(7).map_err(invalid)?),
+            EdfFormat::Bdf => fields.next(7).map_err(invalid)?.into(),
+        };
+        let patient = fields.next(80).map_err(invalid)?.into();
+        let recording = fields.next(80).map_err(invalid)?.into();
+        let start_date = fields.next(8).map_err(invalid)?.into();
+        let start_time = fields.next(8).map_err(invalid)?.into();
+        let header_bytes = fields.parse(8, "number of header bytes").map_err(invalid)?;
+        let reserved = fields.next(44).map_err(invalid)?.into();
+        let num_records = fields
+            .parse::<i64>(8, "number of data records")
+            .map_err(invalid)?;
+        let record_duration = fields
+            .parse(8, "duration of a data record")
+            .map_err(invalid)?;
+        let num_signals = fields
+            .parse::<usize>(4, "number of signals")
+            .map_err(invalid)?;
+
+        // Signal fields are stored one after the other for every signal, field by field
+        let labels = fields.next_n(num_signals, 16).map_err(invalid)?;
+        let transducers = fields.next_n(num_signals, 80).map_err(invalid)?;
+        let physical_dimensions = fields.next_n(num_signals, 8).map_err(invalid)?;
+        let physical_mins = fields
+            .parse_n::<f64>(num_signals, 8, "physical minimum")
+            .map_err(invalid)?;
+        let physical_maxs = fields
+            .parse_n::<f64>(num_signals, 8, "physical maximum")
+            .map_err(invalid)?;
+        let digital_mins = fields
+            .parse_n::<i32>(num_signals, 8, "digital minimum")
+            .map_err(invalid)?;
+        let digital_maxs = fields
+            .parse_n::<i32>(num_signals, 8, "digital maximum")
+            .map_err(invalid)?;
+        let prefilterings = fields.next_n(num_signals, 80).map_err(invalid)?;
+        let samples_per_records = fields
+            .parse_n::<usize>(num_signals, 8, "number of samples per data record")
+            .map_err(invalid)?;
+
+        let signals = (0..num_signals)
+            .map(|i| EdfSignal {
+                label: labels[i].clone(),
+                transducer: transducers[i].clone(),
+                physical_dimension: physical_dimensions[i].clone(),
+                physical_min: physical_mins[i],
+                physical_max: physical_maxs[i],
+                digital_min: digital_mins[i],
+                digital_max: digital_maxs[i],
+                prefiltering: prefilterings[i].clone(),
+                samples_per_record: samples_per_records[i],
+            })
+            .collect::<Vec<EdfSignal>>();
+
+        // The number of data records is -1 while the recording is still ongoing
+        let num_records = if num_records < 0 {
+            let record_bytes = record_bytes(&signals, format);
+            if record_bytes == 0 {
+                return Err(invalid("data records of no samples".into()));
+            }
+            raw.len().saturating_sub(header_bytes) / record_bytes
+        } else {
+            num_records as usize
+        };
+
+        Ok(EdfHeader {
+            data_file,
+            format,
+            version,
+            patient,
+            recording,
+            start_date,
+            start_time,
+            header_bytes,
+            reserved,
+            num_records,
+            record_duration,
+            signals,
+        })
+    }
+
+    // Number of bytes of a data record, holding the samples of every signal
+    pub fn record_bytes(&self) -> usize {
+        record_bytes(&self.signals, self.format)
+    }
+
+    // Whether the file follows the EDF+ (or BDF+) specification
+    pub fn is_plus(&self) -> bool {
+        self.reserved.starts_with("EDF+") || self.reserved.starts_with("BDF+")
+    }
+
+    // Highest sampling rate across the data signals, in Hz
+    pub fn sampling_rate(&self) -> f64 {
+        self.data_signals()
+            .map(|s| s.samples_per_record)
+            .max()
+            .unwrap_or(0) as f64
+            / self.record_duration
+    }
+
+    // Signals holding recorded data, i.e. all but the EDF+ annotations
+    pub fn data_signals(&self) -> impl Iterator<Item = &EdfSignal> {
+        self.signals.iter().filter(|s| !s.is_annotations())
+    }
+}
+
+fn record_bytes(signals: &[EdfSignal], format: EdfFormat) -> usize {
+    signals
+        .iter()
+        .map(|s| s.samples_per_record * format.sample_bytes())
+        .sum()
+}
+
+// Information about a signal
+#[derive(Debug)]
+pub struct EdfSignal {
+    pub label: String,
+    // Transducer type, e.g. AgAgCl electrode
+    pub transducer: String,
+    // Physical dimension, e.g. uV
+    pub physical_dimension: String,
+    pub physical_min: f64,
+    pub physical_max: f64,
+    pub digital_min: i32,
+    pub digital_max: i32,
+    // Prefiltering, e.g. HP:0.1Hz LP:75Hz
+    pub prefiltering: String,
+    // Number of samples of the signal in each data record
+    pub samples_per_record: usize,
+}
+
+impl EdfSignal {
+    pub fn is_annotations(&self) -> bool {
+        ANNOTATIONS_LABELS.contains(&self.label.as_str())
+    }
+
+    pub fn is_status(&self) -> bool {
+        self.label == STATUS_LABEL
+    }
+
+    // Maps a digital value onto its physical value
+    pub fn to_physical(&self, digital: i32) -> f32 {
+        let gain =
+            (self.physical_max - self.physical_min) / (self.digital_max - self.digital_min) as f64;
+
+        ((digital - self.digital_min) as f64 * gain + self.physical_min) as f32
+    }
+}
+
+// The calibrated data associated with an EDF(+) or BDF(+) header
+//
+// Signals recorded at different sampling rates are linearly interpolated up to the highest
+// sampling rate, so that every channel shares the same time axis
+#[derive(Debug)]
+pub struct EdfData {
+    data: Array2<f32>,
+    annotations: Marker,
+    // Digital values of the BioSemi `Status` signal, if any
+    status: Option<Array1<i32>>,
+}
+
+impl EdfData {
+    // Load the data records of the file of a header
+    // Fails with `ReadError::InvalidDataFile` if the file cannot be read, is shorter than its
+    // header and data records, or holds malformed annotations
+    pub fn load<P: AsRef<Path>>(
+        path: &BIDSPath<P>,
+        header: &EdfHeader,
+    ) -> Result<EdfData, ReadError> {
+        let invalid = |reason: String| ReadError::InvalidDataFile {
+            file: header.data_file.clone(),
+            reason,
+        };
+        let raw = fs::read(path.directory().join(header.data_file.as_str()))
+            .map_err(|e| invalid(e.to_string()))?;
+        let expected = header
+            .record_bytes()
+            .checked_mul(header.num_records)
+            .and_then(|bytes| bytes.checked_add(header.header_bytes));
+        if expected.is_none_or(|expected| raw.len() < expected) {
+            return Err(invalid(format!(
+                "{} bytes are not enough for the header record and {} data records of {} bytes",
+                raw.len(),
+                header.num_records,
+                header.record_bytes()
+            )));
+        }
+        let records = &raw[header.header_bytes..];
+
+        let max_samples_per_record = header
+            .data_signals()
+            .map(|s| s.samples_per_record)
+            .max()
+            .unwrap_or(0);
+        let num_samples = max_samples_per_record * header.num_records;
+        let sampling_rate = header.sampling_rate();
+
+        let mut signals = header
+            .signals
+            .iter()
+            .map(|s| Vec::with_capacity(s.samples_per_record * header.num_records))
+            .collect::<Vec<Vec<i32>>>();
+        let mut annotations = Vec::new();
+
+        // Each data record holds the samples of every signal, signal after signal
+        let mut idx = 0;
+        for _ in 0..header.num_records {
+            for (signal, samples) in header.signals.iter().zip(signals.iter_mut()) {
+                let sample_bytes = header.format.sample_bytes();
+                let bytes = &records[idx..idx + signal.samples_per_record * sample_bytes];
+                idx += bytes.len();
+
+                if signal.is_annotations() {
+                    annotations.extend(parse_annotations(bytes, sampling_rate).map_err(invalid)?);
+                } else {
+                    samples.extend(
+                        bytes
+                            .chunks_exact(sample_bytes)
+                            .map(|c| header.format.decode(c)),
+                    );
+                }
+            }
+        }
+
+        let data_signals = header
+            .signals
+            .iter()
+            .zip(signals)
+            .filter(|(s, _)| !s.is_annotations())
+            .collect::<Vec<(&EdfSignal, Vec<i32>)>>();
+
+        let status = data_signals
+            .iter()
+            .find(|(s, _)| s.is_status())
+            .map(|(_, samples)| Array1::from_vec(samples.clone()));
+
+        let mut data = Array2::zeros((data_signals.len(), num_samples));
+        for (mut row, (signal, samples)) in data.rows_mut().into_iter().zip(data_signals) {
+            let physical = samples
+                .iter()
+                .map(|&d| signal.to_physical(d))
+                .collect::<Array1<f32>>();
+
+            row.assign(&upsample(&physical, num_samples));
+        }
+
+        Ok(EdfData {
+            data,
+            annotations: Marker {
+                data_file: header.data_file.clone(),
+                markers: annotations,
+            },
+            status,
+        })
+    }
+
+    pub fn channel(&self, index: usize) -> ArrayView1<'_, f32> {
+        self.data.row(index)
+    }
+
+    // View of the whole data, oriented channels x samples
+    pub fn view(&self) -> ArrayView2<'_, f32> {
+        self.data.view()
+    }
+
+    // The EDF+ annotations, empty for plain EDF files
+    pub fn annotations(&self) -> &Marker {
+        &self.annotations
+    }
+
+    // Digital values of the BioSemi `Status` signal, at its own sampling rate
+    pub fn status(&self) -> Option<ArrayView1<'_, i32>> {
+        self.status.as_ref().map(|s| s.view())
+    }
+}
+
+// Decode the trigger bits of a BioSemi `Status` signal into markers
+// A marker is placed at every sample where the masked value changes to a non-zero value and spans
+// as long as the value is held
+pub fn status_to_markers<S>(status: &ArrayBase<S, Ix1>, mask: i32) -> Vec<MarkerInfo>
+where
+    S: Data<Elem = i32>,
+{
+    let mut markers: Vec<MarkerInfo> = Vec::new();
+    let mut previous = 0;
+
+    for (i, &value) in status.iter().enumerate() {
+        let value = value & mask;
+
+        if value != previous {
+            if previous != 0 {
+                if let Some(last) = markers.last_mut() {
+                    last.size = i + 1 - last.position;
+                }
+            }
+            if value != 0 {
+                markers.push(MarkerInfo {
+                    marker_type: "Stimulus".into(),
+                    description: value.to_string(),
+                    position: i + 1,
+                    size: 1,
+                    channel: 0,
+                    date: None,
+                });
+            }
+            previous = value;
+        }
+    }
+    // A trigger still held at the end of the recording
+    if previous != 0 {
+        if let Some(last) = markers.last_mut() {
+            last.size = status.len() + 1 - last.position;
+        }
+    }
+
+    markers
+}
+
+// Cursor over the fixed-width ASCII fields of a header record
+struct Fields<'a> {
+    raw: &'a [u8],
+    idx: usize,
+}
+
+// Each field fails with the reason if the header record ends before it or it is not ASCII, and
+// each parsed field with the name of the field if it is not a number
+impl<'a> Fields<'a> {
+    fn next(&mut self, width: usize) -> Result<&'a str, String> {
+        let field = self
+            .raw
+            .get(self.idx..self.idx + width)
+            .ok_or_else(|| "truncated header record".to_string())?;
+        self.idx += width;
+
+        std::str::from_utf8(field)
+            .map(str::trim)
+            .map_err(|_| "non-ASCII header field".into())
+    }
+
+    fn next_n(&mut self, n: usize, width: usize) -> Result<Vec<String>, String> {
+        (0..n).map(|_| self.next(width).map(Into::into)).collect()
+    }
+
+    fn parse<T: std::str::FromStr>(&mut self, width: usize, name: &str) -> Result<T, String> {
+        let value = self.next(width)?;
+
+        value
+            .parse::<T>()
+            .map_err(|_| format!("invalid {name} '{value}'"))
+    }
+
+    fn parse_n<T: std::str::FromStr>(
+        &mut self,
+        n: usize,
+        width: usize,
+        name: &str,
+    ) -> Result<Vec<T>, String> {
+        (0..n).map(|_| self.parse(width, name)).collect()
+    }
+}
+
+// Parse the Time-stamped Annotations Lists (TALs) of a data record into markers
+// Fails with the reason if the onset or duration of a TAL is not a number
+//
+// +<onset>[\x15<duration>]\x14[<annotation>\x14]*\x00
+fn parse_annotations(bytes: &[u8], sampling_rate: f64) -> Result<Vec<MarkerInfo>, String> {
+    let mut markers = Vec::new();
+
+    for tal in bytes.split(|&b| b == 0).filter(|tal| !tal.is_empty()) {
+        let tal = String::from_utf8_lossy(tal);
+        let mut parts = tal.split('\x14');
+        // Splitting always yields at least one part
+        let mut timing = parts.next().unwrap_or_default().split('\x15');
+
+        let number = |name: &str, value: &str| {
+            value
+                .parse::<f64>()
+                .map_err(|_| format!("invalid annotation {name} '{value}'"))
+        };
+        let onset = number("onset", timing.next().unwrap_or_default())?;
+        let duration = timing.next().map_or(Ok(0.0), |d| number("duration", d))?;
+
+        // The first, empty, annotation of each data record only keeps time
+        for description in parts.filter(|p| !p.is_empty()) {
+            markers.push(MarkerInfo {
+                marker_type: "Annotation".into(),
+                description: description.into(),
+                position: (onset * sampling_rate).round() as usize + 1,
+                size: ((duration * sampling_rate).round() as usize).max(1),
+                channel: 0,
+                date: None,
+            });
+        }
+    }
+
+    Ok(markers)
+}
+
+// Linearly interpolate a signal to `n` samples spanning the same duration
+fn upsample(signal: &Array1<f32>, n: usize) -> Array1<f32> {
+    let m = signal.len();
+    if m == n || m == 0 {
+        return signal.clone();
+    }
+
+    let ratio = m as f32 / n as f32;
+    Array1::from_shape_fn(n, |i| {
+        let t = i as f32 * ratio;
+        let lo = t.floor() as usize;
+        let hi = (lo + 1).min(m - 1);
+        let frac = t - lo as f32;
+
+        signal[lo] * (1.0 - frac) + signal[hi] * frac
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::fixtures::{path, TempDir};
+
+    // A signal of the fixture, along with the bytes of its samples in each data record
+    struct Signal {
+        label: &'static str,
+        physical: (f64, f64),
+        digital: (i32, i32),
+        records: Vec<Vec<u8>>,
+    }
+
+    impl Signal {
+        fn new(label: &'static str, physical: (f64, f64), digital: (i32, i32)) -> Signal {
+            Signal {
+                label,
+                physical,
+                digital,
+                records: Vec::new(),
+            }
+        }
+
+        fn edf(mut self, records: &[&[i16]]) -> Signal {
+            self.records = records
+                .iter()
+                .map(|r| r.iter().flat_map(|s| s.to_le_bytes()).collect())
+                .collect();
+            self
+        }
+
+        fn bdf(mut self, records: &[&[i32]]) -> Signal {
+            self.records = records
+                .iter()
+                .map(|r| {
+                    r.iter()
+                        .flat_map(|s| s.to_le_bytes()[..3].to_vec())
+                        .collect()
+                })
+                .collect();
+            self
+        }
+
+        // Annotations signal of `samples` 2-byte samples per record, padded with zeros
+        fn annotations(samples: usize, records: &[&str]) -> Signal {
+            let mut signal = Signal::new("EDF Annotations", (-1.0, 1.0), (-32768, 32767));
+            signal.records = records
+                .iter()
+                .map(|tals| {
+                    let mut bytes = tals.as_bytes().to_vec();
+                    bytes.resize(samples * 2, 0);
+                    bytes
+                })
+                .collect();
+            signal
+        }
+    }
+
+    // Writes the EDF or BDF file of the `signals`, of data records of `duration` seconds, the
+    // number of data records being left as -1 if `ongoing`
+    fn write(
+        root: &TempDir,
+        format: EdfFormat,
+        reserved: &str,
+        duration: f64,
+        ongoing: bool,
+        signals: &[Signal],
+    ) {
+        let field = |buf: &mut Vec<u8>, value: &str, width: usize| {
+            buf.extend(format!("{value:<width$}").as_bytes());
+        };
+        let sample_bytes = format.sample_bytes();
+        let num_records = signals[0].records.len();
+
+        let mut buf = Vec::new();
+        match format {
+            EdfFormat::Edf => field(&mut buf, "0", 8),
+            EdfFormat::Bdf => {
+                buf.push(0xFF);
+                field(&mut buf, "BIOSEMI", 7);
+            }
+        }
+        field(&mut buf, "X X X X", 80);
+        field(&mut buf, "Startdate X X X X", 80);
+        field(&mut buf, "31.01.24", 8);
+        field(&mut buf, "23.59.59", 8);
+        field(&mut buf, &(256 * (signals.len() + 1)).to_string(), 8);
+        field(&mut buf, reserved, 44);
+        field(
+            &mut buf,
+            if ongoing {
+                "-1".into()
+            } else {
+                num_records.to_string()
+            }
+            .as_str(),
+            8,
+        );
+        field(&mut buf, &duration.to_string(), 8);
+        field(&mut buf, &signals.len().to_string(), 4);
+        for signal in signals {
+            field(&mut buf, signal.label, 16);
+        }
+        for _ in signals {
+            field(&mut buf, "AgAgCl electrode", 80);
+        }
+        for _ in signals {
+            field(&mut buf, "uV", 8);
+        }
+        for signal in signals {
+            field(&mut buf, &signal.physical.0.to_string(), 8);
+        }
+        for signal in signals {
+            field(&mut buf, &signal.physical.1.to_string(), 8);
+        }
+        for signal in signals {
+            field(&mut buf, &signal.digital.0.to_string(), 8);
+        }
+        for signal in signals {
+            field(&mut buf, &signal.digital.1.to_string(), 8);
+        }
+        for _ in signals {
+            field(&mut buf, "HP:0.1Hz LP:75Hz", 80);
+        }
+        for signal in signals {
+            field(
+                &mut buf,
+                &(signal.records[0].len() / sample_bytes).to_string(),
+                8,
+            );
+        }
+        for _ in signals {
+            field(&mut buf, "", 32);
+        }
+        for record in 0..num_records {
+            for signal in signals {
+                buf.extend(&signal.records[record]);
+            }
+        }
+
+        let extension = match format {
+            EdfFormat::Edf => "edf",
+            EdfFormat::Bdf => "bdf",
+        };
+        let file = path(root).filename("eeg", extension);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(file, buf).unwrap();
+    }
+
+    #[test]
+    fn edf_plus_fixture_is_calibrated_and_annotated() {
+        let root = TempDir::new("edf");
+        write(
+            &root,
+            EdfFormat::Edf,
+            "EDF+C",
+            1.0,
+            false,
+            &[
+                Signal::new("Fp1", (-500.0, 500.0), (-1000, 1000))
+                    .edf(&[&[-1000, -2, 0, 2], &[4, 6, 8, 1000]]),
+                Signal::new("Cz", (-500.0, 500.0), (-1000, 1000)).edf(&[&[0, 20], &[40, 60]]),
+                Signal::annotations(
+                    16,
+                    &[
+                        "+0\x14\x14\x00+0.5\x150.25\x14Blink\x14\x00",
+                        "+1\x14\x14\x00",
+                    ],
+                ),
+            ],
+        );
+        let path = path(&root);
+        let header = EdfHeader::load(&path).unwrap();
+
+        assert_eq!(header.format, EdfFormat::Edf);
+        assert_eq!(header.version, "0");
+        assert!(header.is_plus());
+        assert_eq!((header.num_records, header.record_duration), (2, 1.0));
+        assert_eq!(header.signals.len(), 3);
+        assert_eq!(header.data_signals().count(), 2);
+        assert_eq!(header.sampling_rate(), 4.0);
+        assert_eq!(header.signals[0].physical_dimension, "uV");
+
+        let data = EdfData::load(&path, &header).unwrap();
+        assert_eq!(data.view().dim(), (2, 8));
+        assert_eq!(
+            data.channel(0).to_vec(),
+            vec![-500.0, -1.0, 0.0, 1.0, 2.0, 3.0, 4.0, 500.0]
+        );
+        // Recorded at 2 Hz, then linearly interpolated to 4 Hz
+        assert_eq!(
+            data.channel(1).to_vec(),
+            vec![0.0, 5.0, 10.0, 15.0, 20.0, 25.0, 30.0, 30.0]
+        );
+
+        let markers = &data.annotations().markers;
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].description, "Blink");
+        assert_eq!((markers[0].position, markers[0].size), (3, 1));
+        assert!(data.status().is_none());
+    }
+
+    #[test]
+    fn edf_ongoing_record_count_is_deduced_from_the_file_size() {
+        let root = TempDir::new("edf-ongoing");
+        write(
+            &root,
+            EdfFormat::Edf,
+            "",
+            0.5,
+            true,
+            &[Signal::new("Oz", (-1.0, 1.0), (-1, 1)).edf(&[&[-1, 1], &[1, -1], &[0, 0]])],
+        );
+        let header = EdfHeader::load(&path(&root)).unwrap();
+
+        assert!(!header.is_plus());
+        assert_eq!(header.num_records, 3);
+        assert_eq!(header.sampling_rate(), 4.0);
+    }
+
+    #[test]
+    fn bdf_sign_extends_24_bit_values() {
+        let root = TempDir::new("bdf");
+        let bound = (-8388608, 8388607);
+        write(
+            &root,
+            EdfFormat::Bdf,
+            "24BIT",
+            1.0,
+            false,
+            &[
+                Signal::new("A1", (bound.0 as f64, bound.1 as f64), bound)
+                    .bdf(&[&[8388607, -8388608, -8388607], &[-1, 0, 1]]),
+                Signal::new("Status", (bound.0 as f64, bound.1 as f64), bound).bdf(&[
+                    &[0x100000, 0xFF0003, 0xFF0003],
+                    &[0x100000, 0x100005, 0x100005],
+                ]),
+            ],
+        );
+        let path = path(&root);
+        let header = EdfHeader::load_bdf(&path).unwrap();
+
+        assert_eq!(header.format, EdfFormat::Bdf);
+        assert_eq!(header.version, "BIOSEMI");
+        assert_eq!(header.format.sample_bytes(), 3);
+
+        let data = EdfData::load(&path, &header).unwrap();
+        assert_eq!(
+            data.channel(0).to_vec(),
+            vec![8388607.0, -8388608.0, -8388607.0, -1.0, 0.0, 1.0]
+        );
+
+        // The system bits set in the upper byte make the status values negative
+        let status = data.status().unwrap();
+        assert_eq!(status[1], 0xFF0003u32 as i32 | !0xFFFFFF);
+        let markers = status_to_markers(&status, TRIGGER_MASK);
+        assert_eq!(
+            markers
+                .iter()
+                .map(|m| (m.description.as_str(), m.position, m.size))
+                .collect::<Vec<_>>(),
+            vec![("3", 2, 2), ("5", 5, 2)]
+        );
+    }
+
+    // Writes a plain EDF file of a single signal of 2 data records of 4 samples
+    fn write_plain(root: &TempDir) -> std::path::PathBuf {
+        write(
+            root,
+            EdfFormat::Edf,
+            "",
+            1.0,
+            false,
+            &[Signal::new("Cz", (-1.0, 1.0), (-1, 1)).edf(&[&[1, 0, -1, 0], &[1, 0, -1, 0]])],
+        );
+        path(root).filename("eeg", "edf")
+    }
+
+    #[test]
+    fn truncated_data_records_fail_instead_of_panicking() {
+        let root = TempDir::new("edf-truncated");
+        let file = write_plain(&root);
+        let mut raw = fs::read(&file).unwrap();
+        raw.truncate(raw.len() - 3);
+        fs::write(&file, raw).unwrap();
+
+        let header = EdfHeader::load(&path(&root)).unwrap();
+        assert_eq!((header.num_records, header.record_bytes()), (2, 8));
+        match EdfData::load(&path(&root), &header) {
+            Err(ReadError::InvalidDataFile { file, reason }) => assert_eq!(
+                (file.as_str(), reason.as_str()),
+                (
+                    "sub-01_task-test_eeg.edf",
+                    "525 bytes are not enough for the header record and 2 data records of 8 bytes"
+                )
+            ),
+            other => panic!("Unexpected result {other:?} !"),
+        }
+    }
+
+    #[test]
+    fn invalid_header_records_fail_instead_of_panicking() {
+        let root = TempDir::new("edf-invalid");
+        let file = write_plain(&root);
+        let raw = fs::read(&file).unwrap();
+
+        // The duration of a data record spans bytes 244 to 252
+        let mut garbage = raw.clone();
+        garbage[244..252].copy_from_slice(b"one     ");
+        let mut truncated = raw.clone();
+        truncated.truncate(300);
+        for (contents, expected) in [
+            (garbage, "invalid duration of a data record 'one'"),
+            (truncated, "truncated header record"),
+            (Vec::new(), "empty file"),
+        ] {
+            fs::write(&file, contents).unwrap();
+            match EdfHeader::load(&path(&root)) {
+                Err(ReadError::InvalidHeader { file, reason }) => assert_eq!(
+                    (file.as_str(), reason.as_str()),
+                    ("sub-01_task-test_eeg.edf", expected)
+                ),
+                other => panic!("Unexpected result {other:?} !"),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_annotations_fail_instead_of_panicking() {
+        let root = TempDir::new("edf-annotations");
+        write(
+            &root,
+            EdfFormat::Edf,
+            "EDF+C",
+            1.0,
+            false,
+            &[
+                Signal::new("Cz", (-1.0, 1.0), (-1, 1)).edf(&[&[0, 0]]),
+                Signal::annotations(8, &["+0\x14\x14\x00+soon\x14Blink\x14\x00"]),
+            ],
+        );
+        let header = EdfHeader::load(&path(&root)).unwrap();
+
+        assert!(matches!(
+            EdfData::load(&path(&root), &header),
+            Err(ReadError::InvalidDataFile { reason, .. })
+                if reason == "invalid annotation onset '+soon'"
+        ));
+    }
+}
@@ -0,0 +1,277 @@
+// * https://bids-specification.readthedocs.io/en/stable/modality-agnostic-files/events.html
+
+use std::path::Path;
+
+use super::{brainvision_core::Marker, tsv::Tsv, ReadError};
+
+// Events of a recording, from either the `events.tsv` sidecar or the markers of a marker file
+//
+// sub-<subject>[_ses-<session>]_task-<task>[_acq-<acquisition>][_run-<run>]_events.tsv
+#[derive(Debug, Clone, Default)]
+pub struct Events {
+    pub events: Vec<Event>,
+}
+
+// A single event, a row of the `events.tsv` sidecar
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    // Onset of the event, in seconds from the beginning of the recording, `None` for `n/a`
+    pub onset: Option<f64>,
+    // Duration of the event, in seconds
+    pub duration: Option<f64>,
+    pub trial_type: Option<String>,
+    pub value: Option<String>,
+    // Zero-based sample at which the event occurs
+    pub sample: Option<usize>,
+}
+
+impl Events {
+    // Load the `events.tsv` sidecar at `path`, every `n/a` value being `None`
+    // Fails with `ReadError::InvalidSidecar` if it cannot be read, or holds a non-numeric onset,
+    // duration or sample
+    pub fn load_tsv<P: AsRef<Path>>(path: P) -> Result<Events, ReadError> {
+        let tsv = Tsv::load(path)?;
+
+        let events = (0..tsv.num_rows())
+            .map(|row| {
+                Ok(Event {
+                    onset: tsv.parse_value(row, "onset")?,
+                    duration: tsv.parse_value(row, "duration")?,
+                    trial_type: tsv.parse_value(row, "trial_type")?,
+                    value: tsv.parse_value(row, "value")?,
+                    sample: tsv.parse_value(row, "sample")?,
+                })
+            })
+            .collect::<Result<Vec<Event>, ReadError>>()?;
+
+        Ok(Events { events })
+    }
+
+    // Convert the `markers` of a marker file, using the `sampling_interval` in microseconds
+    // The trial type of each event is `<Type>/<Description>`, e.g. `Stimulus/S  1`
+    pub fn from_markers(markers: &Marker, sampling_interval: f64) -> Events {
+        let to_secs = |samples: usize| samples as f64 * sampling_interval / 1e6;
+
+        let events = markers
+            .markers
+            .iter()
+            .map(|m| Event {
+                onset: Some(to_secs(m.sample())),
+                duration: Some(to_secs(m.size)),
+                trial_type: Some(if m.description.is_empty() {
+                    m.marker_type.clone()
+                } else {
+                    format!("{}/{}", m.marker_type, m.description)
+                }),
+                value: None,
+                sample: Some(m.sample()),
+            })
+            .collect();
+
+        Events { events }
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    // Zero-based sample of each event at the sampling rate `fs` in Hz, `None` for the events of
+    // neither a sample nor an onset
+    // The `sample` column takes precedence over the onset when provided
+    pub fn to_sample_indices(&self, fs: f64) -> Vec<Option<usize>> {
+        self.events
+            .iter()
+            .map(|e| {
+                e.sample
+                    .or_else(|| e.onset.map(|onset| (onset * fs).round() as usize))
+            })
+            .collect()
+    }
+
+    // Events of the given `trial_type` only
+    pub fn filter_trial_type(&self, trial_type: &str) -> Events {
+        Events {
+            events: self
+                .events
+                .iter()
+                .filter(|e| e.trial_type.as_deref() == Some(trial_type))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+// Events of a recording decimated by the integer `factor`, as by `remap_events_rational`
+pub fn remap_events(events: &Events, factor: usize, num_samples: usize) -> Events {
+    remap_events_rational(events, 1, factor, num_samples)
+}
+
+// Events of a recording resampled by the rational factor `up / down` into `num_samples` samples:
+// the sample of each event is mapped to `floor(sample * up / down)`, the events falling outside the
+// new recording being left out
+// Panics if either factor is zero
+// Onsets and durations, in seconds, are unaffected, as are the events without a sample
+pub fn remap_events_rational(
+    events: &Events,
+    up: usize,
+    down: usize,
+    num_samples: usize,
+) -> Events {
+    assert!(up > 0 && down > 0, "Resampling factors must be positive !");
+
+    Events {
+        events: events
+            .events
+            .iter()
+            .filter_map(|e| match e.sample {
+                Some(sample) => {
+                    let sample = remap_sample(sample, up, down);
+                    (sample < num_samples).then(|| Event {
+                        sample: Some(sample),
+                        ..e.clone()
+                    })
+                }
+                None => Some(e.clone()),
+            })
+            .collect(),
+    }
+}
+
+// Zero-based `sample` of a recording resampled by `up / down`, rounded down
+pub(crate) fn remap_sample(sample: usize, up: usize, down: usize) -> usize {
+    sample * up / down
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array2;
+
+    use super::*;
+    use crate::{
+        epochs::{epochs, epochs_events, OutOfBounds},
+        read::{
+            brainvision_core::Header,
+            fixtures::{path, Recording, TempDir},
+        },
+    };
+
+    #[test]
+    fn events_tsv_and_vmrk_give_identical_epochs() {
+        let root = TempDir::new("events");
+        let data = Array2::from_shape_fn((2, 200), |(c, t)| (c * 1000 + t) as f32);
+        let recording = Recording::new(&["Cz", "Pz"], data.clone()).markers(&[
+            "Stimulus,S  1,21,1,0",
+            "Stimulus,S  2,101,1,0",
+            "Response,R  1,196,1,0",
+        ]);
+        let header = Header::load(&recording.write(&root)).unwrap();
+        let marker = Marker::load(&path(&root), &header).unwrap().unwrap();
+        // At 500 Hz, the second event given by its sample only
+        let tsv = root.write(
+            "sub-01/eeg/sub-01_task-test_events.tsv",
+            "onset\tduration\ttrial_type\tsample\n\
+             0.04\t0.002\tS  1\tn/a\n\
+             n/a\tn/a\tS  2\t100\n\
+             0.39\t0.002\tR  1\tn/a\n",
+        );
+        let events = Events::load_tsv(tsv).unwrap();
+        assert_eq!(events.events[1].onset, None);
+        assert_eq!(events.events[1].duration, None);
+        assert_eq!(
+            events.to_sample_indices(500.0),
+            vec![Some(20), Some(100), Some(195)]
+        );
+
+        for out_of_bounds in [OutOfBounds::Skip, OutOfBounds::Error] {
+            let from_markers = epochs(&data, &marker.markers, -10, 10, out_of_bounds);
+            let from_tsv = epochs_events(&data, &events, 500.0, -10, 10, out_of_bounds);
+            match (from_markers, from_tsv) {
+                (Ok(a), Ok(b)) => assert_eq!(a, b),
+                (Err(a), Err(b)) => assert_eq!(a.to_string(), b.to_string()),
+                (a, b) => panic!("Mismatched results {a:?} and {b:?} !"),
+            }
+        }
+
+        let from_markers = Events::from_markers(&marker, header.sampling_interval);
+        assert_eq!(
+            from_markers.to_sample_indices(500.0),
+            events.to_sample_indices(500.0)
+        );
+    }
+
+    #[test]
+    fn events_of_no_position_are_left_out_of_epochs() {
+        let root = TempDir::new("events-missing");
+        let tsv = root.write("events.tsv", "onset\ttrial_type\nn/a\ta\n0.1\tb\n");
+        let events = Events::load_tsv(tsv).unwrap();
+        assert_eq!(events.to_sample_indices(100.0), vec![None, Some(10)]);
+
+        let data = Array2::zeros((1, 30));
+        let (epochs, used) =
+            epochs_events(&data, &events, 100.0, 0, 5, OutOfBounds::Error).unwrap();
+        assert_eq!((epochs.dim(), used), ((1, 1, 5), vec![1]));
+    }
+
+    #[test]
+    fn non_numeric_values_fail() {
+        let root = TempDir::new("events-invalid");
+        let tsv = root.write("events.tsv", "onset\tduration\n1.0\t0.5\nsoon\t0.5\n");
+
+        match Events::load_tsv(tsv) {
+            Err(ReadError::InvalidSidecar { reason, .. }) => {
+                assert_eq!(reason, "invalid onset 'soon' in row 2")
+            }
+            other => panic!("Unexpected result {other:?} !"),
+        }
+    }
+
+    fn event(sample: Option<usize>) -> Event {
+        Event {
+            onset: sample.map(|s| s as f64 / 500.0),
+            duration: Some(0.0),
+            trial_type: Some("S  1".into()),
+            value: None,
+            sample,
+        }
+    }
+
+    #[test]
+    fn remapped_events_follow_the_new_rate() {
+        // At 500 Hz, an event at exactly 2 s, another past the end of 3 s, and one of no sample
+        let events = Events {
+            events: vec![event(Some(1000)), event(Some(1600)), event(None)],
+        };
+
+        for (rate, up, down) in [
+            (250.0, 1, 2),
+            (100.0, 1, 5),
+            (200.0, 2, 5),
+            (300.0, 3, 5),
+            (1000.0, 2, 1),
+        ] {
+            let num_samples = (3.0 * rate) as usize;
+            let remapped = remap_events_rational(&events, up, down, num_samples);
+
+            assert_eq!(
+                remapped.events.iter().map(|e| e.sample).collect::<Vec<_>>(),
+                vec![Some((2.0 * rate) as usize), None],
+                "{rate}"
+            );
+            assert_eq!(remapped.events[0].onset, events.events[0].onset);
+        }
+
+        // Rounded down
+        let events = Events {
+            events: vec![event(Some(7))],
+        };
+        assert_eq!(remap_events(&events, 4, 100).events[0].sample, Some(1));
+        assert_eq!(
+            remap_events(&events, 4, 100).events,
+            remap_events_rational(&events, 1, 4, 100).events
+        );
+    }
+}
@@ -0,0 +1,198 @@
+// Small BIDS datasets written to temporary directories, for the tests of the readers
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use ndarray::Array2;
+
+use super::BIDSPath;
+
+// Subject and task of the recordings written by `Recording::write`
+pub(crate) const SUBJECT: &str = "01";
+pub(crate) const TASK: &str = "test";
+
+// Number of temporary directories created so far, telling apart those of concurrent tests
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// Directory under the temporary directory of the system, removed along with its contents on drop
+pub(crate) struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub(crate) fn new(name: &str) -> TempDir {
+        let path = std::env::temp_dir().join(format!(
+            "rusty-brain-{name}-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+
+        TempDir { path }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Writes the `contents` to the file at the `relative` path, creating its directories
+    pub(crate) fn write(&self, relative: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.path.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+// Binary format of the data file of a `Recording`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Format {
+    Float32,
+    Int16,
+}
+
+// A BrainVision recording, written as `sub-01_task-test_eeg.{vhdr,vmrk,eeg}`
+pub(crate) struct Recording {
+    // Channel infos, e.g. `Fp1,,1,µV`
+    pub(crate) channels: Vec<String>,
+    // Values (channels x samples), rounded for `Format::Int16`
+    pub(crate) data: Array2<f32>,
+    // Sampling interval, in microseconds
+    pub(crate) sampling_interval: f64,
+    pub(crate) format: Format,
+    pub(crate) vectorized: bool,
+    pub(crate) big_endian: bool,
+    // Marker infos, e.g. `Stimulus,S  1,11,1,0`, or `None` for no marker file
+    pub(crate) markers: Option<Vec<String>>,
+    // Lines appended to the `[Common Infos]` section of the header
+    pub(crate) common_infos: Vec<String>,
+    // Sections appended to the header, e.g. `[Coordinates]` and its lines
+    pub(crate) extra: String,
+}
+
+impl Recording {
+    // Recording of the `data` (channels x samples) at 500 Hz, in microvolts of a resolution of 1,
+    // the channels being named `names`
+    pub(crate) fn new(names: &[&str], data: Array2<f32>) -> Recording {
+        assert_eq!(names.len(), data.nrows());
+
+        Recording {
+            channels: names.iter().map(|name| format!("{name},,1,µV")).collect(),
+            data,
+            sampling_interval: 2000.0,
+            format: Format::Float32,
+            vectorized: false,
+            big_endian: false,
+            markers: Some(Vec::new()),
+            common_infos: Vec::new(),
+            extra: String::new(),
+        }
+    }
+
+    pub(crate) fn markers(mut self, markers: &[&str]) -> Recording {
+        self.markers = Some(markers.iter().map(|m| m.to_string()).collect());
+        self
+    }
+
+    pub(crate) fn header(&self) -> String {
+        let mut header = String::from("Brain Vision Data Exchange Header File Version 1.0\n\n");
+        header.push_str("[Common Infos]\nCodepage=UTF-8\n");
+        header.push_str(&format!("DataFile={}.eeg\n", stem()));
+        if self.markers.is_some() {
+            header.push_str(&format!("MarkerFile={}.vmrk\n", stem()));
+        }
+        header.push_str("DataFormat=BINARY\n");
+        header.push_str(&format!(
+            "DataOrientation={}\n",
+            if self.vectorized {
+                "VECTORIZED"
+            } else {
+                "MULTIPLEXED"
+            }
+        ));
+        header.push_str(&format!("NumberOfChannels={}\n", self.channels.len()));
+        header.push_str(&format!("SamplingInterval={}\n", self.sampling_interval));
+        for line in &self.common_infos {
+            header.push_str(&format!("{line}\n"));
+        }
+
+        header.push_str("\n[Binary Infos]\n");
+        header.push_str(match self.format {
+            Format::Float32 => "BinaryFormat=IEEE_FLOAT_32\n",
+            Format::Int16 => "BinaryFormat=INT_16\n",
+        });
+        if self.big_endian {
+            header.push_str("UseBigEndianOrder=YES\n");
+        }
+
+        header.push_str("\n[Channel Infos]\n");
+        for (i, channel) in self.channels.iter().enumerate() {
+            header.push_str(&format!("Ch{}={channel}\n", i + 1));
+        }
+        header.push_str(&self.extra);
+
+        header
+    }
+
+    // Values of the data file, in the orientation, format and byte order of the recording
+    pub(crate) fn data_bytes(&self) -> Vec<u8> {
+        let values = if self.vectorized {
+            self.data.iter().copied().collect::<Vec<f32>>()
+        } else {
+            self.data.t().iter().copied().collect()
+        };
+
+        values
+            .into_iter()
+            .flat_map(|value| match (self.format, self.big_endian) {
+                (Format::Float32, false) => value.to_le_bytes().to_vec(),
+                (Format::Float32, true) => value.to_be_bytes().to_vec(),
+                (Format::Int16, false) => (value.round() as i16).to_le_bytes().to_vec(),
+                (Format::Int16, true) => (value.round() as i16).to_be_bytes().to_vec(),
+            })
+            .collect()
+    }
+
+    // Writes the header, marker and data files of the recording under the dataset `root`
+    pub(crate) fn write(&self, root: &TempDir) -> BIDSPath<'static, PathBuf> {
+        root.write(&format!("{}.vhdr", relative()), self.header());
+        if let Some(markers) = &self.markers {
+            let mut contents = format!(
+                "Brain Vision Data Exchange Marker File, Version 1.0\n\n\
+                 [Common Infos]\nCodepage=UTF-8\nDataFile={}.eeg\n\n[Marker Infos]\n",
+                stem()
+            );
+            for (i, marker) in markers.iter().enumerate() {
+                contents.push_str(&format!("Mk{}={marker}\n", i + 1));
+            }
+            root.write(&format!("{}.vmrk", relative()), contents);
+        }
+        root.write(&format!("{}.eeg", relative()), self.data_bytes());
+
+        path(root)
+    }
+}
+
+// Path of the recordings written by `Recording::write` under the dataset `root`
+pub(crate) fn path(root: &TempDir) -> BIDSPath<'static, PathBuf> {
+    BIDSPath::new(root.path().to_path_buf(), SUBJECT).task(TASK)
+}
+
+fn stem() -> String {
+    format!("sub-{SUBJECT}_task-{TASK}_eeg")
+}
+
+fn relative() -> String {
+    format!("sub-{SUBJECT}/eeg/{}", stem())
+}
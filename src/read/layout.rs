@@ -0,0 +1,331 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::BIDSPath;
+
+// The recordings available in a BIDS-compliant dataset, for a given type of data recorded
+//
+// sub-<subject>/
+//     -> [ses-<ses>]/
+//         -> <datatype>/
+//             -> sub-<subject>[_ses-<session>]_task-<task>[_acq-<acquisition>][_run-<run>]_<datatype>.*
+#[derive(Debug)]
+pub struct BIDSLayout {
+    root: PathBuf,
+    datatype: String,
+    // Sessions of each subject, empty for subjects without sessions
+    sessions: BTreeMap<String, Vec<String>>,
+    recordings: Vec<Recording>,
+}
+
+// A recording, uniquely identified by its entities
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Recording {
+    root: PathBuf,
+    pub subject: String,
+    pub session: Option<String>,
+    pub task: String,
+    pub acquisition: Option<String>,
+    pub run: Option<String>,
+    pub datatype: String,
+}
+
+// Entities to match recordings against, `None` matching any value
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Query<'a> {
+    pub subject: Option<&'a str>,
+    pub session: Option<&'a str>,
+    pub task: Option<&'a str>,
+    pub acquisition: Option<&'a str>,
+    pub run: Option<&'a str>,
+}
+
+impl BIDSLayout {
+    // Walk the dataset at `root`, collecting the recordings of the given `datatype`, e.g. `eeg`
+    // Hidden files and directories, as well as `derivatives/`, are ignored
+    pub fn scan<P: AsRef<Path>>(root: P, datatype: &str) -> BIDSLayout {
+        let root = root.as_ref().to_path_buf();
+        let mut sessions = BTreeMap::new();
+        let mut recordings = BTreeSet::new();
+
+        for (subject, subject_dir) in entries_with_prefix(&root, "sub-") {
+            let subject_sessions = entries_with_prefix(&subject_dir, "ses-");
+
+            let mut dirs = subject_sessions
+                .iter()
+                .map(|(session, dir)| (Some(session.clone()), dir.join(datatype)))
+                .collect::<Vec<(Option<String>, PathBuf)>>();
+            dirs.push((None, subject_dir.join(datatype)));
+
+            for (session, dir) in dirs {
+                let Ok(files) = fs::read_dir(&dir) else {
+                    continue;
+                };
+
+                for file in files.flatten() {
+                    let name = file.file_name().to_string_lossy().into_owned();
+                    if name.starts_with('.') || !file.path().is_file() {
+                        continue;
+                    }
+
+                    let Some(entities) = Entities::parse(&name) else {
+                        continue;
+                    };
+                    if entities.suffix != datatype
+                        || entities.get("sub") != Some(subject.as_str())
+                        || entities.get("ses") != session.as_deref()
+                    {
+                        continue;
+                    }
+                    let Some(task) = entities.get("task") else {
+                        continue;
+                    };
+
+                    recordings.insert(Recording {
+                        root: root.clone(),
+                        subject: subject.clone(),
+                        session: session.clone(),
+                        task: task.into(),
+                        acquisition: entities.get("acq").map(Into::into),
+                        run: entities.get("run").map(Into::into),
+                        datatype: datatype.into(),
+                    });
+                }
+            }
+
+            sessions.insert(
+                subject,
+                subject_sessions.into_iter().map(|(s, _)| s).collect(),
+            );
+        }
+
+        BIDSLayout {
+            root,
+            datatype: datatype.into(),
+            sessions,
+            recordings: recordings.into_iter().collect(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn datatype(&self) -> &str {
+        &self.datatype
+    }
+
+    pub fn subjects(&self) -> Vec<&str> {
+        self.sessions.keys().map(String::as_str).collect()
+    }
+
+    // Sessions of the `subject`, empty if it has none or does not exist
+    pub fn sessions(&self, subject: &str) -> Vec<&str> {
+        self.sessions
+            .get(subject)
+            .map_or_else(Vec::new, |s| s.iter().map(String::as_str).collect())
+    }
+
+    pub fn recordings(&self) -> &[Recording] {
+        &self.recordings
+    }
+
+    // Recordings matching every entity specified in the `query`
+    pub fn query(&self, query: &Query) -> Vec<&Recording> {
+        let matches = |value: Option<&str>, expected: Option<&str>| {
+            expected.is_none_or(|expected| value == Some(expected))
+        };
+
+        self.recordings
+            .iter()
+            .filter(|r| {
+                matches(Some(&r.subject), query.subject)
+                    && matches(r.session.as_deref(), query.session)
+                    && matches(Some(&r.task), query.task)
+                    && matches(r.acquisition.as_deref(), query.acquisition)
+                    && matches(r.run.as_deref(), query.run)
+            })
+            .collect()
+    }
+}
+
+impl Recording {
+    // The path to the recording, to be provided to the loaders
+    pub fn bids_path(&self) -> BIDSPath<'_, &Path> {
+        let mut path = BIDSPath::new(self.root.as_path(), &self.subject)
+            .task(&self.task)
+            .datatype(&self.datatype);
+        if let Some(session) = &self.session {
+            path = path.session(session);
+        }
+        if let Some(acquisition) = &self.acquisition {
+            path = path.acquisition(acquisition);
+        }
+        if let Some(run) = &self.run {
+            path = path.run(run);
+        }
+
+        path
+    }
+}
+
+// The key-value entities and the suffix of a BIDS filename
+//
+// <key>-<value>[_<key>-<value>]*_<suffix>.<extension>
+struct Entities<'a> {
+    pairs: Vec<(&'a str, &'a str)>,
+    suffix: &'a str,
+}
+
+impl<'a> Entities<'a> {
+    fn parse(filename: &'a str) -> Option<Entities<'a>> {
+        let stem = filename.split('.').next()?;
+        let (entities, suffix) = stem.rsplit_once('_')?;
+
+        let pairs = entities
+            .split('_')
+            .map(|pair| pair.split_once('-'))
+            .collect::<Option<Vec<(&str, &str)>>>()?;
+
+        Some(Entities { pairs, suffix })
+    }
+
+    fn get(&self, key: &str) -> Option<&'a str> {
+        self.pairs.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    }
+}
+
+// The non-hidden directories in `dir` named `<prefix><label>`, along with their label
+pub(super) fn entries_with_prefix(dir: &Path, prefix: &str) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut entries = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            name.strip_prefix(prefix)
+                .map(|label| (label.into(), e.path()))
+        })
+        .collect::<Vec<(String, PathBuf)>>();
+    entries.sort();
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::fixtures::TempDir;
+
+    // Two subjects, the first with two sessions and the second without any, along with files
+    // that are not recordings
+    fn dataset() -> TempDir {
+        let root = TempDir::new("layout");
+        for file in [
+            "sub-01/ses-01/eeg/sub-01_ses-01_task-rest_eeg.vhdr",
+            "sub-01/ses-01/eeg/sub-01_ses-01_task-rest_eeg.eeg",
+            "sub-01/ses-01/eeg/sub-01_ses-01_task-rest_channels.tsv",
+            "sub-01/ses-02/eeg/sub-01_ses-02_task-oddball_run-1_eeg.vhdr",
+            "sub-01/ses-02/eeg/sub-01_ses-02_task-oddball_run-2_eeg.vhdr",
+            "sub-02/eeg/sub-02_task-rest_acq-dense_eeg.vhdr",
+            "sub-02/eeg/sub-02_task-oddball_eeg.edf",
+            "sub-02/eeg/.sub-02_task-hidden_eeg.vhdr",
+            "sub-02/anat/sub-02_T1w.nii",
+            "derivatives/sub-03/eeg/sub-03_task-rest_eeg.vhdr",
+            "participants.tsv",
+        ] {
+            root.write(file, "");
+        }
+
+        root
+    }
+
+    #[test]
+    fn scan_enumerates_subjects_sessions_and_recordings() {
+        let dataset = dataset();
+        let layout = BIDSLayout::scan(dataset.path(), "eeg");
+
+        assert_eq!(layout.subjects(), vec!["01", "02"]);
+        assert_eq!(layout.sessions("01"), vec!["01", "02"]);
+        assert!(layout.sessions("02").is_empty());
+        assert!(layout.sessions("03").is_empty());
+
+        let recordings = layout
+            .recordings()
+            .iter()
+            .map(|r| {
+                (
+                    r.subject.as_str(),
+                    r.session.as_deref(),
+                    r.task.as_str(),
+                    r.acquisition.as_deref(),
+                    r.run.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            recordings,
+            vec![
+                ("01", Some("01"), "rest", None, None),
+                ("01", Some("02"), "oddball", None, Some("1")),
+                ("01", Some("02"), "oddball", None, Some("2")),
+                ("02", None, "oddball", None, None),
+                ("02", None, "rest", Some("dense"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn query_matches_every_given_entity() {
+        let dataset = dataset();
+        let layout = BIDSLayout::scan(dataset.path(), "eeg");
+
+        let oddball = Query {
+            task: Some("oddball"),
+            ..Default::default()
+        };
+        assert_eq!(layout.query(&oddball).len(), 3);
+        assert_eq!(
+            layout
+                .query(&Query {
+                    subject: Some("01"),
+                    run: Some("2"),
+                    ..oddball
+                })
+                .len(),
+            1
+        );
+        assert!(layout
+            .query(&Query {
+                session: Some("01"),
+                subject: Some("02"),
+                ..Default::default()
+            })
+            .is_empty());
+    }
+
+    #[test]
+    fn recordings_point_to_their_files() {
+        let dataset = dataset();
+        let layout = BIDSLayout::scan(dataset.path(), "eeg");
+
+        let run = layout.query(&Query {
+            run: Some("2"),
+            ..Default::default()
+        })[0];
+        let path = run.bids_path();
+        assert!(path.validate().is_ok());
+        assert_eq!(
+            path.filename("eeg", "vhdr"),
+            dataset
+                .path()
+                .join("sub-01/ses-02/eeg/sub-01_ses-02_task-oddball_run-2_eeg.vhdr")
+        );
+    }
+}
@@ -1,39 +1,458 @@
-use std::path::{Path, PathBuf};
+use std::{
+    error::Error,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
 
 pub mod brainvision_core;
+pub mod channels;
+pub mod edf;
+pub mod events;
+#[cfg(test)]
+pub(crate) mod fixtures;
+pub mod layout;
+pub mod montage;
+pub mod participants;
+pub mod raw;
+pub mod sidecar;
+mod tsv;
 
-// The path to a BIDS-compliant data recording root
-// Each recording is uniquely identified by a `root`, `subject`, `session` and a type of data recorded
-// and points to a directory:
+// The path to a BIDS-compliant data recording
+// Each recording is uniquely identified by a `root`, `subject`, `session`, the type of data recorded
+// and the `task`, `acquisition` and `run` entities, and points to files of a directory:
 //
 // sub-<subject>/
 //     -> [ses-<ses>]/
 //         -> eeg/
+//             -> sub-<subject>[_ses-<session>]_task-<task>[_acq-<acquisition>][_run-<run>]_<suffix>.<extension>
 //
 // As of now, it is adapted specifically for the EEG specification
 // <https://bids-specification.readthedocs.io/en/stable/modality-specific-files/electroencephalography.html>
 pub struct BIDSPath<'a, P: AsRef<Path>> {
-    path: PathBuf,
     root: P,
     subject: &'a str,
     session: Option<&'a str>,
+    task: Option<&'a str>,
+    acquisition: Option<&'a str>,
+    run: Option<&'a str>,
     datatype: &'a str,
 }
 
 impl<'a, P: AsRef<Path>> BIDSPath<'a, P> {
-    pub fn new(root: P, subject: &'a str, session: Option<&'a str>, datatype: &'a str) -> Self {
-        let mut path = root.as_ref().join(format!("sub-{subject}"));
-        if let Some(session) = session {
-            path.push(format!("ses-{session}"));
-        }
-        path.push(datatype);
-
+    // The path to the EEG recordings of a `subject`, further identified through the builder
+    // methods, e.g. `BIDSPath::new(root, "01").session("01").task("rest").run("1")`
+    pub fn new(root: P, subject: &'a str) -> Self {
         Self {
-            path,
             root,
             subject,
-            session,
-            datatype,
+            session: None,
+            task: None,
+            acquisition: None,
+            run: None,
+            datatype: "eeg",
+        }
+    }
+
+    pub fn session(mut self, session: &'a str) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    pub fn task(mut self, task: &'a str) -> Self {
+        self.task = Some(task);
+        self
+    }
+
+    pub fn acquisition(mut self, acquisition: &'a str) -> Self {
+        self.acquisition = Some(acquisition);
+        self
+    }
+
+    pub fn run(mut self, run: &'a str) -> Self {
+        self.run = Some(run);
+        self
+    }
+
+    // Type of data recorded, `eeg` by default
+    pub fn datatype(mut self, datatype: &'a str) -> Self {
+        self.datatype = datatype;
+        self
+    }
+
+    // The directory holding the files of the recording
+    pub fn directory(&self) -> PathBuf {
+        let mut path = self.root.as_ref().join(format!("sub-{}", self.subject));
+        if let Some(session) = self.session {
+            path.push(format!("ses-{session}"));
+        }
+        path.push(self.datatype);
+
+        path
+    }
+
+    // The path to a file of the recording, with the entities always in the BIDS-mandated order
+    //
+    // sub-<subject>[_ses-<session>][_task-<task>][_acq-<acquisition>][_run-<run>]_<suffix>.<extension>
+    pub fn filename(&self, suffix: &str, extension: &str) -> PathBuf {
+        self.directory().join(format!(
+            "{}_{suffix}.{}",
+            self.entities(),
+            extension.trim_start_matches('.')
+        ))
+    }
+
+    // The key-value entities shared by every file of the recording
+    fn entities(&self) -> String {
+        let mut entities = format!("sub-{}", self.subject);
+        for (key, value) in [
+            ("ses", self.session),
+            ("task", self.task),
+            ("acq", self.acquisition),
+            ("run", self.run),
+        ] {
+            if let Some(value) = value {
+                entities.push_str(&format!("_{key}-{value}"));
+            }
+        }
+
+        entities
+    }
+
+    // Check that every level of the path exists, down to at least one file of the recording
+    // Fails with `ReadError::PathNotFound` naming the first missing level, along with the
+    // alternatives available at that level
+    pub fn validate(&self) -> Result<(), ReadError> {
+        let root = self.root.as_ref();
+        if !root.is_dir() {
+            return Err(ReadError::PathNotFound {
+                level: PathLevel::Root,
+                value: root.display().to_string(),
+                available: Vec::new(),
+            });
+        }
+
+        let labels = |dir: &Path, prefix: &str| {
+            layout::entries_with_prefix(dir, prefix)
+                .into_iter()
+                .map(|(label, _)| label)
+                .collect::<Vec<String>>()
+        };
+
+        let mut dir = root.join(format!("sub-{}", self.subject));
+        if !dir.is_dir() {
+            return Err(ReadError::PathNotFound {
+                level: PathLevel::Subject,
+                value: self.subject.into(),
+                available: labels(root, "sub-"),
+            });
+        }
+
+        if let Some(session) = self.session {
+            let session_dir = dir.join(format!("ses-{session}"));
+            if !session_dir.is_dir() {
+                return Err(ReadError::PathNotFound {
+                    level: PathLevel::Session,
+                    value: session.into(),
+                    available: labels(&dir, "ses-"),
+                });
+            }
+            dir = session_dir;
         }
+
+        let datatype_dir = dir.join(self.datatype);
+        if !datatype_dir.is_dir() {
+            return Err(ReadError::PathNotFound {
+                level: PathLevel::Datatype,
+                value: self.datatype.into(),
+                available: subdirectories(&dir),
+            });
+        }
+
+        // Files of the recording share every entity, only differing by suffix and extension
+        let entities = self.entities();
+        let files = file_names(&datatype_dir);
+        if !files.iter().any(|f| f.starts_with(&format!("{entities}_"))) {
+            let mut available = files
+                .iter()
+                .filter_map(|f| f.rsplit_once('_').map(|(entities, _)| entities.to_string()))
+                .collect::<Vec<String>>();
+            available.sort();
+            available.dedup();
+
+            return Err(ReadError::PathNotFound {
+                level: PathLevel::Recording,
+                value: entities,
+                available,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+// The non-hidden subdirectories of `dir`
+fn subdirectories(dir: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .filter(|name| !name.starts_with('.'))
+                .collect::<Vec<String>>()
+        })
+        .unwrap_or_default();
+    names.sort();
+
+    names
+}
+
+// The non-hidden file names of `dir`
+fn file_names(dir: &Path) -> Vec<String> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_file())
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .filter(|name| !name.starts_with('.'))
+                .collect::<Vec<String>>()
+        })
+        .unwrap_or_default()
+}
+
+// Level of a `BIDSPath`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathLevel {
+    Root,
+    Subject,
+    Session,
+    Datatype,
+    // Files sharing the entities of the path
+    Recording,
+}
+
+impl Display for PathLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PathLevel::Root => "root",
+            PathLevel::Subject => "subject",
+            PathLevel::Session => "session",
+            PathLevel::Datatype => "datatype",
+            PathLevel::Recording => "recording",
+        })
+    }
+}
+
+// Errors that can occur while loading recorded data
+#[derive(Debug)]
+pub enum ReadError {
+    // A level of a `BIDSPath` does not exist
+    PathNotFound {
+        level: PathLevel,
+        value: String,
+        // Closest existing alternatives, at the same level
+        available: Vec<String>,
+    },
+    // The `NumberOfChannels` of a header differs from the number of channels it lists
+    ChannelCountMismatch {
+        declared: usize,
+        listed: usize,
+    },
+    // The size of a data file does not amount to a whole number of samples
+    DataSizeMismatch {
+        file_bytes: usize,
+        num_channels: usize,
+        value_bytes: usize,
+    },
+    // The samples of a segmented data file do not amount to a whole number of segments
+    SegmentMismatch {
+        num_samples: usize,
+        segment_data_points: usize,
+    },
+    // The number of `New Segment` markers differs from the number of segments of the data
+    SegmentCountMismatch {
+        expected: usize,
+        found: usize,
+    },
+    // A `New Segment` marker does not lie at the start of its segment
+    SegmentBoundaryMismatch {
+        segment: usize,
+        expected: usize,
+        found: usize,
+    },
+    // The data set is neither segmented by its header, nor by `New Segment` markers
+    NotSegmented,
+    // The marker file referenced by a header exists, but cannot be read or parsed
+    InvalidMarkerFile {
+        file: String,
+        reason: String,
+    },
+    // The header file of a recording cannot be read or parsed, or lacks mandatory keys
+    InvalidHeader {
+        file: String,
+        reason: String,
+    },
+    // The data file of a recording cannot be opened or read
+    InvalidDataFile {
+        file: String,
+        reason: String,
+    },
+    // A sidecar file exists, but cannot be read or holds invalid values
+    InvalidSidecar {
+        file: String,
+        reason: String,
+    },
+    // There are no runs to be concatenated
+    NoRuns,
+    // A run to be concatenated does not have the same channels as the first run
+    ChannelSetMismatch {
+        run: usize,
+        // Channels of the first run the run lacks
+        missing: Vec<String>,
+        // Channels of the run the first run lacks
+        unexpected: Vec<String>,
+    },
+    // A run to be concatenated is not sampled at the rate of the first run, in Hz
+    SamplingRateMismatch {
+        run: usize,
+        expected: f64,
+        found: f64,
+    },
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::PathNotFound {
+                level,
+                value,
+                available,
+            } => {
+                write!(f, "{level} '{value}' not found")?;
+                if available.is_empty() {
+                    Ok(())
+                } else {
+                    write!(f, "; available: {}", available.join(", "))
+                }
+            }
+            ReadError::ChannelCountMismatch { declared, listed } => {
+                write!(f, "header declares {declared} channels, but lists {listed}")
+            }
+            ReadError::DataSizeMismatch {
+                file_bytes,
+                num_channels,
+                value_bytes,
+            } => write!(
+                f,
+                "data file has {file_bytes} bytes, which is not a multiple of {num_channels} \
+                 channels x {value_bytes} bytes per value ({} trailing bytes)",
+                file_bytes % (num_channels * value_bytes)
+            ),
+            ReadError::SegmentMismatch {
+                num_samples,
+                segment_data_points,
+            } => write!(
+                f,
+                "data file has {num_samples} samples, which is not a multiple of \
+                 {segment_data_points} data points per segment"
+            ),
+            ReadError::SegmentCountMismatch { expected, found } => write!(
+                f,
+                "data has {expected} segments, but {found} 'New Segment' markers"
+            ),
+            ReadError::SegmentBoundaryMismatch {
+                segment,
+                expected,
+                found,
+            } => write!(
+                f,
+                "segment {segment} starts at sample {expected}, \
+                 but its 'New Segment' marker is at sample {found}"
+            ),
+            ReadError::NotSegmented => f.write_str("data set is not segmented"),
+            ReadError::InvalidMarkerFile { file, reason } => {
+                write!(f, "invalid marker file '{file}': {reason}")
+            }
+            ReadError::InvalidHeader { file, reason } => {
+                write!(f, "invalid header file '{file}': {reason}")
+            }
+            ReadError::InvalidDataFile { file, reason } => {
+                write!(f, "invalid data file '{file}': {reason}")
+            }
+            ReadError::InvalidSidecar { file, reason } => {
+                write!(f, "invalid sidecar '{file}': {reason}")
+            }
+            ReadError::NoRuns => f.write_str("no runs to concatenate"),
+            ReadError::ChannelSetMismatch {
+                run,
+                missing,
+                unexpected,
+            } => {
+                write!(f, "run {run} does not have the channels of the first run")?;
+                if !missing.is_empty() {
+                    write!(f, "; missing: {}", missing.join(", "))?;
+                }
+                if !unexpected.is_empty() {
+                    write!(f, "; unexpected: {}", unexpected.join(", "))?;
+                }
+                Ok(())
+            }
+            ReadError::SamplingRateMismatch {
+                run,
+                expected,
+                found,
+            } => write!(
+                f,
+                "run {run} is sampled at {found} Hz, but the first run at {expected} Hz"
+            ),
+        }
+    }
+}
+
+impl Error for ReadError {}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array2;
+
+    use super::{
+        brainvision_core::{Data, Header, Validation},
+        fixtures::{Recording, TempDir},
+        *,
+    };
+
+    #[test]
+    fn filenames_follow_the_order_of_the_entities() {
+        let path = BIDSPath::new("/data", "01")
+            .run("2")
+            .acquisition("dense")
+            .task("rest")
+            .session("a");
+
+        assert_eq!(path.directory(), Path::new("/data/sub-01/ses-a/eeg"));
+        assert_eq!(
+            path.filename("eeg", ".vhdr"),
+            Path::new("/data/sub-01/ses-a/eeg/sub-01_ses-a_task-rest_acq-dense_run-2_eeg.vhdr")
+        );
+        assert_eq!(
+            BIDSPath::new("/data", "01")
+                .datatype("meg")
+                .filename("channels", "tsv"),
+            Path::new("/data/sub-01/meg/sub-01_channels.tsv")
+        );
+    }
+
+    #[test]
+    fn loaders_read_the_files_named_by_the_path() {
+        let root = TempDir::new("bids-path");
+        let data = Array2::from_shape_fn((2, 6), |(c, t)| (c * 6 + t) as f32);
+        let path = Recording::new(&["Cz", "Pz"], data.clone()).write(&root);
+
+        let header = Header::load(&path).unwrap();
+        assert_eq!(header.data_file, "sub-01_task-test_eeg.eeg");
+        let loaded = Data::<f32>::load(&path, &header, Validation::Strict).unwrap();
+        assert_eq!(loaded.view(), data);
     }
 }
@@ -0,0 +1,91 @@
+// Standard electrode positions of the 10-20 and 10-10 systems, on a unit sphere, in the spherical
+// convention of the `[Coordinates]` section of a BrainVision header:
+// - theta: inclination from the vertex (Cz), in degrees, negative over the left hemisphere
+// - phi: angle from the right (T8) towards the front (Fpz), in degrees
+//
+// The positions are those of an idealised, equidistant cap, and only approximate any actual montage
+use super::brainvision_core::Coordinates;
+
+// <Name>, <Theta>, <Phi>
+const STANDARD_1020: &[(&str, f64, f64)] = &[
+    // Midline
+    ("Fpz", 90.0, 90.0),
+    ("AFz", 67.0, 90.0),
+    ("Fz", 45.0, 90.0),
+    ("FCz", 23.0, 90.0),
+    ("Cz", 0.0, 0.0),
+    ("CPz", 23.0, -90.0),
+    ("Pz", 45.0, -90.0),
+    ("POz", 67.0, -90.0),
+    ("Oz", 90.0, -90.0),
+    // Left hemisphere
+    ("Fp1", -90.0, -72.0),
+    ("AF7", -90.0, -54.0),
+    ("AF3", -74.0, -65.0),
+    ("F7", -90.0, -36.0),
+    ("F5", -75.0, -41.0),
+    ("F3", -60.0, -51.0),
+    ("F1", -50.0, -68.0),
+    ("FT9", -113.0, -18.0),
+    ("FT7", -90.0, -18.0),
+    ("FC5", -69.0, -21.0),
+    ("FC1", -31.0, -46.0),
+    ("T7", -90.0, 0.0),
+    ("C5", -68.0, 0.0),
+    ("C3", -45.0, 0.0),
+    ("C1", -23.0, 0.0),
+    ("TP9", -113.0, 18.0),
+    ("TP7", -90.0, 18.0),
+    ("CP5", -69.0, 21.0),
+    ("CP1", -31.0, 46.0),
+    ("P7", -90.0, 36.0),
+    ("P5", -75.0, 41.0),
+    ("P3", -60.0, 51.0),
+    ("P1", -50.0, 68.0),
+    ("PO7", -90.0, 54.0),
+    ("PO3", -74.0, 65.0),
+    ("O1", -90.0, 72.0),
+    // Right hemisphere
+    ("Fp2", 90.0, 72.0),
+    ("AF8", 90.0, 54.0),
+    ("AF4", 74.0, 65.0),
+    ("F8", 90.0, 36.0),
+    ("F6", 75.0, 41.0),
+    ("F4", 60.0, 51.0),
+    ("F2", 50.0, 68.0),
+    ("FT10", 113.0, 18.0),
+    ("FT8", 90.0, 18.0),
+    ("FC6", 69.0, 21.0),
+    ("FC2", 31.0, 46.0),
+    ("T8", 90.0, 0.0),
+    ("C6", 68.0, 0.0),
+    ("C4", 45.0, 0.0),
+    ("C2", 23.0, 0.0),
+    ("TP10", 113.0, -18.0),
+    ("TP8", 90.0, -18.0),
+    ("CP6", 69.0, -21.0),
+    ("CP2", 31.0, -46.0),
+    ("P8", 90.0, -36.0),
+    ("P6", 75.0, -41.0),
+    ("P4", 60.0, -51.0),
+    ("P2", 50.0, -68.0),
+    ("PO8", 90.0, -54.0),
+    ("PO4", 74.0, -65.0),
+    ("O2", 90.0, -72.0),
+];
+
+// Names of the original 10-20 system, renamed in the 10-10 system
+const ALIASES: &[(&str, &str)] = &[("T3", "T7"), ("T4", "T8"), ("T5", "P7"), ("T6", "P8")];
+
+// Standard position of the electrode called `name`, compared case-insensitively
+pub fn standard_position(name: &str) -> Option<Coordinates> {
+    let name = ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+        .map_or(name, |(_, name)| name);
+
+    STANDARD_1020
+        .iter()
+        .find(|(standard, _, _)| standard.eq_ignore_ascii_case(name))
+        .map(|&(_, theta, phi)| Coordinates::new(1.0, theta, phi))
+}
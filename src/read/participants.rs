@@ -0,0 +1,254 @@
+// * https://bids-specification.readthedocs.io/en/stable/modality-agnostic-files.html#participants-file
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::ErrorKind,
+    path::Path,
+};
+
+use serde_json::Value;
+
+use super::{sidecar::optional, tsv::Tsv, ReadError};
+
+// Columns of the `participants.tsv` file parsed into typed fields of `Participant`
+const KNOWN_COLUMNS: [&str; 5] = ["participant_id", "age", "sex", "handedness", "group"];
+
+// Struct containing the metadata of every participant of a dataset, provided in the
+// `participants.tsv` file at its root
+#[derive(Debug, Default)]
+pub struct Participants {
+    // Indicates whether the `participants.tsv` file exists
+    pub found: bool,
+    // Participants by their identifier, e.g. `sub-01`
+    pub participants: BTreeMap<String, Participant>,
+    // Descriptions of the columns, provided in the `participants.json` file, if present
+    pub descriptions: HashMap<String, ColumnDescription>,
+}
+
+// Metadata of a participant, a row of the `participants.tsv` file
+#[derive(Debug, Default)]
+pub struct Participant {
+    pub participant_id: String,
+    // Age, in years
+    pub age: Option<f64>,
+    pub sex: Option<String>,
+    pub handedness: Option<String>,
+    pub group: Option<String>,
+    // Every other column, kept as text
+    pub other: HashMap<String, String>,
+}
+
+// Description of a column of the `participants.tsv` file
+#[derive(Debug)]
+pub struct ColumnDescription {
+    pub long_name: Option<String>,
+    pub description: Option<String>,
+    // Meaning of each of the categorical values of the column
+    pub levels: Option<HashMap<String, String>>,
+    pub units: Option<String>,
+}
+
+impl ColumnDescription {
+    // Descriptions of the columns of `participants.json`, an object of an object per column
+    fn parse_all(value: &Value) -> Result<HashMap<String, ColumnDescription>, String> {
+        let text = |value: &Value| value.as_str().map(String::from);
+        let levels = |value: &Value| {
+            value.as_object().and_then(|levels| {
+                levels
+                    .iter()
+                    .map(|(level, meaning)| Some((level.clone(), meaning.as_str()?.into())))
+                    .collect()
+            })
+        };
+
+        value
+            .as_object()
+            .ok_or_else(|| "not a JSON object".to_string())?
+            .iter()
+            .map(|(column, description)| {
+                let keys = description
+                    .as_object()
+                    .ok_or_else(|| format!("description of {column} is not a JSON object"))?;
+
+                Ok((
+                    column.clone(),
+                    ColumnDescription {
+                        long_name: optional(keys, "LongName", text)?,
+                        description: optional(keys, "Description", text)?,
+                        levels: optional(keys, "Levels", levels)?,
+                        units: optional(keys, "Units", text)?,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+impl Participants {
+    // Load the `participants.tsv` (and `participants.json`) files at the `root` of a dataset
+    // A missing `participants.tsv` yields no participants, with `found` unset, and an `n/a` age
+    // no age
+    // Fails with `ReadError::InvalidSidecar` if either file cannot be read, a participant lacks its
+    // identifier or age is not numeric, or `participants.json` is not a valid description of the
+    // columns
+    pub fn load<P: AsRef<Path>>(root: P) -> Result<Participants, ReadError> {
+        let root = root.as_ref();
+        let tsv_path = root.join("participants.tsv");
+        if !tsv_path.is_file() {
+            return Ok(Participants::default());
+        }
+
+        let tsv = Tsv::load(tsv_path)?;
+        let participants = (0..tsv.num_rows())
+            .map(|row| {
+                let participant = Participant {
+                    participant_id: tsv.require(row, "participant_id")?.into(),
+                    age: tsv.parse_value(row, "age")?,
+                    sex: tsv.get(row, "sex").map(Into::into),
+                    handedness: tsv.get(row, "handedness").map(Into::into),
+                    group: tsv.get(row, "group").map(Into::into),
+                    other: tsv
+                        .columns()
+                        .iter()
+                        .filter(|c| !KNOWN_COLUMNS.contains(&c.as_str()))
+                        .filter_map(|c| tsv.get(row, c).map(|v| (c.clone(), v.into())))
+                        .collect(),
+                };
+
+                Ok((participant.participant_id.clone(), participant))
+            })
+            .collect::<Result<BTreeMap<String, Participant>, ReadError>>()?;
+
+        let invalid = |reason: String| ReadError::InvalidSidecar {
+            file: "participants.json".into(),
+            reason,
+        };
+        let descriptions = match fs::read_to_string(root.join("participants.json")) {
+            Ok(contents) => serde_json::from_str::<Value>(&contents)
+                .map_err(|e| invalid(e.to_string()))
+                .and_then(|value| ColumnDescription::parse_all(&value).map_err(invalid))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(invalid(e.to_string())),
+        };
+
+        Ok(Participants {
+            found: true,
+            participants,
+            descriptions,
+        })
+    }
+
+    // Metadata of the `subject`, given either as `01` or `sub-01`
+    pub fn get(&self, subject: &str) -> Option<&Participant> {
+        if subject.starts_with("sub-") {
+            self.participants.get(subject)
+        } else {
+            self.participants.get(&format!("sub-{subject}"))
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.participants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.participants.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::fixtures::TempDir;
+
+    const PARTICIPANTS: &str = "participant_id\tage\tsex\thandedness\tgroup\tiq\n\
+                                sub-01\t34\tF\tR\tcontrol\t110\n\
+                                sub-02\tn/a\tM\tL\tpatient\tn/a\n\
+                                sub-03\t27.5\tn/a\tA\tpatient\t98\n";
+
+    #[test]
+    fn numeric_and_categorical_columns_are_parsed() {
+        let root = TempDir::new("participants");
+        root.write("participants.tsv", PARTICIPANTS);
+        root.write(
+            "participants.json",
+            r#"{"group": {"Description": "Study arm",
+                          "Levels": {"control": "Healthy", "patient": "Diagnosed"}},
+                "age": {"Units": "year"}}"#,
+        );
+
+        let participants = Participants::load(root.path()).unwrap();
+        assert!(participants.found);
+        assert_eq!(participants.len(), 3);
+
+        let first = participants.get("01").unwrap();
+        assert_eq!(first.age, Some(34.0));
+        assert_eq!(
+            (
+                first.sex.as_deref(),
+                first.handedness.as_deref(),
+                first.group.as_deref()
+            ),
+            (Some("F"), Some("R"), Some("control"))
+        );
+        assert_eq!(first.other.get("iq").map(String::as_str), Some("110"));
+
+        let second = participants.get("sub-02").unwrap();
+        assert_eq!(second.age, None);
+        assert!(second.other.is_empty());
+        assert_eq!(participants.get("03").unwrap().age, Some(27.5));
+        assert_eq!(participants.get("03").unwrap().sex, None);
+        assert!(participants.get("04").is_none());
+
+        let group = &participants.descriptions["group"];
+        assert_eq!(group.description.as_deref(), Some("Study arm"));
+        assert_eq!(group.levels.as_ref().unwrap()["patient"], "Diagnosed");
+        assert_eq!(
+            participants.descriptions["age"].units.as_deref(),
+            Some("year")
+        );
+    }
+
+    #[test]
+    fn missing_participants_tsv_is_not_an_error() {
+        let root = TempDir::new("participants-missing");
+        let participants = Participants::load(root.path()).unwrap();
+
+        assert!(!participants.found);
+        assert!(participants.is_empty());
+    }
+
+    #[test]
+    fn invalid_participants_fail_instead_of_panicking() {
+        for (tsv, json, expected) in [
+            ("age\tsex\n34\tF\n", None, "missing participant_id column"),
+            (
+                "participant_id\tage\nsub-01\tthirty\n",
+                None,
+                "invalid age 'thirty' in row 1",
+            ),
+            ("participant_id\tage\nsub-01\t30\n", Some("{\"age\": "), ""),
+        ] {
+            let root = TempDir::new("participants-invalid");
+            root.write("participants.tsv", tsv);
+            if let Some(json) = json {
+                root.write("participants.json", json);
+            }
+
+            match Participants::load(root.path()) {
+                Err(ReadError::InvalidSidecar { file, reason }) if json.is_some() => {
+                    assert_eq!(file, "participants.json");
+                    assert!(!reason.is_empty());
+                }
+                Err(ReadError::InvalidSidecar { file, reason }) => {
+                    assert_eq!(
+                        (file.as_str(), reason.as_str()),
+                        ("participants.tsv", expected)
+                    )
+                }
+                other => panic!("Unexpected result {other:?} !"),
+            }
+        }
+    }
+}
@@ -0,0 +1,665 @@
+// A BrainVision recording as a whole: its header, its markers and its data, in physical values
+
+use std::{ops::Range, path::Path};
+
+use ndarray::{concatenate, s, Array1, Array2, ArrayView1, ArrayView2, Axis};
+
+use crate::{
+    filter::{rational, resample_poly},
+    reference::{rereference_average, rereference_to},
+    Error,
+};
+
+use super::{
+    brainvision_core::{
+        BinaryFormatType, Coordinates, Data, Header, LoadOptions, Marker, MarkerInfo,
+    },
+    events::{remap_sample, Events},
+    BIDSPath, ReadError,
+};
+
+// Reference the data of a `Raw` recording can be set to
+#[derive(Clone, Copy, Debug)]
+pub enum Reference<'a> {
+    // Common average of all channels, except those called as given
+    Average { exclude: &'a [&'a str] },
+    // Mean of the channels called as given, e.g. both mastoids
+    Channels(&'a [&'a str]),
+}
+
+#[derive(Clone, Debug)]
+pub struct Raw {
+    header: Header,
+    marker: Marker,
+    // Physical values of the data, oriented channels x samples
+    data: Array2<f32>,
+}
+
+impl Raw {
+    // Load the header, markers and data of a BIDS-compliant data recording
+    // The data is converted to physical values, whatever its binary format
+    pub fn load<P: AsRef<Path>>(path: &BIDSPath<P>) -> Result<Raw, ReadError> {
+        Self::load_with(path, &LoadOptions::new())
+    }
+
+    // Load only the samples and channels of a BIDS-compliant data recording selected by `options`
+    // The header and markers are restricted accordingly, as by `crop` and `pick`
+    pub fn load_with<P: AsRef<Path>>(
+        path: &BIDSPath<P>,
+        options: &LoadOptions,
+    ) -> Result<Raw, ReadError> {
+        let header = Header::load(path)?;
+        // Recordings without a marker file simply have no markers
+        let marker = Marker::load(path, &header)?.unwrap_or_else(|| Marker {
+            data_file: header.data_file.clone(),
+            markers: Vec::new(),
+        });
+
+        let indices = options
+            .channel_indices()
+            .map_or_else(|| (0..header.channels.len()).collect(), <[usize]>::to_vec);
+        let picked = pick_header(&header, &indices);
+        let data = match header.binary_format {
+            BinaryFormatType::IeeeFloat32 => {
+                Data::<f32>::load_with(path, &header, options)?.scaled(&picked)
+            }
+            BinaryFormatType::Int16 => {
+                Data::<i16>::load_with(path, &header, options)?.scaled(&picked)
+            }
+        };
+
+        let start = options.sample_range().map_or(0, |samples| samples.start);
+        let marker = pick_markers(
+            &crop_markers(&marker, start..start + data.ncols()),
+            &indices,
+        );
+
+        Ok(Raw {
+            header: picked,
+            marker,
+            data,
+        })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    pub fn marker(&self) -> &Marker {
+        &self.marker
+    }
+
+    // Physical values of the data, oriented channels x samples
+    pub fn data(&self) -> ArrayView2<'_, f32> {
+        self.data.view()
+    }
+
+    // Sampling frequency, in Hz
+    pub fn sfreq(&self) -> f64 {
+        self.header.sampling_rate_hz()
+    }
+
+    pub fn num_samples(&self) -> usize {
+        self.data.ncols()
+    }
+
+    pub fn channel_names(&self) -> Vec<&str> {
+        self.header.channels.iter().map(|c| c.name()).collect()
+    }
+
+    // Time of each sample, in seconds from the first one
+    pub fn times(&self) -> Array1<f64> {
+        self.header.times(self.num_samples())
+    }
+
+    // Data of the channel called `name`
+    pub fn get_channel(&self, name: &str) -> Option<ArrayView1<'_, f32>> {
+        self.header
+            .channel_indices(&[name])
+            .first()
+            .map(|&i| self.data.row(i))
+    }
+
+    // Events of the markers, relative to the first sample
+    pub fn events(&self) -> Events {
+        Events::from_markers(&self.marker, self.header.sampling_interval)
+    }
+
+    // Re-reference the data, updating the reference channel name of each channel
+    // Names not matching any channel are ignored
+    pub fn set_reference(&mut self, reference: Reference) {
+        let ref_name = match reference {
+            Reference::Average { exclude } => {
+                rereference_average(&mut self.data, &self.header.channel_indices(exclude));
+                "average".into()
+            }
+            Reference::Channels(names) => {
+                let indices = self.header.channel_indices(names);
+                rereference_to(&mut self.data, &indices);
+                indices
+                    .iter()
+                    .map(|&i| self.header.channels[i].name())
+                    .collect::<Vec<&str>>()
+                    .join("+")
+            }
+        };
+
+        for channel in &mut self.header.channels {
+            channel.set_ref_name(ref_name.clone());
+        }
+    }
+
+    // Recording restricted to the samples between `tmin` and `tmax` seconds, both included
+    // Markers outside of it are left out, while the rest are shifted to the new first sample
+    pub fn crop(&self, tmin: f64, tmax: f64) -> Raw {
+        let sfreq = self.sfreq();
+        let num_samples = self.num_samples();
+        let start = ((tmin * sfreq).round().max(0.0) as usize).min(num_samples);
+        let end = (((tmax * sfreq).round().max(0.0) as usize) + 1).clamp(start, num_samples);
+
+        Raw {
+            header: self.header.clone(),
+            marker: crop_markers(&self.marker, start..end),
+            data: self.data.slice(s![.., start..end]).to_owned(),
+        }
+    }
+
+    // Recording resampled to `fs_out` Hz, each channel by `resample_poly`, the ratio of the rates
+    // being approximated by the nearest fraction of a denominator up to 1000 (exact for any two
+    // integer rates of such a ratio), which sets the actual new rate
+    // Markers are moved along, as by `remap_events_rational`, spanning at least one sample
+    // Panics if `fs_out` is not positive
+    pub fn resample(&self, fs_out: f64) -> Raw {
+        assert!(fs_out > 0.0, "Sampling frequency must be positive !");
+        let (up, down) = rational(fs_out / self.sfreq());
+
+        let channels = self
+            .data
+            .outer_iter()
+            .map(|channel| resample_poly(&channel, up, down))
+            .collect::<Vec<Array1<f32>>>();
+        let num_samples = channels.first().map_or(0, Array1::len);
+        let mut data = Array2::zeros((channels.len(), num_samples));
+        for (mut row, channel) in data.outer_iter_mut().zip(&channels) {
+            row.assign(channel);
+        }
+
+        let mut header = self.header.clone();
+        header.sampling_interval = self.header.sampling_interval * down as f64 / up as f64;
+        header.segment_data_points =
+            remap_sample(header.segment_data_points as usize, up, down) as u32;
+
+        let mut marker = self.marker.clone();
+        marker.markers.retain_mut(|m| {
+            let sample = remap_sample(m.sample(), up, down);
+            m.position = sample + 1;
+            m.size = remap_sample(m.size, up, down).max(1);
+            sample < num_samples
+        });
+
+        Raw {
+            header,
+            marker,
+            data,
+        }
+    }
+
+    // Recording restricted to the channels called `names`, in the order of `names`
+    // Names not matching any channel are left out, as are the markers related to channels left out
+    pub fn pick(&self, names: &[&str]) -> Raw {
+        let indices = self.header.channel_indices(names);
+
+        Raw {
+            header: pick_header(&self.header, &indices),
+            marker: pick_markers(&self.marker, &indices),
+            data: self.data.select(Axis(0), &indices),
+        }
+    }
+}
+
+// Type of the markers of BrainVision recordings at their discontinuities, e.g. between runs
+const NEW_SEGMENT: &str = "New Segment";
+
+// Runs of a session concatenated in time, e.g. run-1, run-2, ... of a task, into one recording of
+// the header of the first run
+// The channels of each run are reordered after those of the first run, by name, and its markers
+// offset by the samples of the runs before it, a `New Segment` marker being inserted at each seam
+// unless the run already starts with one
+// Fails if a run does not have the same channels as the first run, naming those it lacks or has
+// in excess, or is not sampled at the same rate, or if there are no runs
+pub fn concatenate_raw(runs: &[Raw]) -> Result<Raw, Error> {
+    let first = runs.first().ok_or(ReadError::NoRuns)?;
+    let names = first.channel_names();
+
+    let mut reordered = Vec::with_capacity(runs.len());
+    for (run, raw) in runs.iter().enumerate() {
+        if raw.header.sampling_interval != first.header.sampling_interval {
+            return Err(ReadError::SamplingRateMismatch {
+                run,
+                expected: first.sfreq(),
+                found: raw.sfreq(),
+            }
+            .into());
+        }
+
+        let own = raw.channel_names();
+        let lacking = |names: &[&str], of: &[&str]| {
+            names
+                .iter()
+                .filter(|name| !of.contains(name))
+                .map(|&name| name.into())
+                .collect::<Vec<String>>()
+        };
+        let (missing, unexpected) = (lacking(&names, &own), lacking(&own, &names));
+        if !missing.is_empty() || !unexpected.is_empty() {
+            return Err(ReadError::ChannelSetMismatch {
+                run,
+                missing,
+                unexpected,
+            }
+            .into());
+        }
+
+        reordered.push(if own == names {
+            raw.clone()
+        } else {
+            raw.pick(&names)
+        });
+    }
+
+    let mut marker = first.marker.clone();
+    let mut offset = first.num_samples();
+    for raw in &reordered[1..] {
+        let starts_segment = raw
+            .marker
+            .markers
+            .iter()
+            .any(|m| m.marker_type == NEW_SEGMENT && m.sample() == 0);
+        if !starts_segment {
+            marker.markers.push(MarkerInfo {
+                marker_type: NEW_SEGMENT.into(),
+                description: String::new(),
+                position: offset + 1,
+                size: 1,
+                channel: 0,
+                date: None,
+            });
+        }
+        marker
+            .markers
+            .extend(raw.marker.markers.iter().map(|m| MarkerInfo {
+                position: m.position + offset,
+                ..m.clone()
+            }));
+        offset += raw.num_samples();
+    }
+
+    let views = reordered.iter().map(Raw::data).collect::<Vec<_>>();
+
+    Ok(Raw {
+        header: first.header.clone(),
+        marker,
+        data: concatenate(Axis(1), &views).unwrap(),
+    })
+}
+
+// Header restricted to the channels at `indices`, in the order of `indices`
+// Channels beyond a partial `[Coordinates]` section are given a NaN, i.e. unknown, position
+fn pick_header(header: &Header, indices: &[usize]) -> Header {
+    let mut picked = header.clone();
+    picked.num_channels = indices.len() as u32;
+    picked.channels = indices
+        .iter()
+        .map(|&i| header.channels[i].clone())
+        .collect();
+    picked.channel_coords = header.channel_coords.as_ref().map(|coords| {
+        indices
+            .iter()
+            .map(|&i| {
+                coords
+                    .get(i)
+                    .copied()
+                    .unwrap_or_else(|| Coordinates::new(f64::NAN, f64::NAN, f64::NAN))
+            })
+            .collect()
+    });
+
+    picked
+}
+
+// Markers related to all channels or to those at `indices`, renumbered after the order of `indices`
+fn pick_markers(marker: &Marker, indices: &[usize]) -> Marker {
+    // Channels markers are related to are one-based, 0 meaning all channels
+    let mut picked = marker.clone();
+    picked.markers.retain_mut(|m| match m.channel {
+        0 => true,
+        channel => match indices.iter().position(|&i| i + 1 == channel) {
+            Some(index) => {
+                m.channel = index + 1;
+                true
+            }
+            None => false,
+        },
+    });
+
+    picked
+}
+
+// Markers within the zero-based range of `samples`, shifted to its first sample
+fn crop_markers(marker: &Marker, samples: Range<usize>) -> Marker {
+    let mut cropped = marker.clone();
+    cropped.markers.retain(|m| samples.contains(&m.sample()));
+    for m in &mut cropped.markers {
+        m.position -= samples.start;
+    }
+
+    cropped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::fixtures::{Format, Recording, TempDir};
+
+    // Three Int16 channels of 20 samples at 500 Hz, the first of a resolution of 0.5 µV, with a
+    // stimulus on all channels and a response on `Cz`, and coordinates for the first channel only
+    fn recording() -> Recording {
+        let data = Array2::from_shape_fn((3, 20), |(c, t)| (c as f32 - 1.0) * 100.0 + t as f32);
+        let mut recording = Recording::new(&["Fp1", "Cz", "Pz"], data)
+            .markers(&["Stimulus,S  1,3,1,0", "Response,R  1,11,1,2"]);
+        recording.format = Format::Int16;
+        recording.channels[0] = "Fp1,,0.5,µV".into();
+        recording.extra = "\n[Coordinates]\nCh1=1,-90,72\n".into();
+
+        recording
+    }
+
+    #[test]
+    fn accessors_expose_the_scaled_recording() {
+        let root = TempDir::new("raw");
+        let recording = recording();
+        let raw = Raw::load(&recording.write(&root)).unwrap();
+
+        assert_eq!(raw.header().num_channels, 3);
+        assert_eq!(raw.marker().markers.len(), 2);
+        assert_eq!(raw.sfreq(), 500.0);
+        assert_eq!(raw.num_samples(), 20);
+        assert_eq!(raw.channel_names(), vec!["Fp1", "Cz", "Pz"]);
+        assert_eq!(raw.times()[10], 0.02);
+        assert_eq!(raw.data().dim(), (3, 20));
+        assert_eq!(raw.data()[[0, 4]], -48.0);
+        assert_eq!(raw.get_channel("Pz").unwrap(), recording.data.row(2));
+        assert!(raw.get_channel("Oz").is_none());
+
+        let events = raw.events();
+        assert_eq!(
+            events.to_sample_indices(raw.sfreq()),
+            vec![Some(2), Some(10)]
+        );
+        assert_eq!(
+            events.events[1].trial_type.as_deref(),
+            Some("Response/R  1")
+        );
+    }
+
+    #[test]
+    fn set_reference_rereferences_and_renames() {
+        let root = TempDir::new("raw-reference");
+        let mut raw = Raw::load(&recording().write(&root)).unwrap();
+        let original = raw.data().to_owned();
+
+        raw.set_reference(Reference::Channels(&["Pz"]));
+        assert!(raw.get_channel("Pz").unwrap().iter().all(|&v| v == 0.0));
+        assert_eq!(
+            raw.get_channel("Cz").unwrap(),
+            &original.row(1) - &original.row(2)
+        );
+        assert!(raw.header().channels.iter().all(|c| c.ref_name() == "Pz"));
+
+        raw.set_reference(Reference::Average { exclude: &[] });
+        let mean = raw.data().mean_axis(Axis(0)).unwrap();
+        assert!(mean.iter().all(|v| v.abs() < 1e-4));
+        assert_eq!(raw.header().channels[0].ref_name(), "average");
+    }
+
+    #[test]
+    fn crop_keeps_the_markers_within() {
+        let root = TempDir::new("raw-crop");
+        let raw = Raw::load(&recording().write(&root)).unwrap();
+
+        let cropped = raw.crop(0.01, 0.03);
+        assert_eq!(cropped.num_samples(), 11);
+        assert_eq!(cropped.data(), raw.data().slice(s![.., 5..16]));
+        assert_eq!(cropped.marker().markers.len(), 1);
+        assert_eq!(cropped.marker().markers[0].position, 6);
+    }
+
+    #[test]
+    fn resample_moves_the_markers_along() {
+        let root = TempDir::new("raw-resample");
+        let raw = Raw::load(&recording().write(&root)).unwrap();
+
+        let resampled = raw.resample(250.0);
+        assert_eq!(resampled.sfreq(), 250.0);
+        assert_eq!(resampled.num_samples(), 10);
+        assert_eq!(
+            resampled
+                .marker()
+                .markers
+                .iter()
+                .map(MarkerInfo::sample)
+                .collect::<Vec<usize>>(),
+            vec![1, 5]
+        );
+    }
+
+    #[test]
+    fn pick_handles_partial_coordinates() {
+        let root = TempDir::new("raw-pick");
+        let raw = Raw::load(&recording().write(&root)).unwrap();
+
+        let picked = raw.pick(&["Pz", "Fp1"]);
+        assert_eq!(picked.channel_names(), vec!["Pz", "Fp1"]);
+        assert_eq!(picked.data().row(1), raw.data().row(0));
+        // The response on `Cz` is left out
+        assert_eq!(picked.marker().markers.len(), 1);
+
+        let coords = picked.header().channel_coords.as_ref().unwrap();
+        assert!(coords[0].radius().is_nan());
+        assert_eq!((coords[1].theta(), coords[1].phi()), (-90.0, 72.0));
+
+        // `Pz` falls back to its standard position rather than the NaN one
+        let positions = picked.header().channel_positions();
+        assert_eq!(positions.templated, vec![0]);
+        assert!(positions.positions.iter().all(|v| v.is_finite()));
+
+        let cz = raw.pick(&["Cz"]);
+        assert_eq!(cz.marker().markers[1].channel, 1);
+    }
+
+    #[test]
+    fn marker_file_is_optional() {
+        // Header not referencing any marker file
+        let root = TempDir::new("raw-no-marker-key");
+        let mut absent = recording();
+        absent.markers = None;
+        let path = absent.write(&root);
+        assert!(Marker::load(&path, &Header::load(&path).unwrap())
+            .unwrap()
+            .is_none());
+        assert!(Raw::load(&path).unwrap().marker().markers.is_empty());
+
+        // Header referencing a marker file that does not exist
+        let root = TempDir::new("raw-no-marker-file");
+        let path = recording().write(&root);
+        std::fs::remove_file(path.filename("eeg", "vmrk")).unwrap();
+        assert!(Marker::load(&path, &Header::load(&path).unwrap())
+            .unwrap()
+            .is_none());
+        let raw = Raw::load(&path).unwrap();
+        assert!(raw.marker().markers.is_empty());
+        assert_eq!(raw.marker().data_file, raw.header().data_file);
+
+        // Header referencing a valid marker file
+        let root = TempDir::new("raw-marker");
+        let path = recording().write(&root);
+        let marker = Marker::load(&path, &Header::load(&path).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(marker.data_file, "sub-01_task-test_eeg.eeg");
+        assert_eq!(marker.markers.len(), 2);
+        assert_eq!(Raw::load(&path).unwrap().marker().markers.len(), 2);
+    }
+
+    #[test]
+    fn invalid_marker_file_fails() {
+        let root = TempDir::new("raw-invalid-marker");
+        let path = recording().write(&root);
+        std::fs::write(
+            path.filename("eeg", "vmrk"),
+            "Brain Vision Data Exchange Marker File, Version 1.0\n[Marker Infos]\n",
+        )
+        .unwrap();
+
+        match Raw::load(&path) {
+            Err(ReadError::InvalidMarkerFile { file, reason }) => {
+                assert_eq!(file, "sub-01_task-test_eeg.vmrk");
+                assert_eq!(reason, "missing [Common Infos] section");
+            }
+            other => panic!("Unexpected result {other:?} !"),
+        }
+    }
+
+    #[test]
+    fn load_with_matches_cropping_and_picking_a_full_load() {
+        let root = TempDir::new("raw-load-with");
+        let path = recording().write(&root);
+        let full = Raw::load(&path).unwrap();
+
+        let options = LoadOptions::new().samples(5..20).channels(&[2, 1]);
+        let partial = Raw::load_with(&path, &options).unwrap();
+        let expected = full.crop(0.01, 1.0).pick(&["Pz", "Cz"]);
+
+        assert_eq!(partial.data(), expected.data());
+        assert_eq!(partial.channel_names(), expected.channel_names());
+        assert_eq!(
+            partial
+                .marker()
+                .markers
+                .iter()
+                .map(|m| (m.position, m.channel))
+                .collect::<Vec<_>>(),
+            vec![(6, 2)]
+        );
+    }
+
+    #[test]
+    fn resampled_marker_at_two_seconds_stays_at_two_seconds() {
+        let root = TempDir::new("raw-resample-rates");
+        let data = Array2::from_shape_fn((1, 1500), |(_, t)| (t as f32 * 0.05).sin());
+        let recording = Recording::new(&["Cz"], data).markers(&["Stimulus,S  1,1001,1,0"]);
+        let raw = Raw::load(&recording.write(&root)).unwrap();
+
+        for rate in [100.0, 125.0, 200.0, 250.0, 300.0, 1000.0] {
+            let resampled = raw.resample(rate);
+            let sample = resampled.marker().markers[0].sample();
+
+            assert_eq!(resampled.sfreq(), rate);
+            assert_eq!(sample, (2.0 * rate).round() as usize, "{rate}");
+            assert!((resampled.times()[sample] - 2.0).abs() < 1e-9);
+            assert_eq!(
+                resampled.events().to_sample_indices(rate),
+                vec![Some(sample)]
+            );
+        }
+    }
+
+    #[test]
+    fn concatenated_runs_offset_their_markers() {
+        let (first_root, second_root) = (TempDir::new("raw-run-1"), TempDir::new("raw-run-2"));
+        let first = Raw::load(&recording().write(&first_root)).unwrap();
+        // The same channels in another order, of 10 samples, with a response on `Fp1`
+        let data = Array2::from_shape_fn((3, 10), |(c, t)| c as f32 * 10.0 - t as f32);
+        let second = Recording::new(&["Pz", "Fp1", "Cz"], data)
+            .markers(&["Stimulus,S  2,1,1,0", "Response,R  2,5,1,2"]);
+        let second = Raw::load(&second.write(&second_root)).unwrap();
+
+        let concatenated = concatenate_raw(&[first.clone(), second.clone()]).unwrap();
+        assert_eq!(concatenated.channel_names(), vec!["Fp1", "Cz", "Pz"]);
+        assert_eq!(concatenated.num_samples(), 30);
+        assert_eq!(concatenated.data().slice(s![.., ..20]), first.data().view());
+        assert_eq!(
+            concatenated.data().slice(s![.., 20..]),
+            second.pick(&["Fp1", "Cz", "Pz"]).data().view()
+        );
+        assert_eq!(
+            concatenated
+                .marker()
+                .markers
+                .iter()
+                .map(|m| (m.marker_type.as_str(), m.sample(), m.channel))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Stimulus", 2, 0),
+                ("Response", 10, 2),
+                (NEW_SEGMENT, 20, 0),
+                ("Stimulus", 20, 0),
+                ("Response", 24, 1),
+            ]
+        );
+        assert_eq!(
+            concatenated.events().to_sample_indices(500.0),
+            vec![Some(2), Some(10), Some(20), Some(20), Some(24)]
+        );
+
+        // A run already starting a segment is given no other boundary
+        let segmented_root = TempDir::new("raw-run-3");
+        let segmented = Recording::new(&["Fp1", "Cz", "Pz"], Array2::zeros((3, 5)))
+            .markers(&["New Segment,,1,1,0", "Stimulus,S  3,2,1,0"]);
+        let segmented = Raw::load(&segmented.write(&segmented_root)).unwrap();
+        let concatenated = concatenate_raw(&[first, segmented]).unwrap();
+        assert_eq!(
+            concatenated
+                .marker()
+                .markers
+                .iter()
+                .skip(2)
+                .map(|m| (m.marker_type.as_str(), m.sample()))
+                .collect::<Vec<_>>(),
+            vec![(NEW_SEGMENT, 20), ("Stimulus", 21)]
+        );
+    }
+
+    #[test]
+    fn mismatched_runs_fail() {
+        let (first_root, second_root) = (TempDir::new("raw-run-1"), TempDir::new("raw-run-2"));
+        let first = Raw::load(&recording().write(&first_root)).unwrap();
+        let second = Recording::new(&["Fp1", "Oz", "Pz", "O2"], Array2::zeros((4, 10)));
+        let second = Raw::load(&second.write(&second_root)).unwrap();
+
+        let error = concatenate_raw(&[first.clone(), second]).unwrap_err();
+        assert!(matches!(
+            &error,
+            Error::Read(ReadError::ChannelSetMismatch { run: 1, missing, unexpected })
+                if missing == &["Cz"] && unexpected == &["Oz", "O2"]
+        ));
+        assert_eq!(
+            error.to_string(),
+            "run 1 does not have the channels of the first run; missing: Cz; unexpected: Oz, O2"
+        );
+
+        let resampled = first.resample(250.0);
+        assert!(matches!(
+            concatenate_raw(&[first, resampled]),
+            Err(Error::Read(ReadError::SamplingRateMismatch {
+                run: 1,
+                expected: 500.0,
+                found: 250.0
+            }))
+        ));
+        assert!(matches!(
+            concatenate_raw(&[]),
+            Err(Error::Read(ReadError::NoRuns))
+        ));
+    }
+}
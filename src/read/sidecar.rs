@@ -0,0 +1,244 @@
+// * https://bids-specification.readthedocs.io/en/stable/modality-specific-files/electroencephalography.html#sidecar-json-_eegjson
+
+use std::{collections::HashMap, fs, io::ErrorKind, path::Path};
+
+use serde_json::{Map, Value};
+
+use super::{brainvision_core::Header, BIDSPath, ReadError};
+
+// Maximum relative disagreement tolerated between the sampling rates of a sidecar and a header
+const SAMPLING_RATE_TOLERANCE: f64 = 1e-3;
+
+// Struct containing the metadata provided in the `eeg.json` sidecar of a recording
+// Keys are parsed from JSON by hand, so that sidecars are read without the `serde` feature, every
+// optional key specified as `n/a` being `None`
+//
+// sub-<subject>[_ses-<session>]_task-<task>[_acq-<acquisition>][_run-<run>]_eeg.json
+#[derive(Debug)]
+pub struct EegSidecar {
+    pub task_name: String,
+    // Sampling frequency of the recording, in Hz
+    pub sampling_frequency: f64,
+    // Frequency of the power grid at the recording location, in Hz
+    pub power_line_frequency: Option<f64>,
+    // Filters applied after acquisition, by name, along with their parameters
+    pub software_filters: Option<HashMap<String, Value>>,
+    pub eeg_reference: String,
+    // Filters applied during acquisition, by name, along with their parameters
+    pub hardware_filters: Option<HashMap<String, Value>>,
+    // Length of the recording, in seconds
+    pub recording_duration: Option<f64>,
+    // CONTINUOUS, EPOCHED or DISCONTINUOUS
+    pub recording_type: Option<String>,
+    pub eeg_ground: Option<String>,
+    pub eeg_placement_scheme: Option<String>,
+    pub eeg_channel_count: Option<u32>,
+    pub eog_channel_count: Option<u32>,
+    pub ecg_channel_count: Option<u32>,
+    pub emg_channel_count: Option<u32>,
+    pub misc_channel_count: Option<u32>,
+    pub trigger_channel_count: Option<u32>,
+    pub manufacturer: Option<String>,
+    pub manufacturers_model_name: Option<String>,
+    pub cap_manufacturer: Option<String>,
+    pub institution_name: Option<String>,
+    pub task_description: Option<String>,
+    pub instructions: Option<String>,
+}
+
+// Warning about a sampling rate of a sidecar disagreeing with the one of a header
+#[derive(Debug)]
+pub struct SamplingRateMismatch {
+    pub sidecar_hz: f64,
+    pub header_hz: f64,
+    // Relative difference with respect to the sidecar's sampling rate
+    pub relative_difference: f64,
+}
+
+impl EegSidecar {
+    // Load the `eeg.json` sidecar of a BIDS-compliant data recording
+    //
+    // Following the inheritance principle, the dataset-level `task-<task>_eeg.json` at the root is
+    // read first, when the `path` has a task, and the recording-level sidecar next to the data
+    // overrides its keys
+    // Returns `None` if neither exist
+    // Fails with `ReadError::InvalidSidecar` if either cannot be read, is not a JSON object, or
+    // its keys are not of the expected types
+    pub fn load<P: AsRef<Path>>(path: &BIDSPath<P>) -> Result<Option<EegSidecar>, ReadError> {
+        let local = path.filename(path.datatype, "json");
+        let root = path.task.map(|task| {
+            path.root
+                .as_ref()
+                .join(format!("task-{task}_{}.json", path.datatype))
+        });
+
+        let mut sidecar = Map::new();
+        let mut files = Vec::new();
+        for file in root.into_iter().chain([local]) {
+            let name = file
+                .file_name()
+                .map_or_else(String::new, |f| f.to_string_lossy().into_owned());
+            let invalid = |reason: String| ReadError::InvalidSidecar {
+                file: name.clone(),
+                reason,
+            };
+
+            let contents = match fs::read_to_string(&file) {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(invalid(e.to_string())),
+            };
+            match serde_json::from_str::<Value>(&contents).map_err(|e| invalid(e.to_string()))? {
+                Value::Object(keys) => sidecar.extend(keys),
+                _ => return Err(invalid("not a JSON object".into())),
+            }
+            files.push(name);
+        }
+        if files.is_empty() {
+            return Ok(None);
+        }
+
+        Self::from_keys(&sidecar)
+            .map(Some)
+            .map_err(|reason| ReadError::InvalidSidecar {
+                file: files.join(", "),
+                reason,
+            })
+    }
+
+    fn from_keys(keys: &Map<String, Value>) -> Result<EegSidecar, String> {
+        let text = |value: &Value| value.as_str().map(String::from);
+        let count = |value: &Value| value.as_u64().and_then(|n| u32::try_from(n).ok());
+        let filters = |value: &Value| {
+            value
+                .as_object()
+                .map(|filters| filters.clone().into_iter().collect())
+        };
+
+        Ok(EegSidecar {
+            task_name: required(keys, "TaskName", text)?,
+            sampling_frequency: required(keys, "SamplingFrequency", Value::as_f64)?,
+            power_line_frequency: optional(keys, "PowerLineFrequency", Value::as_f64)?,
+            software_filters: optional(keys, "SoftwareFilters", filters)?,
+            eeg_reference: required(keys, "EEGReference", text)?,
+            hardware_filters: optional(keys, "HardwareFilters", filters)?,
+            recording_duration: optional(keys, "RecordingDuration", Value::as_f64)?,
+            recording_type: optional(keys, "RecordingType", text)?,
+            eeg_ground: optional(keys, "EEGGround", text)?,
+            eeg_placement_scheme: optional(keys, "EEGPlacementScheme", text)?,
+            eeg_channel_count: optional(keys, "EEGChannelCount", count)?,
+            eog_channel_count: optional(keys, "EOGChannelCount", count)?,
+            ecg_channel_count: optional(keys, "ECGChannelCount", count)?,
+            emg_channel_count: optional(keys, "EMGChannelCount", count)?,
+            misc_channel_count: optional(keys, "MiscChannelCount", count)?,
+            trigger_channel_count: optional(keys, "TriggerChannelCount", count)?,
+            manufacturer: optional(keys, "Manufacturer", text)?,
+            manufacturers_model_name: optional(keys, "ManufacturersModelName", text)?,
+            cap_manufacturer: optional(keys, "CapManufacturer", text)?,
+            institution_name: optional(keys, "InstitutionName", text)?,
+            task_description: optional(keys, "TaskDescription", text)?,
+            instructions: optional(keys, "Instructions", text)?,
+        })
+    }
+
+    // Compare the sampling frequency against the sampling interval of the `header`, returning a
+    // warning when they disagree by more than 0.1%
+    pub fn check_sampling_rate(&self, header: &Header) -> Option<SamplingRateMismatch> {
+        // The sampling interval is provided in microseconds
+        let header_hz = header.sampling_rate_hz();
+        let relative_difference =
+            (self.sampling_frequency - header_hz).abs() / self.sampling_frequency;
+
+        (relative_difference > SAMPLING_RATE_TOLERANCE).then_some(SamplingRateMismatch {
+            sidecar_hz: self.sampling_frequency,
+            header_hz,
+            relative_difference,
+        })
+    }
+}
+
+// Value of the optional `key` of a JSON object converted by `convert`, `None` if the key is
+// absent, null or `n/a`, failing if it is of another type
+pub(super) fn optional<T>(
+    keys: &Map<String, Value>,
+    key: &str,
+    convert: impl Fn(&Value) -> Option<T>,
+) -> Result<Option<T>, String> {
+    match keys.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) if s == "n/a" => Ok(None),
+        Some(value) => convert(value)
+            .map(Some)
+            .ok_or_else(|| format!("invalid {key} {value}")),
+    }
+}
+
+// Value of the required `key` of a JSON object converted by `convert`, failing if it is absent
+fn required<T>(
+    keys: &Map<String, Value>,
+    key: &str,
+    convert: impl Fn(&Value) -> Option<T>,
+) -> Result<T, String> {
+    optional(keys, key, convert)?.ok_or_else(|| format!("missing {key}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::fixtures::{path, TempDir};
+
+    const LOCAL: &str = "sub-01/eeg/sub-01_task-test_eeg.json";
+
+    #[test]
+    fn recording_sidecar_overrides_the_dataset_one() {
+        let root = TempDir::new("sidecar");
+        root.write(
+            "task-test_eeg.json",
+            r#"{"TaskName": "test", "SamplingFrequency": 250, "EEGReference": "Cz",
+                "PowerLineFrequency": 50}"#,
+        );
+        root.write(
+            LOCAL,
+            r#"{"SamplingFrequency": 500, "PowerLineFrequency": "n/a"}"#,
+        );
+
+        let sidecar = EegSidecar::load(&path(&root)).unwrap().unwrap();
+        assert_eq!(sidecar.task_name, "test");
+        assert_eq!(sidecar.eeg_reference, "Cz");
+        assert_eq!(sidecar.sampling_frequency, 500.0);
+        assert_eq!(sidecar.power_line_frequency, None);
+    }
+
+    #[test]
+    fn dataset_sidecar_is_skipped_without_a_task() {
+        let root = TempDir::new("sidecar-no-task");
+        root.write("task-_eeg.json", "not even JSON");
+        root.write(
+            "sub-01/eeg/sub-01_eeg.json",
+            r#"{"TaskName": "rest", "SamplingFrequency": 500, "EEGReference": "Cz"}"#,
+        );
+
+        let path = BIDSPath::new(root.path(), "01");
+        assert_eq!(EegSidecar::load(&path).unwrap().unwrap().task_name, "rest");
+        assert!(EegSidecar::load(&path.task("other")).unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_sidecars_fail_instead_of_panicking() {
+        let root = TempDir::new("sidecar-invalid");
+        for contents in [
+            "{\"TaskName\": ",
+            "[1, 2]",
+            r#"{"TaskName": "test", "SamplingFrequency": "fast", "EEGReference": "Cz"}"#,
+            r#"{"TaskName": "test"}"#,
+        ] {
+            root.write(LOCAL, contents);
+            match EegSidecar::load(&path(&root)) {
+                Err(ReadError::InvalidSidecar { file, .. }) => {
+                    assert_eq!(file, "sub-01_task-test_eeg.json")
+                }
+                other => panic!("Unexpected result {other:?} for {contents} !"),
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::{fs, path::Path, str::FromStr};
+
+use super::ReadError;
+
+// Tab-separated values file, as used by BIDS for tabular sidecars
+// <https://bids-specification.readthedocs.io/en/stable/common-principles.html#tabular-files>
+pub(crate) struct Tsv {
+    // Name of the file, for the errors of its values
+    file: String,
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Tsv {
+    // Fails with `ReadError::InvalidSidecar` if the file cannot be read
+    pub(crate) fn load<P: AsRef<Path>>(path: P) -> Result<Tsv, ReadError> {
+        let path = path.as_ref();
+        let file = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |f| f.to_string_lossy().into(),
+        );
+        let contents = fs::read_to_string(path).map_err(|e| ReadError::InvalidSidecar {
+            file: file.clone(),
+            reason: e.to_string(),
+        })?;
+
+        Ok(Tsv {
+            file,
+            ..Self::parse(&contents)
+        })
+    }
+
+    pub(crate) fn parse(contents: &str) -> Tsv {
+        let mut lines = contents.lines().filter(|l| !l.trim().is_empty());
+
+        let columns = lines.next().map_or_else(Vec::new, |l| {
+            l.split('\t').map(|c| c.trim().into()).collect()
+        });
+        let rows = lines
+            .map(|l| l.split('\t').map(|c| c.trim().into()).collect())
+            .collect();
+
+        Tsv {
+            file: String::new(),
+            columns,
+            rows,
+        }
+    }
+
+    pub(crate) fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    pub(crate) fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    // Value of the `column` at `row`, `None` if the column is absent, the cell missing or `n/a`
+    pub(crate) fn get(&self, row: usize, column: &str) -> Option<&str> {
+        let idx = self.columns.iter().position(|c| c == column)?;
+
+        self.rows[row]
+            .get(idx)
+            .map(String::as_str)
+            .filter(|v| !v.is_empty() && *v != "n/a")
+    }
+
+    // Value of the `column` at `row` parsed as a `T`, `None` as for `Tsv::get`
+    // Fails with `ReadError::InvalidSidecar` if the value cannot be parsed
+    pub(crate) fn parse_value<T: FromStr>(
+        &self,
+        row: usize,
+        column: &str,
+    ) -> Result<Option<T>, ReadError> {
+        self.get(row, column)
+            .map(|value| {
+                value.parse::<T>().map_err(|_| {
+                    self.invalid(format!("invalid {column} '{value}' in row {}", row + 1))
+                })
+            })
+            .transpose()
+    }
+
+    // Value of the required `column` at `row`
+    // Fails with `ReadError::InvalidSidecar` if the column is absent, or the value missing
+    pub(crate) fn require(&self, row: usize, column: &str) -> Result<&str, ReadError> {
+        if !self.columns.iter().any(|c| c == column) {
+            return Err(self.invalid(format!("missing {column} column")));
+        }
+
+        self.get(row, column)
+            .ok_or_else(|| self.invalid(format!("missing {column} in row {}", row + 1)))
+    }
+
+    pub(crate) fn invalid(&self, reason: String) -> ReadError {
+        ReadError::InvalidSidecar {
+            file: self.file.clone(),
+            reason,
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use ndarray::{ArrayBase, Axis, DataMut, Ix2};
+
+// Re-reference the `data` (channels x samples) to the common average of its channels
+// The channels at `exclude` (e.g. EOG or bad channels) do not contribute to the average, but are
+// still re-referenced themselves
+pub fn rereference_average<S>(data: &mut ArrayBase<S, Ix2>, exclude: &[usize])
+where
+    S: DataMut<Elem = f32>,
+{
+    let included = (0..data.nrows())
+        .filter(|i| !exclude.contains(i))
+        .collect::<Vec<usize>>();
+
+    rereference_to(data, &included);
+}
+
+// Re-reference the `data` (channels x samples) to the mean of the channels at `ref_indices`, e.g.
+// both mastoids for a linked mastoids reference
+// The mean is subtracted from every channel, the reference channels included
+pub fn rereference_to<S>(data: &mut ArrayBase<S, Ix2>, ref_indices: &[usize])
+where
+    S: DataMut<Elem = f32>,
+{
+    if ref_indices.is_empty() {
+        return;
+    }
+
+    let reference = data
+        .select(Axis(0), ref_indices)
+        .mean_axis(Axis(0))
+        .unwrap();
+    *data -= &reference;
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{s, Array2, Axis};
+
+    use super::*;
+
+    fn data() -> Array2<f32> {
+        Array2::from_shape_fn((4, 50), |(c, t)| {
+            ((c + 1) as f32 * t as f32 * 0.1).sin() * (c + 1) as f32 + c as f32
+        })
+    }
+
+    #[test]
+    fn average_reference_zeroes_the_mean_of_the_included_channels() {
+        let original = data();
+        let mut data = original.clone();
+        // The last channel, e.g. EOG, left out of the average
+        rereference_average(&mut data, &[3]);
+
+        let mean = data.slice(s![..3, ..]).mean_axis(Axis(0)).unwrap();
+        assert!(mean.iter().all(|m| m.abs() < 1e-6));
+
+        // It is still re-referenced, but to the average of the others only
+        let reference = original.slice(s![..3, ..]).mean_axis(Axis(0)).unwrap();
+        let expected = &original.row(3) - &reference;
+        assert!(data
+            .row(3)
+            .iter()
+            .zip(&expected)
+            .all(|(a, b)| (a - b).abs() < 1e-6));
+    }
+
+    #[test]
+    fn linked_mastoids_subtract_their_mean() {
+        let original = data();
+        let mut data = original.clone();
+        rereference_to(&mut data, &[0, 1]);
+
+        let reference = (&original.row(0) + &original.row(1)) / 2.0;
+        for c in 0..4 {
+            let expected = &original.row(c) - &reference;
+            assert!(data
+                .row(c)
+                .iter()
+                .zip(&expected)
+                .all(|(a, b)| (a - b).abs() < 1e-6));
+        }
+        assert!(data
+            .row(0)
+            .iter()
+            .zip(data.row(1))
+            .all(|(a, b)| (a + b).abs() < 1e-6));
+
+        let mut unchanged = original.clone();
+        rereference_to(&mut unchanged, &[]);
+        assert_eq!(unchanged, original);
+    }
+}
@@ -0,0 +1,146 @@
+use std::ops::Range;
+
+use nalgebra::DMatrix;
+use ndarray::{Array2, ArrayBase, Axis, DataMut, Ix2};
+
+use crate::covariance::{Covariance, CovarianceError, CovarianceType};
+
+// G. Gratton, M. G. H. Coles and E. Donchin, "A new method for off-line removal of ocular
+// artifact," Electroencephalography and Clinical Neurophysiology, vol. 55, no. 4, pp. 468-484,
+// 1983.
+
+// Fraction of the variance of a reference channel under which it is deemed a linear combination
+// of the previous ones, above the rounding of covariances of single precision
+const DEPENDENCE_TOLERANCE: f64 = 1e-6;
+
+// Regresses the reference channels at `reference` (e.g. EOG) out of the channels at `eeg` of the
+// `data` (channels x samples), in place
+// The least-squares coefficients of each EEG channel on the reference channels are those of their
+// sample covariance, over the samples within the `segments` (e.g. blinks) or over all of them when
+// not given, and the fitted contribution of the demeaned reference channels is subtracted from
+// every sample, leaving the mean of each EEG channel as it is
+// Fails if there are not enough samples to fit, or the reference channels are linearly dependent
+//
+// Returns the coefficients (eeg x reference)
+pub fn regress_out<S>(
+    data: &mut ArrayBase<S, Ix2>,
+    eeg: &[usize],
+    reference: &[usize],
+    segments: Option<&[Range<usize>]>,
+) -> Result<Array2<f32>, CovarianceError>
+where
+    S: DataMut<Elem = f32>,
+{
+    let num_samples = data.ncols();
+    let samples = match segments {
+        Some(segments) => segments
+            .iter()
+            .flat_map(|range| range.start.min(num_samples)..range.end.min(num_samples))
+            .collect::<Vec<usize>>(),
+        None => (0..num_samples).collect(),
+    };
+    let channels = [reference, eeg].concat();
+    let fitted = data.select(Axis(0), &channels).select(Axis(1), &samples);
+
+    // Coefficients B solving B C_rr = C_er, of the covariance blocks of the reference channels
+    // with themselves and of the EEG channels with them
+    let cov = fitted.try_compute_covariance(CovarianceType::Sample)?;
+    let r = reference.len();
+    let cov_rr = DMatrix::from_fn(r, r, |i, j| cov[[i, j]] as f64);
+    let cov_re = DMatrix::from_fn(r, eeg.len(), |i, j| cov[[i, r + j]] as f64);
+    let cholesky = cov_rr.clone().cholesky().ok_or(CovarianceError::Singular)?;
+    // The squared pivots are the variances of the reference channels left unexplained by the
+    // previous ones, which rounding keeps from dropping to zero for dependent channels
+    let l = cholesky.l();
+    if (0..r).any(|i| l[(i, i)].powi(2) <= DEPENDENCE_TOLERANCE * cov_rr[(i, i)]) {
+        return Err(CovarianceError::Singular);
+    }
+    let solution = cholesky.solve(&cov_re);
+    let coefficients = Array2::from_shape_fn((eeg.len(), r), |(i, j)| solution[(j, i)] as f32);
+
+    let references = data.select(Axis(0), reference);
+    let means = references.mean_axis(Axis(1)).unwrap().insert_axis(Axis(1));
+    let contribution = coefficients.dot(&(&references - &means));
+    for (i, &channel) in eeg.iter().enumerate() {
+        let mut row = data.row_mut(channel);
+        row -= &contribution.row(i);
+    }
+
+    Ok(coefficients)
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{s, Array1};
+
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    fn correlation(a: &Array1<f32>, b: &Array1<f32>) -> f32 {
+        let (a, b) = (a - a.mean().unwrap(), b - b.mean().unwrap());
+        a.dot(&b) / (a.dot(&a) * b.dot(&b)).sqrt()
+    }
+
+    // Blinks of 100 µV every 500 samples, lasting about 100 samples
+    fn eog() -> Array1<f32> {
+        Array1::from_shape_fn(5000, |t| {
+            let phase = (t % 500) as f32 - 250.0;
+            100.0 * (-0.5 * (phase / 25.0).powi(2)).exp()
+        })
+    }
+
+    // The EOG on the last channel, leaking with a gain of 0.3 into 3 channels of neural noise of
+    // distinct offsets
+    fn data() -> Array2<f32> {
+        let mut generator = Lcg::new(21);
+        let eog = eog();
+        Array2::from_shape_fn((4, 5000), |(c, t)| match c {
+            3 => eog[t],
+            _ => 10.0 * c as f32 + 20.0 * (generator.next_f32() - 0.5) + 0.3 * eog[t],
+        })
+    }
+
+    #[test]
+    fn regressed_channels_no_longer_correlate_with_the_eog() {
+        let original = data();
+        let blinks = (0..10)
+            .map(|b| b * 500 + 150..b * 500 + 350)
+            .collect::<Vec<_>>();
+
+        for segments in [None, Some(blinks.as_slice())] {
+            let mut data = original.clone();
+            assert!(correlation(&data.row(0).to_owned(), &eog()) > 0.5);
+
+            let coefficients = regress_out(&mut data, &[0, 1, 2], &[3], segments).unwrap();
+            assert_eq!(coefficients.dim(), (3, 1));
+            for (c, &coefficient) in coefficients.iter().enumerate() {
+                assert!((coefficient - 0.3).abs() < 0.01, "{coefficient}");
+
+                let residual = data.row(c).to_owned();
+                let r = correlation(&residual, &eog());
+                assert!(r.abs() < 0.05, "{r}");
+                let mean_shift = residual.mean().unwrap() - original.row(c).mean().unwrap();
+                assert!(mean_shift.abs() < 1e-3);
+            }
+            assert_eq!(data.row(3), original.row(3));
+        }
+    }
+
+    #[test]
+    fn degenerate_references_fail() {
+        let mut data = data();
+        data.row_mut(2).assign(&eog());
+
+        assert!(matches!(
+            regress_out(&mut data, &[0, 1], &[2, 3], None),
+            Err(CovarianceError::Singular)
+        ));
+        assert!(matches!(
+            // A single sample, the empty range adding none
+            regress_out(&mut data, &[0], &[3], Some(&[10..11, 20..20])),
+            Err(CovarianceError::NotEnoughSamples { .. })
+        ));
+        // Left as they were
+        assert_eq!(data.slice(s![..2, ..]), self::data().slice(s![..2, ..]));
+    }
+}
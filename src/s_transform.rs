@@ -1,90 +1,982 @@
 // R. G. Stockwell, L. Mansinha and R. P. Lowe, "Localization of the complex spectrum: the S transform," in IEEE Transactions on Signal Processing, vol. 44, no. 4, pp. 998-1001, April 1996, doi: 10.1109/78.492555.
 
-use std::f32::consts::PI;
+use std::{error::Error, fmt::Display, ops::Range};
 
 use nalgebra::Complex;
-use ndarray::{Array1, Array2, ArrayBase, Data, Ix1, Ix2};
+use ndarray::{s, Array1, Array2, Array3, ArrayBase, Axis, Data, Ix1, Ix2};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
-use crate::fft::{FourierTransform, InverseFourierTransform};
+use crate::fft::{from_usize, FftElement, FourierTransform, InverseFourierTransform};
+
+// Errors that can occur while computing Stockwell transforms
+#[derive(Debug)]
+pub enum StError {
+    // The range of frequencies requested is empty or exceeds the Nyquist frequency
+    FrequencyOutOfRange {
+        fmin: f32,
+        fmax: f32,
+        nyquist: f32,
+    },
+    // The transform to invert is not of the shape resulting from the forward transform
+    ShapeMismatch {
+        shape: (usize, usize),
+        expected: (usize, usize),
+    },
+    // The transform to invert is decimated by a factor of zero
+    ZeroDecimation,
+    // The signals compared are not of the same length
+    LengthMismatch {
+        x: usize,
+        y: usize,
+    },
+    // The signal to transform holds no samples
+    EmptySignal,
+}
+
+impl Display for StError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StError::FrequencyOutOfRange {
+                fmin,
+                fmax,
+                nyquist,
+            } => write!(
+                f,
+                "frequencies {fmin}-{fmax} Hz are empty or exceed the Nyquist frequency of \
+                 {nyquist} Hz"
+            ),
+            StError::ShapeMismatch { shape, expected } => write!(
+                f,
+                "transform of shape {shape:?} does not match the expected shape {expected:?}"
+            ),
+            StError::ZeroDecimation => f.write_str("time decimation must be positive"),
+            StError::LengthMismatch { x, y } => {
+                write!(f, "signals of {x} and {y} samples are not comparable")
+            }
+            StError::EmptySignal => f.write_str("signal holds no samples"),
+        }
+    }
+}
+
+impl Error for StError {}
+
+// Stockwell Transform restricted to a range of frequencies and decimated in time
+#[derive(Clone, Debug)]
+pub struct StRange<A = f32> {
+    // Oriented frequencies x times
+    pub st: Array2<Complex<A>>,
+    // Frequency of each row, in Hz
+    pub freqs: Array1<A>,
+    // Time of each column, in seconds from the first sample
+    pub times: Array1<A>,
+}
+
+impl<A: FftElement> StRange<A> {
+    // Power |S|^2 of the transform, in dB clamped to `db_floor` if any, along with its axes
+    pub fn spectrogram(&self, db_floor: Option<A>) -> StSpectrogram<A> {
+        StSpectrogram {
+            power: power(&self.st, db_floor),
+            freqs: self.freqs.clone(),
+            times: self.times.clone(),
+        }
+    }
+}
+
+// Power of a Stockwell Transform, ready to be plotted against its axes
+#[derive(Clone, Debug)]
+pub struct StSpectrogram<A = f32> {
+    // Oriented frequencies x times, either linear or in dB
+    pub power: Array2<A>,
+    // Frequency of each row, in Hz
+    pub freqs: Array1<A>,
+    // Time of each column, in seconds from the first sample
+    pub times: Array1<A>,
+}
 
 pub trait STransform {
+    // `f32` or `f64`, the latter being accurate over the long windows of low frequencies
+    type Elem;
+
     // Stockwell Transform
     // Computations are done in the Fourier Transform form
-    // Should only be applied on signals of length power of 2 for compatibility with the FFT implementation
-    fn st(&self) -> Array2<Complex<f32>>;
+    // Signals of any length are supported, those of length power of 2 being the fastest
+    // With the `rayon` feature, frequency rows are computed in parallel, to the same result
+    // Panics if the signal is empty
+    fn st(&self) -> Array2<Complex<Self::Elem>>;
+
+    // The same transform, failing instead of panicking
+    fn try_st(&self) -> Result<Array2<Complex<Self::Elem>>, StError>;
+
+    // Stockwell Transform of the signal sampled at `fs` Hz, only computing every `freq_step`-th
+    // frequency row between `fmin` and `fmax` Hz, and keeping every `time_decim`-th sample
+    // Rows are the same as those of `st` at the corresponding frequencies
+    fn st_range(
+        &self,
+        fs: Self::Elem,
+        fmin: Self::Elem,
+        fmax: Self::Elem,
+        freq_step: usize,
+        time_decim: usize,
+    ) -> Result<StRange<Self::Elem>, StError>;
+
+    // Power of the Stockwell Transform of the signal sampled at `fs` Hz, in dB clamped to
+    // `db_floor` if any, along with the frequency and time axes
+    fn st_spectrogram(
+        &self,
+        fs: Self::Elem,
+        db_floor: Option<Self::Elem>,
+    ) -> StSpectrogram<Self::Elem>;
+}
+
+pub trait MultichannelSTransform {
+    // Stockwell Transform of each channel of channels x samples data
+    // With the `rayon` feature, channels are transformed in parallel
+    //
+    // Returns the transforms oriented channels x frequencies x times, whose size in bytes is given
+    // by `st_output_size`
+    fn st_channels(&self) -> Array3<Complex<f32>>;
+
+    // Stockwell Transform of each channel in turn, handed to `visit` along with the index of the
+    // channel instead of keeping all of them in memory
+    fn st_each<F>(&self, visit: F)
+    where
+        F: FnMut(usize, Array2<Complex<f32>>);
+}
+
+// R. G. Stockwell, "A basis for efficient representation of the S-transform," Digital Signal
+// Processing, vol. 17, no. 1, pp. 371-393, 2007, doi: 10.1016/j.dsp.2006.04.006.
+pub trait DiscreteOrthonormalSTransform {
+    // Discrete Orthonormal Stockwell Transform of a signal of length power of 2
+    // The spectrum is partitioned into the dyadic bands of `dost_bands`, each of them brought back
+    // to as many times as it holds frequencies through an orthonormal inverse DFT
+    //
+    // Returns the n coefficients, band after band, the energy of the signal being preserved
+    fn dost(&self) -> Array1<Complex<f32>>;
+}
+
+pub trait InverseDiscreteOrthonormalSTransform {
+    // Inverse Discrete Orthonormal Stockwell Transform, back to the real signal
+    fn idost(&self) -> Array1<f32>;
+}
+
+// Dyadic bands of frequency bins of the DOST of a signal of length `n`, in the order of the FFT:
+// the DC and Nyquist bins on their own, the positive bins [2^(p-1), 2^p) and their negative mirrors
+// Coefficients of the band `b` are those at `b`, evenly spread over the whole signal in time
+pub fn dost_bands(n: usize) -> Vec<Range<usize>> {
+    assert!(n.is_power_of_two(), "Signal length must be a power of 2 !");
+
+    let mut positive = Vec::new();
+    let mut width = 1;
+    while 2 * width <= n / 2 {
+        positive.push(width..2 * width);
+        width *= 2;
+    }
+
+    let mut bands = Vec::with_capacity(2 * positive.len() + 2);
+    bands.push(0..1);
+    bands.extend(positive.iter().cloned());
+    if n > 1 {
+        bands.push(n / 2..n / 2 + 1);
+    }
+    bands.extend(
+        positive
+            .iter()
+            .rev()
+            .map(|band| n - band.end + 1..n - band.start + 1),
+    );
+
+    bands
+}
+
+// Size in bytes of the Stockwell Transforms of `n_channels` channels of `n_samples` samples each
+pub fn st_output_size(n_channels: usize, n_samples: usize) -> usize {
+    n_channels * (n_samples / 2 + 1) * n_samples * std::mem::size_of::<Complex<f32>>()
 }
 
 pub trait InverseSTransform {
-    // Inverse Stockwell Transform
-    fn ist(&self) -> Array1<f32>;
+    type Elem;
+
+    // Inverse Stockwell Transform of the frequencies (n / 2 + 1) x times (n) transform of a signal
+    // of n samples, as returned by `st`
+    // Summing each row over time gives back the Fourier Transform of the signal at that frequency
+    fn ist(&self) -> Result<Array1<Self::Elem>, StError>;
+
+    // Inverse Stockwell Transform of the transform of a signal of `num_samples` samples, of which
+    // only every `time_decim`-th time was kept
+    // Exact without decimation, the reconstruction otherwise degrades at the frequencies whose
+    // Gaussian window is wider than the decimated sampling rate
+    // Fails if `time_decim` is zero
+    fn ist_decimated(
+        &self,
+        num_samples: usize,
+        time_decim: usize,
+    ) -> Result<Array1<Self::Elem>, StError>;
 }
 
-impl<S> STransform for ArrayBase<S, Ix1>
+impl<A, S> STransform for ArrayBase<S, Ix1>
 where
-    S: Data<Elem = f32>,
+    A: FftElement,
+    S: Data<Elem = A>,
 {
+    type Elem = A;
+
+    fn st(&self) -> Array2<Complex<A>> {
+        self.try_st().expect("Signal must not be empty !")
+    }
+
     #[allow(non_snake_case)]
-    fn st(&self) -> Array2<Complex<f32>> {
+    fn try_st(&self) -> Result<Array2<Complex<A>>, StError> {
         let n = self.len();
-        let gauss = |n: usize, m: usize| (-2.0 * PI * PI * (m * m) as f32 / (n * n) as f32).exp();
+        if n == 0 {
+            return Err(StError::EmptySignal);
+        }
 
         // Compute FFT of signal
-        let H = self.map(Complex::from).fft();
+        let H = self.map(|&x| Complex::from(x)).fft();
 
-        let mut result = Array2::<Complex<f32>>::zeros((n / 2 + 1, n));
+        let mut result = Array2::<Complex<A>>::zeros((n / 2 + 1, n));
 
-        result.row_mut(0).assign(&Array1::from_elem(
-            n,
-            Complex::new(self.mean().unwrap(), 0.0),
-        ));
-        for f in 1..=n / 2 {
-            // Build Gaussian in frequency domain
+        result
+            .row_mut(0)
+            .assign(&Array1::from_elem(n, Complex::from(self.mean().unwrap())));
+        let mut rows = result.slice_axis_mut(Axis(0), (1..).into());
+
+        // Each worker reuses its own buffer for the Gaussians
+        #[cfg(feature = "rayon")]
+        rows.axis_iter_mut(Axis(0))
+            .into_par_iter()
+            .enumerate()
+            .for_each_init(
+                || Array1::zeros(n),
+                |wgauss, (i, mut row)| row.assign(&voice(&H, i + 1, wgauss)),
+            );
+        #[cfg(not(feature = "rayon"))]
+        {
             let mut wgauss = Array1::zeros(n);
-            wgauss[0] = gauss(f, 0);
-            for i in 1..=n / 2 {
-                let val = gauss(f, i);
-                wgauss[i] = val;
-                wgauss[n - i] = val;
+            for (i, mut row) in rows.outer_iter_mut().enumerate() {
+                row.assign(&voice(&H, i + 1, &mut wgauss));
             }
+        }
+
+        Ok(result)
+    }
+
+    #[allow(non_snake_case)]
+    fn st_range(
+        &self,
+        fs: A,
+        fmin: A,
+        fmax: A,
+        freq_step: usize,
+        time_decim: usize,
+    ) -> Result<StRange<A>, StError> {
+        assert!(
+            freq_step > 0 && time_decim > 0,
+            "Frequency step and time decimation must be positive !"
+        );
 
-            // Multiply FFT{x} by frequency-localized Gaussian
-            // Correct by Convolution Theorem
-            let filtered = Array1::from_shape_fn(n, |i| {
-                let mut k = i + f;
-                if k >= n {
-                    k -= n;
-                }
-                H[k] * wgauss[i]
+        let n = self.len();
+        let nyquist = fs / from_usize(2);
+        if fmin < A::zero() || fmin > fmax || fmax > nyquist {
+            return Err(StError::FrequencyOutOfRange {
+                fmin: fmin.to_f32().unwrap(),
+                fmax: fmax.to_f32().unwrap(),
+                nyquist: nyquist.to_f32().unwrap(),
             });
+        }
 
-            // Compute Inverse FFT to get back time-localized signal
-            let inverse = filtered.ifft();
+        // Frequency bins within the range, of `fs / n` Hz each
+        let first = (fmin * from_usize(n) / fs).ceil().to_usize().unwrap();
+        let last = (fmax * from_usize(n) / fs)
+            .floor()
+            .to_usize()
+            .unwrap()
+            .min(n / 2);
+        let rows = (first..=last).step_by(freq_step).collect::<Vec<usize>>();
+        let columns = (0..n).step_by(time_decim).collect::<Vec<usize>>();
 
-            result.row_mut(f).assign(&inverse);
+        let H = self.map(|&x| Complex::from(x)).fft();
+        let mut wgauss = Array1::zeros(n);
+        let mut st = Array2::<Complex<A>>::zeros((rows.len(), columns.len()));
+        for (mut row, &f) in st.outer_iter_mut().zip(&rows) {
+            if f == 0 {
+                row.fill(Complex::from(self.mean().unwrap()));
+            } else {
+                let voice = voice(&H, f, &mut wgauss);
+                row.assign(&Array1::from_shape_fn(columns.len(), |i| voice[columns[i]]));
+            }
+        }
+
+        Ok(StRange {
+            st,
+            freqs: rows
+                .iter()
+                .map(|&f| from_usize::<A>(f) * fs / from_usize(n))
+                .collect(),
+            times: columns.iter().map(|&i| from_usize::<A>(i) / fs).collect(),
+        })
+    }
+
+    fn st_spectrogram(&self, fs: A, db_floor: Option<A>) -> StSpectrogram<A> {
+        let n = self.len();
+
+        StSpectrogram {
+            power: power(&self.st(), db_floor),
+            freqs: Array1::from_shape_fn(n / 2 + 1, |f| from_usize::<A>(f) * fs / from_usize(n)),
+            times: Array1::from_shape_fn(n, |i| from_usize::<A>(i) / fs),
+        }
+    }
+}
+
+impl<S> MultichannelSTransform for ArrayBase<S, Ix2>
+where
+    S: Data<Elem = f32>,
+{
+    fn st_channels(&self) -> Array3<Complex<f32>> {
+        let (num_channels, n) = self.dim();
+        let mut result = Array3::<Complex<f32>>::zeros((num_channels, n / 2 + 1, n));
+
+        #[cfg(feature = "rayon")]
+        result
+            .axis_iter_mut(Axis(0))
+            .into_par_iter()
+            .zip(self.axis_iter(Axis(0)).into_par_iter())
+            .for_each(|(mut st, channel)| st.assign(&channel.st()));
+        #[cfg(not(feature = "rayon"))]
+        for (mut st, channel) in result.outer_iter_mut().zip(self.outer_iter()) {
+            st.assign(&channel.st());
+        }
+
+        result
+    }
+
+    fn st_each<F>(&self, mut visit: F)
+    where
+        F: FnMut(usize, Array2<Complex<f32>>),
+    {
+        for (i, channel) in self.outer_iter().enumerate() {
+            visit(i, channel.st());
+        }
+    }
+}
+
+impl<S> DiscreteOrthonormalSTransform for ArrayBase<S, Ix1>
+where
+    S: Data<Elem = f32>,
+{
+    #[allow(non_snake_case)]
+    fn dost(&self) -> Array1<Complex<f32>> {
+        let n = self.len();
+        let bands = dost_bands(n);
+
+        // Orthonormal FFT of the signal
+        let H = self.map(Complex::from).fft() / (n as f32).sqrt();
+
+        let mut result = Array1::zeros(n);
+        for band in bands {
+            let width = (band.len() as f32).sqrt();
+            let voice = H.slice(s![band.clone()]).ifft() * width;
+            result.slice_mut(s![band]).assign(&voice);
         }
 
         result
     }
 }
 
-impl<S> InverseSTransform for ArrayBase<S, Ix2>
+impl<S> InverseDiscreteOrthonormalSTransform for ArrayBase<S, Ix1>
 where
     S: Data<Elem = Complex<f32>>,
 {
-    fn ist(&self) -> Array1<f32> {
-        let (nfreqs, ntimes) = self.dim();
+    fn idost(&self) -> Array1<f32> {
+        let n = self.len();
+        let bands = dost_bands(n);
+
+        let mut spectrum = Array1::zeros(n);
+        for band in bands {
+            let width = (band.len() as f32).sqrt();
+            let voice = self.slice(s![band.clone()]).fft() / width;
+            spectrum.slice_mut(s![band]).assign(&voice);
+        }
+
+        (spectrum * (n as f32).sqrt()).ifft().map(|z| z.re)
+    }
+}
+
+// Filtering of the `signal` in the time-frequency plane, weighting its Stockwell Transform by the
+// `mask` (frequencies (n / 2 + 1) x times (n)) before inverting it, e.g. to zero out an artifact
+//
+// Returns the filtered signal, of the length of the original one
+pub fn st_filter<S, T>(
+    signal: &ArrayBase<S, Ix1>,
+    mask: &ArrayBase<T, Ix2>,
+) -> Result<Array1<f32>, StError>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+{
+    let n = signal.len();
+    if mask.dim() != (n / 2 + 1, n) {
+        return Err(StError::ShapeMismatch {
+            shape: mask.dim(),
+            expected: (n / 2 + 1, n),
+        });
+    }
+
+    let mut st = signal.st();
+    st.zip_mut_with(mask, |z, &m| *z *= m);
+
+    st.ist()
+}
+
+// Cross-spectrum S_x conj(S_y) of the Stockwell Transforms of the signals `x` and `y`
+pub fn st_cross<S, T>(
+    x: &ArrayBase<S, Ix1>,
+    y: &ArrayBase<T, Ix1>,
+) -> Result<Array2<Complex<f32>>, StError>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+{
+    if x.len() != y.len() {
+        return Err(StError::LengthMismatch {
+            x: x.len(),
+            y: y.len(),
+        });
+    }
+
+    let mut cross = x.st();
+    cross.zip_mut_with(&y.st(), |sx, sy| *sx *= sy.conj());
+
+    Ok(cross)
+}
+
+// Time-resolved magnitude-squared coherence between the signals `x` and `y`, their cross- and
+// auto-spectra being averaged over `smoothing_window` samples centered on each time beforehand,
+// truncated at the edges
+// Without smoothing, the coherence of any pair of signals is trivially 1
+//
+// Returns the coherence in [0, 1] (frequencies x times), 0 wherever either signal has no power
+pub fn st_coherence<S, T>(
+    x: &ArrayBase<S, Ix1>,
+    y: &ArrayBase<T, Ix1>,
+    smoothing_window: usize,
+) -> Result<Array2<f32>, StError>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+{
+    if x.len() != y.len() {
+        return Err(StError::LengthMismatch {
+            x: x.len(),
+            y: y.len(),
+        });
+    }
+
+    let (sx, sy) = (x.st(), y.st());
+    let cross = smooth(
+        &Array2::from_shape_fn(sx.dim(), |i| sx[i] * sy[i].conj()),
+        smoothing_window,
+    );
+    let power_x = smooth(&sx.mapv(|z| Complex::from(z.norm_sqr())), smoothing_window);
+    let power_y = smooth(&sy.mapv(|z| Complex::from(z.norm_sqr())), smoothing_window);
+
+    Ok(Array2::from_shape_fn(sx.dim(), |i| {
+        let denominator = power_x[i].re * power_y[i].re;
+        if denominator > 0.0 {
+            (cross[i].norm_sqr() / denominator).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }))
+}
+
+// Moving average of each row of `st` over `window` samples centered on each time, truncated at the
+// edges
+fn smooth(st: &Array2<Complex<f32>>, window: usize) -> Array2<Complex<f32>> {
+    let n = st.ncols();
+    let before = window.saturating_sub(1) / 2;
+    let after = window.saturating_sub(1) - before;
+
+    let mut result = Array2::zeros(st.dim());
+    for (mut smoothed, row) in result.outer_iter_mut().zip(st.outer_iter()) {
+        // Prefix sums, each average being the difference of two of them
+        let mut sums = Vec::with_capacity(n + 1);
+        sums.push(Complex::new(0.0, 0.0));
+        for &z in row {
+            sums.push(sums[sums.len() - 1] + z);
+        }
+
+        for (t, value) in smoothed.iter_mut().enumerate() {
+            let (start, end) = (t.saturating_sub(before), (t + after + 1).min(n));
+            *value = (sums[end] - sums[start]) / (end - start) as f32;
+        }
+    }
+
+    result
+}
+
+// Power |S|^2 of the Stockwell Transform `st`, in dB clamped to `db_floor` if any
+pub fn power<A, S>(st: &ArrayBase<S, Ix2>, db_floor: Option<A>) -> Array2<A>
+where
+    A: FftElement,
+    S: Data<Elem = Complex<A>>,
+{
+    match db_floor {
+        Some(floor) => st.mapv(|z| (from_usize::<A>(10) * z.norm_sqr().log10()).max(floor)),
+        None => st.mapv(|z| z.norm_sqr()),
+    }
+}
+
+// Row of the Stockwell Transform at the frequency bin `f`, from the FFT `H` of the signal
+// `wgauss` is a buffer of the length of the signal, overwritten by the Gaussian of the row
+#[allow(non_snake_case)]
+fn voice<A: FftElement>(
+    H: &Array1<Complex<A>>,
+    f: usize,
+    wgauss: &mut Array1<A>,
+) -> Array1<Complex<A>> {
+    let n = H.len();
+    let gauss =
+        |n: usize, m: usize| (-(A::TAU() * A::PI()) * from_usize(m * m) / from_usize(n * n)).exp();
+
+    // Build Gaussian in frequency domain
+    wgauss[0] = gauss(f, 0);
+    for i in 1..=n / 2 {
+        let val = gauss(f, i);
+        wgauss[i] = val;
+        wgauss[n - i] = val;
+    }
+
+    // Multiply FFT{x} by frequency-localized Gaussian
+    // Correct by Convolution Theorem
+    let filtered = Array1::from_shape_fn(n, |i| {
+        let mut k = i + f;
+        if k >= n {
+            k -= n;
+        }
+        H[k].scale(wgauss[i])
+    });
+
+    // Compute Inverse FFT to get back time-localized signal
+    filtered.ifft()
+}
 
-        let mut result = Array1::<Complex<f32>>::zeros(ntimes);
+impl<A, S> InverseSTransform for ArrayBase<S, Ix2>
+where
+    A: FftElement,
+    S: Data<Elem = Complex<A>>,
+{
+    type Elem = A;
 
-        // Sum each frequency component across time
-        for f in 0..nfreqs {
-            result[f] = self.row(f).sum();
+    fn ist(&self) -> Result<Array1<A>, StError> {
+        self.ist_decimated(self.ncols(), 1)
+    }
+
+    fn ist_decimated(&self, num_samples: usize, time_decim: usize) -> Result<Array1<A>, StError> {
+        if time_decim == 0 {
+            return Err(StError::ZeroDecimation);
         }
 
-        for i in ntimes / 2 + 1..ntimes {
-            result[i] = result[ntimes - i].conj();
+        let n = num_samples;
+        let expected = (n / 2 + 1, n.div_ceil(time_decim));
+        if self.dim() != expected {
+            return Err(StError::ShapeMismatch {
+                shape: self.dim(),
+                expected,
+            });
         }
 
-        result.ifft().map(|z| z.re)
+        // Sum each frequency row across time, each kept time standing for `time_decim` samples
+        let mut spectrum = Array1::<Complex<A>>::zeros(n);
+        for (f, row) in self.outer_iter().enumerate() {
+            spectrum[f] = row.sum().scale(from_usize(time_decim));
+        }
+
+        // Mirror the conjugates of the positive frequencies, the Nyquist one of even lengths being
+        // its own mirror
+        for f in 1..n.div_ceil(2) {
+            spectrum[n - f] = spectrum[f].conj();
+        }
+
+        Ok(spectrum.ifft().map(|z| z.re))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array1;
+
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    // Uniform noise of `n` samples in [-0.5, 0.5)
+    fn noise(n: usize, seed: u64) -> Array1<f32> {
+        let mut generator = Lcg::new(seed);
+        Array1::from_shape_fn(n, |_| generator.next_f32() - 0.5)
+    }
+
+    fn max_difference<A: FftElement>(
+        a: &ArrayBase<impl Data<Elem = Complex<A>>, Ix2>,
+        b: &Array2<Complex<A>>,
+    ) -> f64 {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| (x - y).norm().to_f64().unwrap())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn restricted_transform_is_a_slice_of_the_full_one() {
+        // 1 Hz bins at 128 Hz
+        let signal = noise(128, 1);
+        let full = signal.st();
+
+        let range = signal.st_range(128.0, 4.5, 40.0, 3, 4).unwrap();
+        assert_eq!(range.st.dim(), (12, 32));
+        assert_eq!(
+            range.freqs,
+            Array1::from_iter((5..=40).step_by(3).map(|f| f as f32))
+        );
+        assert_eq!(
+            range.times,
+            Array1::from_shape_fn(32, |i| (4 * i) as f32 / 128.0)
+        );
+        let expected = full.slice(s![5..=40;3, ..;4]).to_owned();
+        assert!(max_difference(&range.st, &expected) < 1e-6);
+
+        // Up to the Nyquist frequency, the mean included
+        let range = signal.st_range(128.0, 0.0, 64.0, 1, 1).unwrap();
+        assert!(max_difference(&range.st, &full) < 1e-6);
+    }
+
+    #[test]
+    fn ranges_beyond_nyquist_are_rejected() {
+        let signal = noise(64, 2);
+        for (fmin, fmax) in [(1.0, 33.0), (-1.0, 10.0), (20.0, 10.0)] {
+            assert!(matches!(
+                signal.st_range(64.0, fmin, fmax, 1, 1),
+                Err(StError::FrequencyOutOfRange { nyquist: 32.0, .. })
+            ));
+        }
+    }
+
+    // Stockwell Transform by its definition, at the frequency bins `rows`, the Gaussian window
+    // of each bin k being periodized over the n samples of the signal:
+    // S[k, j] = sum_t x[t] exp(-2i pi k t / n) sum_p k / (n sqrt(2 pi)) exp(-k^2 (j - t + p n)^2 / 2n^2)
+    fn direct_st(signal: &Array1<f64>, rows: &[usize]) -> Array2<Complex<f64>> {
+        use std::f64::consts::{PI, TAU};
+
+        let n = signal.len();
+        let mut result = Array2::zeros((rows.len(), n));
+        for (mut row, &k) in result.outer_iter_mut().zip(rows) {
+            if k == 0 {
+                row.fill(Complex::from(signal.mean().unwrap()));
+                continue;
+            }
+
+            let (k, nf) = (k as f64, n as f64);
+            let window = (0..n)
+                .map(|lag| {
+                    (-8..=8)
+                        .map(|p| {
+                            let lag = lag as f64 + (p as f64) * nf;
+                            k / (nf * TAU.sqrt()) * (-k * k * lag * lag / (2.0 * nf * nf)).exp()
+                        })
+                        .sum::<f64>()
+                })
+                .collect::<Vec<f64>>();
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..n)
+                    .map(|t| {
+                        let angle = -2.0 * PI * k * t as f64 / nf;
+                        Complex::new(angle.cos(), angle.sin())
+                            * (signal[t] * window[(j + n - t) % n])
+                    })
+                    .sum();
+            }
+        }
+
+        result
+    }
+
+    #[test]
+    fn transform_of_any_length_matches_its_definition() {
+        let signal = noise(300, 3).mapv(|x| x as f64);
+        let st = signal.st();
+        assert_eq!(st.dim(), (151, 300));
+        let rows = (0..=150).step_by(7).collect::<Vec<usize>>();
+        let expected = direct_st(&signal, &rows);
+        let actual = ndarray::stack(
+            Axis(0),
+            &rows.iter().map(|&k| st.row(k)).collect::<Vec<_>>(),
+        );
+        assert!(max_difference(&actual.unwrap(), &expected) < 1e-8);
+
+        // Only a few rows of the longer signal, which are those of `st`, of n / 2 + 1 rows
+        assert_eq!(noise(63, 5).st().dim(), (32, 63));
+        let signal = noise(1023, 4).mapv(|x| x as f64);
+        let rows = [0, 1, 2, 5, 33, 200, 400, 511];
+        let expected = direct_st(&signal, &rows);
+        for (i, &k) in rows.iter().enumerate() {
+            let row = signal.st_range(1023.0, k as f64, k as f64, 1, 1).unwrap();
+            assert!(max_difference(&row.st, &expected.slice(s![i..=i, ..]).to_owned()) < 1e-8);
+        }
+    }
+
+    #[test]
+    fn rows_are_the_same_whether_computed_in_parallel_or_not() {
+        // `st_range` computes its rows one after the other, with or without the `rayon` feature
+        let signal = noise(256, 6);
+        let serial = signal.st_range(256.0, 0.0, 128.0, 1, 1).unwrap();
+        assert_eq!(signal.st(), serial.st);
+    }
+
+    #[test]
+    fn inverse_transform_gives_back_the_signal() {
+        for n in [1, 2, 7, 64, 100, 255] {
+            let signal = noise(n, n as u64);
+            let reconstructed = signal.st().ist().unwrap();
+            assert_eq!(reconstructed.len(), n);
+            let error = (&reconstructed - &signal)
+                .iter()
+                .fold(0.0f32, |max, d| max.max(d.abs()));
+            assert!(error < 1e-5, "{n} {error}");
+        }
+
+        let signal = noise(101, 7).mapv(|x| x as f64);
+        let error = (&signal.st().ist().unwrap() - &signal)
+            .iter()
+            .fold(0.0f64, |max, d| max.max(d.abs()));
+        assert!(error < 1e-12);
+    }
+
+    #[test]
+    fn decimated_transforms_are_validated() {
+        let signal = noise(64, 8);
+        let decimated = signal.st_range(64.0, 0.0, 32.0, 1, 3).unwrap().st;
+        assert_eq!(decimated.dim(), (33, 22));
+        assert_eq!(decimated.ist_decimated(64, 3).unwrap().len(), 64);
+        assert_eq!(
+            signal.st().ist_decimated(64, 1).unwrap(),
+            signal.st().ist().unwrap()
+        );
+
+        assert!(matches!(
+            decimated.ist_decimated(64, 0),
+            Err(StError::ZeroDecimation)
+        ));
+        assert!(matches!(
+            decimated.ist_decimated(64, 2),
+            Err(StError::ShapeMismatch {
+                shape: (33, 22),
+                expected: (33, 32),
+            })
+        ));
+        assert!(matches!(
+            signal.st().slice(s![..32, ..]).ist(),
+            Err(StError::ShapeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn power_of_a_tone_concentrates_at_its_frequency() {
+        // 2 Hz bins at 200 Hz
+        let signal = Array1::from_shape_fn(100, |t| {
+            (std::f32::consts::TAU * 20.0 * t as f32 / 200.0).sin()
+        });
+        let spectrogram = signal.st_spectrogram(200.0, None);
+        assert_eq!(spectrogram.power.dim(), (51, 100));
+        assert_eq!(spectrogram.freqs[10], 20.0);
+        assert_eq!(spectrogram.times[50], 0.25);
+
+        let row_power = spectrogram.power.sum_axis(Axis(1));
+        let peak = (0..row_power.len())
+            .max_by(|&a, &b| row_power[a].total_cmp(&row_power[b]))
+            .unwrap();
+        assert_eq!(spectrogram.freqs[peak], 20.0);
+
+        // The same out of a restricted transform, in dB floored at -40
+        let range = signal.st_range(200.0, 10.0, 30.0, 1, 1).unwrap();
+        let db = range.spectrogram(Some(-40.0));
+        assert_eq!(db.freqs, spectrogram.freqs.slice(s![5..=15]));
+        for (&db, &linear) in db.power.iter().zip(spectrogram.power.slice(s![5..=15, ..])) {
+            assert!((db - (10.0 * linear.log10()).max(-40.0)).abs() < 1e-3);
+        }
+        assert!(db.power.iter().all(|&p| p >= -40.0));
+    }
+
+    // Amplitude of the component of the `signal` at the frequency bin `k`
+    fn amplitude(signal: &Array1<f32>, k: usize) -> f32 {
+        2.0 * signal.mapv(Complex::from).fft()[k].norm() / signal.len() as f32
+    }
+
+    #[test]
+    fn masking_rows_around_line_noise_removes_it() {
+        // 1 Hz bins at 250 Hz
+        let tone = |f: f32, t: usize| (std::f32::consts::TAU * f * t as f32 / 250.0).sin();
+        let signal = Array1::from_shape_fn(250, |t| tone(10.0, t) + 0.5 * tone(50.0, t));
+
+        let ones = Array2::ones((126, 250));
+        let unchanged = st_filter(&signal, &ones).unwrap();
+        assert!((&unchanged - &signal).iter().all(|d| d.abs() < 1e-4));
+
+        let mut mask = ones;
+        mask.slice_mut(s![45..=55, ..]).fill(0.0);
+        let filtered = st_filter(&signal, &mask).unwrap();
+        assert_eq!(filtered.len(), 250);
+        let db = |after: f32, before: f32| 20.0 * (after / before).log10();
+        assert!(db(amplitude(&filtered, 50), amplitude(&signal, 50)) < -20.0);
+        assert!(db(amplitude(&filtered, 10), amplitude(&signal, 10)).abs() < 1.0);
+
+        assert!(matches!(
+            st_filter(&signal, &Array2::ones((125, 250))),
+            Err(StError::ShapeMismatch {
+                shape: (125, 250),
+                expected: (126, 250),
+            })
+        ));
+    }
+
+    #[test]
+    fn multichannel_transform_matches_each_channel() {
+        let data = Array2::from_shape_fn((3, 64), |(c, t)| noise(64, 10 + c as u64)[t]);
+        let transforms = data.st_channels();
+        assert_eq!(transforms.dim(), (3, 33, 64));
+        for (c, channel) in data.outer_iter().enumerate() {
+            assert_eq!(transforms.index_axis(Axis(0), c), channel.st());
+        }
+
+        let mut visited = Vec::new();
+        data.st_each(|c, st| {
+            assert_eq!(st, transforms.index_axis(Axis(0), c));
+            visited.push(c);
+        });
+        assert_eq!(visited, [0, 1, 2]);
+
+        assert_eq!(st_output_size(3, 64), 3 * 33 * 64 * 8);
+    }
+
+    #[test]
+    fn dost_is_invertible_and_preserves_energy() {
+        let signal = noise(256, 11);
+        let coefficients = signal.dost();
+        assert_eq!(coefficients.len(), 256);
+
+        let error = (&coefficients.idost() - &signal)
+            .iter()
+            .fold(0.0f32, |max, d| max.max(d.abs()));
+        assert!(error < 1e-5, "{error}");
+
+        let energy = signal.mapv(|x| x * x).sum();
+        let coefficient_energy = coefficients.mapv(|z| z.norm_sqr()).sum();
+        assert!((coefficient_energy - energy).abs() < 1e-4 * energy);
+    }
+
+    #[test]
+    fn dost_localizes_tones_in_their_bands() {
+        // Tones at the bins 5 and 40, within the bands [4, 8) and [32, 64)
+        let n = 128;
+        let tone = |k: f32, t: usize| (std::f32::consts::TAU * k * t as f32 / n as f32).cos();
+        let signal = Array1::from_shape_fn(n, |t| tone(5.0, t) + tone(40.0, t));
+        let coefficients = signal.dost();
+
+        let bands = dost_bands(n);
+        assert_eq!(bands.iter().map(|band| band.len()).sum::<usize>(), n);
+        let energy = |band: &Range<usize>| {
+            coefficients
+                .slice(s![band.clone()])
+                .mapv(|z| z.norm_sqr())
+                .sum()
+        };
+        let total = coefficients.mapv(|z| z.norm_sqr()).sum();
+        let localized = [4..8, 32..64, n - 63..n - 31, n - 7..n - 3]
+            .iter()
+            .map(|band| {
+                assert!(bands.contains(band), "{band:?}");
+                energy(band)
+            })
+            .sum::<f32>();
+        assert!(localized > 0.999 * total, "{localized} {total}");
+        // Both tones share their energy evenly with their negative mirrors
+        assert!((energy(&(4..8)) - energy(&(n - 7..n - 3))).abs() < 1e-3 * total);
+        assert!((energy(&(4..8)) - 0.25 * total).abs() < 1e-3 * total);
+    }
+
+    #[test]
+    fn coherence_of_identical_signals_is_one() {
+        let signal = noise(64, 12);
+        let coherence = st_coherence(&signal, &signal, 5).unwrap();
+        assert_eq!(coherence.dim(), (33, 64));
+        assert!(coherence.iter().all(|&c| (c - 1.0).abs() < 1e-4));
+
+        let cross = st_cross(&signal, &signal).unwrap();
+        let power = power(&signal.st(), None);
+        assert!(cross
+            .iter()
+            .zip(&power)
+            .all(|(z, &p)| (z.re - p).abs() < 1e-6 && z.im.abs() < 1e-6));
+
+        assert!(matches!(
+            st_coherence(&signal, &noise(63, 13), 5),
+            Err(StError::LengthMismatch { x: 64, y: 63 })
+        ));
+        assert!(matches!(
+            st_cross(&signal, &noise(63, 13)),
+            Err(StError::LengthMismatch { x: 64, y: 63 })
+        ));
+    }
+
+    #[test]
+    fn coherence_rises_where_signals_share_a_rhythm() {
+        // 0.5 Hz bins at 100 Hz, a common 10 Hz rhythm in the second half only
+        let rhythm = |t: usize| {
+            if t >= 100 {
+                (std::f32::consts::TAU * 10.0 * t as f32 / 100.0).sin()
+            } else {
+                0.0
+            }
+        };
+        let (a, b) = (noise(200, 14), noise(200, 15));
+        let mean = |coherence: &Array2<f32>, row: usize, times: Range<usize>| {
+            coherence.slice(s![row, times]).mean().unwrap()
+        };
+
+        // Independent noise, at frequencies whose windows are short before the smoothing
+        let coherence = st_coherence(&a, &b, 41).unwrap();
+        let independent = coherence.slice(s![40.., ..]).mean().unwrap();
+        assert!(independent < 0.2, "{independent}");
+
+        let x = Array1::from_shape_fn(200, |t| a[t] + rhythm(t));
+        let y = Array1::from_shape_fn(200, |t| b[t] + rhythm(t));
+        let coherence = st_coherence(&x, &y, 41).unwrap();
+        let (first, second) = (mean(&coherence, 20, 10..70), mean(&coherence, 20, 130..190));
+        let elsewhere = mean(&coherence, 60, 130..190);
+        assert!(second > 0.9, "{second}");
+        assert!(first < 0.5, "{first}");
+        assert!(elsewhere < 0.5, "{elsewhere}");
+    }
+
+    #[test]
+    fn double_precision_round_trips_more_accurately() {
+        let signal = noise(4096, 16);
+        let error = (&signal.st().ist().unwrap() - &signal)
+            .iter()
+            .fold(0.0f32, |max, d| max.max(d.abs())) as f64;
+
+        let signal = signal.mapv(|x| x as f64);
+        let error_f64 = (&signal.st().ist().unwrap() - &signal)
+            .iter()
+            .fold(0.0f64, |max, d| max.max(d.abs()));
+        assert!(error_f64 < 1e-4 * error, "{error} {error_f64}");
+    }
+
+    #[test]
+    fn empty_signal_fails() {
+        assert!(matches!(
+            Array1::<f32>::zeros(0).try_st(),
+            Err(StError::EmptySignal)
+        ));
+        assert_eq!(noise(1, 0).try_st().unwrap().dim(), (1, 1));
     }
 }
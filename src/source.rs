@@ -0,0 +1,47 @@
+use ndarray::{Array1, ArrayBase, CowArray, Data, Ix1, Ix2};
+
+use crate::read::brainvision_core::ScaledView;
+
+// Multichannel data (channels x samples) whose channels can be had as `f32`, be it held as such or
+// converted on the fly, e.g. from Int16 by a `ScaledView`
+// Accepted by the functions processing data channel by channel, which then only ever hold a single
+// channel in `f32` at once
+pub trait SignalSource {
+    fn num_channels(&self) -> usize;
+
+    fn num_samples(&self) -> usize;
+
+    // Samples of the channel at `index`, borrowed where they are held as `f32`
+    fn channel(&self, index: usize) -> CowArray<'_, f32, Ix1>;
+}
+
+impl<S> SignalSource for ArrayBase<S, Ix2>
+where
+    S: Data<Elem = f32>,
+{
+    fn num_channels(&self) -> usize {
+        self.nrows()
+    }
+
+    fn num_samples(&self) -> usize {
+        self.ncols()
+    }
+
+    fn channel(&self, index: usize) -> CowArray<'_, f32, Ix1> {
+        self.row(index).into()
+    }
+}
+
+impl SignalSource for ScaledView<'_> {
+    fn num_channels(&self) -> usize {
+        ScaledView::num_channels(self)
+    }
+
+    fn num_samples(&self) -> usize {
+        ScaledView::num_samples(self)
+    }
+
+    fn channel(&self, index: usize) -> CowArray<'_, f32, Ix1> {
+        self.channel_iter(index).collect::<Array1<f32>>().into()
+    }
+}
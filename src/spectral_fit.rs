@@ -0,0 +1,455 @@
+use std::{error::Error, fmt::Display};
+
+use nalgebra::{DMatrix, DVector};
+use ndarray::{Array1, ArrayBase, Data, Ix1};
+
+// T. Donoghue et al., "Parameterizing neural power spectra into periodic and aperiodic
+// components," Nature Neuroscience, vol. 23, pp. 1655-1665, 2020.
+
+// Height of a peak, in standard deviations of the flattened spectrum, below which peaks are no
+// longer searched for
+const PEAK_THRESHOLD: f64 = 2.0;
+// Height of a peak, in log10 power, below which it is numerical noise rather than a peak
+const MIN_PEAK_HEIGHT: f64 = 1e-3;
+// Extent of a peak about its center, in standard deviations, beyond which it overlaps another
+const OVERLAP_STDS: f64 = 0.75;
+// Percentile of the flattened spectrum the points of the robust aperiodic fit lie below
+const APERIODIC_PERCENTILE: f64 = 0.025;
+// Distance to the edges of the spectrum, in bandwidths, under which peaks are dropped
+const EDGE_BANDWIDTHS: f64 = 1.0;
+// Iterations of the Levenberg-Marquardt refinement of the peaks
+const MAX_ITERATIONS: usize = 100;
+
+// Errors that can occur while fitting a power spectrum
+#[derive(Debug)]
+pub enum SpectralFitError {
+    // There are not as many frequencies as values of the PSD
+    LengthMismatch { freqs: usize, psd: usize },
+    // There are too few frequencies to fit the aperiodic component
+    NotEnoughPoints { len: usize },
+    // A frequency is not positive, so that its logarithm is undefined
+    NonPositiveFrequency { index: usize, freq: f32 },
+    // A value of the PSD is not positive, so that its logarithm is undefined
+    NonPositivePower { index: usize, power: f32 },
+}
+
+impl Display for SpectralFitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpectralFitError::LengthMismatch { freqs, psd } => {
+                write!(f, "{freqs} frequencies given for {psd} values of the PSD")
+            }
+            SpectralFitError::NotEnoughPoints { len } => {
+                write!(f, "{len} frequencies are not enough to fit the spectrum")
+            }
+            SpectralFitError::NonPositiveFrequency { index, freq } => {
+                write!(f, "frequency {index} of {freq} Hz is not positive")
+            }
+            SpectralFitError::NonPositivePower { index, power } => {
+                write!(f, "value {index} of the PSD, {power}, is not positive")
+            }
+        }
+    }
+}
+
+impl Error for SpectralFitError {}
+
+// Oscillatory peak of a power spectrum, a Gaussian over the aperiodic component in log10 power
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectralPeak {
+    // Center frequency, in Hz
+    pub center: f32,
+    // Height above the aperiodic component, in log10 power
+    pub power: f32,
+    // Bandwidth, twice the standard deviation of the Gaussian, in Hz
+    pub width: f32,
+}
+
+// Power spectrum decomposed into its aperiodic and periodic components:
+// log10 P(f) = offset - exponent log10 f + sum of the Gaussian peaks
+#[derive(Clone, Debug)]
+pub struct SpectralFit {
+    pub offset: f32,
+    pub exponent: f32,
+    // Peaks in decreasing order of power
+    pub peaks: Vec<SpectralPeak>,
+    // PSD of the model at each frequency, in the units of the PSD fitted
+    pub model: Array1<f32>,
+    // Coefficient of determination of the model, in log10 power
+    pub r_squared: f32,
+}
+
+// Fits the `psd` of the `freqs` in Hz with an aperiodic component and up to `max_peaks` Gaussian
+// peaks of bandwidths within `peak_width_limits` (min, max) in Hz, as FOOOF does in its fixed mode:
+// - the aperiodic component is fitted by least squares in log-log space, then refitted over the
+//   points below its flattened spectrum, robust to the peaks
+// - peaks are searched for one at a time in the flattened spectrum, each one subtracted before the
+//   next, until the highest point left is within 2 standard deviations of it, then refined jointly
+//   by Levenberg-Marquardt, those within a bandwidth of the edges or overlapping a higher one
+//   (within 0.75 standard deviations of their centers) being dropped
+// - the aperiodic component is finally refitted to the spectrum without the peaks
+//
+// Fails if the frequencies or the values of the PSD are not all positive
+pub fn fit_spectrum<S, T>(
+    freqs: &ArrayBase<S, Ix1>,
+    psd: &ArrayBase<T, Ix1>,
+    max_peaks: usize,
+    peak_width_limits: (f32, f32),
+) -> Result<SpectralFit, SpectralFitError>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+{
+    if freqs.len() != psd.len() {
+        return Err(SpectralFitError::LengthMismatch {
+            freqs: freqs.len(),
+            psd: psd.len(),
+        });
+    }
+    if freqs.len() < 3 {
+        return Err(SpectralFitError::NotEnoughPoints { len: freqs.len() });
+    }
+    if let Some((index, &freq)) = freqs
+        .iter()
+        .enumerate()
+        .find(|(_, &f)| f.is_nan() || f <= 0.0)
+    {
+        return Err(SpectralFitError::NonPositiveFrequency { index, freq });
+    }
+    if let Some((index, &power)) = psd
+        .iter()
+        .enumerate()
+        .find(|(_, &p)| p.is_nan() || p <= 0.0)
+    {
+        return Err(SpectralFitError::NonPositivePower { index, power });
+    }
+
+    let f = freqs.iter().map(|&f| f as f64).collect::<Vec<f64>>();
+    let log_f = f.iter().map(|f| f.log10()).collect::<Vec<f64>>();
+    let log_p = psd
+        .iter()
+        .map(|&p| (p as f64).log10())
+        .collect::<Vec<f64>>();
+
+    // Robust aperiodic fit, over the points of the initial one at its lowest flattened percentile
+    let all = (0..f.len()).collect::<Vec<usize>>();
+    let (offset, exponent) = fit_aperiodic(&log_f, &log_p, &all);
+    let mut flattened = flatten(&log_f, &log_p, offset, exponent)
+        .into_iter()
+        .map(|value| value.max(0.0))
+        .collect::<Vec<f64>>();
+    let threshold = percentile(&flattened, APERIODIC_PERCENTILE);
+    let below = all
+        .iter()
+        .copied()
+        .filter(|&i| flattened[i] <= threshold)
+        .collect::<Vec<usize>>();
+    let (offset, exponent) = fit_aperiodic(&log_f, &log_p, &below);
+
+    // Peaks guessed from the flattened spectrum, one at a time
+    let flat = flatten(&log_f, &log_p, offset, exponent);
+    flattened.clone_from(&flat);
+    let (min_std, max_std) = (
+        peak_width_limits.0 as f64 / 2.0,
+        peak_width_limits.1 as f64 / 2.0,
+    );
+    let mut guesses = Vec::new();
+    while guesses.len() < max_peaks {
+        let (index, height) = flattened
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        if height <= (PEAK_THRESHOLD * std(&flattened)).max(MIN_PEAK_HEIGHT) {
+            break;
+        }
+
+        // Half-width at half its height, from the closer of its two half-height crossings
+        let half = height / 2.0;
+        let left = (0..index).rev().find(|&i| flattened[i] <= half);
+        let right = (index + 1..f.len()).find(|&i| flattened[i] <= half);
+        let half_width = [
+            left.map(|i| f[index] - f[i]),
+            right.map(|i| f[i] - f[index]),
+        ]
+        .into_iter()
+        .flatten()
+        .fold(f64::INFINITY, f64::min);
+        let sigma = if half_width.is_finite() {
+            half_width / (2.0 * 2f64.ln()).sqrt()
+        } else {
+            max_std
+        }
+        .clamp(min_std, max_std);
+
+        let guess = (f[index], height, sigma);
+        for (value, &freq) in flattened.iter_mut().zip(&f) {
+            *value -= gaussian(freq, guess);
+        }
+        guesses.push(guess);
+    }
+
+    let mut peaks = refine_peaks(&f, &flat, guesses, (min_std, max_std));
+    let (first, last) = (f[0], f[f.len() - 1]);
+    peaks.retain(|&(center, _, sigma)| {
+        let margin = EDGE_BANDWIDTHS * 2.0 * sigma;
+        center - first >= margin && last - center >= margin
+    });
+    drop_overlaps(&mut peaks);
+    peaks.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    // Aperiodic component of the spectrum without its peaks
+    let periodic = f
+        .iter()
+        .map(|&freq| peaks.iter().map(|&peak| gaussian(freq, peak)).sum::<f64>())
+        .collect::<Vec<f64>>();
+    let aperiodic = log_p
+        .iter()
+        .zip(&periodic)
+        .map(|(p, g)| p - g)
+        .collect::<Vec<f64>>();
+    let (offset, exponent) = fit_aperiodic(&log_f, &aperiodic, &all);
+
+    let log_model = log_f
+        .iter()
+        .zip(&periodic)
+        .map(|(lf, g)| offset - exponent * lf + g)
+        .collect::<Vec<f64>>();
+    let mean = log_p.iter().sum::<f64>() / log_p.len() as f64;
+    let residual = log_p
+        .iter()
+        .zip(&log_model)
+        .map(|(p, m)| (p - m).powi(2))
+        .sum::<f64>();
+    let total = log_p.iter().map(|p| (p - mean).powi(2)).sum::<f64>();
+
+    Ok(SpectralFit {
+        offset: offset as f32,
+        exponent: exponent as f32,
+        peaks: peaks
+            .iter()
+            .map(|&(center, height, sigma)| SpectralPeak {
+                center: center as f32,
+                power: height as f32,
+                width: (2.0 * sigma) as f32,
+            })
+            .collect(),
+        model: log_model.iter().map(|m| 10f64.powf(*m) as f32).collect(),
+        r_squared: if total > 0.0 {
+            (1.0 - residual / total) as f32
+        } else {
+            1.0
+        },
+    })
+}
+
+// Offset and exponent of the least-squares line `log_p = offset - exponent log_f`, over the points
+// at `indices`
+fn fit_aperiodic(log_f: &[f64], log_p: &[f64], indices: &[usize]) -> (f64, f64) {
+    let n = indices.len() as f64;
+    let mean_f = indices.iter().map(|&i| log_f[i]).sum::<f64>() / n;
+    let mean_p = indices.iter().map(|&i| log_p[i]).sum::<f64>() / n;
+    let (covariance, variance) = indices.iter().fold((0.0, 0.0), |(c, v), &i| {
+        let df = log_f[i] - mean_f;
+        (c + df * (log_p[i] - mean_p), v + df * df)
+    });
+    let slope = if variance > 0.0 {
+        covariance / variance
+    } else {
+        0.0
+    };
+
+    (mean_p - slope * mean_f, -slope)
+}
+
+// Spectrum `log_p` above the aperiodic component of `offset` and `exponent`
+fn flatten(log_f: &[f64], log_p: &[f64], offset: f64, exponent: f64) -> Vec<f64> {
+    log_f
+        .iter()
+        .zip(log_p)
+        .map(|(lf, p)| p - (offset - exponent * lf))
+        .collect()
+}
+
+// Gaussian of `(center, height, sigma)` at the frequency `freq`
+fn gaussian(freq: f64, (center, height, sigma): (f64, f64, f64)) -> f64 {
+    height * (-(freq - center).powi(2) / (2.0 * sigma * sigma)).exp()
+}
+
+// Peaks of (center, height, sigma) fitted jointly to the `flattened` spectrum by
+// Levenberg-Marquardt, from their `guesses`, the heights kept positive and the standard deviations
+// within `sigma_limits`
+fn refine_peaks(
+    f: &[f64],
+    flattened: &[f64],
+    guesses: Vec<(f64, f64, f64)>,
+    (min_std, max_std): (f64, f64),
+) -> Vec<(f64, f64, f64)> {
+    if guesses.is_empty() {
+        return guesses;
+    }
+    let residuals = |peaks: &[(f64, f64, f64)]| {
+        DVector::from_iterator(
+            f.len(),
+            f.iter().zip(flattened).map(|(&freq, &value)| {
+                value - peaks.iter().map(|&peak| gaussian(freq, peak)).sum::<f64>()
+            }),
+        )
+    };
+
+    let mut peaks = guesses;
+    let mut residual = residuals(&peaks);
+    let mut damping = 1e-3;
+    for _ in 0..MAX_ITERATIONS {
+        // Jacobian of the model with respect to the center, height and sigma of each peak
+        let jacobian = DMatrix::from_fn(f.len(), 3 * peaks.len(), |i, j| {
+            let (center, height, sigma) = peaks[j / 3];
+            let offset = f[i] - center;
+            let value = gaussian(f[i], (center, 1.0, sigma));
+            match j % 3 {
+                0 => height * value * offset / (sigma * sigma),
+                1 => value,
+                _ => height * value * offset * offset / sigma.powi(3),
+            }
+        });
+        let normal = jacobian.transpose() * &jacobian;
+        let gradient = jacobian.transpose() * &residual;
+        let mut damped = normal.clone();
+        for k in 0..damped.nrows() {
+            damped[(k, k)] += damping * normal[(k, k)].max(1e-12);
+        }
+        let Some(step) = damped.lu().solve(&gradient) else {
+            break;
+        };
+
+        let candidate = peaks
+            .iter()
+            .enumerate()
+            .map(|(k, &(center, height, sigma))| {
+                (
+                    center + step[3 * k],
+                    (height + step[3 * k + 1]).max(f64::EPSILON),
+                    (sigma + step[3 * k + 2]).clamp(min_std, max_std),
+                )
+            })
+            .collect::<Vec<(f64, f64, f64)>>();
+        let candidate_residual = residuals(&candidate);
+        if candidate_residual.norm_squared() < residual.norm_squared() {
+            let improvement = residual.norm_squared() - candidate_residual.norm_squared();
+            (peaks, residual) = (candidate, candidate_residual);
+            damping /= 10.0;
+            if improvement <= 1e-12 * residual.norm_squared().max(1e-300) {
+                break;
+            }
+        } else {
+            damping *= 10.0;
+        }
+    }
+
+    peaks
+}
+
+// Drops the lower of each pair of neighbouring `peaks` overlapping each other
+fn drop_overlaps(peaks: &mut Vec<(f64, f64, f64)>) {
+    peaks.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut i = 0;
+    while i + 1 < peaks.len() {
+        let (left, right) = (peaks[i], peaks[i + 1]);
+        if left.0 + OVERLAP_STDS * left.2 > right.0 - OVERLAP_STDS * right.2 {
+            peaks.remove(if left.1 < right.1 { i } else { i + 1 });
+        } else {
+            i += 1;
+        }
+    }
+}
+
+// Percentile `q` of the `values`, linearly interpolated
+fn percentile(values: &[f64], q: f64) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let position = q * (sorted.len() - 1) as f64;
+    let (below, fraction) = (position.floor() as usize, position.fract());
+    let above = (below + 1).min(sorted.len() - 1);
+
+    sorted[below] + fraction * (sorted[above] - sorted[below])
+}
+
+// Population standard deviation of the `values`
+fn std(values: &[f64]) -> f64 {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    // A 1/f^1.5 spectrum from 1 to 50 Hz by 0.25 Hz with an alpha peak of 0.8 log10 power at
+    // 10 Hz, of a standard deviation of 1 Hz, and noise of up to 0.05 log10 power
+    fn spectrum() -> (Array1<f32>, Array1<f32>) {
+        let mut generator = Lcg::new(13);
+        let freqs = Array1::from_shape_fn(197, |i| 1.0 + 0.25 * i as f32);
+        let psd = freqs.mapv(|f: f32| {
+            let peak = 0.8 * (-0.5 * (f - 10.0).powi(2)).exp();
+            let noise = 0.1 * (generator.next_f32() - 0.5);
+            10f32.powf(1.0 - 1.5 * f.log10() + peak + noise)
+        });
+
+        (freqs, psd)
+    }
+
+    #[test]
+    fn aperiodic_exponent_and_alpha_peak_are_recovered() {
+        let (freqs, psd) = spectrum();
+        let fit = fit_spectrum(&freqs, &psd, 4, (0.5, 8.0)).unwrap();
+
+        assert!((fit.exponent - 1.5).abs() < 0.1, "{}", fit.exponent);
+        assert!((fit.offset - 1.0).abs() < 0.1, "{}", fit.offset);
+        let alpha = fit.peaks[0];
+        assert!((alpha.center - 10.0).abs() < 0.5, "{alpha:?}");
+        assert!((alpha.power - 0.8).abs() < 0.1, "{alpha:?}");
+        assert!((alpha.width - 2.0).abs() < 0.5, "{alpha:?}");
+        // Any other peak is fitted noise
+        assert!(fit.peaks[1..].iter().all(|peak| peak.power < 0.1));
+        assert_eq!(fit.model.len(), psd.len());
+        assert!(fit.r_squared > 0.95, "{}", fit.r_squared);
+    }
+
+    #[test]
+    fn invalid_spectra_fail() {
+        let (freqs, mut psd) = spectrum();
+
+        assert!(matches!(
+            fit_spectrum(&freqs.slice(ndarray::s![..10]), &psd, 1, (0.5, 8.0)),
+            Err(SpectralFitError::LengthMismatch {
+                freqs: 10,
+                psd: 197
+            })
+        ));
+        assert!(matches!(
+            fit_spectrum(
+                &freqs.slice(ndarray::s![..2]),
+                &psd.slice(ndarray::s![..2]),
+                1,
+                (0.5, 8.0)
+            ),
+            Err(SpectralFitError::NotEnoughPoints { len: 2 })
+        ));
+        let mut zero_frequency = freqs.clone();
+        zero_frequency[0] = 0.0;
+        assert!(matches!(
+            fit_spectrum(&zero_frequency, &psd, 1, (0.5, 8.0)),
+            Err(SpectralFitError::NonPositiveFrequency { index: 0, .. })
+        ));
+        for power in [0.0, -1.0, f32::NAN] {
+            psd[40] = power;
+            assert!(matches!(
+                fit_spectrum(&freqs, &psd, 1, (0.5, 8.0)),
+                Err(SpectralFitError::NonPositivePower { index: 40, .. })
+            ));
+        }
+    }
+}
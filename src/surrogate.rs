@@ -0,0 +1,170 @@
+use std::f32::consts::TAU;
+
+use nalgebra::Complex;
+use ndarray::{Array1, ArrayBase, Data, Ix1};
+
+use crate::fft::{InverseFourierTransform, RealFourierTransform};
+
+// J. Theiler, S. Eubank, A. Longtin, B. Galdrikian and J. D. Farmer, "Testing for nonlinearity in
+// time series: the method of surrogate data," Physica D, vol. 58, no. 1-4, pp. 77-94, 1992.
+
+// Surrogate of the `signal` of the same amplitude spectrum, the phase of each of its frequencies
+// drawn uniformly at random from the `seed`, the DC and Nyquist components being kept real
+// Destroys any phase relationship to other signals while preserving the power spectrum (and
+// autocorrelation), the same `seed` always yielding the same surrogate
+pub fn phase_randomized_surrogate<S>(signal: &ArrayBase<S, Ix1>, seed: u64) -> Array1<f32>
+where
+    S: Data<Elem = f32>,
+{
+    let n = signal.len();
+    let mut spectrum = signal.rfft();
+    let mut generator = Lcg::new(seed);
+
+    // Each positive frequency is rotated, its negative counterpart staying its conjugate
+    for k in 1..n.div_ceil(2) {
+        let rotation = Complex::from_polar(1.0, TAU * generator.next_f32());
+        spectrum[k] *= rotation;
+        spectrum[n - k] = spectrum[k].conj();
+    }
+
+    spectrum.ifft().mapv(|z| z.re)
+}
+
+// Surrogate of the `signal` circularly shifted by `shift` samples, sample `i` moving to
+// `(i + shift) % n`
+// Preserves the signal whole while breaking its alignment to other signals
+pub fn circular_shift_surrogate<S>(signal: &ArrayBase<S, Ix1>, shift: usize) -> Array1<f32>
+where
+    S: Data<Elem = f32>,
+{
+    let n = signal.len();
+    if n == 0 {
+        return Array1::zeros(0);
+    }
+
+    let shift = shift % n;
+    Array1::from_shape_fn(n, |i| signal[(i + n - shift) % n])
+}
+
+// Significance threshold of the `metric` between the signals `x` and `y`, e.g. their PLV: the
+// (1 - `alpha`) quantile (linearly interpolated) of the metric between `x` and `n_surrogates`
+// phase-randomized surrogates of `y`, seeded in turn from the `seed`
+// Values of the metric above the threshold are significant at the level `alpha`
+//
+// Panics if there are no surrogates or `alpha` is not within (0, 1)
+pub fn surrogate_threshold<F, S, T>(
+    metric: F,
+    x: &ArrayBase<S, Ix1>,
+    y: &ArrayBase<T, Ix1>,
+    n_surrogates: usize,
+    alpha: f32,
+    seed: u64,
+) -> f32
+where
+    F: Fn(&Array1<f32>, &Array1<f32>) -> f32,
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+{
+    assert!(n_surrogates > 0, "No surrogates !");
+    assert!(
+        alpha > 0.0 && alpha < 1.0,
+        "Significance level not within (0, 1) !"
+    );
+
+    let x = x.to_owned();
+    let mut generator = Lcg::new(seed);
+    let mut values = (0..n_surrogates)
+        .map(|_| metric(&x, &phase_randomized_surrogate(y, generator.next_u64())))
+        .collect::<Vec<f32>>();
+    values.sort_by(f32::total_cmp);
+
+    let position = (1.0 - alpha) * (n_surrogates - 1) as f32;
+    let (below, fraction) = (position.floor() as usize, position.fract());
+    let above = (below + 1).min(n_surrogates - 1);
+
+    values[below] + fraction * (values[above] - values[below])
+}
+
+// Linear congruential generator, of Knuth's MMIX constants
+pub(crate) struct Lcg(u64);
+
+impl Lcg {
+    pub(crate) fn new(seed: u64) -> Self {
+        Lcg(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0
+    }
+
+    // Uniformly distributed in [0, 1)
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectivity::plv;
+
+    fn noise(n: usize, seed: u64) -> Array1<f32> {
+        let mut generator = Lcg::new(seed);
+        Array1::from_shape_fn(n, |_| generator.next_f32() - 0.5)
+    }
+
+    #[test]
+    fn phase_randomization_preserves_the_amplitude_spectrum() {
+        for n in [64, 101, 1000] {
+            let signal = noise(n, 3);
+            let surrogate = phase_randomized_surrogate(&signal, 17);
+
+            let (original, randomized) = (signal.rfft(), surrogate.rfft());
+            let largest = original.iter().fold(0.0f32, |max, z| max.max(z.norm()));
+            for (a, b) in original.iter().zip(&randomized) {
+                assert!((a.norm() - b.norm()).abs() < 1e-4 * largest, "{n}");
+            }
+            assert!((&surrogate - &signal).mapv(f32::abs).sum() > 1.0);
+            assert_eq!(surrogate, phase_randomized_surrogate(&signal, 17));
+            assert_ne!(surrogate, phase_randomized_surrogate(&signal, 18));
+        }
+    }
+
+    #[test]
+    fn phase_randomization_destroys_phase_locking() {
+        // A copy of the signal 2 samples later, plus a little noise, locked to it in any band
+        let (fs, band) = (250.0, (8.0, 12.0));
+        let x = noise(2000, 5);
+        let y = &circular_shift_surrogate(&x, 2) + &(0.1 * &noise(2000, 6));
+
+        let locked = plv(&x, &y, fs, band).unwrap();
+        assert!(locked > 0.9, "{locked}");
+        let destroyed = plv(&x, &phase_randomized_surrogate(&y, 1), fs, band).unwrap();
+        assert!(destroyed < 0.2, "{destroyed}");
+
+        let metric = |a: &Array1<f32>, b: &Array1<f32>| plv(a, b, fs, band).unwrap();
+        let threshold = surrogate_threshold(metric, &x, &y, 50, 0.05, 9);
+        // Far below the PLV of the locked signals, which is thus significant
+        assert!(threshold > 0.05 && threshold < 0.5, "{threshold}");
+        assert_eq!(threshold, surrogate_threshold(metric, &x, &y, 50, 0.05, 9));
+    }
+
+    #[test]
+    fn circular_shift_wraps_samples_around() {
+        let signal = Array1::from(vec![1.0, 2.0, 3.0, 4.0]);
+
+        assert_eq!(
+            circular_shift_surrogate(&signal, 1).to_vec(),
+            vec![4.0, 1.0, 2.0, 3.0]
+        );
+        assert_eq!(
+            circular_shift_surrogate(&signal, 6),
+            circular_shift_surrogate(&signal, 2)
+        );
+        assert!(circular_shift_surrogate(&Array1::<f32>::zeros(0), 3).is_empty());
+    }
+}
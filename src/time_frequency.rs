@@ -0,0 +1,367 @@
+use std::{error::Error, fmt::Display};
+
+use nalgebra::Complex;
+use ndarray::{s, Array1, Array2, Array3, ArrayBase, Axis, Data, DataMut, Ix1, Ix2, Ix3};
+
+use crate::{
+    s_transform::STransform,
+    wavelet::{Morlet, WaveletTransform},
+};
+
+// Central angular frequency of the Morlet wavelet the epochs are analysed with
+const MORLET_OMEGA: f32 = 6.0;
+
+// Errors that can occur while analysing epochs in time and frequency
+#[derive(Debug)]
+pub enum TfError {
+    // The baseline window of samples is empty or does not fit inside the epochs
+    BaselineOutOfBounds {
+        start: usize,
+        end: usize,
+        num_samples: usize,
+    },
+}
+
+impl Display for TfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TfError::BaselineOutOfBounds {
+                start,
+                end,
+                num_samples,
+            } => write!(
+                f,
+                "baseline spans samples {start}..{end}, \
+                 which is empty or exceeds the {num_samples} samples of the epochs"
+            ),
+        }
+    }
+}
+
+impl Error for TfError {}
+
+// Time-frequency decomposition the epochs are analysed with
+#[derive(Clone, Copy, Debug)]
+pub enum TimeFrequencyMethod {
+    // Continuous Wavelet Transform, using a Morlet wavelet
+    Cwt,
+    // Stockwell Transform, the epochs being zero-padded to a power of 2
+    STransform,
+}
+
+// Convention the time-frequency power is expressed in relative to its baseline
+#[derive(Clone, Copy, Debug)]
+pub enum BaselineMode {
+    // 10 log10(P / mean), in dB
+    DbRatio,
+    // 100 (P - mean) / mean, in percent
+    PercentChange,
+    // (P - mean) / std, in standard deviations of the baseline
+    ZScore,
+}
+
+// Normalizes the time-frequency power `tf_power` (freqs x times) in place, relative to the mean
+// (and population standard deviation) of each frequency row over the `baseline` window
+// `[start, end]` of the `times` in seconds, one per column, as per the `mode`
+// Rows whose baseline is zero in mean (ratio modes) or in variance (z-score) carry no reference to
+// be expressed in and are set to NaN
+// Panics if the `times` are not one per column of `tf_power`
+pub fn baseline_normalize<S>(
+    tf_power: &mut ArrayBase<S, Ix2>,
+    times: &[f32],
+    (start, end): (f32, f32),
+    mode: BaselineMode,
+) -> Result<(), TfError>
+where
+    S: DataMut<Elem = f32>,
+{
+    let num_samples = tf_power.ncols();
+    assert_eq!(times.len(), num_samples, "One time per column expected !");
+
+    let first = times.iter().take_while(|&&t| t < start).count();
+    let last = first + times[first..].iter().take_while(|&&t| t <= end).count();
+    if first >= last {
+        return Err(TfError::BaselineOutOfBounds {
+            start: first,
+            end: last,
+            num_samples,
+        });
+    }
+
+    for mut row in tf_power.outer_iter_mut() {
+        let reference = row.slice(s![first..last]);
+        let mean = reference.mean().unwrap();
+        let scale = match mode {
+            BaselineMode::DbRatio | BaselineMode::PercentChange => mean,
+            BaselineMode::ZScore => reference.std(0.0),
+        };
+        if scale == 0.0 {
+            row.fill(f32::NAN);
+            continue;
+        }
+
+        match mode {
+            BaselineMode::DbRatio => row.mapv_inplace(|p| 10.0 * (p / mean).log10()),
+            BaselineMode::PercentChange => row.mapv_inplace(|p| 100.0 * (p - mean) / mean),
+            BaselineMode::ZScore => row.mapv_inplace(|p| (p - mean) / scale),
+        }
+    }
+
+    Ok(())
+}
+
+// Event-related spectral perturbation of the `epochs` (epochs x channels x samples), sampled at
+// `fs` Hz, at each of the `freqs` in Hz
+// The time-frequency power of each epoch is averaged across epochs, then expressed in dB relative
+// to its mean over the `baseline` window `[start, end)` of samples, or as is when not given
+// Fails if the `baseline` is empty or exceeds the epochs
+//
+// Returns the ERSP in dB (channels x freqs x samples)
+pub fn ersp<S>(
+    epochs: &ArrayBase<S, Ix3>,
+    fs: f32,
+    freqs: &[f32],
+    baseline: Option<(usize, usize)>,
+    method: TimeFrequencyMethod,
+) -> Result<Array3<f32>, TfError>
+where
+    S: Data<Elem = f32>,
+{
+    let (num_epochs, num_channels, num_samples) = epochs.dim();
+    if let Some((start, end)) = baseline {
+        if start >= end || end > num_samples {
+            return Err(TfError::BaselineOutOfBounds {
+                start,
+                end,
+                num_samples,
+            });
+        }
+    }
+
+    let mut power = Array3::<f32>::zeros((num_channels, freqs.len(), num_samples));
+    for epoch in epochs.outer_iter() {
+        for (mut channel_power, signal) in power.outer_iter_mut().zip(epoch.outer_iter()) {
+            channel_power += &transform(&signal, fs, freqs, method).mapv(|z| z.norm_sqr());
+        }
+    }
+    power /= num_epochs.max(1) as f32;
+
+    if let Some((start, end)) = baseline {
+        let reference = power
+            .slice(s![.., .., start..end])
+            .mean_axis(Axis(2))
+            .unwrap()
+            .insert_axis(Axis(2));
+        power /= &reference;
+    }
+
+    Ok(power.mapv(|p| 10.0 * p.log10()))
+}
+
+// Inter-trial (phase) coherence of the `epochs` (epochs x channels x samples), sampled at `fs` Hz,
+// at each of the `freqs` in Hz: the length of the mean unit phase vector across epochs, in [0, 1]
+//
+// Returns the ITC (channels x freqs x samples)
+pub fn itc<S>(
+    epochs: &ArrayBase<S, Ix3>,
+    fs: f32,
+    freqs: &[f32],
+    method: TimeFrequencyMethod,
+) -> Array3<f32>
+where
+    S: Data<Elem = f32>,
+{
+    let (num_epochs, num_channels, num_samples) = epochs.dim();
+
+    let mut phase_sum = Array3::<Complex<f32>>::zeros((num_channels, freqs.len(), num_samples));
+    for epoch in epochs.outer_iter() {
+        for (mut channel_sum, signal) in phase_sum.outer_iter_mut().zip(epoch.outer_iter()) {
+            let coefficients = transform(&signal, fs, freqs, method);
+            channel_sum.zip_mut_with(&coefficients, |sum, &z| {
+                let norm = z.norm();
+                if norm > 0.0 {
+                    *sum += z / norm;
+                }
+            });
+        }
+    }
+
+    phase_sum.mapv(|z| z.norm() / num_epochs.max(1) as f32)
+}
+
+// Time-frequency coefficients of the `signal`, sampled at `fs` Hz, at each of the `freqs` in Hz
+// (freqs x samples)
+fn transform<S>(
+    signal: &ArrayBase<S, Ix1>,
+    fs: f32,
+    freqs: &[f32],
+    method: TimeFrequencyMethod,
+) -> Array2<Complex<f32>>
+where
+    S: Data<Elem = f32>,
+{
+    let n = signal.len();
+
+    match method {
+        TimeFrequencyMethod::Cwt => {
+            signal
+                .cwt_frequencies(&Morlet::new(MORLET_OMEGA), freqs, fs)
+                .coeffs
+        }
+        TimeFrequencyMethod::STransform => {
+            let padded_len = n.next_power_of_two();
+            let mut padded = Array1::zeros(padded_len);
+            padded.slice_mut(s![..n]).assign(signal);
+            let st = padded.st();
+
+            // Row `k` of the S-transform is the frequency `k * fs / padded_len`
+            let rows = freqs
+                .iter()
+                .map(|&f| ((f * padded_len as f32 / fs).round() as usize).min(padded_len / 2))
+                .collect::<Vec<usize>>();
+
+            st.select(Axis(0), &rows).slice(s![.., ..n]).to_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    const FS: f32 = 250.0;
+
+    // Twelve epochs of 1 s on a single channel of noise, a 10 Hz burst of the same phase in every
+    // epoch spanning 0.4 to 0.7 s
+    fn bursts() -> Array3<f32> {
+        let mut generator = Lcg::new(11);
+        Array3::from_shape_fn((12, 1, 250), |(_, _, t)| {
+            let time = t as f32 / FS;
+            let burst = if (0.4..0.7).contains(&time) {
+                5.0 * (TAU * 10.0 * time).sin()
+            } else {
+                0.0
+            };
+            burst + (generator.next_f32() - 0.5)
+        })
+    }
+
+    #[test]
+    fn phase_locked_burst_is_coherent_and_increases_power() {
+        for method in [TimeFrequencyMethod::Cwt, TimeFrequencyMethod::STransform] {
+            let coherence = itc(&bursts(), FS, &[10.0], method);
+            let perturbation = ersp(&bursts(), FS, &[10.0], Some((0, 50)), method).unwrap();
+
+            // Samples around the middle of the burst, and of the baseline
+            let (burst, baseline) = (137, 25);
+            assert!(coherence[[0, 0, burst]] > 0.9, "{method:?}");
+            assert!(coherence[[0, 0, baseline]] < 0.6, "{method:?}");
+            assert!(perturbation[[0, 0, burst]] > 10.0, "{method:?}");
+            assert!(perturbation[[0, 0, baseline]].abs() < 3.0, "{method:?}");
+        }
+    }
+
+    #[test]
+    fn itc_is_within_0_and_1() {
+        let coherence = itc(&bursts(), FS, &[6.0, 10.0, 20.0], TimeFrequencyMethod::Cwt);
+
+        assert_eq!(coherence.dim(), (1, 3, 250));
+        assert!(coherence.iter().all(|&c| (0.0..=1.0 + 1e-5).contains(&c)));
+    }
+
+    #[test]
+    fn baseline_outside_the_epochs_fails() {
+        for baseline in [(200, 260), (50, 50), (60, 40)] {
+            let result = ersp(
+                &bursts(),
+                FS,
+                &[10.0],
+                Some(baseline),
+                TimeFrequencyMethod::Cwt,
+            );
+            match result {
+                Err(TfError::BaselineOutOfBounds {
+                    start,
+                    end,
+                    num_samples,
+                }) => assert_eq!((start, end, num_samples), (baseline.0, baseline.1, 250)),
+                other => panic!("Unexpected result {other:?} !"),
+            }
+        }
+    }
+
+    // Power of 2 frequencies at 6 times, the first two being the baseline, the second row being
+    // constant over it
+    fn tf_power() -> (Array2<f32>, [f32; 6]) {
+        (
+            ndarray::array![
+                [1.0, 3.0, 2.0, 4.0, 20.0, 0.2],
+                [4.0, 4.0, 4.0, 8.0, 2.0, 0.0]
+            ],
+            [-0.2, -0.1, 0.0, 0.1, 0.2, 0.3],
+        )
+    }
+
+    fn assert_close(actual: &Array2<f32>, expected: [[f32; 6]; 2]) {
+        for (&a, &e) in actual.iter().zip(expected.iter().flatten()) {
+            assert!(
+                (a.is_nan() && e.is_nan()) || a == e || (a - e).abs() < 1e-4,
+                "{actual} {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn baseline_normalization_matches_hand_computed_values() {
+        // Ratios of 2 and 1.5 to the baseline mean, in dB
+        let (double, half_more) = (10.0 * 2f32.log10(), 10.0 * 1.5f32.log10());
+        let (mut power, times) = tf_power();
+        baseline_normalize(&mut power, &times, (-0.2, -0.1), BaselineMode::DbRatio).unwrap();
+        assert_close(
+            &power,
+            [
+                [-double, half_more, 0.0, double, 10.0, -10.0],
+                [0.0, 0.0, 0.0, double, -double, f32::NEG_INFINITY],
+            ],
+        );
+
+        let (mut power, times) = tf_power();
+        baseline_normalize(
+            &mut power,
+            &times,
+            (-0.2, -0.1),
+            BaselineMode::PercentChange,
+        )
+        .unwrap();
+        assert_close(
+            &power,
+            [
+                [-50.0, 50.0, 0.0, 100.0, 900.0, -90.0],
+                [0.0, 0.0, 0.0, 100.0, -50.0, -100.0],
+            ],
+        );
+
+        // The baseline of the second row has no variance
+        let (mut power, times) = tf_power();
+        baseline_normalize(&mut power, &times, (-0.2, -0.1), BaselineMode::ZScore).unwrap();
+        assert_close(&power, [[-1.0, 1.0, 0.0, 2.0, 18.0, -1.8], [f32::NAN; 6]]);
+    }
+
+    #[test]
+    fn baseline_outside_the_times_fails() {
+        let (mut power, times) = tf_power();
+
+        match baseline_normalize(&mut power, &times, (1.0, 2.0), BaselineMode::DbRatio) {
+            Err(TfError::BaselineOutOfBounds {
+                start,
+                end,
+                num_samples,
+            }) => assert_eq!((start, end, num_samples), (6, 6, 6)),
+            other => panic!("Unexpected result {other:?} !"),
+        }
+        assert_eq!(power, tf_power().0);
+    }
+}
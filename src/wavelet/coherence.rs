@@ -0,0 +1,249 @@
+// C. Torrence and P. J. Webster, "Interdecadal Changes in the ENSO-Monsoon System," Journal of
+// Climate, vol. 12, no. 8, pp. 2679-2690, 1999.
+// A. Grinsted, J. C. Moore and S. Jevrejeva, "Application of the cross wavelet transform and wavelet
+// coherence to geophysical time series," Nonlinear Processes in Geophysics, vol. 11, no. 5/6,
+// pp. 561-566, 2004, doi: 10.5194/npg-11-561-2004.
+
+use nalgebra::Complex;
+use ndarray::{Array2, ArrayBase, Data, Ix1};
+
+use super::{Wavelet, WaveletError, WaveletTransform};
+
+// Smoothing of the wavelet spectra before forming the coherence, adapting to each scale
+#[derive(Clone, Copy, Debug)]
+pub struct CoherenceSmoothing {
+    // Deviation of the Gaussian window in time, in units of scale
+    pub time_factor: f32,
+    // Width of the boxcar window across scales, in octaves
+    pub scale_width: f32,
+}
+
+impl Default for CoherenceSmoothing {
+    // Those of the Morlet wavelet of 6 rad/s
+    fn default() -> Self {
+        CoherenceSmoothing {
+            time_factor: 1.0,
+            scale_width: 0.6,
+        }
+    }
+}
+
+// Cross-wavelet transform W_x conj(W_y) of the signals `x` and `y`, at the `scales` (samples) of the
+// `wavelet`
+pub fn xwt<S, T, W>(
+    x: &ArrayBase<S, Ix1>,
+    y: &ArrayBase<T, Ix1>,
+    scales: &[f32],
+    wavelet: &W,
+) -> Result<Array2<Complex<f32>>, WaveletError>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+    W: Wavelet<Dtype = f32>,
+    W::WaveletDtype: Into<Complex<f32>> + Clone,
+{
+    if x.len() != y.len() {
+        return Err(WaveletError::LengthMismatch {
+            x: x.len(),
+            y: y.len(),
+        });
+    }
+
+    let mut cross = x.cwt(wavelet, scales);
+    cross.zip_mut_with(&y.cwt(wavelet, scales), |wx, wy| *wx *= wy.conj());
+
+    Ok(cross)
+}
+
+// Wavelet coherence between the signals `x` and `y`, at the `scales` (samples) of the `wavelet`:
+// R^2 = |S(W_xy / s)|^2 / (S(|W_x|^2 / s) S(|W_y|^2 / s)), S smoothing in time by a Gaussian of a
+// deviation proportional to the scale, then across scales by a boxcar
+//
+// Returns the coherence in [0, 1] (scales x times), 0 wherever either signal has no power
+pub fn wavelet_coherence<S, T, W>(
+    x: &ArrayBase<S, Ix1>,
+    y: &ArrayBase<T, Ix1>,
+    scales: &[f32],
+    wavelet: &W,
+    smoothing: CoherenceSmoothing,
+) -> Result<Array2<f32>, WaveletError>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+    W: Wavelet<Dtype = f32>,
+    W::WaveletDtype: Into<Complex<f32>> + Clone,
+{
+    if x.len() != y.len() {
+        return Err(WaveletError::LengthMismatch {
+            x: x.len(),
+            y: y.len(),
+        });
+    }
+
+    let (wx, wy) = (x.cwt(wavelet, scales), y.cwt(wavelet, scales));
+    let rectify = |mut spectrum: Array2<Complex<f32>>| {
+        for (mut row, &a) in spectrum.outer_iter_mut().zip(scales) {
+            row.mapv_inplace(|z| z / a);
+        }
+        smooth(&spectrum, scales, smoothing)
+    };
+    let cross = rectify(Array2::from_shape_fn(wx.dim(), |i| wx[i] * wy[i].conj()));
+    let power_x = rectify(wx.mapv(|z| Complex::from(z.norm_sqr())));
+    let power_y = rectify(wy.mapv(|z| Complex::from(z.norm_sqr())));
+
+    Ok(Array2::from_shape_fn(wx.dim(), |i| {
+        let denominator = power_x[i].re * power_y[i].re;
+        if denominator > 0.0 {
+            (cross[i].norm_sqr() / denominator).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }))
+}
+
+// Smoothing of the `spectrum` (scales x times) in time, by a Gaussian truncated at 3 deviations and
+// renormalized at the edges, then across the `scales` within half the width of the boxcar
+fn smooth(
+    spectrum: &Array2<Complex<f32>>,
+    scales: &[f32],
+    smoothing: CoherenceSmoothing,
+) -> Array2<Complex<f32>> {
+    let n = spectrum.ncols();
+
+    let mut in_time = Array2::zeros(spectrum.dim());
+    for ((mut smoothed, row), &a) in in_time
+        .outer_iter_mut()
+        .zip(spectrum.outer_iter())
+        .zip(scales)
+    {
+        let sigma = (smoothing.time_factor * a).max(f32::EPSILON);
+        let half = (3.0 * sigma).ceil() as usize;
+        let window = (0..=2 * half)
+            .map(|k| (-0.5 * ((k as f32 - half as f32) / sigma).powi(2)).exp())
+            .collect::<Vec<f32>>();
+
+        for (t, value) in smoothed.iter_mut().enumerate() {
+            let (start, end) = (t.saturating_sub(half), (t + half + 1).min(n));
+            let (mut sum, mut weight) = (Complex::new(0.0, 0.0), 0.0);
+            for u in start..end {
+                let w = window[u + half - t];
+                sum += row[u] * w;
+                weight += w;
+            }
+            *value = sum / weight;
+        }
+    }
+
+    let mut result = Array2::zeros(spectrum.dim());
+    for (mut smoothed, &a) in result.outer_iter_mut().zip(scales) {
+        let neighbours = scales
+            .iter()
+            .enumerate()
+            .filter(|(_, &b)| (b / a).log2().abs() <= smoothing.scale_width / 2.0)
+            .map(|(j, _)| j)
+            .collect::<Vec<usize>>();
+
+        for &j in &neighbours {
+            smoothed += &in_time.row(j);
+        }
+        smoothed.mapv_inplace(|z| z / neighbours.len() as f32);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array1;
+
+    use super::*;
+    use crate::{
+        surrogate::Lcg,
+        wavelet::{frequencies_for_scales, log_scales, Morlet},
+    };
+
+    fn noise(n: usize, seed: u64) -> Array1<f32> {
+        let mut generator = Lcg::new(seed);
+        Array1::from_shape_fn(n, |_| generator.next_f32() - 0.5)
+    }
+
+    #[test]
+    fn cross_transform_of_a_signal_with_itself_is_its_power() {
+        let x = noise(256, 1);
+        let scales = [2.0, 4.0, 8.0];
+        let cross = xwt(&x, &x, &scales, &Morlet::default()).unwrap();
+        let power = x.cwt(&Morlet::default(), &scales).mapv(|z| z.norm_sqr());
+        for (z, &p) in cross.iter().zip(&power) {
+            assert!((z.re - p).abs() < 1e-5 * p.max(1.0) && z.im.abs() < 1e-5 * p.max(1.0));
+        }
+
+        let coherence = wavelet_coherence(
+            &x,
+            &x,
+            &scales,
+            &Morlet::default(),
+            CoherenceSmoothing::default(),
+        )
+        .unwrap();
+        assert!(coherence.iter().all(|&c| (c - 1.0).abs() < 1e-4));
+
+        let y = noise(255, 2);
+        assert!(matches!(
+            xwt(&x, &y, &scales, &Morlet::default()),
+            Err(WaveletError::LengthMismatch { x: 256, y: 255 })
+        ));
+        assert!(matches!(
+            wavelet_coherence(
+                &x,
+                &y,
+                &scales,
+                &Morlet::default(),
+                CoherenceSmoothing::default()
+            ),
+            Err(WaveletError::LengthMismatch { x: 256, y: 255 })
+        ));
+    }
+
+    #[test]
+    fn coherence_rises_only_where_and_when_signals_share_a_rhythm() {
+        // 4 s at 250 Hz, sharing a 10 Hz rhythm from 1.5 to 2.5 s only
+        let fs = 250.0;
+        let rhythm = |t: usize| {
+            if (375..625).contains(&t) {
+                (std::f32::consts::TAU * 10.0 * t as f32 / fs).sin()
+            } else {
+                0.0
+            }
+        };
+        let (a, b) = (noise(1000, 3), noise(1000, 4));
+        let x = Array1::from_shape_fn(1000, |t| a[t] + rhythm(t));
+        let y = Array1::from_shape_fn(1000, |t| b[t] + rhythm(t));
+
+        let wavelet = Morlet::default();
+        let scales = log_scales(5.0, 40.0, 4, fs, &wavelet);
+        let freqs = frequencies_for_scales(&scales, fs, &wavelet);
+        let coherence =
+            wavelet_coherence(&x, &y, &scales, &wavelet, CoherenceSmoothing::default()).unwrap();
+        assert_eq!(coherence.dim(), (13, 1000));
+        assert!(coherence.iter().all(|&c| (0.0..=1.0).contains(&c)));
+
+        let row = |f: f32| {
+            (0..freqs.len())
+                .min_by(|&i, &j| (freqs[i] - f).abs().total_cmp(&(freqs[j] - f).abs()))
+                .unwrap()
+        };
+        let mean = |row: usize, times: std::ops::Range<usize>| {
+            coherence.slice(ndarray::s![row, times]).mean().unwrap()
+        };
+        let (alpha, beta) = (row(10.0), row(40.0));
+        let (during, before, after) = (
+            mean(alpha, 450..550),
+            mean(alpha, 100..250),
+            mean(alpha, 750..900),
+        );
+        assert!(during > 0.9, "{during}");
+        assert!(before < 0.5 && after < 0.5, "{before} {after}");
+        let elsewhere = mean(beta, 450..550);
+        assert!(elsewhere < 0.5, "{elsewhere}");
+    }
+}
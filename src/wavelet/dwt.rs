@@ -0,0 +1,512 @@
+// I. Daubechies, "Ten Lectures on Wavelets," SIAM, 1992, doi: 10.1137/1.9781611970104.
+
+use ndarray::{s, Array1, ArrayBase, Data, Ix1};
+
+// Orthogonal wavelets of the Discrete Wavelet Transform, by their number of vanishing moments
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiscreteWavelet {
+    // Daubechies wavelet of 1 vanishing moment
+    Haar,
+    Db2,
+    Db3,
+    Db4,
+    Db5,
+    Db6,
+    Db7,
+    Db8,
+}
+
+impl DiscreteWavelet {
+    // Low-pass reconstruction filter, of unit norm and summing to sqrt(2)
+    pub fn filter(&self) -> &'static [f64] {
+        match self {
+            DiscreteWavelet::Haar => &HAAR,
+            DiscreteWavelet::Db2 => &DB2,
+            DiscreteWavelet::Db3 => &DB3,
+            DiscreteWavelet::Db4 => &DB4,
+            DiscreteWavelet::Db5 => &DB5,
+            DiscreteWavelet::Db6 => &DB6,
+            DiscreteWavelet::Db7 => &DB7,
+            DiscreteWavelet::Db8 => &DB8,
+        }
+    }
+
+    // High-pass reconstruction filter, the quadrature mirror of the low-pass one
+    pub fn high_pass(&self) -> Vec<f64> {
+        let low = self.filter();
+        let len = low.len();
+
+        (0..len)
+            .map(|j| {
+                if j % 2 == 0 {
+                    low[len - 1 - j]
+                } else {
+                    -low[len - 1 - j]
+                }
+            })
+            .collect()
+    }
+}
+
+// Coefficients of a multi-level Discrete Wavelet Transform
+#[derive(Clone, Debug)]
+pub struct WaveletDecomposition {
+    pub wavelet: DiscreteWavelet,
+    // Approximation coefficients of the coarsest level
+    pub approximation: Array1<f32>,
+    // Detail coefficients of each level, the coarsest first
+    pub details: Vec<Array1<f32>>,
+    // Length of the signal before its extension to a multiple of 2^levels
+    pub len: usize,
+    // Number of samples the signal was extended by
+    pub pad: usize,
+}
+
+// Single-level Discrete Wavelet Transform of the `signal`, of even length, by the periodized filter
+// bank of the `wavelet`, an orthogonal transform preserving the energy of the signal
+//
+// Returns the approximation and detail coefficients, of half the length of the signal each
+pub fn dwt<S>(signal: &ArrayBase<S, Ix1>, wavelet: DiscreteWavelet) -> (Array1<f32>, Array1<f32>)
+where
+    S: Data<Elem = f32>,
+{
+    let n = signal.len();
+    assert!(n.is_multiple_of(2), "Signal length must be even !");
+
+    let low = wavelet.filter();
+    let high = wavelet.high_pass();
+
+    // a[k] = sum_j g[j] x[2k + j], d[k] = sum_j h[j] x[2k + j], wrapping around the signal
+    let mut approximation = Array1::zeros(n / 2);
+    let mut detail = Array1::zeros(n / 2);
+    for k in 0..n / 2 {
+        let (mut a, mut d) = (0.0, 0.0);
+        for (j, (&g, &h)) in low.iter().zip(&high).enumerate() {
+            let x = signal[(2 * k + j) % n] as f64;
+            a += g * x;
+            d += h * x;
+        }
+        approximation[k] = a as f32;
+        detail[k] = d as f32;
+    }
+
+    (approximation, detail)
+}
+
+// Inverse of the single-level `dwt`, from the `approximation` and `detail` coefficients of the
+// same length
+pub fn idwt<S, T>(
+    approximation: &ArrayBase<S, Ix1>,
+    detail: &ArrayBase<T, Ix1>,
+    wavelet: DiscreteWavelet,
+) -> Array1<f32>
+where
+    S: Data<Elem = f32>,
+    T: Data<Elem = f32>,
+{
+    assert_eq!(
+        approximation.len(),
+        detail.len(),
+        "Approximation and detail coefficients must be of the same length !"
+    );
+
+    let n = 2 * approximation.len();
+    let low = wavelet.filter();
+    let high = wavelet.high_pass();
+
+    // The transpose of the orthogonal analysis
+    let mut result = vec![0.0f64; n];
+    for k in 0..n / 2 {
+        let (a, d) = (approximation[k] as f64, detail[k] as f64);
+        for (j, (&g, &h)) in low.iter().zip(&high).enumerate() {
+            result[(2 * k + j) % n] += g * a + h * d;
+        }
+    }
+
+    result.into_iter().map(|x| x as f32).collect()
+}
+
+// Discrete Wavelet Transform of the `signal` over `levels` levels, each decomposing the
+// approximation of the previous one
+// Signals whose length is not a multiple of 2^levels are extended symmetrically beforehand, by the
+// recorded `pad`
+pub fn wavedec<S>(
+    signal: &ArrayBase<S, Ix1>,
+    wavelet: DiscreteWavelet,
+    levels: usize,
+) -> WaveletDecomposition
+where
+    S: Data<Elem = f32>,
+{
+    let len = signal.len();
+    assert!(len > 0, "Signal must not be empty !");
+
+    let padded_len = len.next_multiple_of(1 << levels);
+    let mut approximation = Array1::from_shape_fn(padded_len, |i| signal[symmetric(i, len)]);
+
+    let mut details = Vec::with_capacity(levels);
+    for _ in 0..levels {
+        let (a, d) = dwt(&approximation, wavelet);
+        approximation = a;
+        details.push(d);
+    }
+    details.reverse();
+
+    WaveletDecomposition {
+        wavelet,
+        approximation,
+        details,
+        len,
+        pad: padded_len - len,
+    }
+}
+
+// Reconstruction of the signal from its multi-level `decomposition`, back to its original length
+pub fn waverec(decomposition: &WaveletDecomposition) -> Array1<f32> {
+    let mut approximation = decomposition.approximation.clone();
+    for detail in &decomposition.details {
+        approximation = idwt(&approximation, detail, decomposition.wavelet);
+    }
+
+    approximation.slice_move(s![..decomposition.len])
+}
+
+// D. L. Donoho and I. M. Johnstone, "Adapting to Unknown Smoothness via Wavelet Shrinkage," Journal
+// of the American Statistical Association, vol. 90, no. 432, pp. 1200-1224, 1995.
+
+// Rule choosing the threshold of the detail coefficients, in units of the deviation of the noise
+#[derive(Clone, Copy, Debug)]
+pub enum ThresholdRule {
+    // sqrt(2 ln n) for a signal of n samples (VisuShrink), the same for every level
+    Universal,
+    // The minimizer of Stein's unbiased risk estimate of each level (SureShrink), capped by the
+    // universal threshold
+    Sure,
+}
+
+// Shrinkage of the detail coefficients by a threshold
+#[derive(Clone, Copy, Debug, Default)]
+pub enum ThresholdMode {
+    // Coefficients below the threshold zeroed, the others shrunk toward 0 by it
+    #[default]
+    Soft,
+    // Coefficients below the threshold zeroed, the others kept as they are
+    Hard,
+}
+
+// Denoising of the `signal` by thresholding its detail coefficients over `levels` levels of the
+// `wavelet`, the deviation of the noise being estimated as MAD / 0.6745 of the finest details
+pub fn wavelet_denoise<S>(
+    signal: &ArrayBase<S, Ix1>,
+    wavelet: DiscreteWavelet,
+    levels: usize,
+    rule: ThresholdRule,
+    mode: ThresholdMode,
+) -> Array1<f32>
+where
+    S: Data<Elem = f32>,
+{
+    let mut decomposition = wavedec(signal, wavelet, levels);
+    let Some(finest) = decomposition.details.last() else {
+        return signal.to_owned();
+    };
+
+    let mut magnitudes = finest.iter().map(|d| d.abs()).collect::<Vec<f32>>();
+    magnitudes.sort_by(f32::total_cmp);
+    let sigma = median(&magnitudes) / 0.6745;
+    if sigma == 0.0 {
+        return signal.to_owned();
+    }
+
+    let universal = (2.0 * (signal.len() as f32).ln()).sqrt();
+    for detail in decomposition.details.iter_mut() {
+        let threshold = sigma
+            * match rule {
+                ThresholdRule::Universal => universal,
+                ThresholdRule::Sure => sure_threshold(&detail.mapv(|d| d / sigma)).min(universal),
+            };
+
+        detail.mapv_inplace(|d| match mode {
+            _ if d.abs() <= threshold => 0.0,
+            ThresholdMode::Soft => d.signum() * (d.abs() - threshold),
+            ThresholdMode::Hard => d,
+        });
+    }
+
+    waverec(&decomposition)
+}
+
+// Threshold minimizing Stein's unbiased risk estimate of soft thresholding the coefficients `x`,
+// of unit noise:
+// SURE(t) = n - 2 #{|x_i| <= t} + sum_i min(x_i^2, t^2)
+// The risk being piecewise quadratic in t, its minimum lies at one of the |x_i|
+fn sure_threshold(x: &Array1<f32>) -> f32 {
+    let mut squares = x.iter().map(|&v| (v * v) as f64).collect::<Vec<f64>>();
+    squares.sort_by(f64::total_cmp);
+    let n = squares.len();
+
+    // Risk of thresholding at the k-th smallest magnitude, those below being accumulated
+    let mut best = (f64::INFINITY, 0.0);
+    let mut below = 0.0;
+    for (k, &square) in squares.iter().enumerate() {
+        below += square;
+        let risk = n as f64 - 2.0 * (k + 1) as f64 + below + (n - k - 1) as f64 * square;
+        if risk < best.0 {
+            best = (risk, square.sqrt());
+        }
+    }
+
+    best.1 as f32
+}
+
+// Median of the `sorted` values
+fn median(sorted: &[f32]) -> f32 {
+    let n = sorted.len();
+    if n.is_multiple_of(2) {
+        (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+    } else {
+        sorted[n / 2]
+    }
+}
+
+// Index within a signal of length `n` of the sample `i` past its end, mirrored about it, the last
+// sample being repeated
+pub(super) fn symmetric(i: usize, n: usize) -> usize {
+    let i = i % (2 * n);
+    if i < n {
+        i
+    } else {
+        2 * n - 1 - i
+    }
+}
+
+const HAAR: [f64; 2] = [
+    std::f64::consts::FRAC_1_SQRT_2,
+    std::f64::consts::FRAC_1_SQRT_2,
+];
+
+const DB2: [f64; 4] = [
+    0.48296291314453416,
+    0.8365163037378079,
+    0.2241438680420134,
+    -0.12940952255126037,
+];
+
+const DB3: [f64; 6] = [
+    0.33267055295008263,
+    0.8068915093110925,
+    0.45987750211849154,
+    -0.13501102001025458,
+    -0.08544127388202666,
+    0.03522629188570953,
+];
+
+const DB4: [f64; 8] = [
+    0.2303778133088965,
+    0.7148465705529157,
+    0.6308807679298589,
+    -0.027983769416859854,
+    -0.18703481171909309,
+    0.030841381835560764,
+    0.0328830116668852,
+    -0.010597401785069032,
+];
+
+const DB5: [f64; 10] = [
+    0.16010239797419293,
+    0.6038292697971896,
+    0.7243085284377729,
+    0.13842814590132074,
+    -0.24229488706638203,
+    -0.032244869584638375,
+    0.07757149384004572,
+    -0.006241490212798274,
+    -0.012580751999081999,
+    0.0033357252854737712,
+];
+
+const DB6: [f64; 12] = [
+    0.11154074335010947,
+    0.49462389039845306,
+    0.7511339080210954,
+    0.31525035170919763,
+    -0.22626469396543983,
+    -0.12976686756726194,
+    0.09750160558732304,
+    0.027522865530305727,
+    -0.03158203931748603,
+    0.0005538422011614961,
+    0.004777257510945511,
+    -0.0010773010853084796,
+];
+
+const DB7: [f64; 14] = [
+    0.07785205408500918,
+    0.3965393194819173,
+    0.7291320908462351,
+    0.4697822874051931,
+    -0.14390600392856498,
+    -0.22403618499387498,
+    0.07130921926683026,
+    0.08061260915108308,
+    -0.03802993693501441,
+    -0.01657454163066688,
+    0.01255099855609984,
+    0.0004295779729213665,
+    -0.0018016407040474908,
+    0.00035371379997452024,
+];
+
+const DB8: [f64; 16] = [
+    0.05441584224310401,
+    0.31287159091429995,
+    0.6756307362972898,
+    0.5853546836542067,
+    -0.015829105256349306,
+    -0.2840155429615469,
+    0.0004724845739132828,
+    0.12874742662047847,
+    -0.017369301001807547,
+    -0.044088253930794755,
+    0.013981027917398282,
+    0.008746094047405777,
+    -0.004870352993451574,
+    -0.00039174037337694705,
+    0.0006754494064505693,
+    -0.00011747678412476953,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    const WAVELETS: [DiscreteWavelet; 8] = [
+        DiscreteWavelet::Haar,
+        DiscreteWavelet::Db2,
+        DiscreteWavelet::Db3,
+        DiscreteWavelet::Db4,
+        DiscreteWavelet::Db5,
+        DiscreteWavelet::Db6,
+        DiscreteWavelet::Db7,
+        DiscreteWavelet::Db8,
+    ];
+
+    fn noise(n: usize, seed: u64) -> Array1<f32> {
+        let mut generator = Lcg::new(seed);
+        Array1::from_shape_fn(n, |_| generator.next_f32() - 0.5)
+    }
+
+    #[test]
+    fn filters_are_orthonormal() {
+        for wavelet in WAVELETS {
+            let low = wavelet.filter();
+            assert!(
+                (low.iter().sum::<f64>() - 2.0f64.sqrt()).abs() < 1e-10,
+                "{wavelet:?}"
+            );
+            assert!((low.iter().map(|g| g * g).sum::<f64>() - 1.0).abs() < 1e-10);
+            let high = wavelet.high_pass();
+            assert!(low.iter().zip(&high).map(|(g, h)| g * h).sum::<f64>().abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn signals_of_any_length_are_perfectly_reconstructed() {
+        for wavelet in WAVELETS {
+            for (n, levels) in [(8, 3), (100, 3), (257, 4), (1000, 5)] {
+                let signal = noise(n, n as u64);
+                let decomposition = wavedec(&signal, wavelet, levels);
+                assert_eq!(decomposition.details.len(), levels);
+                assert_eq!(decomposition.len, n);
+                assert_eq!(decomposition.pad, n.next_multiple_of(1 << levels) - n);
+
+                let reconstructed = waverec(&decomposition);
+                assert_eq!(reconstructed.len(), n);
+                let error = (&reconstructed - &signal)
+                    .iter()
+                    .fold(0.0f32, |max, d| max.max(d.abs()));
+                assert!(error < 1e-5, "{wavelet:?} {n} {error}");
+            }
+        }
+    }
+
+    #[test]
+    fn single_level_transform_preserves_energy() {
+        let signal = noise(256, 1);
+        for wavelet in WAVELETS {
+            let (approximation, detail) = dwt(&signal, wavelet);
+            let energy = approximation.mapv(|a| a * a).sum() + detail.mapv(|d| d * d).sum();
+            assert!((energy - signal.mapv(|x| x * x).sum()).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn haar_details_of_a_step_are_at_the_step() {
+        let step = Array1::from_shape_fn(128, |t| if t < 37 { 0.0 } else { 1.0 });
+
+        let (_, detail) = dwt(&step, DiscreteWavelet::Haar);
+        for (k, &d) in detail.iter().enumerate() {
+            if k == 18 {
+                assert!((d + std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+            } else {
+                assert_eq!(d, 0.0);
+            }
+        }
+
+        // At every level, at the coefficient whose support holds the step
+        let decomposition = wavedec(&step, DiscreteWavelet::Haar, 4);
+        for (level, detail) in decomposition.details.iter().rev().enumerate() {
+            let at_step = 37 >> (level + 1);
+            for (k, &d) in detail.iter().enumerate() {
+                assert_eq!(d != 0.0, k == at_step, "{level} {k} {d}");
+            }
+        }
+    }
+
+    // Smooth ERP-like waveform of 512 samples: a negative then a positive Gaussian deflection
+    fn erp() -> Array1<f32> {
+        let bump = |t: f32, center: f32, width: f32| (-0.5 * ((t - center) / width).powi(2)).exp();
+        Array1::from_shape_fn(512, |t| {
+            let t = t as f32;
+            -0.6 * bump(t, 150.0, 15.0) + bump(t, 260.0, 35.0)
+        })
+    }
+
+    fn rmse(a: &Array1<f32>, b: &Array1<f32>) -> f32 {
+        (a - b).mapv(|d| d * d).mean().unwrap().sqrt()
+    }
+
+    #[test]
+    fn denoising_removes_most_of_white_noise() {
+        let clean = erp();
+        // Gaussian noise of deviation 0.2, by the Box-Muller transform
+        let mut generator = Lcg::new(2);
+        let noisy = clean.mapv(|x| {
+            let (u, v) = (1.0 - generator.next_f32(), generator.next_f32());
+            x + 0.2 * (-2.0 * u.ln()).sqrt() * (std::f32::consts::TAU * v).cos()
+        });
+        let noisy_error = rmse(&noisy, &clean);
+        assert!((noisy_error - 0.2).abs() < 0.02);
+
+        for rule in [ThresholdRule::Universal, ThresholdRule::Sure] {
+            for mode in [ThresholdMode::Soft, ThresholdMode::Hard] {
+                let denoised = wavelet_denoise(&noisy, DiscreteWavelet::Db4, 5, rule, mode);
+                assert_eq!(denoised.len(), 512);
+                let error = rmse(&denoised, &clean);
+                assert!(error < 0.5 * noisy_error, "{rule:?} {mode:?} {error}");
+            }
+        }
+    }
+
+    #[test]
+    fn noise_free_signals_pass_through_denoising() {
+        let clean = erp();
+        for rule in [ThresholdRule::Universal, ThresholdRule::Sure] {
+            let denoised =
+                wavelet_denoise(&clean, DiscreteWavelet::Db4, 5, rule, ThresholdMode::Soft);
+            let error = rmse(&denoised, &clean);
+            assert!(error < 1e-2, "{rule:?} {error}");
+        }
+    }
+}
@@ -0,0 +1,1698 @@
+pub mod coherence;
+pub mod dwt;
+pub mod packet;
+pub mod streaming;
+
+use std::error::Error;
+use std::f32::consts::PI;
+use std::f64::consts::PI as PI64;
+use std::fmt::Display;
+
+use nalgebra::Complex;
+use ndarray::s;
+use ndarray::Array;
+use ndarray::Array1;
+use ndarray::Array2;
+use ndarray::Array3;
+use ndarray::ArrayBase;
+use ndarray::ArrayViewMut2;
+use ndarray::Axis;
+use ndarray::CowArray;
+use ndarray::Data;
+use ndarray::Dimension;
+use ndarray::Ix1;
+use ndarray::Ix2;
+use num_traits::Float;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::fft::{FourierTransform, InverseFourierTransform};
+
+// Errors that can occur while computing wavelet transforms
+#[derive(Debug)]
+pub enum WaveletError {
+    // The signals compared are not of the same length
+    LengthMismatch { x: usize, y: usize },
+    // No scales were given to transform the signal at
+    NoScales,
+    // A scale is not a positive, finite number
+    InvalidScale { index: usize, scale: f32 },
+}
+
+impl Display for WaveletError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WaveletError::LengthMismatch { x, y } => {
+                write!(f, "signals of {x} and {y} samples are not comparable")
+            }
+            WaveletError::NoScales => f.write_str("no scales to transform the signal at"),
+            WaveletError::InvalidScale { index, scale } => {
+                write!(f, "scale {index} of {scale} is not positive and finite")
+            }
+        }
+    }
+}
+
+impl Error for WaveletError {}
+
+// Wavelets carry their own parameters, and are generated over times in units of their scale
+pub trait Wavelet {
+    type Dtype: Float;
+    type WaveletDtype: Into<Complex<f32>>;
+
+    fn generate(&self, time: &Array1<Self::Dtype>) -> Array1<Self::WaveletDtype>;
+
+    // The same values as `generate`, written into `out`, a buffer of the length of `times` reused
+    // from one call to the next
+    fn generate_into(&self, times: &Array1<Self::Dtype>, out: &mut Array1<Self::WaveletDtype>);
+
+    // Frequency at which the spectrum of the wavelet of scale 1 peaks, in cycles per unit of time
+    fn center_frequency(&self) -> f32;
+
+    // Fourier Transform of the wavelet of scale 1 at each of the angular frequencies `omegas`, in
+    // radians per unit of time, if known analytically, sparing the CWT the sampling of the wavelet
+    fn fourier_domain(&self, _omegas: &Array1<f32>) -> Option<Array1<Complex<f32>>> {
+        None
+    }
+
+    // Time after which the power of the CWT of a discontinuity at the edge drops by e^2, in units
+    // of scale, bounding the cone of influence
+    fn efolding_time(&self) -> f32;
+
+    // Reconstruction factor C_delta of the inverse CWT, as tabulated by Torrence & Compo, if known
+    fn reconstruction_factor(&self) -> Option<f32> {
+        None
+    }
+}
+
+// Complex Morlet wavelet, a Gaussian-windowed complex sinusoid of angular frequency `omega0`
+#[derive(Clone, Copy, Debug)]
+pub struct Morlet {
+    pub omega0: f32,
+}
+
+impl Morlet {
+    pub fn new(omega0: f32) -> Self {
+        Morlet { omega0 }
+    }
+
+    fn value(&self, t: f32) -> Complex<f32> {
+        let gaussian = (-0.5f32 * t * t).exp();
+        let sinusoid = Complex::new(0.0f32, self.omega0 * t).exp();
+
+        sinusoid * gaussian
+    }
+}
+
+impl Default for Morlet {
+    fn default() -> Self {
+        Morlet { omega0: 6.0 }
+    }
+}
+
+impl Wavelet for Morlet {
+    type Dtype = f32;
+    type WaveletDtype = Complex<f32>;
+
+    fn generate(&self, time: &Array1<f32>) -> Array1<Complex<f32>> {
+        time.map(|&t| self.value(t))
+    }
+
+    fn generate_into(&self, times: &Array1<f32>, out: &mut Array1<Complex<f32>>) {
+        fill(times, out, |t| self.value(t));
+    }
+
+    fn center_frequency(&self) -> f32 {
+        self.omega0 / (2.0 * PI)
+    }
+
+    fn efolding_time(&self) -> f32 {
+        2.0f32.sqrt()
+    }
+
+    fn reconstruction_factor(&self) -> Option<f32> {
+        (self.omega0 == 6.0).then_some(0.776)
+    }
+}
+
+// Mexican hat (Ricker) wavelet, the negated second derivative of a Gaussian of deviation `sigma`
+#[derive(Clone, Copy, Debug)]
+pub struct MexicanHat {
+    pub sigma: f32,
+}
+
+impl MexicanHat {
+    pub fn new(sigma: f32) -> Self {
+        MexicanHat { sigma }
+    }
+
+    fn value(&self, t: f32) -> f32 {
+        let normalized_time = (t / self.sigma).powi(2);
+
+        let factor = 1.0 - normalized_time;
+        let gaussian = (-0.5 * normalized_time).exp();
+
+        factor * gaussian
+    }
+}
+
+impl Default for MexicanHat {
+    fn default() -> Self {
+        MexicanHat { sigma: 6.0 }
+    }
+}
+
+impl Wavelet for MexicanHat {
+    type Dtype = f32;
+    type WaveletDtype = f32;
+
+    fn generate(&self, time: &Array1<f32>) -> Array1<f32> {
+        time.map(|&t| self.value(t))
+    }
+
+    fn generate_into(&self, times: &Array1<f32>, out: &mut Array1<f32>) {
+        fill(times, out, |t| self.value(t));
+    }
+
+    // The spectrum w^2 exp(-sigma^2 w^2 / 2) peaks at w = sqrt(2) / sigma
+    fn center_frequency(&self) -> f32 {
+        2.0f32.sqrt() / (2.0 * PI * self.sigma)
+    }
+
+    // That of the derivative of Gaussian wavelets, stretched by the width
+    fn efolding_time(&self) -> f32 {
+        2.0f32.sqrt() * self.sigma
+    }
+
+    // That of the derivative of Gaussian wavelet of order 2, unaffected by the width
+    fn reconstruction_factor(&self) -> Option<f32> {
+        Some(3.541)
+    }
+}
+
+// C. Torrence and G. P. Compo, "A Practical Guide to Wavelet Analysis," Bulletin of the American
+// Meteorological Society, vol. 79, no. 1, pp. 61-78, 1998.
+
+// Paul wavelet of order `order`, complex, of a better time resolution than the Morlet wavelet
+#[derive(Clone, Copy, Debug)]
+pub struct Paul {
+    pub order: u32,
+}
+
+impl Paul {
+    pub fn new(order: u32) -> Self {
+        Paul { order }
+    }
+
+    // Normalization 2^m i^m m! / sqrt(pi (2m)!)
+    fn normalization(&self) -> Complex<f32> {
+        let m = self.order;
+        let magnitude = 2.0f64.powi(m as i32) * factorial(m) / (PI64 * factorial(2 * m)).sqrt();
+
+        Complex::i().powi(m as i32) * magnitude as f32
+    }
+
+    fn value(&self, t: f32) -> Complex<f32> {
+        self.normalization() * Complex::new(1.0, -t).powi(-(self.order as i32 + 1))
+    }
+}
+
+impl Default for Paul {
+    fn default() -> Self {
+        Paul { order: 4 }
+    }
+}
+
+impl Wavelet for Paul {
+    type Dtype = f32;
+    type WaveletDtype = Complex<f32>;
+
+    fn generate(&self, time: &Array1<f32>) -> Array1<Complex<f32>> {
+        time.map(|&t| self.value(t))
+    }
+
+    fn generate_into(&self, times: &Array1<f32>, out: &mut Array1<Complex<f32>>) {
+        fill(times, out, |t| self.value(t));
+    }
+
+    // The spectrum w^m exp(-w) peaks at w = m
+    fn center_frequency(&self) -> f32 {
+        self.order as f32 / (2.0 * PI)
+    }
+
+    fn efolding_time(&self) -> f32 {
+        1.0 / 2.0f32.sqrt()
+    }
+
+    fn reconstruction_factor(&self) -> Option<f32> {
+        (self.order == 4).then_some(1.132)
+    }
+
+    // 2^m i^m 2 sqrt(pi) / sqrt((2m)!) w^m exp(-w), on positive frequencies only
+    fn fourier_domain(&self, omegas: &Array1<f32>) -> Option<Array1<Complex<f32>>> {
+        let m = self.order;
+        let factor = self.normalization() * (2.0 * PI64 / factorial(m)) as f32;
+
+        Some(omegas.mapv(|w| {
+            if w > 0.0 {
+                factor * w.powi(m as i32) * (-w).exp()
+            } else {
+                Complex::new(0.0, 0.0)
+            }
+        }))
+    }
+}
+
+// Derivative of Gaussian wavelet of order `order`, real, the order 2 being the Mexican hat of unit
+// deviation up to its normalization
+#[derive(Clone, Copy, Debug)]
+pub struct Dog {
+    pub order: u32,
+}
+
+impl Dog {
+    pub fn new(order: u32) -> Self {
+        Dog { order }
+    }
+
+    // Normalization (-1)^(m + 1) / sqrt(gamma(m + 1/2))
+    fn normalization(&self) -> f32 {
+        let gamma = (0..self.order).fold(PI64.sqrt(), |gamma, k| gamma * (k as f64 + 0.5));
+
+        (-1.0f32).powi(self.order as i32 + 1) / gamma.sqrt() as f32
+    }
+
+    // The m-th derivative of exp(-t^2 / 2) is (-1)^m He_m(t) exp(-t^2 / 2), He_m being the
+    // probabilists' Hermite polynomial
+    fn value(&self, t: f32) -> f32 {
+        let (mut previous, mut hermite) = (0.0, 1.0);
+        for k in 0..self.order {
+            (previous, hermite) = (hermite, t * hermite - k as f32 * previous);
+        }
+
+        self.normalization() * (-1.0f32).powi(self.order as i32) * hermite * (-0.5 * t * t).exp()
+    }
+}
+
+impl Default for Dog {
+    fn default() -> Self {
+        Dog { order: 2 }
+    }
+}
+
+impl Wavelet for Dog {
+    type Dtype = f32;
+    type WaveletDtype = f32;
+
+    fn generate(&self, time: &Array1<f32>) -> Array1<f32> {
+        time.map(|&t| self.value(t))
+    }
+
+    fn generate_into(&self, times: &Array1<f32>, out: &mut Array1<f32>) {
+        fill(times, out, |t| self.value(t));
+    }
+
+    // The spectrum w^m exp(-w^2 / 2) peaks at w = sqrt(m)
+    fn center_frequency(&self) -> f32 {
+        (self.order as f32).sqrt() / (2.0 * PI)
+    }
+
+    fn efolding_time(&self) -> f32 {
+        2.0f32.sqrt()
+    }
+
+    fn reconstruction_factor(&self) -> Option<f32> {
+        match self.order {
+            2 => Some(3.541),
+            6 => Some(1.966),
+            _ => None,
+        }
+    }
+
+    // (-1)^(m + 1) / sqrt(gamma(m + 1/2)) (i w)^m sqrt(2 pi) exp(-w^2 / 2)
+    fn fourier_domain(&self, omegas: &Array1<f32>) -> Option<Array1<Complex<f32>>> {
+        let factor =
+            Complex::i().powi(self.order as i32) * self.normalization() * (2.0 * PI).sqrt();
+
+        Some(omegas.mapv(|w| factor * w.powi(self.order as i32) * (-0.5 * w * w).exp()))
+    }
+}
+
+// J. M. Lilly and S. C. Olhede, "Generalized Morse Wavelets as a Superfamily of Analytic
+// Wavelets," IEEE Transactions on Signal Processing, vol. 60, no. 11, pp. 6036-6041, 2012,
+// doi: 10.1109/TSP.2012.2210890.
+
+// Generalized Morse wavelet of parameters `beta` and `gamma`, analytic, defined in the frequency
+// domain by a w^beta exp(-w^gamma) on positive frequencies
+// `gamma` = 3 makes it nearly symmetric in time and frequency, `gamma` = 1 is the Paul wavelet of
+// order `beta`, and `gamma` = 2 resembles the Morlet wavelet
+//
+// It has no closed form in time: `generate` panics, the CWT going through `fourier_domain` instead
+#[derive(Clone, Copy, Debug)]
+pub struct Morse {
+    pub beta: f32,
+    pub gamma: f32,
+}
+
+impl Morse {
+    pub fn new(beta: f32, gamma: f32) -> Self {
+        assert!(
+            beta > 0.0 && gamma > 0.0,
+            "Morse wavelet parameters must be positive !"
+        );
+
+        Morse { beta, gamma }
+    }
+
+    // Angular frequency (beta / gamma)^(1 / gamma) at which the spectrum peaks, in radians per unit
+    // of time at scale 1
+    pub fn peak_frequency(&self) -> f32 {
+        (self.beta / self.gamma).powf(1.0 / self.gamma)
+    }
+
+    // Time-bandwidth product sqrt(beta gamma), the number of oscillations of the wavelet at its peak
+    // frequency within its time window, up to a factor of pi / 2
+    pub fn duration(&self) -> f32 {
+        (self.beta * self.gamma).sqrt()
+    }
+
+    // Of unit energy: a^2 / (2 pi) integral_0^inf w^(2 beta) exp(-2 w^gamma) dw = 1, that is
+    // a = sqrt(2 pi gamma 2^r / gamma(r)), r = (2 beta + 1) / gamma
+    fn normalization(&self) -> f32 {
+        let (beta, gamma) = (self.beta as f64, self.gamma as f64);
+        let r = (2.0 * beta + 1.0) / gamma;
+
+        (2.0 * PI64 * gamma * (r * 2.0f64.ln() - ln_gamma(r)).exp()).sqrt() as f32
+    }
+}
+
+impl Default for Morse {
+    fn default() -> Self {
+        Morse {
+            beta: 20.0,
+            gamma: 3.0,
+        }
+    }
+}
+
+impl Wavelet for Morse {
+    type Dtype = f32;
+    type WaveletDtype = Complex<f32>;
+
+    fn generate(&self, _time: &Array1<f32>) -> Array1<Complex<f32>> {
+        panic!("Morse wavelets are only defined in the frequency domain !");
+    }
+
+    fn generate_into(&self, _times: &Array1<f32>, _out: &mut Array1<Complex<f32>>) {
+        panic!("Morse wavelets are only defined in the frequency domain !");
+    }
+
+    fn center_frequency(&self) -> f32 {
+        self.peak_frequency() / (2.0 * PI)
+    }
+
+    // The spectrum has a deviation of w_peak / sqrt(beta gamma) about its peak, hence a deviation in
+    // time of sqrt(beta gamma) / w_peak, e-folding as the Morlet wavelet of unit deviation
+    fn efolding_time(&self) -> f32 {
+        2.0f32.sqrt() * self.duration() / self.peak_frequency()
+    }
+
+    fn fourier_domain(&self, omegas: &Array1<f32>) -> Option<Array1<Complex<f32>>> {
+        let factor = self.normalization();
+
+        Some(omegas.mapv(|w| {
+            if w > 0.0 {
+                // In logarithms, w^beta overflowing at high orders
+                Complex::new(
+                    factor * (self.beta * w.ln() - w.powf(self.gamma)).exp(),
+                    0.0,
+                )
+            } else {
+                Complex::new(0.0, 0.0)
+            }
+        }))
+    }
+}
+
+// Handling of the edges of the signal by the CWT, the signal being extended by its length on
+// either side before being transformed, then cropped back
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Boundary {
+    // Zeros, the wavelets reaching past the edges seeing no signal
+    #[default]
+    ZeroPad,
+    // The signal mirrored about its first and last samples
+    Reflect,
+    // The signal repeated, as if it were periodic
+    Periodic,
+}
+
+// Normalization of the wavelet dilated by each scale
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Normalization {
+    // 1 / sqrt(a), preserving the energy of the wavelet: the power of white noise is the same at
+    // every scale, while the amplitude of a sinusoid grows as the square root of its scale
+    #[default]
+    L2,
+    // 1 / a, preserving the amplitude of the wavelet: sinusoids of equal amplitude have equal
+    // coefficients whatever their frequency, as usually reported in EEG time-frequency analyses
+    L1,
+}
+
+impl Normalization {
+    // Factor the wavelet dilated by the `scale`, in samples, is multiplied by
+    // Coefficients are converted from L2 to L1 by multiplying them by 1 / sqrt(`scale`)
+    pub fn factor(&self, scale: f32) -> f32 {
+        match self {
+            Normalization::L2 => 1.0 / scale.sqrt(),
+            Normalization::L1 => 1.0 / scale,
+        }
+    }
+}
+
+// Options of the Continuous Wavelet Transform
+#[derive(Clone, Debug)]
+pub struct CwtOptions {
+    pub boundary: Boundary,
+    pub normalization: Normalization,
+    // Sampling interval, the scales being in its units of time (e.g. seconds for 1 / fs), 1 sample
+    // by default
+    pub dt: f32,
+}
+
+impl Default for CwtOptions {
+    fn default() -> Self {
+        CwtOptions {
+            boundary: Boundary::default(),
+            normalization: Normalization::default(),
+            dt: 1.0,
+        }
+    }
+}
+
+impl CwtOptions {
+    pub fn new() -> Self {
+        CwtOptions::default()
+    }
+
+    pub fn boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    pub fn normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    pub fn dt(mut self, dt: f32) -> Self {
+        self.dt = dt;
+        self
+    }
+
+    // The `scales`, in units of time of `dt`, brought to samples
+    // The wavelet dilated by a scale s sampled every dt is that dilated by s / dt sampled every
+    // sample, its normalization sqrt(dt / s) included
+    fn samples(&self, scales: &[f32]) -> Vec<f32> {
+        assert!(self.dt > 0.0, "Sampling interval must be positive !");
+        scales.iter().map(|&a| a / self.dt).collect()
+    }
+}
+
+// Writes the `value` of the wavelet at each of the `times` into `out`
+fn fill<V>(times: &Array1<f32>, out: &mut Array1<V>, value: impl Fn(f32) -> V) {
+    assert_eq!(
+        times.len(),
+        out.len(),
+        "Output buffer must be of the length of the times !"
+    );
+
+    for (o, &t) in out.iter_mut().zip(times) {
+        *o = value(t);
+    }
+}
+
+// Cone of influence of the CWT of a signal of `n` samples, at each of the `scales` of the `wavelet`,
+// in units of time of `dt` per sample
+//
+// Returns, per scale, the number of samples at either edge within an e-folding time of it, whose
+// coefficients are unreliable
+pub fn coi<T: Wavelet>(n: usize, scales: &[f32], wavelet: &T, dt: f32) -> Vec<usize> {
+    scales
+        .iter()
+        .map(|&a| ((wavelet.efolding_time() * a / dt).ceil() as usize).min(n.div_ceil(2)))
+        .collect()
+}
+
+// Inverse Continuous Wavelet Transform of the `coeffs` (scales x times) of a signal, at the
+// logarithmically spaced `scales` of the `wavelet`, in units of time of `dt` per sample, through
+// the single integral of Torrence & Compo:
+//
+// x[n] = dj sqrt(dt) / (C_delta psi(0)) sum_j Re(W[j, n]) / sqrt(s_j)
+//
+// dj being the spacing of the scales in octaves, and the scales dense enough for the sum to
+// approximate the integral, e.g. 12 voices per octave, and the `coeffs` L2-normalized
+pub fn icwt<S, T>(coeffs: &ArrayBase<S, Ix2>, scales: &[f32], wavelet: &T, dt: f32) -> Array1<f32>
+where
+    S: Data<Elem = Complex<f32>>,
+    T: Wavelet<Dtype = f32>,
+    T::WaveletDtype: Into<Complex<f32>> + Clone,
+{
+    assert!(
+        scales.len() > 1 && coeffs.nrows() == scales.len(),
+        "At least two scales are needed, each matching a row of coefficients !"
+    );
+    let reconstruction_factor = wavelet
+        .reconstruction_factor()
+        .expect("No reconstruction factor is known for this wavelet !");
+
+    let dj = (scales[scales.len() - 1] / scales[0]).log2() / (scales.len() - 1) as f32;
+    let psi0 = wavelet.generate(&Array1::zeros(1))[0].clone().into().re;
+
+    let mut result = Array1::zeros(coeffs.ncols());
+    for (row, &a) in coeffs.outer_iter().zip(scales) {
+        result.zip_mut_with(&row, |x, w| *x += w.re / a.sqrt());
+    }
+
+    result * (dj * dt.sqrt() / (reconstruction_factor * psi0))
+}
+
+fn factorial(n: u32) -> f64 {
+    (1..=n).map(f64::from).product()
+}
+
+// Logarithm of the gamma function of `x` > 0, by the Lanczos approximation (g = 7, 9 terms)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.9999999999998099,
+        676.5203681218851,
+        -1259.1392167224028,
+        771.3234287776531,
+        -176.6150291621406,
+        12.507343278686905,
+        -0.13857109526572012,
+        9.984369578019572e-06,
+        1.5056327351493116e-07,
+    ];
+
+    // Reflected below 1/2, where the approximation loses accuracy
+    if x < 0.5 {
+        return (PI64 / (PI64 * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        });
+    let t = x + 7.5;
+
+    0.5 * (2.0 * PI64).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+// Scales (samples) at which the `wavelet` peaks at each of the `freqs` in Hz, sampled at `fs` Hz
+pub fn scales_for_frequencies<T: Wavelet>(freqs: &[f32], fs: f32, wavelet: &T) -> Vec<f32> {
+    freqs
+        .iter()
+        .map(|&f| wavelet.center_frequency() * fs / f)
+        .collect()
+}
+
+// Frequencies in Hz at which the `wavelet` peaks at each of the `scales` (samples), sampled at `fs`
+// Hz
+pub fn frequencies_for_scales<T: Wavelet>(scales: &[f32], fs: f32, wavelet: &T) -> Vec<f32> {
+    scales
+        .iter()
+        .map(|&a| wavelet.center_frequency() * fs / a)
+        .collect()
+}
+
+// Logarithmically spaced scales (samples) of `n_voices` per octave, from the one peaking at `fmax`
+// down to the ones peaking at `fmin` Hz, sampled at `fs` Hz
+pub fn log_scales<T: Wavelet>(
+    fmin: f32,
+    fmax: f32,
+    n_voices: usize,
+    fs: f32,
+    wavelet: &T,
+) -> Vec<f32> {
+    assert!(
+        fmin > 0.0 && fmin <= fmax && n_voices > 0,
+        "Frequencies must be positive and increasing, with at least one voice per octave !"
+    );
+
+    // A hair of tolerance keeping `fmin` itself when it lies an exact number of voices away
+    let num_scales = (n_voices as f32 * (fmax / fmin).log2() + 1e-4).floor() as usize + 1;
+    let smallest = wavelet.center_frequency() * fs / fmax;
+
+    (0..num_scales)
+        .map(|i| smallest * 2.0f32.powf(i as f32 / n_voices as f32))
+        .collect()
+}
+
+// Continuous Wavelet Transform along with the scale and frequency of each of its rows
+#[derive(Clone, Debug)]
+pub struct CwtResult {
+    // Oriented scales x times
+    pub coeffs: Array2<Complex<f32>>,
+    // Scale of each row, in samples
+    pub scales: Array1<f32>,
+    // Frequency at which the wavelet of each row peaks, in Hz
+    pub freqs: Array1<f32>,
+}
+
+impl CwtResult {
+    // Power |W|^2 of the transform, rectified by the scale of each row if `rectify`, in dB clamped
+    // to `db_floor` if any, along with its axes
+    pub fn scalogram(&self, rectify: bool, db_floor: Option<f32>) -> Scalogram {
+        let scales = self.scales.as_slice().unwrap();
+        let power = scalogram_power(&self.coeffs, rectify.then_some(scales));
+
+        Scalogram {
+            power: match db_floor {
+                Some(floor) => to_db(&power, floor),
+                None => power,
+            },
+            scales: self.scales.clone(),
+            freqs: self.freqs.clone(),
+        }
+    }
+
+    // Global wavelet spectrum of the transform, rectified by the scale of each row if `rectify`, in
+    // dB clamped to `db_floor` if any, along with its axes
+    pub fn global_spectrum(&self, rectify: bool, db_floor: Option<f32>) -> GlobalWaveletSpectrum {
+        let scales = self.scales.as_slice().unwrap();
+        let power = global_wavelet_spectrum(&self.coeffs, rectify.then_some(scales));
+
+        GlobalWaveletSpectrum {
+            power: match db_floor {
+                Some(floor) => to_db(&power, floor),
+                None => power,
+            },
+            scales: self.scales.clone(),
+            freqs: self.freqs.clone(),
+        }
+    }
+}
+
+// Power of a Continuous Wavelet Transform, ready to be plotted against its axes
+#[derive(Clone, Debug)]
+pub struct Scalogram {
+    // Oriented scales x times, either linear or in dB
+    pub power: Array2<f32>,
+    // Scale of each row, in samples
+    pub scales: Array1<f32>,
+    // Frequency of each row, in Hz
+    pub freqs: Array1<f32>,
+}
+
+// Time-averaged power of a Continuous Wavelet Transform at each of its scales
+#[derive(Clone, Debug)]
+pub struct GlobalWaveletSpectrum {
+    // Either linear or in dB
+    pub power: Array1<f32>,
+    // Scale of each value, in samples
+    pub scales: Array1<f32>,
+    // Frequency of each value, in Hz
+    pub freqs: Array1<f32>,
+}
+
+// Y. Liu, X. S. Liang and R. H. Weisberg, "Rectification of the Bias in the Wavelet Power
+// Spectrum," Journal of Atmospheric and Oceanic Technology, vol. 24, no. 12, pp. 2093-2102, 2007,
+// doi: 10.1175/2007JTECHO511.1.
+//
+// Power |W|^2 of the CWT `coeffs` (scales x times)
+// The power of a sinusoid grows with the scale it peaks at, overstating low frequencies: given the
+// `scales` of the rows, each of them is divided by its scale, sinusoids of equal amplitude then
+// having equal power whatever their frequency
+pub fn scalogram_power<S>(coeffs: &ArrayBase<S, Ix2>, scales: Option<&[f32]>) -> Array2<f32>
+where
+    S: Data<Elem = Complex<f32>>,
+{
+    let mut power = coeffs.mapv(|z| z.norm_sqr());
+
+    if let Some(scales) = scales {
+        assert_eq!(
+            scales.len(),
+            power.nrows(),
+            "There must be one scale per row of coefficients !"
+        );
+        for (mut row, &a) in power.outer_iter_mut().zip(scales) {
+            row /= a;
+        }
+    }
+
+    power
+}
+
+// Global wavelet spectrum of the CWT `coeffs` (scales x times), the power of each scale averaged
+// over time, rectified by the `scales` if any as per `scalogram_power`
+pub fn global_wavelet_spectrum<S>(coeffs: &ArrayBase<S, Ix2>, scales: Option<&[f32]>) -> Array1<f32>
+where
+    S: Data<Elem = Complex<f32>>,
+{
+    let power = scalogram_power(coeffs, scales);
+    if power.ncols() == 0 {
+        return Array1::zeros(power.nrows());
+    }
+
+    power.mean_axis(Axis(1)).unwrap()
+}
+
+// Power in dB, 10 log10(p), clamped to `db_floor` from below
+pub fn to_db<S, D>(power: &ArrayBase<S, D>, db_floor: f32) -> Array<f32, D>
+where
+    S: Data<Elem = f32>,
+    D: Dimension,
+{
+    power.mapv(|p| (10.0 * p.log10()).max(db_floor))
+}
+
+// Samples of the signals of which Continuous Wavelet Transforms are computed, either real or
+// complex, e.g. the analytic signal, free of negative frequencies
+// Real samples are promoted to complex ones, the transforms sharing the same FFT-based engine
+pub trait CwtElement: Copy + Send + Sync {
+    fn to_complex(self) -> Complex<f32>;
+}
+
+impl CwtElement for f32 {
+    fn to_complex(self) -> Complex<f32> {
+        Complex::new(self, 0.0)
+    }
+}
+
+impl CwtElement for Complex<f32> {
+    fn to_complex(self) -> Complex<f32> {
+        self
+    }
+}
+
+pub trait WaveletTransform {
+    // Continuous Wavelet Transform, correlating the signal with the wavelet dilated by each scale
+    // Computed in the frequency domain, by FFTs of the signal and of each dilated wavelet zero-padded
+    // to avoid wrapping around
+    fn cwt<T>(&self, wavelet: &T, scale: &[f32]) -> Array2<Complex<f32>>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone;
+
+    // Continuous Wavelet Transform, with the edges of the signal handled and the scales given in
+    // units of time as per the `options`
+    fn cwt_with<T>(&self, wavelet: &T, scale: &[f32], options: &CwtOptions) -> Array2<Complex<f32>>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone;
+
+    // The same transforms, failing on an empty list of scales or on scales that are not positive
+    // and finite, instead of transforming the signal at none or at invalid ones
+    fn try_cwt<T>(&self, wavelet: &T, scale: &[f32]) -> Result<Array2<Complex<f32>>, WaveletError>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone;
+
+    fn try_cwt_with<T>(
+        &self,
+        wavelet: &T,
+        scale: &[f32],
+        options: &CwtOptions,
+    ) -> Result<Array2<Complex<f32>>, WaveletError>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone;
+
+    // Continuous Wavelet Transform computed directly in the time domain, slower than `cwt` by a
+    // factor of the length of the signal but useful to validate it
+    fn cwt_direct<T>(&self, wavelet: &T, scale: &[f32]) -> Array2<Complex<f32>>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone;
+
+    // Continuous Wavelet Transform of the signal sampled at `fs` Hz, at the scales at which the
+    // `wavelet` peaks at each of the `freqs` in Hz
+    fn cwt_frequencies<T>(&self, wavelet: &T, freqs: &[f32], fs: f32) -> CwtResult
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone;
+}
+
+pub trait MultichannelWaveletTransform {
+    // Continuous Wavelet Transform of each channel of channels x samples data, the dilated wavelets
+    // being transformed once for all channels
+    // With the `rayon` feature, channels are transformed in parallel
+    //
+    // Returns the transforms oriented channels x scales x times
+    fn cwt_channels<T>(&self, wavelet: &T, scale: &[f32]) -> Array3<Complex<f32>>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone;
+
+    // The same transforms, with the edges of each channel handled and the scales given in units of
+    // time as per the `options`
+    fn cwt_channels_with<T>(
+        &self,
+        wavelet: &T,
+        scale: &[f32],
+        options: &CwtOptions,
+    ) -> Array3<Complex<f32>>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone;
+}
+
+impl<A, S> WaveletTransform for ArrayBase<S, Ix1>
+where
+    A: CwtElement,
+    S: Data<Elem = A>,
+{
+    fn cwt<T>(&self, wavelet: &T, scales: &[f32]) -> Array2<Complex<f32>>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone,
+    {
+        self.cwt_with(wavelet, scales, &CwtOptions::new())
+    }
+
+    fn cwt_with<T>(&self, wavelet: &T, scales: &[f32], options: &CwtOptions) -> Array2<Complex<f32>>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone,
+    {
+        let n = self.len();
+        let mut result = Array2::zeros((scales.len(), n));
+        if n == 0 {
+            return result;
+        }
+
+        let scales = options.samples(scales);
+        let (extended, pad) = extend(self, options.boundary);
+        let spectra = wavelet_spectra(wavelet, &scales, extended.len());
+        fft_cwt(
+            &extended,
+            &spectra,
+            &scales,
+            options.normalization,
+            pad,
+            result.view_mut(),
+        );
+
+        result
+    }
+
+    fn try_cwt<T>(&self, wavelet: &T, scales: &[f32]) -> Result<Array2<Complex<f32>>, WaveletError>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone,
+    {
+        self.try_cwt_with(wavelet, scales, &CwtOptions::new())
+    }
+
+    fn try_cwt_with<T>(
+        &self,
+        wavelet: &T,
+        scales: &[f32],
+        options: &CwtOptions,
+    ) -> Result<Array2<Complex<f32>>, WaveletError>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone,
+    {
+        check_scales(scales)?;
+
+        Ok(self.cwt_with(wavelet, scales, options))
+    }
+
+    fn cwt_frequencies<T>(&self, wavelet: &T, freqs: &[f32], fs: f32) -> CwtResult
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone,
+    {
+        let scales = scales_for_frequencies(freqs, fs, wavelet);
+
+        CwtResult {
+            coeffs: self.cwt(wavelet, &scales),
+            scales: Array1::from(scales),
+            freqs: Array1::from(freqs.to_vec()),
+        }
+    }
+
+    fn cwt_direct<T>(&self, wavelet: &T, scales: &[f32]) -> Array2<Complex<f32>>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone,
+    {
+        let n = self.len();
+        let mut result = Array2::zeros((scales.len(), n));
+        if n == 0 {
+            return result;
+        }
+
+        // Buffers of the shifted and scaled times and of the wavelet at them, reused for every shift
+        let mut shifted_scaled_time = Array1::zeros(n);
+        let mut wavelet_coeffs = wavelet.generate(&shifted_scaled_time);
+
+        for (mut scale_result, a) in result.outer_iter_mut().zip(scales) {
+            let normalization_factor = 1.0 / a.sqrt();
+
+            for (b, coeff) in scale_result.iter_mut().enumerate() {
+                // Signed, samples before `b` lying at negative times
+                for (t, time) in shifted_scaled_time.iter_mut().enumerate() {
+                    *time = (t as f32 - b as f32) / a;
+                }
+                wavelet.generate_into(&shifted_scaled_time, &mut wavelet_coeffs);
+
+                let sum: Complex<f32> = self
+                    .iter()
+                    .zip(&wavelet_coeffs)
+                    .map(|(x, w)| x.to_complex() * w.clone().into().conj())
+                    .sum();
+
+                *coeff = normalization_factor * sum;
+            }
+        }
+
+        result
+    }
+}
+
+impl<A, S> MultichannelWaveletTransform for ArrayBase<S, Ix2>
+where
+    A: CwtElement,
+    S: Data<Elem = A>,
+{
+    fn cwt_channels<T>(&self, wavelet: &T, scales: &[f32]) -> Array3<Complex<f32>>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone,
+    {
+        self.cwt_channels_with(wavelet, scales, &CwtOptions::new())
+    }
+
+    fn cwt_channels_with<T>(
+        &self,
+        wavelet: &T,
+        scales: &[f32],
+        options: &CwtOptions,
+    ) -> Array3<Complex<f32>>
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone,
+    {
+        let (num_channels, n) = self.dim();
+        let mut result = Array3::zeros((num_channels, scales.len(), n));
+        if num_channels == 0 || n == 0 {
+            return result;
+        }
+
+        let scales = &options.samples(scales)[..];
+        // Every channel is extended to the same length, hence dilated wavelets of the same spectra
+        let spectra = wavelet_spectra(
+            wavelet,
+            scales,
+            extend(&self.row(0), options.boundary).0.len(),
+        );
+        let transform = |(coeffs, channel): (ArrayViewMut2<Complex<f32>>, _)| {
+            let (extended, pad) = extend(&channel, options.boundary);
+            fft_cwt(
+                &extended,
+                &spectra,
+                scales,
+                options.normalization,
+                pad,
+                coeffs,
+            );
+        };
+
+        #[cfg(feature = "rayon")]
+        result
+            .axis_iter_mut(Axis(0))
+            .into_par_iter()
+            .zip(self.axis_iter(Axis(0)).into_par_iter())
+            .for_each(transform);
+        #[cfg(not(feature = "rayon"))]
+        result
+            .outer_iter_mut()
+            .zip(self.outer_iter())
+            .for_each(transform);
+
+        result
+    }
+}
+
+// Validation of the `scales` of a transform, at least one of them, all positive and finite
+fn check_scales(scales: &[f32]) -> Result<(), WaveletError> {
+    if scales.is_empty() {
+        return Err(WaveletError::NoScales);
+    }
+    if let Some((index, &scale)) = scales
+        .iter()
+        .enumerate()
+        .find(|(_, &a)| !(a.is_finite() && a > 0.0))
+    {
+        return Err(WaveletError::InvalidScale { index, scale });
+    }
+
+    Ok(())
+}
+
+// The `signal` extended past its edges as per the `boundary`, along with the number of samples
+// added before it
+fn extend<A, S>(signal: &ArrayBase<S, Ix1>, boundary: Boundary) -> (CowArray<'_, A, Ix1>, usize)
+where
+    A: CwtElement,
+    S: Data<Elem = A>,
+{
+    let n = signal.len();
+    let extended = match boundary {
+        Boundary::ZeroPad => return (CowArray::from(signal.view()), 0),
+        // Mirrored about the edge samples, themselves not repeated
+        Boundary::Reflect => {
+            let pad = n.saturating_sub(1);
+            let left = signal.slice(s![1..=pad; -1]);
+            let right = signal.slice(s![n - 1 - pad..n - 1; -1]);
+            ndarray::concatenate(Axis(0), &[left, signal.view(), right]).unwrap()
+        }
+        Boundary::Periodic => {
+            ndarray::concatenate(Axis(0), &[signal.view(), signal.view(), signal.view()]).unwrap()
+        }
+    };
+    let pad = (extended.len() - n) / 2;
+
+    (CowArray::from(extended), pad)
+}
+
+// Spectra of the wavelet dilated by each of the `scales`, conjugated for the correlation with a
+// signal of `n` samples zero-padded to avoid wrapping around
+fn wavelet_spectra<T>(wavelet: &T, scales: &[f32], n: usize) -> Array2<Complex<f32>>
+where
+    T: Wavelet<Dtype = f32>,
+    T::WaveletDtype: Into<Complex<f32>> + Clone,
+{
+    // Linear correlation of lags -(n - 1)..n, unaffected by the circular convolution of FFTs
+    let m = (2 * n - 1).next_power_of_two();
+    let mut spectra = Array2::zeros((scales.len(), m));
+
+    let lags = Array1::from_iter(-(n as isize - 1)..n as isize);
+
+    // Angular frequencies of the FFT bins, in radians per sample
+    let omegas = Array1::from_shape_fn(m, |k| {
+        let k = if k <= m / 2 {
+            k as f32
+        } else {
+            k as f32 - m as f32
+        };
+        2.0 * PI * k / m as f32
+    });
+
+    for (mut row, &a) in spectra.outer_iter_mut().zip(scales) {
+        // The wavelet dilated by `a` has the spectrum a psi(a w), conjugated by the correlation
+        let kernel_spectrum = match wavelet.fourier_domain(&omegas.mapv(|w| a * w)) {
+            Some(spectrum) => spectrum.mapv(|z| z.conj() * a),
+            None => {
+                // c[b] = sum_t x[t] w[t - b], the convolution of x with the kernel g[j] = w[-j]
+                let wavelet_coeffs_conj = wavelet
+                    .generate(&lags.mapv(|lag| lag as f32 / a))
+                    .mapv(|v| v.into().conj());
+                let mut kernel = Array1::zeros(m);
+                for (&lag, &w) in lags.iter().zip(&wavelet_coeffs_conj) {
+                    kernel[(m as isize - lag) as usize % m] = w;
+                }
+                kernel.fft()
+            }
+        };
+        row.assign(&kernel_spectrum);
+    }
+
+    spectra
+}
+
+// FFT-based CWT of the zero-padded `signal` by the dilated wavelet `spectra`, normalized as per the
+// `normalization`, written into `result` (scales x times) from the `pad`-th sample of the `signal`
+// on
+fn fft_cwt<A, S>(
+    signal: &ArrayBase<S, Ix1>,
+    spectra: &Array2<Complex<f32>>,
+    scales: &[f32],
+    normalization: Normalization,
+    pad: usize,
+    mut result: ArrayViewMut2<Complex<f32>>,
+) where
+    A: CwtElement,
+    S: Data<Elem = A>,
+{
+    let n = result.ncols();
+    let mut padded = Array1::zeros(spectra.ncols());
+    padded
+        .slice_mut(s![..signal.len()])
+        .assign(&signal.mapv(A::to_complex));
+    let spectrum = padded.fft();
+
+    for ((mut row, kernel_spectrum), &a) in result
+        .outer_iter_mut()
+        .zip(spectra.outer_iter())
+        .zip(scales)
+    {
+        let normalization_factor = normalization.factor(a);
+
+        let convolution = (&spectrum * &kernel_spectrum).ifft();
+        row.assign(
+            &convolution
+                .slice(s![pad..pad + n])
+                .mapv(|z| z * normalization_factor),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sinusoid of `n` samples, of `frequency` cycles per sample
+    fn sinusoid(frequency: f32, n: usize) -> Array1<f32> {
+        Array1::from_shape_fn(n, |t| (2.0 * PI * frequency * t as f32).sin())
+    }
+
+    // Mean magnitude of each row of the `coeffs`, over the samples of `times`
+    fn ridge(coeffs: &Array2<Complex<f32>>, times: std::ops::Range<usize>) -> Array1<f32> {
+        coeffs
+            .slice(s![.., times])
+            .map_axis(Axis(1), |row| row.mapv(|z| z.norm()).mean().unwrap())
+    }
+
+    fn argmax(values: &Array1<f32>) -> usize {
+        (0..values.len())
+            .max_by(|&a, &b| values[a].total_cmp(&values[b]))
+            .unwrap()
+    }
+
+    #[test]
+    fn morlet_at_zero_is_one() {
+        let values = Morlet::default().generate(&Array1::from(vec![0.0, 1.0]));
+        assert_eq!(values[0], Complex::new(1.0, 0.0));
+        let expected = Complex::new(0.0f32, 6.0).exp() * (-0.5f32).exp();
+        assert!((values[1] - expected).norm() < 1e-6);
+
+        assert_eq!(MexicanHat::new(1.0).generate(&Array1::zeros(1))[0], 1.0);
+    }
+
+    // Scales half an octave apart around the scale `center`, the fifth of them
+    // The power of L2-normalized transforms peaks at slightly larger scales than the matching
+    // one, by less than a quarter of an octave
+    fn grid(center: f32) -> Vec<f32> {
+        (-4..=4)
+            .map(|k| center * 2.0f32.powf(k as f32 / 2.0))
+            .collect()
+    }
+
+    // Row of the grid around the scale of the `wavelet` matching a sinusoid of a period of 32
+    // samples at which the transform of the sinusoid peaks
+    fn peak_row<T>(wavelet: &T) -> usize
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone,
+    {
+        let signal = sinusoid(1.0 / 32.0, 512);
+        let coeffs = signal.cwt(wavelet, &grid(wavelet.center_frequency() * 32.0));
+        assert_eq!(coeffs.dim(), (9, 512));
+
+        argmax(&ridge(&coeffs, 128..384))
+    }
+
+    #[test]
+    fn transform_of_a_sinusoid_peaks_at_the_matching_scale() {
+        assert_eq!(peak_row(&Morlet::default()), 4);
+        assert_eq!(peak_row(&MexicanHat::new(1.0)), 4);
+        assert_eq!(peak_row(&MexicanHat::default()), 4);
+    }
+
+    #[test]
+    fn fft_transform_matches_the_direct_one() {
+        let mut generator = crate::surrogate::Lcg::new(1);
+        let signal = Array1::from_shape_fn(512, |_| generator.next_f32() - 0.5);
+        let scales = (0..10)
+            .map(|k| 2.0 * 2.0f32.powf(k as f32 / 2.0))
+            .collect::<Vec<f32>>();
+
+        for (fast, direct) in [
+            (
+                signal.cwt(&Morlet::default(), &scales),
+                signal.cwt_direct(&Morlet::default(), &scales),
+            ),
+            (
+                signal.cwt(&MexicanHat::new(1.0), &scales),
+                signal.cwt_direct(&MexicanHat::new(1.0), &scales),
+            ),
+        ] {
+            assert_eq!(fast.dim(), (10, 512));
+            let error = fast
+                .iter()
+                .zip(&direct)
+                .map(|(a, b)| (a - b).norm())
+                .fold(0.0, f32::max);
+            assert!(error < 1e-3, "{error}");
+        }
+    }
+
+    // Scale, among those a sixteenth of an octave apart, at which the transform of a sinusoid of a
+    // period of 32 samples peaks
+    fn peak_scale(wavelet: &Morlet) -> f32 {
+        let scales = (0..64)
+            .map(|k| 8.0 * 2.0f32.powf(k as f32 / 16.0))
+            .collect::<Vec<f32>>();
+        let coeffs = sinusoid(1.0 / 32.0, 1024).cwt(wavelet, &scales);
+
+        scales[argmax(&ridge(&coeffs, 384..640))]
+    }
+
+    #[test]
+    fn morlet_center_frequency_sets_the_peak_scale() {
+        let (default, doubled) = (
+            peak_scale(&Morlet::default()),
+            peak_scale(&Morlet::new(12.0)),
+        );
+        // s = omega0 / (2 pi f), f being of 1 / 32 cycles per sample
+        for (scale, omega0) in [(default, 6.0), (doubled, 12.0)] {
+            let expected = omega0 / (2.0 * PI) * 32.0;
+            assert!((scale / expected - 1.0).abs() < 0.05, "{scale} {expected}");
+        }
+        assert!((doubled / default - 2.0).abs() < 0.1);
+
+        assert_eq!(Morlet::default().omega0, 6.0);
+        assert_eq!(Morlet::default().reconstruction_factor(), Some(0.776));
+        assert_eq!(Morlet::new(12.0).reconstruction_factor(), None);
+    }
+
+    #[test]
+    fn sinusoid_peaks_at_the_row_of_its_frequency() {
+        let fs = 250.0;
+        let signal = sinusoid(10.0 / fs, 1000);
+        let freqs = (2..=40).map(|f| f as f32).collect::<Vec<f32>>();
+        let result = signal.cwt_frequencies(&Morlet::default(), &freqs, fs);
+        assert_eq!(result.coeffs.dim(), (39, 1000));
+        assert_eq!(result.freqs.to_vec(), freqs);
+
+        let peak = argmax(&ridge(&result.coeffs, 250..750));
+        assert_eq!(result.freqs[peak], 10.0);
+        assert!((result.scales[peak] - 6.0 / (2.0 * PI) * 25.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn scales_and_frequencies_convert_back_and_forth() {
+        let wavelet = Morlet::default();
+        let scales = scales_for_frequencies(&[1.0, 10.0, 40.0], 250.0, &wavelet);
+        let freqs = frequencies_for_scales(&scales, 250.0, &wavelet);
+        for (f, expected) in freqs.iter().zip([1.0, 10.0, 40.0]) {
+            assert!((f - expected).abs() < 1e-4 * expected);
+        }
+
+        // 3 octaves of 4 voices, from 40 Hz down to 5 Hz
+        let scales = log_scales(5.0, 40.0, 4, 250.0, &wavelet);
+        assert_eq!(scales.len(), 13);
+        let freqs = frequencies_for_scales(&scales, 250.0, &wavelet);
+        assert!((freqs[0] - 40.0).abs() < 1e-3 && (freqs[12] - 5.0).abs() < 1e-3);
+        for pair in scales.windows(2) {
+            assert!((pair[1] / pair[0] - 2.0f32.powf(0.25)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn second_derivative_of_gaussian_is_the_mexican_hat() {
+        let times = Array1::linspace(-5.0, 5.0, 41);
+        let (dog, hat) = (
+            Dog::new(2).generate(&times),
+            MexicanHat::new(1.0).generate(&times),
+        );
+        // Normalized by 1 / sqrt(gamma(5 / 2)), that is 1 / sqrt(3 sqrt(pi) / 4)
+        let normalization = 1.0 / (0.75 * PI.sqrt()).sqrt();
+        for (d, h) in dog.iter().zip(&hat) {
+            assert!((d - normalization * h).abs() < 1e-6, "{d} {h}");
+        }
+
+        // And so are their transforms, through the analytic spectrum of the former
+        let signal = sinusoid(1.0 / 20.0, 256);
+        let (dog, hat) = (
+            signal.cwt(&Dog::new(2), &[4.0, 8.0]),
+            signal.cwt(&MexicanHat::new(1.0), &[4.0, 8.0]),
+        );
+        for (d, h) in dog.iter().zip(&hat) {
+            assert!((d - h * normalization).norm() < 1e-2, "{d} {h}");
+        }
+    }
+
+    #[test]
+    fn paul_transform_of_a_delta_decays_as_the_wavelet() {
+        let mut delta = Array1::zeros(512);
+        delta[256] = 1.0f32;
+        let scale = 16.0;
+        let coeffs = delta.cwt(&Paul::default(), &[scale]);
+
+        // |psi(t)| decays as (1 + t^2)^(-(m + 1) / 2), t being in units of scale
+        let center = coeffs[[0, 256]].norm();
+        for offset in [8, 16, 32, 64] {
+            let t = offset as f32 / scale;
+            let expected = (1.0 + t * t).powf(-2.5);
+            for b in [256 - offset, 256 + offset] {
+                let ratio = coeffs[[0, b]].norm() / center;
+                assert!(
+                    (ratio / expected - 1.0).abs() < 0.05,
+                    "{b} {ratio} {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reflection_reduces_edge_errors_within_the_cone_of_influence() {
+        // A cosine of a period of 64 samples, symmetric about its first sample, whose transform
+        // away from any edge is that of the middle of a signal three times as long
+        let wavelet = Morlet::default();
+        let scale = wavelet.center_frequency() * 64.0;
+        let cosine = |t: isize| (2.0 * PI * t as f32 / 64.0).cos();
+        let signal = Array1::from_shape_fn(1024, |t| cosine(t as isize));
+        let long = Array1::from_shape_fn(3072, |t| cosine(t as isize - 1024));
+        let reference = long
+            .cwt(&wavelet, &[scale])
+            .slice(s![.., 1024..2048])
+            .to_owned();
+
+        let coi = coi(1024, &[scale], &wavelet, 1.0)[0];
+        assert_eq!(coi, (2.0f32.sqrt() * scale).ceil() as usize);
+        let edge_error = |boundary: Boundary| {
+            let coeffs = signal.cwt_with(&wavelet, &[scale], &CwtOptions::new().boundary(boundary));
+            (0..coi)
+                .chain(1024 - coi..1024)
+                .map(|t| (coeffs[[0, t]] - reference[[0, t]]).norm())
+                .sum::<f32>()
+                / (2 * coi) as f32
+        };
+
+        let (zero, reflect) = (edge_error(Boundary::ZeroPad), edge_error(Boundary::Reflect));
+        assert!(reflect < 0.5 * zero, "{reflect} {zero}");
+        // A whole number of periods, the signal is exactly periodic
+        assert!(edge_error(Boundary::Periodic) < 1e-2 * zero);
+    }
+
+    #[test]
+    fn cone_of_influence_is_bounded_by_half_the_signal() {
+        assert_eq!(
+            coi(100, &[1.0, 10.0, 100.0], &Morlet::default(), 1.0),
+            vec![2, 15, 50]
+        );
+        // Scales in seconds, sampled at 250 Hz
+        assert_eq!(
+            coi(1000, &[0.04], &Morlet::default(), 1.0 / 250.0),
+            vec![15]
+        );
+    }
+
+    // Relative RMS error of the inverse transform of the transform by the `wavelet`, at scales of
+    // 12 voices per octave from `smallest` samples up, of a signal band-limited to periods of 12
+    // to 35 samples, away from its edges
+    fn reconstruction_error<T>(wavelet: &T, smallest: f32) -> f32
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone,
+    {
+        let signal = &sinusoid(1.0 / 12.0, 1024) + &(0.5 * &sinusoid(1.0 / 20.0, 1024))
+            - 0.8 * &sinusoid(1.0 / 35.0, 1024);
+        let fmax = wavelet.center_frequency() / smallest;
+        let scales = log_scales(fmax / 512.0, fmax, 12, 1.0, wavelet);
+        let reconstructed = icwt(&signal.cwt(wavelet, &scales), &scales, wavelet, 1.0);
+        assert_eq!(reconstructed.len(), 1024);
+
+        let rms = |x: ndarray::ArrayView1<f32>| x.mapv(|v| v * v).mean().unwrap().sqrt();
+        let error = &reconstructed - &signal;
+        rms(error.slice(s![256..768])) / rms(signal.slice(s![256..768]))
+    }
+
+    #[test]
+    fn inverse_transform_reconstructs_band_limited_signals() {
+        // From 2 samples up, as Torrence & Compo
+        let morlet = reconstruction_error(&Morlet::default(), 2.0);
+        assert!(morlet < 0.05, "{morlet}");
+        // The spectrum of the DOG wavelet being broader, from lower scales for the highest
+        // frequencies of the signal to be fully covered
+        let dog = reconstruction_error(&Dog::new(2), 0.5);
+        assert!(dog < 0.05, "{dog}");
+        let hat = reconstruction_error(&MexicanHat::new(1.0), 0.5);
+        assert!(hat < 0.05, "{hat}");
+    }
+
+    #[test]
+    fn multichannel_transform_matches_each_channel() {
+        let mut generator = crate::surrogate::Lcg::new(2);
+        let data = Array2::from_shape_fn((4, 300), |_| generator.next_f32() - 0.5);
+        let scales = [2.0, 5.0, 12.0];
+
+        for options in [
+            CwtOptions::new(),
+            CwtOptions::new().boundary(Boundary::Reflect).dt(0.5),
+        ] {
+            let transforms = data.cwt_channels_with(&Morlet::default(), &scales, &options);
+            assert_eq!(transforms.dim(), (4, 3, 300));
+            for (c, channel) in data.outer_iter().enumerate() {
+                assert_eq!(
+                    transforms.index_axis(Axis(0), c),
+                    channel.cwt_with(&Morlet::default(), &scales, &options)
+                );
+            }
+        }
+        assert_eq!(
+            data.cwt_channels(&Paul::default(), &scales)
+                .index_axis(Axis(0), 3),
+            data.row(3).cwt(&Paul::default(), &scales)
+        );
+    }
+
+    #[test]
+    fn scales_in_seconds_peak_at_the_same_frequency_whatever_the_sampling_rate() {
+        let wavelet = Morlet::default();
+        let freqs = (2..=40).map(|f| f as f32).collect::<Vec<f32>>();
+        // Scales in seconds, as for a sampling rate of 1 Hz
+        let scales = scales_for_frequencies(&freqs, 1.0, &wavelet);
+
+        for fs in [250.0, 500.0] {
+            let n = (4.0 * fs) as usize;
+            let signal = sinusoid(10.0 / fs, n);
+            let options = CwtOptions::new().dt(1.0 / fs);
+            let coeffs = signal.cwt_with(&wavelet, &scales, &options);
+            let peak = argmax(&ridge(&coeffs, n / 4..3 * n / 4));
+            assert_eq!(freqs[peak], 10.0, "{fs}");
+        }
+
+        // A sampling interval of 1 is that of scales in samples
+        let signal = sinusoid(0.05, 200);
+        assert_eq!(
+            signal.cwt_with(&wavelet, &[3.0, 7.0], &CwtOptions::new().dt(1.0)),
+            signal.cwt(&wavelet, &[3.0, 7.0])
+        );
+    }
+
+    #[test]
+    fn global_spectrum_of_two_tones_peaks_at_both() {
+        let fs = 250.0;
+        let signal = &sinusoid(6.0 / fs, 2000) + &sinusoid(30.0 / fs, 2000);
+        let freqs = (2..=45).map(|f| f as f32).collect::<Vec<f32>>();
+        let result = signal.cwt_frequencies(&Morlet::default(), &freqs, fs);
+
+        let spectrum = result.global_spectrum(true, None);
+        assert_eq!(spectrum.freqs.to_vec(), freqs);
+        let peaks = (1..freqs.len() - 1)
+            .filter(|&i| {
+                spectrum.power[i] > spectrum.power[i - 1]
+                    && spectrum.power[i] > spectrum.power[i + 1]
+            })
+            .collect::<Vec<usize>>();
+        assert_eq!(
+            peaks.iter().map(|&i| freqs[i]).collect::<Vec<f32>>(),
+            [6.0, 30.0]
+        );
+        // Rectified, tones of equal amplitude have comparable power
+        let (low, high) = (spectrum.power[peaks[0]], spectrum.power[peaks[1]]);
+        assert!((low / high - 1.0).abs() < 0.15, "{low} {high}");
+        // Unlike the raw power, growing with the scale
+        let raw = result.global_spectrum(false, None);
+        assert!(raw.power[peaks[0]] > 3.0 * raw.power[peaks[1]]);
+
+        assert_eq!(
+            global_wavelet_spectrum(&result.coeffs, None),
+            scalogram_power(&result.coeffs, None)
+                .mean_axis(Axis(1))
+                .unwrap()
+        );
+        let db = result.global_spectrum(true, Some(-30.0));
+        for (&db, &linear) in db.power.iter().zip(&spectrum.power) {
+            assert!((db - (10.0 * linear.log10()).max(-30.0)).abs() < 1e-3);
+        }
+    }
+
+    // Values written by `generate_into` into a buffer reused from one call to the next, which are
+    // those of `generate`, the times being left untouched
+    fn check_generate_into<T>(wavelet: &T)
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: PartialEq + std::fmt::Debug,
+    {
+        let mut times = Array1::linspace(-4.0, 4.0, 33);
+        let mut out = wavelet.generate(&Array1::zeros(33));
+        for shift in [0.0, 0.25, 3.0] {
+            times.mapv_inplace(|t| t + shift);
+            let before = times.clone();
+            wavelet.generate_into(&times, &mut out);
+            assert_eq!(times, before);
+            assert_eq!(out, wavelet.generate(&times));
+        }
+    }
+
+    #[test]
+    fn generating_into_a_buffer_matches_generating() {
+        check_generate_into(&Morlet::default());
+        check_generate_into(&MexicanHat::default());
+        check_generate_into(&Paul::default());
+        check_generate_into(&Dog::new(3));
+
+        // The direct transform, reusing its buffers, is the correlation with each shifted wavelet
+        let signal = sinusoid(0.1, 64);
+        let direct = signal.cwt_direct(&MexicanHat::new(1.0), &[2.0, 5.0]);
+        for (row, &a) in [2.0f32, 5.0].iter().enumerate() {
+            for b in [0, 17, 63] {
+                let times = Array1::from_shape_fn(64, |t| (t as f32 - b as f32) / a);
+                let expected = MexicanHat::new(1.0).generate(&times).dot(&signal) / a.sqrt();
+                assert!((direct[[row, b]] - Complex::from(expected)).norm() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn morse_spectrum_peaks_at_its_peak_frequency() {
+        let morse = Morse::new(2.0, 3.0);
+        let expected = (2.0f32 / 3.0).powf(1.0 / 3.0);
+        assert!((morse.peak_frequency() - expected).abs() < 1e-6);
+        assert!((morse.duration() - 6.0f32.sqrt()).abs() < 1e-6);
+
+        let omegas = Array1::linspace(0.0, 4.0, 4001);
+        let spectrum = morse.fourier_domain(&omegas).unwrap().mapv(|z| z.norm());
+        assert!((omegas[argmax(&spectrum)] - expected).abs() < 1e-3);
+        assert_eq!(spectrum[0], 0.0);
+        // Of unit energy
+        let energy = spectrum.mapv(|v| v * v).sum() * 0.001 / (2.0 * PI);
+        assert!((energy - 1.0).abs() < 1e-3, "{energy}");
+    }
+
+    #[test]
+    fn morse_transform_of_a_sinusoid_peaks_at_the_predicted_scale() {
+        let morse = Morse::new(2.0, 3.0);
+        // Amplitude-preserving, for the peak not to be biased toward larger scales
+        let options = CwtOptions::new().normalization(Normalization::L1);
+        let scales = (0..64)
+            .map(|k| 2.0 * 2.0f32.powf(k as f32 / 16.0))
+            .collect::<Vec<f32>>();
+        let coeffs = sinusoid(1.0 / 32.0, 1024).cwt_with(&morse, &scales, &options);
+
+        let peak = scales[argmax(&ridge(&coeffs, 384..640))];
+        let predicted = morse.peak_frequency() / (2.0 * PI / 32.0);
+        assert!((peak / predicted - 1.0).abs() < 0.05, "{peak} {predicted}");
+    }
+
+    #[test]
+    fn analytic_signals_have_no_energy_at_mirrored_frequencies() {
+        let signal = sinusoid(1.0 / 32.0, 1024);
+        let analytic = crate::fft::analytic_signal(&signal);
+        let scales = grid(Dog::new(2).center_frequency() * 32.0);
+
+        // Real samples are transformed as complex ones of no imaginary part
+        let promoted = signal.mapv(|x| Complex::new(x, 0.0));
+        assert_eq!(
+            promoted.cwt(&Dog::new(2), &scales),
+            signal.cwt(&Dog::new(2), &scales)
+        );
+
+        // By a real wavelet, the negative frequency of the real sinusoid beats with the positive
+        // one, the magnitude oscillating in time, while that of the analytic signal is steady
+        let ripple = |coeffs: &Array2<Complex<f32>>| {
+            let magnitudes = coeffs.slice(s![4, 384..640]).mapv(|z| z.norm());
+            let (min, max) = magnitudes
+                .iter()
+                .fold((f32::INFINITY, 0.0f32), |(lo, hi), &m| {
+                    (lo.min(m), hi.max(m))
+                });
+            (max - min) / max
+        };
+        assert!(ripple(&signal.cwt(&Dog::new(2), &scales)) > 0.9);
+        assert!(ripple(&analytic.cwt(&Dog::new(2), &scales)) < 0.01);
+
+        // By an analytic wavelet, the mirror of the analytic signal has no energy at any scale
+        let mirrored = analytic.mapv(|z| z.conj());
+        let energy = |coeffs: Array2<Complex<f32>>| {
+            coeffs.slice(s![.., 384..640]).mapv(|z| z.norm_sqr()).sum()
+        };
+        let (direct, mirror) = (
+            energy(analytic.cwt(&Morlet::default(), &scales)),
+            energy(mirrored.cwt(&Morlet::default(), &scales)),
+        );
+        assert!(mirror < 1e-6 * direct, "{mirror} {direct}");
+    }
+
+    #[test]
+    fn l1_normalization_gives_equal_tones_equal_ridges() {
+        let fs = 250.0;
+        let wavelet = Morlet::default();
+        let freqs = (2..=45).map(|f| f as f32).collect::<Vec<f32>>();
+        let scales = scales_for_frequencies(&freqs, fs, &wavelet);
+        let ridge_peak = |f: f32, normalization: Normalization| {
+            let options = CwtOptions::new().normalization(normalization);
+            let coeffs = sinusoid(f / fs, 2000).cwt_with(&wavelet, &scales, &options);
+            let magnitudes = ridge(&coeffs, 500..1500);
+            magnitudes[argmax(&magnitudes)]
+        };
+
+        let (low, high) = (
+            ridge_peak(5.0, Normalization::L1),
+            ridge_peak(40.0, Normalization::L1),
+        );
+        assert!((low / high - 1.0).abs() < 0.03, "{low} {high}");
+        // Of half the amplitude of the sinusoids, the Morlet wavelet being unnormalized
+        assert!((low - 0.5 * (2.0 * PI).sqrt()).abs() < 0.05, "{low}");
+
+        // Unlike with the L2 normalization, by the square root of the ratio of their scales
+        let ratio = ridge_peak(5.0, Normalization::L2) / ridge_peak(40.0, Normalization::L2);
+        assert!((ratio / 8.0f32.sqrt() - 1.0).abs() < 0.05, "{ratio}");
+
+        // L2 coefficients times the factor are the L1 ones
+        let signal = sinusoid(0.05, 256);
+        let l2 = signal.cwt(&wavelet, &[6.0]);
+        let l1 = signal.cwt_with(
+            &wavelet,
+            &[6.0],
+            &CwtOptions::new().normalization(Normalization::L1),
+        );
+        let factor = Normalization::L2.factor(6.0);
+        assert!(l2
+            .iter()
+            .zip(&l1)
+            .all(|(a, b)| (a * factor - b).norm() < 1e-5));
+    }
+
+    #[test]
+    fn invalid_scales_fail() {
+        let signal = sinusoid(0.1, 64);
+        let wavelet = Morlet::default();
+
+        assert!(matches!(
+            signal.try_cwt(&wavelet, &[]),
+            Err(WaveletError::NoScales)
+        ));
+        for scale in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(matches!(
+                signal.try_cwt_with(&wavelet, &[2.0, scale], &CwtOptions::new()),
+                Err(WaveletError::InvalidScale { index: 1, .. })
+            ));
+        }
+        assert_eq!(
+            signal.try_cwt(&wavelet, &[2.0, 4.0]).unwrap(),
+            signal.cwt(&wavelet, &[2.0, 4.0])
+        );
+    }
+}
@@ -0,0 +1,226 @@
+// R. R. Coifman and M. V. Wickerhauser, "Entropy-based algorithms for best basis selection," IEEE
+// Transactions on Information Theory, vol. 38, no. 2, pp. 713-718, 1992, doi: 10.1109/18.119732.
+
+use ndarray::{s, Array1, ArrayBase, Data, Ix1};
+
+use super::dwt::{dwt, idwt, symmetric, DiscreteWavelet};
+
+// Full wavelet packet tree of a signal, both the approximation and the detail of every node being
+// decomposed further
+//
+// Nodes are addressed by (level, index), level 0 holding the signal itself and level l holding
+// 2^l nodes of len / 2^l coefficients each: the children of (l, i) are its approximation
+// (l + 1, 2i) and its detail (l + 1, 2i + 1), hence indices in the natural (Paley) order rather
+// than by increasing frequency
+#[derive(Clone, Debug)]
+pub struct WaveletPacket {
+    pub wavelet: DiscreteWavelet,
+    pub levels: usize,
+    // Coefficients of the nodes of each level, by index
+    nodes: Vec<Vec<Array1<f32>>>,
+    // Length of the signal before its extension to a multiple of 2^levels
+    pub len: usize,
+    // Number of samples the signal was extended by
+    pub pad: usize,
+}
+
+// Wavelet packet transform of the `signal` over `levels` levels of the `wavelet`
+// Signals whose length is not a multiple of 2^levels are extended symmetrically beforehand, by the
+// recorded `pad`
+pub fn wpt<S>(signal: &ArrayBase<S, Ix1>, wavelet: DiscreteWavelet, levels: usize) -> WaveletPacket
+where
+    S: Data<Elem = f32>,
+{
+    let len = signal.len();
+    assert!(len > 0, "Signal must not be empty !");
+
+    let padded_len = len.next_multiple_of(1 << levels);
+    let root = Array1::from_shape_fn(padded_len, |i| signal[symmetric(i, len)]);
+
+    let mut nodes = Vec::with_capacity(levels + 1);
+    nodes.push(vec![root]);
+    for level in 0..levels {
+        let children = nodes[level]
+            .iter()
+            .flat_map(|node| {
+                let (approximation, detail) = dwt(node, wavelet);
+                [approximation, detail]
+            })
+            .collect();
+        nodes.push(children);
+    }
+
+    WaveletPacket {
+        wavelet,
+        levels,
+        nodes,
+        len,
+        pad: padded_len - len,
+    }
+}
+
+impl WaveletPacket {
+    // Coefficients of the node `index` of the `level`
+    pub fn node(&self, level: usize, index: usize) -> &Array1<f32> {
+        assert!(level <= self.levels, "Level out of the tree !");
+        assert!(index < 1 << level, "Node index out of the level !");
+
+        &self.nodes[level][index]
+    }
+
+    // Addresses of the nodes of the deepest level, a basis of the signal
+    pub fn leaves(&self) -> Vec<(usize, usize)> {
+        (0..1 << self.levels).map(|i| (self.levels, i)).collect()
+    }
+
+    // Component of the signal carried by the node `index` of the `level` alone, at the original
+    // length of the signal
+    pub fn reconstruct_node(&self, level: usize, index: usize) -> Array1<f32> {
+        self.reconstruct(&[(level, index)])
+    }
+
+    // Signal rebuilt from the `selected` nodes, the others being zeroed
+    // Nodes forming a basis, e.g. the `leaves` or the `best_basis`, rebuild the signal exactly
+    pub fn reconstruct(&self, selected: &[(usize, usize)]) -> Array1<f32> {
+        assert!(
+            selected
+                .iter()
+                .all(|&(level, index)| level <= self.levels && index < 1 << level),
+            "Nodes must lie within the tree !"
+        );
+
+        self.rebuild(0, 0, selected).slice_move(s![..self.len])
+    }
+
+    fn rebuild(&self, level: usize, index: usize, selected: &[(usize, usize)]) -> Array1<f32> {
+        if selected.contains(&(level, index)) {
+            return self.nodes[level][index].clone();
+        }
+        if level == self.levels {
+            return Array1::zeros(self.nodes[level][index].len());
+        }
+
+        let approximation = self.rebuild(level + 1, 2 * index, selected);
+        let detail = self.rebuild(level + 1, 2 * index + 1, selected);
+        idwt(&approximation, &detail, self.wavelet)
+    }
+
+    // Shannon entropy -sum_i p_i ln p_i of the coefficients of the node `index` of the `level`, p_i
+    // being the share c_i^2 / E of the energy E of the signal held by each of them, the additive
+    // cost of the best basis search
+    pub fn entropy(&self, level: usize, index: usize) -> f32 {
+        let energy = self.nodes[0][0]
+            .iter()
+            .map(|&c| (c * c) as f64)
+            .sum::<f64>();
+        if energy == 0.0 {
+            return 0.0;
+        }
+
+        -self
+            .node(level, index)
+            .iter()
+            .map(|&c| (c * c) as f64 / energy)
+            .filter(|&share| share > 0.0)
+            .map(|share| share * share.ln())
+            .sum::<f64>() as f32
+    }
+
+    // Best basis of the tree as per the Shannon entropy, each node being kept over its children
+    // whenever its entropy does not exceed the sum of that of the best bases of its children
+    //
+    // Returns the addresses of the selected nodes, ordered from the lowest-pass to the highest-pass
+    // branch of the tree
+    pub fn best_basis(&self) -> Vec<(usize, usize)> {
+        self.best_subtree(0, 0).1
+    }
+
+    fn best_subtree(&self, level: usize, index: usize) -> (f32, Vec<(usize, usize)>) {
+        let own = self.entropy(level, index);
+        if level == self.levels {
+            return (own, vec![(level, index)]);
+        }
+
+        let (approximation_cost, mut basis) = self.best_subtree(level + 1, 2 * index);
+        let (detail_cost, detail_basis) = self.best_subtree(level + 1, 2 * index + 1);
+        if own <= approximation_cost + detail_cost {
+            return (own, vec![(level, index)]);
+        }
+
+        basis.extend(detail_basis);
+        (approximation_cost + detail_cost, basis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::surrogate::Lcg;
+
+    fn max_error(a: &Array1<f32>, b: &Array1<f32>) -> f32 {
+        (a - b).iter().fold(0.0f32, |max, d| max.max(d.abs()))
+    }
+
+    #[test]
+    fn leaves_preserve_the_energy_and_rebuild_the_signal() {
+        let mut generator = Lcg::new(1);
+        let signal = Array1::from_shape_fn(256, |_| generator.next_f32() - 0.5);
+        let packet = wpt(&signal, DiscreteWavelet::Db4, 4);
+
+        let leaves = packet.leaves();
+        assert_eq!(leaves.len(), 16);
+        assert!(leaves
+            .iter()
+            .all(|&(level, i)| packet.node(level, i).len() == 16));
+        let energy = leaves
+            .iter()
+            .map(|&(level, i)| packet.node(level, i).mapv(|c| c * c).sum())
+            .sum::<f32>();
+        let signal_energy = signal.mapv(|x| x * x).sum();
+        assert!((energy - signal_energy).abs() < 1e-4 * signal_energy);
+
+        assert!(max_error(&packet.reconstruct(&leaves), &signal) < 1e-5);
+        // Each node alone carrying its own component of the signal
+        let sum = leaves.iter().fold(Array1::zeros(256), |sum, &(level, i)| {
+            sum + packet.reconstruct_node(level, i)
+        });
+        assert!(max_error(&sum, &signal) < 1e-5);
+    }
+
+    #[test]
+    fn signals_of_any_length_are_rebuilt_at_their_length() {
+        let mut generator = Lcg::new(2);
+        let signal = Array1::from_shape_fn(100, |_| generator.next_f32() - 0.5);
+        let packet = wpt(&signal, DiscreteWavelet::Haar, 3);
+        assert_eq!((packet.len, packet.pad), (100, 4));
+        assert!(max_error(&packet.reconstruct(&packet.leaves()), &signal) < 1e-5);
+        assert!(max_error(&packet.reconstruct(&[(0, 0)]), &signal) == 0.0);
+    }
+
+    #[test]
+    fn best_basis_of_a_tone_selects_few_nodes() {
+        let tone = Array1::from_shape_fn(512, |t| (std::f32::consts::TAU * 0.1 * t as f32).sin());
+        let packet = wpt(&tone, DiscreteWavelet::Db8, 5);
+        let basis = packet.best_basis();
+
+        // A tiling of the frequency axis, far coarser than the 32 leaves, rebuilding the tone
+        let coverage = basis
+            .iter()
+            .map(|&(level, _)| 0.5f32.powi(level as i32))
+            .sum::<f32>();
+        assert!((coverage - 1.0).abs() < 1e-6);
+        assert!(basis.len() < 12, "{basis:?}");
+        assert!(max_error(&packet.reconstruct(&basis), &tone) < 1e-4);
+
+        let entropy = basis
+            .iter()
+            .map(|&(level, i)| packet.entropy(level, i))
+            .sum::<f32>();
+        let leaves_entropy = packet
+            .leaves()
+            .iter()
+            .map(|&(level, i)| packet.entropy(level, i))
+            .sum::<f32>();
+        assert!(entropy <= leaves_entropy);
+    }
+}
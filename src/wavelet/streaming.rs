@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+
+use nalgebra::Complex;
+use ndarray::{Array1, Array2};
+
+use super::{wavelet_spectra, CwtOptions, Normalization, Wavelet};
+use crate::fft::InverseFourierTransform;
+
+// Continuous Wavelet Transform of a signal arriving in blocks, e.g. for online band-power tracking
+//
+// Each dilated wavelet is truncated to `support` e-folding times on either side of its center, so
+// that the coefficients at a time are known as soon as the signal is known that far past it:
+// columns are emitted `latency` samples after their time, equal to those of the batch CWT of the
+// whole signal (zero-padded, L2-normalized) up to the truncation of the wavelets, and only the last
+// 2 `latency` + 1 samples are kept in memory
+#[derive(Clone, Debug)]
+pub struct StreamingCwt {
+    // Taps of the conjugated dilated wavelet of each scale, the normalization included, over lags
+    // -h..=h of its half-width h
+    taps: Vec<Array1<Complex<f32>>>,
+    // Samples at times emitted - latency.., zeros standing for those before the first one
+    buffer: VecDeque<f32>,
+    latency: usize,
+}
+
+impl StreamingCwt {
+    // Streaming CWT at the `scales` (seconds) of the `wavelet`, for a signal sampled at `fs` Hz,
+    // the wavelets being truncated to 4 e-folding times
+    pub fn new<T>(wavelet: &T, scales: &[f32], fs: f32) -> Self
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone,
+    {
+        StreamingCwt::with_support(wavelet, scales, fs, 4.0)
+    }
+
+    // Streaming CWT whose wavelets are truncated to `support` e-folding times, trading latency and
+    // computations for accuracy, wavelets decaying slower than a Gaussian (e.g. Paul) needing more
+    pub fn with_support<T>(wavelet: &T, scales: &[f32], fs: f32, support: f32) -> Self
+    where
+        T: Wavelet<Dtype = f32>,
+        T::WaveletDtype: Into<Complex<f32>> + Clone,
+    {
+        assert!(support > 0.0, "Support must be positive !");
+        let scales = CwtOptions::new().dt(1.0 / fs).samples(scales);
+
+        let taps = scales
+            .iter()
+            .map(|&a| {
+                let half_width = (support * wavelet.efolding_time() * a).ceil() as usize;
+                // The kernel of the batch CWT, whose index (m - lag) % m holds the lag `lag`
+                let kernel = wavelet_spectra(wavelet, &[a], 2 * half_width + 1)
+                    .row(0)
+                    .ifft();
+                let m = kernel.len();
+                let factor = Normalization::L2.factor(a);
+
+                Array1::from_iter(
+                    (-(half_width as isize)..=half_width as isize)
+                        .map(|lag| kernel[(m as isize - lag) as usize % m] * factor),
+                )
+            })
+            .collect::<Vec<_>>();
+        let latency = taps.iter().map(|t| t.len() / 2).max().unwrap_or(0);
+
+        StreamingCwt {
+            taps,
+            buffer: VecDeque::from(vec![0.0; latency]),
+            latency,
+        }
+    }
+
+    // Delay in samples between the arrival of a sample and the emission of its coefficients
+    pub fn latency(&self) -> usize {
+        self.latency
+    }
+
+    // Appends the `block` to the signal
+    //
+    // Returns the coefficients (scales x times) of the times now known `latency` samples past them,
+    // following those emitted by the previous calls
+    pub fn push(&mut self, block: &[f32]) -> Array2<Complex<f32>> {
+        self.buffer.extend(block);
+
+        let num_columns = self.buffer.len().saturating_sub(2 * self.latency);
+        let mut result = Array2::zeros((self.taps.len(), num_columns));
+        for mut column in result.columns_mut() {
+            let window = self.buffer.make_contiguous();
+            for (coeff, taps) in column.iter_mut().zip(&self.taps) {
+                let half_width = taps.len() / 2;
+                let start = self.latency - half_width;
+
+                *coeff = window[start..start + taps.len()]
+                    .iter()
+                    .zip(taps)
+                    .map(|(&x, &w)| w * x)
+                    .sum();
+            }
+            self.buffer.pop_front();
+        }
+
+        result
+    }
+
+    // Ends the signal, zero-padding it as the batch CWT does
+    //
+    // Returns the coefficients of the last `latency` times not emitted yet, the stream starting over
+    // afterward
+    pub fn flush(&mut self) -> Array2<Complex<f32>> {
+        let result = self.push(&vec![0.0; self.latency]);
+
+        self.buffer = VecDeque::from(vec![0.0; self.latency]);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Axis;
+
+    use super::*;
+    use crate::{
+        surrogate::Lcg,
+        wavelet::{scales_for_frequencies, Morlet, WaveletTransform},
+    };
+
+    #[test]
+    fn streamed_coefficients_match_the_batch_transform() {
+        let fs = 250.0;
+        let mut generator = Lcg::new(1);
+        let signal = Array1::from_shape_fn(1000, |_| generator.next_f32() - 0.5);
+        let wavelet = Morlet::default();
+        // Scales in seconds
+        let scales = scales_for_frequencies(&[8.0, 12.0, 20.0, 30.0], 1.0, &wavelet);
+        let batch = signal.cwt_with(&wavelet, &scales, &CwtOptions::new().dt(1.0 / fs));
+
+        let mut streaming = StreamingCwt::new(&wavelet, &scales, fs);
+        let latency = streaming.latency();
+        assert!(latency > 0);
+        let (mut columns, mut emitted) = (Vec::new(), 0);
+        let mut start = 0;
+        while start < signal.len() {
+            let end = (start + 1 + (generator.next_u64() % 60) as usize).min(signal.len());
+            let block = streaming.push(signal.slice(ndarray::s![start..end]).as_slice().unwrap());
+            // Every time is emitted once known `latency` samples past it
+            emitted += block.ncols();
+            assert_eq!(emitted, end.saturating_sub(latency));
+            columns.push(block);
+            start = end;
+        }
+        columns.push(streaming.flush());
+
+        let views = columns.iter().map(|c| c.view()).collect::<Vec<_>>();
+        let streamed = ndarray::concatenate(Axis(1), &views).unwrap();
+        assert_eq!(streamed.dim(), batch.dim());
+        let largest = batch.iter().fold(0.0f32, |max, z| max.max(z.norm()));
+        let error = streamed
+            .iter()
+            .zip(&batch)
+            .fold(0.0f32, |max, (a, b)| max.max((a - b).norm()));
+        assert!(error < 1e-3 * largest, "{error} {largest}");
+
+        // Starting over after the flush
+        let again = streaming.push(signal.as_slice().unwrap());
+        assert_eq!(again.ncols(), 1000 - latency);
+        assert!(again
+            .iter()
+            .zip(batch.slice(ndarray::s![.., ..1000 - latency]))
+            .all(|(a, b)| (a - b).norm() < 1e-3 * largest));
+    }
+}
@@ -0,0 +1,189 @@
+use nalgebra::{DMatrix, SymmetricEigen};
+use ndarray::{Array2, ArrayBase, Data, Ix2};
+use num_traits::{FromPrimitive, ToPrimitive};
+
+use crate::covariance::CovarianceElement;
+
+// Eigenvalues below this fraction of the largest one are considered zero, given the precision of
+// f32 covariances, e.g. the one lost to re-referencing to the average
+// The same tolerance is applied to f64 covariances, so that both retain the same components
+const RELATIVE_TOLERANCE: f64 = 1e-6;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Whitening {
+    // Projection onto the principal components, scaled to unit variance: W = L^-1/2 U^T
+    // Whitened data has one row per component retained
+    Pca,
+    // PCA whitening rotated back into the space of the channels: W = U L^-1/2 U^T
+    // Whitened data stays as close as possible to the original one, with one row per channel
+    Zca,
+}
+
+// Whitening matrix of the covariance `cov` (channels x channels), such that the covariance of the
+// whitened data is the identity over the subspace of the components retained
+//
+// Only the components of the `rank` largest eigenvalues are retained, if given, and never those of
+// zero eigenvalues, so that rank-deficient covariances can be whitened as well
+// `reg` is added to the eigenvalues before their inversion, damping the noisiest components
+pub fn whitening_matrix<S>(
+    cov: &ArrayBase<S, Ix2>,
+    method: Whitening,
+    rank: Option<usize>,
+    reg: S::Elem,
+) -> Array2<S::Elem>
+where
+    S: Data,
+    S::Elem: CovarianceElement,
+{
+    assert_eq!(cov.nrows(), cov.ncols(), "Covariance must be square !");
+
+    let num_channels = cov.nrows();
+    let eigen = SymmetricEigen::new(DMatrix::from_fn(num_channels, num_channels, |i, j| {
+        cov[[i, j]].to_f64().unwrap()
+    }));
+
+    let mut order = (0..num_channels).collect::<Vec<usize>>();
+    order.sort_by(|&a, &b| eigen.eigenvalues[b].total_cmp(&eigen.eigenvalues[a]));
+    let largest = order.first().map_or(0.0, |&i| eigen.eigenvalues[i]);
+    let retained = order
+        .into_iter()
+        .filter(|&i| eigen.eigenvalues[i] > largest * RELATIVE_TOLERANCE)
+        .take(rank.unwrap_or(num_channels))
+        .collect::<Vec<usize>>();
+
+    // L^-1/2 U^T, one row per component retained
+    let pca = Array2::from_shape_fn((retained.len(), num_channels), |(k, j)| {
+        let i = retained[k];
+        eigen.eigenvectors[(j, i)] / (eigen.eigenvalues[i] + reg.to_f64().unwrap()).sqrt()
+    });
+
+    match method {
+        Whitening::Pca => pca.mapv(|v| S::Elem::from_f64(v).unwrap()),
+        Whitening::Zca => {
+            let rotation = Array2::from_shape_fn((num_channels, retained.len()), |(j, k)| {
+                eigen.eigenvectors[(j, retained[k])]
+            });
+            rotation.dot(&pca).mapv(|v| S::Elem::from_f64(v).unwrap())
+        }
+    }
+}
+
+// Whitened `data` (channels x samples), through the whitening matrix `w`
+pub fn apply_whitening<S, T>(w: &ArrayBase<S, Ix2>, data: &ArrayBase<T, Ix2>) -> Array2<S::Elem>
+where
+    S: Data,
+    S::Elem: CovarianceElement,
+    T: Data<Elem = S::Elem>,
+{
+    w.dot(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{Array2, Axis};
+
+    use super::*;
+    use crate::covariance::{Covariance, CovarianceType};
+    use crate::surrogate::Lcg;
+
+    // Four channels of correlated uniform noise, of distinct variances (channels x samples)
+    fn correlated() -> Array2<f64> {
+        let mut generator = Lcg::new(13);
+        let sources = Array2::from_shape_fn((4, 5000), |_| generator.next_f32() as f64 - 0.5);
+        let mixing = ndarray::array![
+            [1.0, 0.5, 0.2, 0.0],
+            [0.3, 2.0, 0.1, 0.4],
+            [0.0, 0.6, 0.5, 0.2],
+            [0.7, 0.0, 0.3, 3.0]
+        ];
+
+        mixing.dot(&sources)
+    }
+
+    fn is_identity(covariance: &Array2<f64>, tolerance: f64) -> bool {
+        covariance.indexed_iter().all(|((i, j), &c)| {
+            let expected = if i == j { 1.0 } else { 0.0 };
+            (c - expected).abs() < tolerance
+        })
+    }
+
+    #[test]
+    fn whitened_data_has_identity_covariance() {
+        let data = correlated();
+        let cov = data.compute_covariance(CovarianceType::Sample);
+
+        for method in [Whitening::Pca, Whitening::Zca] {
+            let w = whitening_matrix(&cov, method, None, 0.0);
+            assert_eq!(w.dim(), (4, 4));
+            let whitened = apply_whitening(&w, &data);
+            assert!(is_identity(
+                &whitened.compute_covariance(CovarianceType::Sample),
+                1e-9
+            ));
+        }
+
+        // ZCA whitening is symmetric, staying close to the channels
+        let w = whitening_matrix(&cov, Whitening::Zca, None, 0.0);
+        assert!(w.iter().zip(w.t()).all(|(a, b)| (a - b).abs() < 1e-9));
+    }
+
+    #[test]
+    fn rank_deficient_covariances_are_whitened_on_their_subspace() {
+        // Re-referenced to the average, the four channels only span three dimensions
+        let mut data = correlated();
+        let mean = data.mean_axis(Axis(0)).unwrap();
+        data -= &mean;
+        let cov = data.compute_covariance(CovarianceType::Sample);
+
+        let w = whitening_matrix(&cov, Whitening::Pca, None, 0.0);
+        assert_eq!(w.dim(), (3, 4));
+        let whitened = apply_whitening(&w, &data);
+        assert!(is_identity(
+            &whitened.compute_covariance(CovarianceType::Sample),
+            1e-6
+        ));
+
+        // ZCA keeps a row per channel, the covariance being a projection of rank 3
+        let w = whitening_matrix(&cov, Whitening::Zca, None, 0.0);
+        let covariance = apply_whitening(&w, &data).compute_covariance(CovarianceType::Sample);
+        assert_eq!(covariance.dim(), (4, 4));
+        assert!((covariance.diag().sum() - 3.0).abs() < 1e-6);
+
+        // And in single precision
+        let single = cov.mapv(|c| c as f32);
+        assert_eq!(
+            whitening_matrix(&single, Whitening::Pca, None, 0.0).nrows(),
+            3
+        );
+    }
+
+    #[test]
+    fn rank_and_regularization_are_applied() {
+        let data = correlated();
+        let cov = data.compute_covariance(CovarianceType::Sample);
+
+        let w = whitening_matrix(&cov, Whitening::Pca, Some(2), 0.0);
+        assert_eq!(w.dim(), (2, 4));
+        let whitened = apply_whitening(&w, &data);
+        assert!(is_identity(
+            &whitened.compute_covariance(CovarianceType::Sample),
+            1e-9
+        ));
+        // The retained components are the two of the largest variance, rows of W being scaled by
+        // the inverse square root of it
+        let mut eigenvalues = SymmetricEigen::new(DMatrix::from_fn(4, 4, |i, j| cov[[i, j]]))
+            .eigenvalues
+            .iter()
+            .copied()
+            .collect::<Vec<f64>>();
+        eigenvalues.sort_by(|a, b| b.total_cmp(a));
+        for (row, eigenvalue) in w.outer_iter().zip(&eigenvalues) {
+            assert!((row.dot(&row) * eigenvalue - 1.0).abs() < 1e-9);
+        }
+
+        // Regularization damps the variance of the whitened components below 1
+        let w = whitening_matrix(&cov, Whitening::Pca, None, 0.1);
+        let covariance = apply_whitening(&w, &data).compute_covariance(CovarianceType::Sample);
+        assert!(covariance.diag().iter().all(|&v| v < 1.0 && v > 0.0));
+    }
+}
@@ -0,0 +1,243 @@
+// * https://www.brainproducts.com/download/specification-of-brainvision-core-data-format-1-0/
+
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use ndarray::{ArrayBase, Data, Ix2};
+
+use crate::read::brainvision_core::{Date, Header, Marker};
+
+// Write a header, its markers and the associated `data` (channels x samples) as a BrainVision Core
+// Data Format 1.0 triplet in `dir`:
+//
+// <basename>.vhdr
+// <basename>.vmrk
+// <basename>.eeg
+//
+// The data is always written as `IEEE_FLOAT_32`, `MULTIPLEXED`, and is stored as given, so it
+// must be expressed in the same units as the one loaded with the `header` for the channel
+// resolutions to remain valid
+pub fn write_brainvision<P, S>(
+    dir: P,
+    basename: &str,
+    header: &Header,
+    markers: &Marker,
+    data: &ArrayBase<S, Ix2>,
+) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    S: Data<Elem = f32>,
+{
+    assert_eq!(
+        data.nrows(),
+        header.channels.len(),
+        "Data must have one row per channel !"
+    );
+
+    let dir = dir.as_ref();
+    let data_file = format!("{basename}.eeg");
+    let marker_file = format!("{basename}.vmrk");
+
+    fs::write(
+        dir.join(format!("{basename}.vhdr")),
+        header_contents(header, &data_file, &marker_file),
+    )?;
+    fs::write(dir.join(&marker_file), marker_contents(markers, &data_file))?;
+
+    let mut writer = BufWriter::new(fs::File::create(dir.join(&data_file))?);
+    // Multiplexed, so the values of all channels are written sample by sample
+    for sample in data.columns() {
+        for value in sample {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+    }
+
+    writer.flush()
+}
+
+fn header_contents(header: &Header, data_file: &str, marker_file: &str) -> String {
+    let mut buf = String::from("Brain Vision Data Exchange Header File Version 1.0\n");
+
+    buf.push_str("\n[Common Infos]\n");
+    buf.push_str("Codepage=UTF-8\n");
+    let _ = writeln!(buf, "DataFile={data_file}");
+    let _ = writeln!(buf, "MarkerFile={marker_file}");
+    buf.push_str("DataFormat=BINARY\n");
+    buf.push_str("DataOrientation=MULTIPLEXED\n");
+    let _ = writeln!(buf, "NumberOfChannels={}", header.channels.len());
+    let _ = writeln!(buf, "SamplingInterval={}", header.sampling_interval);
+    if header.averaged {
+        buf.push_str("Averaged=YES\n");
+        let _ = writeln!(buf, "AveragedSegments={}", header.averaged_segms);
+        let _ = writeln!(buf, "SegmentationType={}", header.segmentation_type);
+        if header.segmentation_type == "MARKERBASED" {
+            let _ = writeln!(buf, "SegmentDataPoints={}", header.segment_data_points);
+        }
+    }
+
+    buf.push_str("\n[Binary Infos]\n");
+    buf.push_str("BinaryFormat=IEEE_FLOAT_32\n");
+
+    buf.push_str("\n[Channel Infos]\n");
+    for (i, channel) in header.channels.iter().enumerate() {
+        let _ = writeln!(
+            buf,
+            "Ch{}={},{},{},{}",
+            i + 1,
+            escape(channel.name()),
+            escape(channel.ref_name()),
+            channel.resolution(),
+            channel.unit()
+        );
+    }
+
+    if let Some(coords) = &header.channel_coords {
+        buf.push_str("\n[Coordinates]\n");
+        for (i, coord) in coords.iter().enumerate() {
+            let _ = writeln!(
+                buf,
+                "Ch{}={},{},{}",
+                i + 1,
+                coord.radius(),
+                coord.theta(),
+                coord.phi()
+            );
+        }
+    }
+
+    // The `[Comment]` section is free text spanning until the end of the file
+    if let Some(comment) = &header.comment {
+        buf.push_str("\n[Comment]");
+        buf.push_str(comment);
+    }
+
+    buf
+}
+
+fn marker_contents(markers: &Marker, data_file: &str) -> String {
+    let mut buf = String::from("Brain Vision Data Exchange Marker File, Version 1.0\n");
+
+    buf.push_str("\n[Common Infos]\n");
+    buf.push_str("Codepage=UTF-8\n");
+    let _ = writeln!(buf, "DataFile={data_file}");
+
+    buf.push_str("\n[Marker Infos]\n");
+    for (i, marker) in markers.markers.iter().enumerate() {
+        let _ = write!(
+            buf,
+            "Mk{}={},{},{},{},{}",
+            i + 1,
+            escape(&marker.marker_type),
+            escape(&marker.description),
+            marker.position,
+            marker.size,
+            marker.channel
+        );
+        if let Some(date) = &marker.date {
+            let _ = write!(buf, ",{}", format_date(date));
+        }
+        buf.push('\n');
+    }
+
+    buf
+}
+
+// Commas inside of a field are escaped as `\1`
+fn escape(field: &str) -> String {
+    field.replace(',', "\\1")
+}
+
+// YYYYMMDDhhmmssuuuuuu
+fn format_date(date: &Date) -> String {
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}{:06}",
+        date.year, date.month, date.day, date.hour, date.minute, date.second, date.microsecond
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array2;
+
+    use super::*;
+    use crate::read::{
+        brainvision_core::{Data, Validation},
+        fixtures::{path, Recording, TempDir},
+    };
+
+    #[test]
+    fn written_triplet_reloads_identically() {
+        let source = TempDir::new("write-source");
+        let data =
+            Array2::from_shape_fn((3, 40), |(c, t)| (c as f32 + 1.0) * (t as f32 * 0.3).sin());
+        let mut recording = Recording::new(&["Fp1", "Fp2", "Cz"], data.clone()).markers(&[
+            "New Segment,,1,1,0,20240131235959123456",
+            "Stimulus,S\\1 1,11,1,0",
+            "Response,R  2,31,2,3",
+        ]);
+        recording.channels[1] = "Fp2,Cz,0.5,µV".into();
+        recording.extra = "\n[Coordinates]\nCh1=1,-90,-72\nCh2=1,90,72\nCh3=1,0,0\n".into();
+        let header = Header::load(&recording.write(&source)).unwrap();
+        let markers = Marker::load(&path(&source), &header).unwrap().unwrap();
+
+        let target = TempDir::new("write-target");
+        let written = path(&target);
+        fs::create_dir_all(written.directory()).unwrap();
+        write_brainvision(
+            written.directory(),
+            "sub-01_task-test_eeg",
+            &header,
+            &markers,
+            &data,
+        )
+        .unwrap();
+
+        let reloaded = Header::load(&written).unwrap();
+        assert_eq!(reloaded.num_channels, 3);
+        assert_eq!(reloaded.sampling_interval, header.sampling_interval);
+        for (a, b) in reloaded.channels.iter().zip(&header.channels) {
+            assert_eq!(
+                (a.name(), a.ref_name(), a.resolution(), a.unit()),
+                (b.name(), b.ref_name(), b.resolution(), b.unit())
+            );
+        }
+        let coords = reloaded.channel_coords.as_ref().unwrap();
+        assert_eq!(coords.len(), 3);
+        assert_eq!((coords[1].theta(), coords[1].phi()), (90.0, 72.0));
+
+        let reloaded_markers = Marker::load(&written, &reloaded).unwrap().unwrap();
+        assert_eq!(reloaded_markers.markers.len(), 3);
+        for (a, b) in reloaded_markers.markers.iter().zip(&markers.markers) {
+            assert_eq!(
+                (
+                    &a.marker_type,
+                    &a.description,
+                    a.position,
+                    a.size,
+                    a.channel
+                ),
+                (
+                    &b.marker_type,
+                    &b.description,
+                    b.position,
+                    b.size,
+                    b.channel
+                )
+            );
+        }
+        assert_eq!(reloaded_markers.markers[1].description, "S, 1");
+        let date = reloaded_markers.markers[0].date.as_ref().unwrap();
+        assert_eq!((date.year, date.microsecond), (2024, 123456));
+
+        let values = Data::<f32>::load(&written, &reloaded, Validation::Strict).unwrap();
+        assert!(values
+            .view()
+            .iter()
+            .zip(&data)
+            .all(|(a, b)| (a - b).abs() < 1e-6));
+    }
+}
@@ -0,0 +1 @@
+pub mod brainvision_core;